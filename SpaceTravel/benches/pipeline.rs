@@ -0,0 +1,107 @@
+// benches/pipeline.rs
+// Benchmarks de las etapas del pipeline: vertex shader, rasterización y fragment shaders.
+// Ejecutar con: cargo bench
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use raylib::prelude::*;
+use space_travel::camera::{FAR_PLANE, NEAR_PLANE};
+use space_travel::fragment::Fragment;
+use space_travel::framebuffer;
+use space_travel::matrix::{self, DepthMode, create_model_matrix, create_projection_matrix, create_viewport_matrix, normal_matrix};
+use space_travel::mesh;
+use space_travel::shader_params::ShaderParams;
+use space_travel::shaders::*;
+use space_travel::stats::PipelineStats;
+use space_travel::triangle;
+use space_travel::uniforms::Uniforms;
+use space_travel::vertex::Vertex;
+use std::hint::black_box;
+
+type FragmentShader<'a> = &'a dyn Fn(&Fragment) -> Vector3;
+
+const WIDTH: f32 = 1280.0;
+const HEIGHT: f32 = 720.0;
+
+fn uniforms() -> Uniforms {
+    let model_matrix = create_model_matrix(Vector3::new(0.0, 0.0, 0.0), 10.0, Vector3::new(0.0, 0.3, 0.0));
+    Uniforms {
+        model_matrix,
+        normal_matrix: normal_matrix(&model_matrix),
+        view_matrix: matrix::create_view_matrix(Vector3::new(0.0, 20.0, 75.0), Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0)),
+        projection_matrix: create_projection_matrix(std::f32::consts::PI / 3.0, WIDTH / HEIGHT, NEAR_PLANE, FAR_PLANE),
+        viewport_matrix: create_viewport_matrix(0.0, 0.0, WIDTH, HEIGHT),
+        depth_mode: DepthMode::Projective,
+        camera_position: Vector3::new(0.0, 20.0, 75.0),
+        time: 1.5,
+        dt: 0.016,
+        displacement: 0.0,
+        noise_frequency: 1.0,
+        vertex_animation: None,
+        geometry_shader: None,
+    }
+}
+
+// Triángulo rectángulo en pantalla con catetos de `size` píxeles
+fn screen_triangle(size: f32) -> [Vertex; 3] {
+    let corner = |x: f32, y: f32| {
+        let mut vertex = Vertex::new(Vector3::new(x, y, 0.0), Vector3::new(0.0, 0.0, 1.0), Vector2::zero());
+        vertex.transformed_position = Vector3::new(100.0 + x, 100.0 + y, 0.5);
+        vertex.transformed_normal = vertex.normal;
+        vertex
+    };
+    [corner(0.0, 0.0), corner(size, 0.0), corner(0.0, size)]
+}
+
+fn bench_vertex_shader(c: &mut Criterion) {
+    let sphere = mesh::generate_uv_sphere(20, 24);
+    let uniforms = uniforms();
+    c.bench_function("vertex_shader/uv_sphere", |b| {
+        b.iter(|| {
+            for vertex in &sphere.vertices {
+                black_box(vertex_shader(black_box(vertex), &uniforms));
+            }
+        })
+    });
+}
+
+fn bench_triangle(c: &mut Criterion) {
+    let depth = vec![f32::INFINITY; (WIDTH * HEIGHT) as usize];
+    let depth_view = framebuffer::DepthView { width: WIDTH as i32, height: HEIGHT as i32, depth: &depth };
+    let mut group = c.benchmark_group("triangle");
+    for size in [4.0, 32.0, 128.0, 512.0] {
+        let [v1, v2, v3] = screen_triangle(size);
+        group.bench_with_input(BenchmarkId::from_parameter(size as u32), &size, |b, _| {
+            b.iter(|| black_box(triangle::triangle(&v1, &v2, &v3, &depth_view, &mut PipelineStats::default())))
+        });
+    }
+    group.finish();
+}
+
+fn bench_fragment_shaders(c: &mut Criterion) {
+    let uniforms = uniforms();
+    let params = ShaderParams::default();
+    let mut fragment = Fragment::new(640.0, 360.0, Vector3::new(0.5, 0.5, 0.5), 0.5, Vector3::new(0.3, 0.2, 0.4));
+    fragment.normal = Vector3::new(0.0, 0.0, 1.0);
+
+    let shaders: [(&str, FragmentShader); 11] = [
+        ("sun", &|f| sun_fragment_shader(f, &uniforms, &params.sun)),
+        ("mercury", &|f| mercury_fragment_shader(f, &uniforms, &params.mercury)),
+        ("earth", &|f| earth_fragment_shader(f, &uniforms, &params.earth)),
+        ("mars", &|f| mars_fragment_shader(f, &uniforms, &params.mars)),
+        ("uranus", &|f| uranus_fragment_shader(f, &uniforms, &params.uranus)),
+        ("nave", &|f| nave_fragment_shader(f, &uniforms, &params.nave)),
+        ("zephyr", &|f| zephyr_fragment_shader(f, &uniforms, &params.zephyr)),
+        ("pyrion", &|f| pyrion_fragment_shader(f, &uniforms, &params.pyrion)),
+        ("glacia", &|f| glacia_fragment_shader(f, &uniforms, &params.glacia)),
+        ("umbraleth", &|f| umbraleth_fragment_shader(f, &uniforms, &params.umbraleth)),
+        ("verdis", &|f| verdis_fragment_shader(f, &uniforms, &params.verdis)),
+    ];
+
+    let mut group = c.benchmark_group("fragment_shader");
+    for (name, shader) in shaders {
+        group.bench_function(name, |b| b.iter(|| black_box(shader(black_box(&fragment)))));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_vertex_shader, bench_triangle, bench_fragment_shaders);
+criterion_main!(benches);
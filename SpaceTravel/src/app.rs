@@ -0,0 +1,1173 @@
+// app.rs
+// Estado del programa que comparten todas sus pantallas (ver states.rs): la ventana, los
+// ajustes, el renderer, la cámara, el mundo y sus sistemas, los efectos, el audio, los menús y
+// la consola. Cada frame se divide en pasos que los estados combinan a su manera: avanzar la
+// simulación, asentarla (warp, colisiones, eventos, audio), dibujar el mundo y presentarlo
+use crate::audio::{Audio, Listener, body_emitters};
+use crate::cockpit::Cockpit;
+use crate::console::{self, CommandRegistry, Console, ConsoleContext};
+use crate::dust::SpaceDust;
+use crate::dynamic_resolution::DynamicResolution;
+use crate::events::{Event, EventBus, Observer};
+use crate::exhaust::Exhaust;
+use crate::ghost::Ghost;
+use crate::instruments::Instruments;
+use crate::landing::Landing;
+use crate::locale::Strings;
+use crate::missions::{Missions, ProximityTracker};
+use crate::music;
+use crate::nebula::Nebula;
+use crate::orbit::OrbitCache;
+use crate::parse_arg;
+use crate::poster;
+use crate::race::Race;
+use crate::scripting::BodyScripts;
+use crate::settings::{QualityPreset, Settings};
+use crate::shader_plugins;
+use crate::ship::ShipHud;
+use crate::simulation::SimulationThread;
+use crate::starfield::Starfield;
+use crate::stream::{FrameStream, StreamTarget};
+use crate::systems::{CollisionSystem, OrbitSystem, RenderSystem};
+use crate::trajectory::{self, PathCamera};
+use crate::ui::{Menu, MenuAction, Notices};
+use crate::warp::{self, Warp, WarpStreaks};
+use crate::watch::FileWatcher;
+use crate::world::{self, Entity, World};
+use space_travel::{Model, Part, Renderer, Scene, View, post, profile_scope};
+use space_travel::assets::{Assets, PlanetMesh};
+use space_travel::camera::{Camera, CameraInput, CameraShake, FAR_PLANE, NEAR_PLANE};
+use space_travel::framebuffer::Framebuffer;
+use space_travel::lighting::{Surface, key_light};
+use space_travel::matrix::{DepthMode, create_projection_matrix, create_viewport_matrix, inverse, jitter_projection, multiply_matrices, multiply_matrix_vector4};
+use space_travel::mesh::Mesh;
+use space_travel::pathtrace;
+use space_travel::post::{EyeAdaptation, PostCamera, PostHistory};
+use space_travel::scene::{CelestialBody, NebulaPalette, Scene, load_scene, reload_scene_file, scene_path};
+use space_travel::shader_params::{AudioParams, ShaderParams};
+use space_travel::shader_registry::ShaderRegistry;
+use space_travel::stats::{PipelineStats, StatsExporter};
+use raylib::prelude::*;
+use std::f32::consts::PI;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+// Radianes que se abre el campo de visión en el pico del warp
+const WARP_FOV_PUNCH: f32 = 0.35;
+
+// Color de fondo del framebuffer
+const BACKGROUND_COLOR: Color = Color::new(35, 35, 40, 255);
+
+
+// Archivos de los ajustes del jugador y de los parámetros de los shaders
+const SETTINGS_PATH: &str = "settings.toml";
+const SHADER_PARAMS_PATH: &str = "shaders.toml";
+
+// Vuelo grabado que se repite como fantasma (ver ghost.rs)
+const GHOST_PATH: &str = "ghost_flight.csv";
+
+// Objetos de nave.obj con shader propio: el cristal de la cabina y las toberas
+const SHIP_GLASS: &str = "Glass";
+const SHIP_ENGINES: &str = "Engines";
+
+// Muestras por píxel del render de calidad si no se pide otro número con --beauty-samples
+const BEAUTY_SAMPLES: u32 = 256;
+
+// Render de calidad en curso en su propio hilo: las filas trazadas hasta ahora y lo que hay
+// que decirle al jugador cuando termina
+struct BeautyRender {
+    rows_done: Arc<AtomicUsize>,
+    rows: usize,
+    handle: JoinHandle<Result<String, String>>,
+}
+
+// El color, la profundidad y la procedencia de un píxel del último frame, en varias líneas
+// para el panel del inspector y la consola
+fn pixel_info(framebuffer: &Framebuffer, x: i32, y: i32) -> Option<String> {
+    let info = framebuffer.inspect(x, y)?;
+    Some(match info.source {
+        Some(source) => format!(
+            "({}, {}) color ({:.3}, {:.3}, {:.3}) depth {:.6}\n{} triangle {}\nnormal ({:.3}, {:.3}, {:.3}) world ({:.3}, {:.3}, {:.3})",
+            x, y,
+            source.color.x, source.color.y, source.color.z,
+            info.depth,
+            source.label, source.triangle,
+            source.normal.x, source.normal.y, source.normal.z,
+            source.world_position.x, source.world_position.y, source.world_position.z,
+        ),
+        None if info.depth.is_finite() => format!("({}, {}) depth {:.6}\norbit line", x, y, info.depth),
+        None => format!("({}, {}) background", x, y),
+    })
+}
+
+// Rayo (origen, dirección) que sale de la cámara por el punto (x, y) de un framebuffer de
+// `size` píxeles, deshaciendo la proyección
+fn pixel_ray(x: f32, y: f32, size: (i32, i32), view_projection: &Matrix) -> Option<(Vector3, Vector3)> {
+    let to_world = inverse(view_projection)?;
+    let ndc_x = 2.0 * x / size.0 as f32 - 1.0;
+    let ndc_y = 1.0 - 2.0 * y / size.1 as f32;
+    let unproject = |ndc_z: f32| {
+        let point = multiply_matrix_vector4(&to_world, &Vector4::new(ndc_x, ndc_y, ndc_z, 1.0));
+        Vector3::new(point.x / point.w, point.y / point.w, point.z / point.w)
+    };
+    let near = unproject(-1.0);
+    Some((near, (unproject(1.0) - near).normalized()))
+}
+
+// Tamaño del framebuffer para la ventana y la escala de resolución de los ajustes
+fn framebuffer_size(window_size: (i32, i32), scale: f32) -> (i32, i32) {
+    (((window_size.0 as f32 * scale) as i32).max(1), ((window_size.1 as f32 * scale) as i32).max(1))
+}
+
+/// Sphere mode asked with --spheres: raytraced (true) traces every body per pixel, mesh (false)
+/// rasterizes them all; None leaves it to each body of the scene
+pub fn spheres_arg() -> Option<bool> {
+    parse_arg("spheres").and_then(|text| match text.as_str() {
+        "raytraced" => Some(true),
+        "mesh" => Some(false),
+        _ => {
+            eprintln!("Unknown sphere mode '{}' (expected raytraced or mesh)", text);
+            None
+        }
+    })
+}
+
+// Avisa de los cuerpos cuyo shader no existe (se dibujarían con el shader por defecto)
+fn warn_unknown_shaders(world: &World, shaders: &ShaderRegistry) {
+    for (name, renderable) in world.names.iter().zip(&world.renderables) {
+        if let Some(renderable) = renderable && !shaders.contains(&renderable.shader) {
+            eprintln!("Unknown shader '{}' for '{}', using the default shader", renderable.shader, name);
+        }
+        if let Some(renderable) = renderable
+            && !renderable.vertex_animation.is_empty()
+            && shaders.vertex_animation(&renderable.vertex_animation).is_none()
+        {
+            eprintln!("Unknown vertex animation '{}' for '{}', drawing it rigid", renderable.vertex_animation, name);
+        }
+        if let Some(renderable) = renderable
+            && !renderable.geometry_shader.is_empty()
+            && shaders.geometry_shader(&renderable.geometry_shader).is_none()
+        {
+            eprintln!("Unknown geometry shader '{}' for '{}', drawing only its mesh", renderable.geometry_shader, name);
+        }
+    }
+}
+
+/// Everything the screens of the program share
+pub struct App<'a> {
+    window_size: (i32, i32),
+    pub settings: Settings,
+    // Textos de los menús y del HUD en el idioma de los ajustes
+    pub strings: Strings,
+    pub renderer: Renderer,
+    pub camera: Camera,
+    depth_mode: DepthMode,
+    sphere_mesh: Arc<Mesh>, // Compartida con el hilo del render de calidad
+    // Malla de los planetas pedida con --planet-mesh; sin ella la elige el preset de calidad
+    planet_mesh_arg: Option<PlanetMesh>,
+    // Esferas trazadas por píxel (true) o rasterizadas (false) para todos los cuerpos, con --spheres
+    spheres_arg: Option<bool>,
+    // Preset con el que están la malla de los planetas y los impostores
+    quality: QualityPreset,
+    // Escala de render que baja con la carga (objetivo de FPS de los ajustes)
+    dynamic_resolution: DynamicResolution,
+    nave_mesh: Mesh,
+    // Vista desde la cabina (V) en lugar de la nave flotante del HUD
+    pub cockpit: bool,
+    cockpit_interior: Cockpit,
+
+    // Escena y entidades; los sistemas las mueven, las apartan de la cámara y las dibujan
+    scene_name: String,
+    pub world: World,
+    orbit_system: OrbitSystem,
+    // Órbitas, giros y vuelo libre en su propio hilo; sin él (--simulation inline) los mueven
+    // orbit_system y la cámara en cada frame
+    simulation: Option<SimulationThread>,
+    // Teclas de vuelo libre del frame, para el hilo, y colisiones de la nave que este devuelve
+    // mientras vuela (None: la cámara es del render y las colisiones se comprueban aquí)
+    flight: Option<CameraInput>,
+    ship_hits: Option<Vec<Entity>>,
+    collision_system: CollisionSystem,
+    render_system: RenderSystem,
+    body_scripts: BodyScripts,
+    nebula_palette: NebulaPalette,
+    scene_watcher: Option<FileWatcher>,
+
+    // Parámetros de los shaders tal como están en shaders.toml y con el preset de calidad aplicado
+    authored_params: ShaderParams,
+    shader_params: ShaderParams,
+    shader_params_watcher: FileWatcher,
+
+    pub time: f32,
+    pub time_scale: f32,
+    dt: f32, // Paso de simulación del frame actual
+    ship_hud: ShipHud,
+    // Instrumentos de vuelo del HUD (velocidad, rumbo, cuerpo seleccionado, cabeceo)
+    pub instruments: bool,
+    flight_instruments: Instruments,
+    orbit_cache: OrbitCache,
+    nebula: Nebula,
+    starfield: Starfield,
+    pub warp: Option<Warp>,
+    warp_streaks: WarpStreaks,
+    audio: Option<Audio<'a>>,
+    previous_eye: Vector3,
+
+    // Bus de eventos: lo publicado en un frame se reparte al asentar la simulación al audio,
+    // la sacudida de la cámara, los avisos en pantalla, la consola y los scripts
+    pub events: EventBus,
+    camera_shake: CameraShake,
+    notices: Notices,
+    // Eclipses vistos desde la cámara en el frame anterior, para avisar solo de los que empiezan
+    active_eclipses: Vec<(Entity, Entity)>,
+    // Objetivos de exploración y quien publica las visitas y vueltas a los cuerpos que los cumplen
+    pub missions: Missions,
+    proximity: ProximityTracker,
+    // Contrarreloj por anillos (G)
+    race: Race,
+    // Grabación del vuelo (durante la contrarreloj o con H) y repetición de la anterior
+    ghost: Ghost,
+
+    exhaust: Exhaust,
+    space_dust: SpaceDust,
+    // Frames enviados a otros programas (--stream)
+    frame_stream: Option<FrameStream>,
+    post_history: PostHistory,
+    eye_adaptation: EyeAdaptation,
+    show_stats: bool,
+    stats_exporter: Option<StatsExporter>,
+    // Lo que hay en el último píxel pulsado con el inspector (I)
+    inspected: Option<String>,
+    // Render de calidad (Shift+Enter en el modo foto) y sus muestras por píxel (--beauty-samples)
+    beauty: Option<BeautyRender>,
+    beauty_samples: u32,
+
+    pub menu: Menu,
+    console: Console,
+    console_commands: CommandRegistry,
+
+    /// Body picked last with the mouse
+    pub selected: Option<Entity>,
+    // Vista y proyección del último frame dibujado, para elegir cuerpos con el ratón
+    view_projection: Matrix,
+}
+
+impl<'a> App<'a> {
+    /// Loads the settings, the assets and the scene chosen on the command line for a window of
+    /// `window_size`; plays sound through `audio_device` if there is one
+    pub fn new(window_size: (i32, i32), audio_device: Option<&'a RaylibAudio>) -> Self {
+        // Ajustes del jugador (menú de ajustes), guardados entre ejecuciones
+        let mut settings = if std::path::Path::new(SETTINGS_PATH).exists() {
+            Settings::load(SETTINGS_PATH).unwrap_or_else(|e| {
+                eprintln!("Using default settings: {}", e);
+                Settings::default()
+            })
+        } else {
+            Settings::default()
+        };
+        // --quality low | medium | high | ultra cambia el preset (y lo que agrupa) solo en esta ejecución
+        if let Some(text) = parse_arg("quality") {
+            match QualityPreset::parse(&text) {
+                Ok(quality) => settings.set_quality(quality),
+                Err(e) => eprintln!("{}; using the quality from the settings", e),
+            }
+        }
+
+        // El framebuffer puede ser más pequeño que la ventana; se escala al presentarlo
+        let (render_width, render_height) = framebuffer_size(window_size, settings.resolution_scale);
+        let mut renderer = Renderer::new(render_width, render_height);
+        renderer.framebuffer.set_background_color(BACKGROUND_COLOR);
+        // Los planetas a media distancia se redibujan solo cada pocos frames (salvo en Ultra)
+        renderer.impostors = settings.quality.impostors();
+
+        // Cámara inicial: por encima del plano del sistema, mirando al centro
+        let mut camera = Camera::new(Vector3::new(0.0, 20.0, 75.0), Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        camera.set_sensitivity(settings.sensitivity);
+
+        // Malla de los planetas: --planet-mesh uv | icosphere[:nivel], o la del preset de calidad
+        let planet_mesh_arg = parse_arg("planet-mesh").map(|text| PlanetMesh::parse(&text).unwrap_or_else(|e| {
+            eprintln!("{}; using UV sphere", e);
+            PlanetMesh::default()
+        }));
+        let planet_mesh = planet_mesh_arg.unwrap_or(settings.quality.planet_mesh());
+
+        // Mapeo de profundidad: --depth projective | log
+        let depth_mode = parse_arg("depth").map(|text| DepthMode::parse(&text, FAR_PLANE).unwrap_or_else(|e| {
+            eprintln!("{}; using projective depth", e);
+            DepthMode::Projective
+        })).unwrap_or(DepthMode::Projective);
+
+        // Cargar modelos (esfera y nave espacial); si faltan se usan mallas procedurales
+        // Lo que haya fallado o se haya corregido queda en la consola
+        let assets = Assets::load(planet_mesh);
+        let mut console = Console::new();
+        for warning in &assets.warnings {
+            console.print(warning, true);
+        }
+        for part in [SHIP_GLASS, SHIP_ENGINES] {
+            if !assets.ship.submeshes.iter().any(|submesh| submesh.name == part) {
+                console.print(&format!("Ship model has no '{}' object; it will be drawn with the hull shader", part), true);
+            }
+        }
+
+        // Escena seleccionada con --scene <nombre> (por defecto el sistema Xerion)
+        let scene_name = parse_arg("scene").unwrap_or_else(|| "xerion".to_string());
+        let scene = load_scene(&scene_name).unwrap_or_else(|e| {
+            eprintln!("Failed to load scene '{}': {}. Falling back to xerion", scene_name, e);
+            load_scene("xerion").expect("Built-in scene must load")
+        });
+        let mut world = World::new(&scene.bodies);
+        let spheres_arg = spheres_arg();
+        if let Some(on) = spheres_arg {
+            world.set_ray_traced(on);
+        }
+        // Shaders que las escenas pueden usar: los de shaders.rs y los registrados por otros módulos
+        shader_plugins::register(&mut renderer.shaders);
+        warn_unknown_shaders(&world, &renderer.shaders);
+
+        // Parámetros de los shaders (frecuencias y paletas), editables en caliente desde shaders.toml;
+        // el preset de calidad de los ajustes rebaja los más costosos
+        let authored_params = ShaderParams::load(SHADER_PARAMS_PATH).unwrap_or_else(|e| {
+            eprintln!("Using default shader parameters: {}", e);
+            ShaderParams::default()
+        });
+        let shader_params = settings.quality.apply(&authored_params);
+
+        // Envío de los frames a otros programas: --stream pipe:<ruta> | shm:<ruta>
+        let frame_stream = parse_arg("stream").and_then(|text| StreamTarget::parse(&text)
+            .and_then(|target| FrameStream::open(&target))
+            .map_err(|e| eprintln!("{}; streaming disabled", e))
+            .ok());
+
+        // Muestras por píxel del render de calidad: --beauty-samples <n>
+        let beauty_samples = parse_arg("beauty-samples").and_then(|text| text.parse::<u32>().ok().filter(|&samples| samples > 0).or_else(|| {
+            eprintln!("Invalid sample count '{}'; using {}", text, BEAUTY_SAMPLES);
+            None
+        })).unwrap_or(BEAUTY_SAMPLES);
+
+        // Contadores del pipeline: F3 los muestra en pantalla; --stats-csv <archivo> los exporta por frame
+        let stats_exporter = parse_arg("stats-csv").and_then(|path| StatsExporter::create(&path).map_err(|e| {
+            eprintln!("Failed to create stats file: {}", e);
+        }).ok());
+
+        // Simulación de las órbitas: en su hilo a paso fijo, o con --simulation inline en cada frame
+        let simulation = match parse_arg("simulation").as_deref() {
+            Some("inline") => None,
+            mode => {
+                if let Some(other) = mode.filter(|&mode| mode != "thread") {
+                    eprintln!("Unknown simulation mode '{}' (expected thread or inline); using thread", other);
+                }
+                SimulationThread::spawn().map_err(|e| eprintln!("{}; simulating inline", e)).ok()
+            }
+        };
+
+        // Consola de depuración (~); cada subsistema registra sus comandos
+        let mut console_commands = CommandRegistry::new();
+        warp::register_commands(&mut console_commands);
+        world::register_commands(&mut console_commands);
+        poster::register_commands(&mut console_commands);
+        console_commands.register("timescale", "timescale <factor>", |context, arguments| {
+            let scale = console::parse_number(arguments)?;
+            if scale < 0.0 {
+                return Err("The time scale can't be negative".to_string());
+            }
+            *context.time_scale = scale;
+            Ok(format!("Time scale set to {}", scale))
+        });
+        console_commands.register("toggle", "toggle <wireframe|impostors|lods|ssao|dither|crt|motion_blur|accumulate|auto_exposure|instruments|stats>", |context, arguments| {
+            let [name] = arguments else {
+                return Err("Expected a switch name".to_string());
+            };
+            let (_, flag) = context.flags.iter_mut().find(|(flag_name, _)| *flag_name == *name)
+                .ok_or_else(|| format!("Unknown switch '{}'", name))?;
+            **flag = !**flag;
+            Ok(format!("{} {}", name, if **flag { "on" } else { "off" }))
+        });
+
+        // El fantasma se dibuja con las aristas de la nave, que pasa al App más abajo
+        let ghost = Ghost::new(&assets.ship, GHOST_PATH);
+
+        // Sonido sintetizado; sin dispositivo de audio el programa sigue en silencio
+        let audio = audio_device.map(|device| {
+            let (audio, error) = Audio::new(device);
+            if let Some(e) = error {
+                console.print(&e, true);
+            }
+            audio
+        });
+
+        App {
+            window_size,
+            renderer,
+            depth_mode,
+            sphere_mesh: Arc::new(assets.sphere),
+            planet_mesh_arg,
+            spheres_arg,
+            quality: settings.quality,
+            dynamic_resolution: DynamicResolution::new(),
+            nave_mesh: assets.ship,
+            cockpit: false,
+            cockpit_interior: Cockpit::new(),
+            orbit_system: OrbitSystem::new(),
+            simulation,
+            flight: None,
+            ship_hits: None,
+            collision_system: CollisionSystem::new(),
+            render_system: RenderSystem,
+            // Scripts de comportamiento de los cuerpos (campo `script` de la escena)
+            body_scripts: BodyScripts::new(&world),
+            // Objetivos de exploración de la escena
+            missions: Missions::new(&world),
+            nebula_palette: scene.nebula,
+            // Las escenas en archivo .toml se recargan al guardarlas, sin reiniciar cámara ni tiempo
+            scene_watcher: scene_path(&scene_name).map(FileWatcher::new),
+            // Pantalla de título al arrancar; Esc abre el menú de pausa en lugar de cerrar la ventana
+            menu: Menu::new(&scene_name),
+            scene_name,
+            world,
+            authored_params,
+            nebula: Nebula::new(),
+            // Estrellas del fondo; se regeneran si cambia su cantidad en shaders.toml
+            starfield: Starfield::new(shader_params.starfield.count),
+            shader_params,
+            shader_params_watcher: FileWatcher::new(SHADER_PARAMS_PATH),
+            time: 0.0,
+            time_scale: 1.0,
+            dt: 0.0,
+            ship_hud: ShipHud::new(),
+            instruments: true,
+            flight_instruments: Instruments::new(),
+            orbit_cache: OrbitCache::new(),
+            // Viaje rápido a los planetas (teclas 1-5) y sus estelas
+            warp: None,
+            warp_streaks: WarpStreaks::new(),
+            audio,
+            previous_eye: camera.eye,
+            camera,
+            strings: Strings::new(settings.language),
+            settings,
+            events: EventBus::new(),
+            camera_shake: CameraShake::new(),
+            notices: Notices::new(),
+            active_eclipses: Vec::new(),
+            proximity: ProximityTracker::new(),
+            race: Race::new(),
+            ghost,
+            // Partículas de los motores de la nave
+            exhaust: Exhaust::new(),
+            // Polvo espacial alrededor de la cámara (estelas según su velocidad)
+            space_dust: SpaceDust::new(),
+            frame_stream,
+            // Lo que guardan los efectos de post-proceso entre frames; cuáles están activos es
+            // parte de los ajustes (O alterna el SSAO, B el dithering, C el monitor CRT, M el
+            // motion blur, T la acumulación temporal)
+            post_history: PostHistory::default(),
+            eye_adaptation: EyeAdaptation::new(),
+            show_stats: false,
+            stats_exporter,
+            inspected: None,
+            beauty: None,
+            beauty_samples,
+            console,
+            console_commands,
+            selected: None,
+            view_projection: Matrix::identity(),
+        }
+    }
+
+    /// Size of the framebuffer in pixels
+    pub fn render_size(&self) -> (i32, i32) {
+        (self.renderer.framebuffer.width, self.renderer.framebuffer.height)
+    }
+
+    /// Lowers or raises the render scale after a frame that took `frame_time` seconds, to hold
+    /// the frame rate of the settings (if dynamic resolution is on)
+    pub fn adapt_resolution(&mut self, frame_time: f32) {
+        let max_scale = self.settings.resolution_scale;
+        if let Some(scale) = self.dynamic_resolution.update(frame_time, self.settings.target_fps, max_scale) {
+            let size = framebuffer_size(self.window_size, scale);
+            if size != self.render_size() {
+                self.renderer.resize(size.0, size.1);
+            }
+        }
+    }
+
+    /// Adds `text` to the console output (in red if it is an error)
+    pub fn print(&mut self, text: &str, error: bool) {
+        self.console.print(text, error);
+    }
+
+    /// Runs the console for this frame; returns whether it has the keyboard (open now or
+    /// closed during this frame)
+    pub fn update_console(&mut self, window: &mut RaylibHandle) -> bool {
+        let console_was_open = self.console.is_open();
+        if let Some(line) = self.console.update(window, &self.console_commands) {
+            let mut context = ConsoleContext {
+                world: &mut self.world,
+                camera: &mut self.camera,
+                warp: &mut self.warp,
+                time: self.time,
+                time_scale: &mut self.time_scale,
+                flags: vec![
+                    ("wireframe", &mut self.renderer.wireframe),
+                    ("impostors", &mut self.renderer.impostors),
+                    ("lods", &mut self.renderer.lods),
+                    ("ssao", &mut self.settings.post.ssao),
+                    ("dither", &mut self.settings.post.dither),
+                    ("crt", &mut self.settings.post.crt),
+                    ("motion_blur", &mut self.settings.post.motion_blur),
+                    ("accumulate", &mut self.settings.post.accumulate),
+                    ("auto_exposure", &mut self.settings.post.auto_exposure),
+                    ("instruments", &mut self.instruments),
+                    ("stats", &mut self.show_stats),
+                ],
+                events: &mut self.events,
+            };
+            self.console.execute(&self.console_commands, &mut context, &line);
+        }
+        console_was_open || self.console.is_open()
+    }
+
+    /// Carries out a choice of the menu
+    pub fn apply_menu_action(&mut self, action: MenuAction) {
+        match action {
+            MenuAction::Start { scene } if scene != self.scene_name => match load_scene(&scene) {
+                Ok(loaded) => {
+                    self.set_scene(&loaded.bodies, loaded.nebula);
+                    self.scene_watcher = scene_path(&scene).map(FileWatcher::new);
+                    self.scene_name = scene;
+                }
+                Err(e) => eprintln!("Failed to load scene '{}': {}", scene, e),
+            },
+            MenuAction::SettingsChanged => {
+                if self.settings.language != self.strings.language() {
+                    self.strings = Strings::new(self.settings.language);
+                }
+                self.shader_params = self.settings.quality.apply(&self.authored_params);
+                if self.settings.quality != self.quality {
+                    self.quality = self.settings.quality;
+                    self.renderer.impostors = self.quality.impostors();
+                    if self.planet_mesh_arg.is_none() {
+                        self.sphere_mesh = Arc::new(self.quality.planet_mesh().generate());
+                    }
+                }
+                self.camera.set_sensitivity(self.settings.sensitivity);
+                // La resolución dinámica vuelve a empezar desde la escala elegida
+                self.dynamic_resolution.reset();
+                let size = framebuffer_size(self.window_size, self.settings.resolution_scale);
+                if size != self.render_size() {
+                    self.renderer.resize(size.0, size.1);
+                }
+                if let Err(e) = self.settings.save(SETTINGS_PATH) {
+                    eprintln!("Failed to save settings: {}", e);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Sustituye las entidades por las de otra escena
+    fn set_scene(&mut self, bodies: &[CelestialBody], nebula: NebulaPalette) {
+        self.world = World::new(bodies);
+        if let Some(on) = self.spheres_arg {
+            self.world.set_ray_traced(on);
+        }
+        if let Some(simulation) = self.simulation.as_mut() {
+            simulation.reset();
+        }
+        warn_unknown_shaders(&self.world, &self.renderer.shaders);
+        self.body_scripts = BodyScripts::new(&self.world);
+        self.missions = Missions::new(&self.world);
+        self.proximity = ProximityTracker::new();
+        self.race.abort();
+        self.ghost.stop(false, GHOST_PATH);
+        self.nebula_palette = nebula;
+        self.selected = None;
+    }
+
+    // Aplica una edición del archivo de la escena: solo se reconstruyen las entidades. Las
+    // misiones, la carrera, el fantasma y la simulación siguen (el hilo toma las órbitas y los
+    // tamaños nuevos en la siguiente petición), y la selección se conserva si el cuerpo sigue
+    fn reload_scene(&mut self, scene: Scene) {
+        let selected = self.selected.map(|entity| self.world.names[entity].clone());
+        let same_scripts = scene.bodies.len() == self.world.names.len()
+            && scene.bodies.iter().zip(&self.world.scripts).all(|(body, script)| &body.script == script);
+        self.world = World::new(&scene.bodies);
+        if let Some(on) = self.spheres_arg {
+            self.world.set_ray_traced(on);
+        }
+        warn_unknown_shaders(&self.world, &self.renderer.shaders);
+        // Los scripts guardan su estado por entidad: solo se recompilan si cambió su código
+        if !same_scripts {
+            self.body_scripts = BodyScripts::new(&self.world);
+        }
+        self.nebula_palette = scene.nebula;
+        self.selected = selected.and_then(|name| self.world.find(&name));
+    }
+
+    /// Debug keys that work on every screen: the post-processing switches, the pipeline
+    /// counters (F3), the profiler summary (P) and the pixel inspector (I, then click)
+    pub fn handle_debug_keys(&mut self, window: &RaylibHandle) {
+        if window.is_key_pressed(KeyboardKey::KEY_O) {
+            self.settings.post.ssao = !self.settings.post.ssao;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_B) {
+            self.settings.post.dither = !self.settings.post.dither;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_C) {
+            self.settings.post.crt = !self.settings.post.crt;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_M) {
+            self.settings.post.motion_blur = !self.settings.post.motion_blur;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_T) {
+            self.settings.post.accumulate = !self.settings.post.accumulate;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_F3) {
+            self.show_stats = !self.show_stats;
+        }
+        // La tabla del perfilador se abre en la consola
+        if window.is_key_pressed(KeyboardKey::KEY_P) {
+            self.console.print(&space_travel::profiler::summary(), false);
+            self.console.open();
+        }
+
+        // Inspector de píxeles: I lo activa y un clic muestra lo que hay en ese píxel en un panel
+        // (y lo deja en la consola para compararlo con otros)
+        let framebuffer = &mut self.renderer.framebuffer;
+        if window.is_key_pressed(KeyboardKey::KEY_I) {
+            let enabled = !framebuffer.is_recording_sources();
+            framebuffer.record_sources(enabled);
+            self.inspected = None;
+            self.console.print(if enabled { "Pixel inspector on (click a pixel)" } else { "Pixel inspector off" }, false);
+        }
+        if framebuffer.is_recording_sources() && window.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            let mouse = window.get_mouse_position() * (framebuffer.width as f32 / self.window_size.0 as f32);
+            self.inspected = pixel_info(framebuffer, mouse.x as i32, mouse.y as i32);
+            if let Some(info) = &self.inspected {
+                self.console.print(info, false);
+            }
+        }
+    }
+
+    /// Starts path-tracing the bodies as the camera sees them now, at the window's resolution
+    /// and with --beauty-samples rays per pixel (256 by default), on a worker thread that saves
+    /// the image to `path`; fails if another one is still running. `advance` reports the result
+    pub fn start_beauty_render(&mut self, path: String) -> Result<(), String> {
+        if self.beauty.is_some() {
+            return Err("A beauty render is already in progress".to_string());
+        }
+        let samples = self.beauty_samples;
+        let (width, height) = self.window_size;
+        let view = View {
+            view_matrix: self.camera.get_view_matrix(),
+            projection_matrix: create_projection_matrix(PI / 3.0, width as f32 / height as f32, NEAR_PLANE, FAR_PLANE),
+            depth_mode: self.depth_mode,
+            eye: self.camera.eye,
+            time: self.time,
+            dt: 0.0,
+        };
+        // El hilo se lleva una copia de los cuerpos: la escena sigue moviéndose mientras traza
+        let world = self.world.clone();
+        let mesh = Arc::clone(&self.sphere_mesh);
+        let params = self.shader_params.clone();
+        let rows_done = Arc::new(AtomicUsize::new(0));
+        let thread_rows_done = Arc::clone(&rows_done);
+        let handle = thread::Builder::new()
+            .name("beauty".to_string())
+            .spawn(move || {
+                let start = Instant::now();
+                // Los shaders propios son closures que no se copian: el hilo registra los suyos
+                let mut shaders = ShaderRegistry::new();
+                shader_plugins::register(&mut shaders);
+                // Solo los cuerpos: la nave y las órbitas son cosa del HUD
+                let scene = Scene { models: RenderSystem.models(&world, &mesh).collect(), lights: Vec::new() };
+                let mut framebuffer = Framebuffer::new(width, height);
+                pathtrace::render(&scene, &view, &shaders, &params, &mut framebuffer, samples, &thread_rows_done);
+
+                framebuffer.color_buffer.export_image(&path);
+                if !Path::new(&path).exists() {
+                    return Err(format!("Failed to write {}", path));
+                }
+                Ok(format!("Saved {} ({}x{}, {} samples per pixel) in {:.0} s", path, width, height, samples, start.elapsed().as_secs_f32()))
+            })
+            .map_err(|e| format!("Failed to start the beauty render: {}", e))?;
+        self.beauty = Some(BeautyRender { rows_done, rows: height as usize, handle });
+        Ok(())
+    }
+
+    /// Progress of the beauty render in progress, as a HUD line
+    pub fn beauty_progress(&self) -> Option<String> {
+        let beauty = self.beauty.as_ref()?;
+        let percent = (beauty.rows_done.load(Ordering::Relaxed) * 100 / beauty.rows.max(1)).min(100);
+        Some(self.strings.format("hud.beauty", &[("percent", &percent)]))
+    }
+
+    // Si el render de calidad terminó, recoge su resultado y lo escribe en la consola
+    fn finish_beauty_render(&mut self) {
+        if !self.beauty.as_ref().is_some_and(|beauty| beauty.handle.is_finished()) {
+            return;
+        }
+        let Some(beauty) = self.beauty.take() else {
+            return;
+        };
+        match beauty.handle.join() {
+            Ok(Ok(message)) => self.console.print(&message, false),
+            Ok(Err(e)) => self.console.print(&e, true),
+            Err(_) => self.console.print("The beauty render failed", true),
+        }
+    }
+
+    /// The body under the mouse when it is clicked, as drawn in the last frame (never while
+    /// the pixel inspector takes the clicks)
+    pub fn clicked_body(&self, window: &RaylibHandle) -> Option<Entity> {
+        let framebuffer = &self.renderer.framebuffer;
+        if framebuffer.is_recording_sources() || !window.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            return None;
+        }
+        let mouse = window.get_mouse_position() * (framebuffer.width as f32 / self.window_size.0 as f32);
+        let (origin, direction) = pixel_ray(mouse.x, mouse.y, self.render_size(), &self.view_projection)?;
+        self.world.pick(origin, direction)
+    }
+
+    /// Moves the simulation `dt` seconds forward: reloads the files that changed, runs the
+    /// body scripts and places the entities on their orbits
+    pub fn advance(&mut self, dt: f32) {
+        self.dt = dt;
+        self.time += dt;
+        self.finish_beauty_render();
+
+        // Recargar la escena si el archivo cambió; si la edición no es válida se sigue con la actual
+        if let Some(watcher) = self.scene_watcher.as_mut() && watcher.changed()
+            && let Some(path) = scene_path(&self.scene_name)
+        {
+            match reload_scene_file(path) {
+                Ok(scene) => self.reload_scene(scene),
+                Err(e) => eprintln!("Failed to reload scene '{}': {}; keeping the current one", self.scene_name, e),
+            }
+        }
+        if self.shader_params_watcher.changed() {
+            match ShaderParams::load(SHADER_PARAMS_PATH) {
+                Ok(params) => {
+                    self.shader_params = self.settings.quality.apply(&params);
+                    self.authored_params = params;
+                }
+                Err(e) => eprintln!("Failed to reload shader parameters: {}", e),
+            }
+        }
+        if !self.body_scripts.is_empty() {
+            profile_scope!("scripts");
+            self.body_scripts.update(&mut self.world, self.time, dt, &mut self.events);
+        }
+        // Con el hilo de simulación se le pide seguir este tiempo (y volar con las teclas del
+        // frame anterior) y se toma su última foto; hasta que hay una de la escena actual los
+        // cuerpos se colocan aquí
+        let flight = self.flight.take();
+        match self.simulation.as_ref() {
+            Some(simulation) => {
+                let rate = if dt > 0.0 { self.time_scale } else { 0.0 };
+                simulation.request(&self.world, &self.camera, flight, self.time, rate, self.sphere_mesh.bounding_sphere.radius);
+                if !simulation.read(&mut self.world) {
+                    self.orbit_system.run(&mut self.world, self.time, 0.0);
+                }
+                self.ship_hits = simulation.read_ship(&mut self.camera);
+            }
+            None => self.orbit_system.run(&mut self.world, self.time, dt),
+        }
+    }
+
+    /// Flies the camera with the keys held in `input` (free flight in the system view): on the
+    /// simulation thread from the next frame, or right away without it or during a warp
+    pub fn fly(&mut self, input: CameraInput) {
+        if self.simulation.is_some() && self.warp.is_none() {
+            self.flight = Some(input);
+        } else {
+            self.camera.apply_input(&input, 1.0);
+        }
+    }
+
+    /// Starts a time trial from where the camera is, or gives up the one in progress (key G)
+    /// The run is recorded and the best one so far flies alongside as a ghost
+    pub fn toggle_race(&mut self) {
+        if self.race.is_running() {
+            self.race.abort();
+            self.ghost.stop(false, GHOST_PATH);
+        } else {
+            self.race.start(self.camera.eye, &self.world);
+            self.ghost.start();
+        }
+    }
+
+    /// Starts recording the flight, replaying the last recording as a ghost, or stops and keeps
+    /// it as the next ghost (key H); during a time trial the race does it on its own
+    pub fn toggle_recording(&mut self) {
+        if self.race.is_running() {
+            return;
+        }
+        if self.ghost.is_recording() {
+            self.ghost.stop(true, GHOST_PATH);
+        } else {
+            self.ghost.start();
+        }
+    }
+
+    /// Starts a warp towards `body` (keys 1-5)
+    pub fn start_warp(&mut self, body: Entity) {
+        self.warp = Some(Warp::new(body, &self.camera));
+        self.events.publish(Event::WarpStarted { body });
+    }
+
+    /// Finishes the frame of simulation once the camera has moved: advances the warp, keeps
+    /// the camera out of the bodies (if `collide`), hands out the events and feeds the audio
+    pub fn settle(&mut self, collide: bool) {
+        let dt = self.dt;
+        if let Some(active) = self.warp.as_mut() && !active.update(&mut self.camera, &self.world, dt) {
+            self.warp = None;
+        }
+
+        // Verificar colisiones y ajustar la posición de la cámara si es necesario; en vuelo libre
+        // ya lo hizo el hilo de simulación
+        let hit = match self.ship_hits.take() {
+            Some(hits) => hits.first().copied(),
+            None if collide => self.collision_system.run(&self.world, &mut self.camera, self.sphere_mesh.bounding_sphere.radius),
+            None => None,
+        };
+
+        // El motor suena según la velocidad de la cámara
+        let camera_speed = if dt > 0.0 { (self.camera.eye - self.previous_eye).length() / dt } else { 0.0 };
+        self.previous_eye = self.camera.eye;
+        if let Some(body) = hit {
+            self.events.publish(Event::CollisionOccurred { body, speed: camera_speed });
+        }
+
+        // Eclipses de las estrellas que empiezan a verse desde la cámara (no tras los menús)
+        let current_eclipses = self.world.eclipses(self.camera.eye);
+        for &(occluder, light) in current_eclipses.iter().filter(|pair| !self.menu.is_open() && !self.active_eclipses.contains(pair)) {
+            self.events.publish(Event::EclipseBegan { occluder, light });
+        }
+        self.active_eclipses = current_eclipses;
+        if !self.menu.is_open() {
+            // La carrera terminada se queda como fantasma si mejora la suya
+            if let Some(time) = self.race.update(self.camera.eye, dt, &self.shader_params.exhaust) {
+                let keep = self.ghost.replay_duration().is_none_or(|best| time < best);
+                self.ghost.stop(keep, GHOST_PATH);
+            }
+            self.ghost.update(self.camera.eye, self.camera.target - self.camera.eye, dt);
+            self.proximity.update(self.camera.eye, &self.world, &mut self.events);
+            self.missions.update(dt);
+        }
+
+        {
+            profile_scope!("events");
+            let mut observers: Vec<&mut dyn Observer> = vec![&mut self.camera_shake, &mut self.notices, &mut self.console, &mut self.body_scripts, &mut self.missions];
+            if let Some(audio) = self.audio.as_mut() {
+                observers.push(audio);
+            }
+            self.events.dispatch(&mut observers, &self.world);
+        }
+        self.camera_shake.update(dt);
+        self.notices.update(dt);
+        self.flight_instruments.update(&self.camera, &self.world, self.selected, dt);
+
+        if let Some(audio) = self.audio.as_mut() {
+            let camera = &self.camera;
+            let near_star = music::near_star_weight(camera.eye, &self.world);
+            let listener = Listener { position: camera.eye, right: (camera.target - camera.eye).cross(camera.up).normalized() };
+            // Los volúmenes de los ajustes escalan el general y el de la música de [audio]
+            let audio_params = AudioParams {
+                master_volume: self.shader_params.audio.master_volume * self.settings.volume,
+                music_volume: self.shader_params.audio.music_volume * self.settings.music_volume,
+                ..self.shader_params.audio.clone()
+            };
+            audio.update(camera_speed, near_star, &listener, &body_emitters(&self.world), &audio_params, dt);
+        }
+    }
+
+    /// Draws the world and its effects into the framebuffer and applies the post-processing;
+    /// `ship` adds the ship of the HUD with its headlight and exhaust, or with `cockpit` the
+    /// cockpit interior around the camera instead, and the flight instruments on top. Returns
+    /// what the pipeline did
+    pub fn render(&mut self, ship: bool) -> PipelineStats {
+        self.adapt_exposure();
+        let (time, dt, depth_mode) = (self.time, self.dt, self.depth_mode);
+        let warp_intensity = self.warp.as_ref().map_or(0.0, Warp::intensity);
+
+        // Crear matrices de transformación comunes
+        let view_matrix = self.camera_shake.view_matrix(&self.camera, time);
+        // El warp abre el campo de visión un instante
+        let fov = PI / 3.0 + WARP_FOV_PUNCH * warp_intensity;
+        let (camera_projection, projection_matrix, viewport_matrix) = self.projections(fov);
+        self.view_projection = multiply_matrices(&camera_projection, &view_matrix);
+        self.clear_to_sky(&view_matrix, &projection_matrix, &viewport_matrix);
+        self.renderer.framebuffer.set_current_color(Color::new(0, 0, 0, 255));
+        let params = &self.shader_params;
+        let camera = &self.camera;
+
+        // Dibujar las órbitas primero; el renderer dibuja encima de lo que ya hay
+        {
+            profile_scope!("orbits");
+            self.orbit_cache.set_camera(&view_matrix, &camera_projection, &viewport_matrix);
+            self.render_system.draw_orbits(&self.world, &mut self.orbit_cache, &mut self.renderer.framebuffer, self.settings.palette.colors(), self.selected);
+        }
+
+        // Los cuerpos del mundo y la nave se rasterizan y sombrean en una sola pasada
+        let mut frame_scene = Scene { models: self.render_system.models(&self.world, &self.sphere_mesh).collect(), lights: Vec::new() };
+
+        // Nave espacial como elemento HUD 3D (siempre visible), con su faro; desde la cabina
+        // solo queda el faro
+        let (nave_model_matrix, headlight) = self.ship_hud.update(camera, time, dt);
+        let nave = ship && !self.cockpit;
+        // El cristal de la cabina y los motores, cada uno con su propio shader
+        let ship_parts = [
+            Part { name: SHIP_GLASS, shader: "ship_glass", surface: Surface::glossy(1.0, 96.0, 0.0) },
+            Part { name: SHIP_ENGINES, shader: "ship_engine", surface: Surface::emissive(1.2) },
+        ];
+        if nave {
+            frame_scene.models.push(Model {
+                mesh: &self.nave_mesh,
+                model_matrix: nave_model_matrix,
+                shader: "nave",
+                label: "nave",
+                surface: Surface::glossy(0.6, 32.0, 0.8), // Casco metálico
+                displacement: 0.0,
+                vertex_animation: "",
+                geometry_shader: "",
+                parts: &ship_parts,
+                ray_traced: false,
+                rings: None,
+                sdf: None,
+            });
+        }
+
+        // Anillos de la contrarreloj que quedan por cruzar
+        if ship {
+            frame_scene.models.extend(self.race.models());
+        }
+
+        // Las estrellas de la escena (Voidheart, Stellaris) iluminan según su color e intensidad, más el faro
+        // y la luz direccional de fondo si está configurada; la que proyecta sombras (Voidheart) hace que
+        // lunas y planetas se sombreen entre sí
+        if !params.lighting.key_light_only {
+            frame_scene.lights = self.world.scene_lights();
+            if ship {
+                frame_scene.lights.push(headlight);
+            }
+        }
+        frame_scene.lights.extend(key_light(&params.lighting));
+
+        let view = View { view_matrix, projection_matrix, depth_mode, eye: camera.eye, time, dt };
+        let mut frame_stats = self.renderer.draw_frame(&frame_scene, &view, params);
+        let scene_lights = std::mem::take(&mut frame_scene.lights);
+
+        let framebuffer = &mut self.renderer.framebuffer;
+        let view_projection = multiply_matrices(&projection_matrix, &view_matrix);
+        if nave {
+            profile_scope!("exhaust");
+            self.exhaust.update(&nave_model_matrix, &self.nave_mesh.aabb, dt, &params.exhaust);
+            self.exhaust.draw(framebuffer, &view_projection, &viewport_matrix, depth_mode, &params.exhaust);
+        }
+        if ship {
+            self.race.draw_sparks(framebuffer, &view_projection, &viewport_matrix, depth_mode, &params.exhaust);
+            self.ghost.draw(framebuffer, &view_projection, &viewport_matrix, depth_mode);
+        }
+        {
+            profile_scope!("dust");
+            self.space_dust.update(camera.eye, dt, &params.dust);
+            self.space_dust.draw(framebuffer, camera.eye, &view_projection, &viewport_matrix, depth_mode, &params.dust);
+        }
+        if ship {
+            profile_scope!("trajectory");
+            let path_camera = PathCamera { view_projection: &view_projection, viewport: &viewport_matrix, depth_mode };
+            let instruments = &self.flight_instruments;
+            trajectory::draw(framebuffer, camera.eye, instruments.velocity(), instruments.target_motion(), &path_camera, &params.trajectory, self.settings.palette.colors());
+        }
+        {
+            profile_scope!("warp");
+            let framebuffer = &mut self.renderer.framebuffer;
+            self.warp_streaks.update(dt, warp_intensity);
+            self.warp_streaks.draw(framebuffer, warp_intensity);
+            if warp_intensity > 0.0 {
+                post::blue_shift(framebuffer, warp_intensity);
+            }
+        }
+
+        // La cabina va encima de todo: su propia proyección de rango cercano y el modo Overlay
+        // la dejan por delante de cualquier profundidad del sistema. La iluminan las mismas
+        // luces (sin sombras: ella sería el único oclusor) y una luz tenue en el ojo
+        if ship && self.cockpit {
+            profile_scope!("cockpit");
+            let eye = self.camera.eye;
+            let mut lights: Vec<_> = scene_lights.into_iter().map(|light| light.with_shadows(false)).collect();
+            lights.push(Cockpit::interior_light(eye));
+            let cockpit_scene = Scene { models: vec![self.cockpit_interior.model(&view_matrix)], lights };
+            let aspect = self.window_size.0 as f32 / self.window_size.1 as f32;
+            let cockpit_view = View {
+                view_matrix,
+                projection_matrix: Cockpit::projection(fov, aspect),
+                depth_mode: DepthMode::Overlay,
+                eye,
+                time,
+                dt,
+            };
+            frame_stats += self.renderer.draw_frame(&cockpit_scene, &cockpit_view, &self.shader_params);
+        }
+        self.apply_post();
+
+        // Los instrumentos van sobre la imagen ya procesada, para que se lean nítidos
+        if ship && self.instruments {
+            profile_scope!("instruments");
+            self.flight_instruments.draw(&mut self.renderer.framebuffer, &self.camera, &self.world, &self.strings, self.settings.palette.colors());
+        }
+        if ship {
+            self.missions.draw_progress(&mut self.renderer.framebuffer, &self.strings, self.settings.palette.colors());
+        }
+        frame_stats
+    }
+
+    /// Draws the surface of the body `landing` stands on, with the other bodies in its sky
+    pub fn render_landing(&mut self, landing: &Landing) -> PipelineStats {
+        self.adapt_exposure();
+        let (time, dt, depth_mode) = (self.time, self.dt, self.depth_mode);
+
+        let view_matrix = landing.camera.get_view_matrix();
+        let (camera_projection, projection_matrix, viewport_matrix) = self.projections(PI / 3.0);
+        self.view_projection = multiply_matrices(&camera_projection, &view_matrix);
+        self.clear_to_sky(&view_matrix, &projection_matrix, &viewport_matrix);
+        self.renderer.framebuffer.set_current_color(Color::new(0, 0, 0, 255));
+
+        let params = &self.shader_params;
+        let mut lights = if params.lighting.key_light_only { Vec::new() } else { landing.lights(&self.world) };
+        lights.extend(key_light(&params.lighting));
+        let frame_scene = Scene { models: landing.models(&self.world, &self.sphere_mesh), lights };
+        let view = View { view_matrix, projection_matrix, depth_mode, eye: landing.camera.eye, time, dt };
+        let frame_stats = self.renderer.draw_frame(&frame_scene, &view, params);
+
+        self.apply_post();
+        frame_stats
+    }
+
+    /// Lands on `body` below the camera (None for stars)
+    pub fn land(&self, body: Entity) -> Option<Landing> {
+        Landing::new(&self.world, body, self.camera.eye, &self.shader_params)
+    }
+
+    // Proyección de la cámara con el campo de visión `fov`, la misma desplazada una fracción de
+    // píxel (con la acumulación activa cada frame se desplaza distinto) y el viewport
+    fn projections(&mut self, fov: f32) -> (Matrix, Matrix, Matrix) {
+        let (width, height) = self.render_size();
+        let camera_projection = create_projection_matrix(fov, self.window_size.0 as f32 / self.window_size.1 as f32, NEAR_PLANE, FAR_PLANE);
+        let viewport_matrix = create_viewport_matrix(0.0, 0.0, width as f32, height as f32);
+        let projection_matrix = if self.settings.post.accumulate {
+            let (dx, dy) = self.post_history.next_jitter();
+            jitter_projection(&camera_projection, dx, dy, width as f32, height as f32)
+        } else {
+            camera_projection
+        };
+        (camera_projection, projection_matrix, viewport_matrix)
+    }
+
+    // Empieza el frame desde el fondo: la nebulosa queda en la capa de fondo del framebuffer
+    // (solo se repinta si se movió la cámara), el clear la repone en los bloques escritos el
+    // frame anterior y las estrellas, que centellean, se suman encima antes que la escena
+    fn clear_to_sky(&mut self, view_matrix: &Matrix, projection_matrix: &Matrix, viewport_matrix: &Matrix) {
+        let params = &self.shader_params;
+        let framebuffer = &mut self.renderer.framebuffer;
+        {
+            profile_scope!("nebula");
+            self.nebula.update(framebuffer, &multiply_matrices(projection_matrix, view_matrix), &self.nebula_palette, &params.nebula);
+        }
+        framebuffer.clear();
+        {
+            profile_scope!("stars");
+            if self.starfield.count() != params.starfield.count {
+                self.starfield = Starfield::new(params.starfield.count);
+            }
+            self.starfield.draw(framebuffer, view_matrix, projection_matrix, viewport_matrix, self.time, &params.starfield);
+        }
+    }
+
+    // Exposición de la iluminación: la de shaders.toml por la de la adaptación del ojo
+    fn adapt_exposure(&mut self) {
+        let adaptation = if self.settings.post.auto_exposure { self.eye_adaptation.exposure } else { 1.0 };
+        self.shader_params.lighting.exposure = self.authored_params.lighting.exposure * adaptation;
+    }
+
+    fn apply_post(&mut self) {
+        profile_scope!("post");
+        // La adaptación mide la imagen antes de los efectos que la estilizan
+        if self.settings.post.auto_exposure {
+            profile_scope!("exposure");
+            self.eye_adaptation.update(&self.renderer.framebuffer, &self.shader_params.post);
+        }
+        let post_camera = PostCamera {
+            view_projection: self.view_projection,
+            depth_mode: self.depth_mode,
+            near: NEAR_PLANE,
+            far: FAR_PLANE,
+        };
+        post::apply(&mut self.renderer.framebuffer, &self.settings.post, &self.shader_params.post, &post_camera, &mut self.post_history);
+    }
+
+    /// Draws the summary of the completed missions over the frame
+    pub fn draw_mission_summary(&mut self) {
+        self.missions.draw_summary(&mut self.renderer.framebuffer, &self.strings, self.settings.palette.colors());
+    }
+
+    /// Draws the open menu over the frame
+    pub fn draw_menu(&mut self) {
+        profile_scope!("menu");
+        self.menu.draw(&mut self.renderer.framebuffer, &self.settings, &self.strings);
+    }
+
+    /// Lines of text over the frame: the pipeline counters (F3) and the recent notices
+    pub fn overlay(&self, frame_stats: &PipelineStats) -> Vec<String> {
+        let mut overlay = if self.show_stats { frame_stats.lines() } else { Vec::new() };
+        overlay.extend(self.race.hud_lines(&self.strings));
+        let max_scale = self.settings.resolution_scale;
+        if self.settings.target_fps > 0 && self.dynamic_resolution.is_active(max_scale) {
+            let percent = (self.dynamic_resolution.scale(max_scale) * 100.0).round();
+            overlay.push(self.strings.format("hud.dynamic_resolution", &[("percent", &percent)]));
+        }
+        if self.ghost.is_recording() && !self.race.is_running() {
+            overlay.push(self.strings.get("hud.recording").to_string());
+        }
+        if self.renderer.framebuffer.is_recording_sources() {
+            match &self.inspected {
+                Some(info) => overlay.extend(info.lines().map(String::from)),
+                None => overlay.push(self.strings.get("hud.inspector").to_string()),
+            }
+        }
+        overlay.extend(self.beauty_progress());
+        overlay.extend(self.notices.lines(&self.world, &self.strings));
+        overlay
+    }
+
+    /// Draws the console if it is open, records the counters of the frame and shows it in the
+    /// window with `overlay` on top (and to --stream)
+    pub fn present(&mut self, window: &mut RaylibHandle, thread: &RaylibThread, frame_stats: &PipelineStats, overlay: &[String]) {
+        if self.console.is_open() {
+            profile_scope!("console");
+            self.console.draw(&mut self.renderer.framebuffer);
+        }
+        if let Some(exporter) = self.stats_exporter.as_mut()
+            && let Err(e) = exporter.record(self.time, frame_stats)
+        {
+            eprintln!("Failed to write stats, export disabled: {}", e);
+            self.stats_exporter = None;
+        }
+        {
+            profile_scope!("present");
+            self.renderer.framebuffer.swap_buffers(window, thread, overlay);
+        }
+        if let Some(stream) = self.frame_stream.as_mut() {
+            profile_scope!("stream");
+            if let Err(e) = stream.send(&self.renderer.framebuffer) {
+                eprintln!("Failed to stream the frame, streaming disabled: {}", e);
+                self.frame_stream = None;
+            }
+        }
+    }
+}
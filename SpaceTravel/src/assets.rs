@@ -0,0 +1,137 @@
+// assets.rs
+use crate::mesh::{Mesh, generate_uv_sphere, generate_icosphere};
+use crate::obj::Obj;
+use crate::vertex::Vertex;
+use raylib::math::{Vector2, Vector3};
+
+// Teselación por defecto de la esfera de los planetas
+pub const SPHERE_STACKS: u32 = 20;
+pub const SPHERE_SLICES: u32 = 24;
+pub const ICOSPHERE_SUBDIVISIONS: u32 = 3;
+
+// Ángulo de pliegue de la nave: suaviza el casco facetado pero mantiene las aristas de las alas
+const SHIP_CREASE_ANGLE: f32 = 60.0 * std::f32::consts::PI / 180.0;
+
+// Niveles de detalle de la esfera y la nave: fracción de los triángulos que conservan y radio en
+// píxeles por debajo del cual se dibujan
+const LOD_LEVELS: [(f32, f32); 3] = [(0.5, 120.0), (0.25, 60.0), (0.1, 25.0)];
+
+/// Procedural mesh used for every planet
+#[derive(Clone, Copy, Debug)]
+pub enum PlanetMesh {
+    UvSphere { stacks: u32, slices: u32 },
+    Icosphere { subdivisions: u32 },
+}
+
+impl Default for PlanetMesh {
+    fn default() -> Self {
+        PlanetMesh::UvSphere { stacks: SPHERE_STACKS, slices: SPHERE_SLICES }
+    }
+}
+
+impl PlanetMesh {
+    /// Parses "uv", "icosphere" or "icosphere:<subdivisions>"
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text.split_once(':') {
+            None if text == "uv" => Ok(PlanetMesh::default()),
+            None if text == "icosphere" => Ok(PlanetMesh::Icosphere { subdivisions: ICOSPHERE_SUBDIVISIONS }),
+            Some(("icosphere", level)) => level.parse::<u32>()
+                .map(|subdivisions| PlanetMesh::Icosphere { subdivisions: subdivisions.min(6) })
+                .map_err(|e| format!("invalid icosphere level '{}': {}", level, e)),
+            _ => Err(format!("unknown planet mesh '{}' (expected uv or icosphere[:level])", text)),
+        }
+    }
+
+    /// Generates the mesh with its simplified levels of detail
+    pub fn generate(&self) -> Mesh {
+        let mesh = match *self {
+            PlanetMesh::UvSphere { stacks, slices } => generate_uv_sphere(stacks, slices),
+            PlanetMesh::Icosphere { subdivisions } => generate_icosphere(subdivisions),
+        };
+        mesh.with_lods(&LOD_LEVELS)
+    }
+}
+
+/// Indexed meshes used by the renderer
+pub struct Assets {
+    pub sphere: Mesh,
+    pub ship: Mesh,
+    pub warnings: Vec<String>, // Lo que se corrigió o sustituyó al cargar, para mostrárselo al jugador
+}
+
+impl Assets {
+    /// Loads every model, substituting a procedural mesh for any that fails so the app always starts
+    pub fn load(planet_mesh: PlanetMesh) -> Self {
+        let mut warnings = Vec::new();
+        let ship = load_model_or("./models/nave.obj", Some(SHIP_CREASE_ANGLE), placeholder_ship, &mut warnings).with_lods(&LOD_LEVELS);
+        Assets {
+            // La esfera de los planetas se genera proceduralmente (UV: 912 triángulos, similar a sphere.obj)
+            sphere: planet_mesh.generate(),
+            ship,
+            warnings,
+        }
+    }
+}
+
+/// Loads an OBJ model as an indexed mesh.
+/// With `crease_angle` the normals are recomputed as smooth normals (see `Obj::smooth_normals`;
+/// the smoothing groups of the file, if it has any, decide instead of the angle).
+/// Corrections made to the model are described in `warnings`
+pub fn load_model(path: &str, crease_angle: Option<f32>, warnings: &mut Vec<String>) -> Result<Mesh, String> {
+    let mut obj = Obj::load(path).map_err(|e| format!("{}: {}", path, e))?;
+    if let Some(angle) = crease_angle {
+        obj.smooth_normals(Some(angle));
+    }
+    if obj.indices.len() < 3 {
+        return Err(format!("{}: model has no triangles", path));
+    }
+    let mut mesh = obj.into_mesh();
+    // Modelos exportados al revés: con las normales hacia dentro se ven negros
+    for correction in mesh.correct_orientation() {
+        warnings.push(format!("{}: {}; corrected", path, correction));
+    }
+    Ok(mesh)
+}
+
+/// Loads an OBJ model, adding the error to `warnings` and using `fallback` if it can't be loaded
+pub fn load_model_or(path: &str, crease_angle: Option<f32>, fallback: impl FnOnce() -> Mesh, warnings: &mut Vec<String>) -> Mesh {
+    match load_model(path, crease_angle, warnings) {
+        Ok(mesh) => mesh,
+        Err(e) => {
+            warnings.push(format!("Failed to load model {}; using procedural placeholder", e));
+            fallback()
+        }
+    }
+}
+
+// Nave simple con forma de flecha, aproximadamente del tamaño de nave.obj (nariz hacia +X)
+fn placeholder_ship() -> Mesh {
+    let nose = Vector3::new(6.0, 0.0, 0.0);
+    let wing_left = Vector3::new(-3.5, 0.0, -5.0);
+    let wing_right = Vector3::new(-3.5, 0.0, 5.0);
+    let top = Vector3::new(-2.0, 1.0, 0.0);
+    let bottom = Vector3::new(-2.0, -1.0, 0.0);
+
+    let faces = [
+        [nose, wing_left, top],
+        [nose, top, wing_right],
+        [nose, bottom, wing_left],
+        [nose, wing_right, bottom],
+        [wing_left, bottom, top],
+        [wing_right, top, bottom],
+    ];
+
+    let mut vertices = Vec::with_capacity(faces.len() * 3);
+    for [a, b, c] in faces {
+        // Normal plana de la cara
+        let mut normal = (b - a).cross(c - a);
+        normal.normalize();
+        for position in [a, b, c] {
+            let mut vertex = Vertex::new(position, normal, Vector2::zero());
+            vertex.color = Vector3::new(1.0, 1.0, 1.0); // Material blanco por defecto
+            vertices.push(vertex);
+        }
+    }
+    let indices = (0..vertices.len() as u32).collect();
+    Mesh::new(vertices, indices)
+}
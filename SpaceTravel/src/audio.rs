@@ -0,0 +1,295 @@
+// audio.rs
+// Sonido sintetizado por software, como el resto del render: un zumbido ambiental de fondo,
+// el motor (tono y volumen según la velocidad), los efectos de warp y colisión y los sonidos de
+// los cuerpos (con posición: panorama y atenuación según la cámara) se mezclan muestra a muestra
+// y se envían a un único stream estéreo de raylib. La música va aparte, en sus propios streams
+// (ver music.rs)
+use crate::events::{Event, Observer};
+use crate::music::MusicPlayer;
+use crate::world::World;
+use space_travel::noise::{hash, value_noise};
+use space_travel::shader_params::AudioParams;
+use space_travel::simd::dot;
+use raylib::prelude::*;
+use std::f32::consts::{FRAC_PI_4, TAU};
+
+const SAMPLE_RATE: u32 = 44100;
+const BUFFER_FRAMES: usize = 2048; // Muestras por bloque enviado al stream (~46 ms)
+
+// Frecuencias del zumbido ambiental: dos notas graves casi iguales (batido lento) y una quinta
+const DRONE_FREQUENCIES: [f32; 3] = [55.0, 55.4, 82.6];
+// Tono del motor parado y cuánto sube por unidad de velocidad
+const ENGINE_BASE_FREQUENCY: f32 = 50.0;
+const ENGINE_FREQUENCY_PER_SPEED: f32 = 2.0;
+// Velocidad de la cámara (unidades/s) a la que el motor suena al máximo
+const ENGINE_FULL_SPEED: f32 = 30.0;
+// Duración de los efectos, en segundos
+const WARP_DURATION: f32 = 1.2;
+const COLLISION_DURATION: f32 = 0.4;
+
+/// Continuous sounds of the bodies, chosen by their shader
+#[derive(Clone, Copy, PartialEq)]
+pub enum BodySound {
+    Rumble, // Volcanes de Pyrion
+    Storm,  // Tormentas de Zephyr
+}
+
+impl BodySound {
+    pub fn for_shader(shader: &str) -> Option<Self> {
+        match shader {
+            "pyrion" => Some(BodySound::Rumble),
+            "zephyr" => Some(BodySound::Storm),
+            _ => None,
+        }
+    }
+}
+
+/// A body sound at a world position; `radius` sets the distance at which it is heard at full volume
+pub struct Emitter {
+    pub sound: BodySound,
+    pub position: Vector3,
+    pub radius: f32,
+}
+
+/// Emitters of the entities whose shader has a sound, at their current positions
+pub fn body_emitters(world: &World) -> Vec<Emitter> {
+    world.renderables.iter().zip(&world.transforms)
+        .filter_map(|(renderable, transform)| {
+            let sound = BodySound::for_shader(&renderable.as_ref()?.shader)?;
+            Some(Emitter {
+                sound,
+                position: transform.position,
+                radius: transform.scale * 0.5, // Las mallas de esfera tienen radio 0.5
+            })
+        })
+        .collect()
+}
+
+/// Where the sounds are heard from: the camera position and its right-hand direction
+pub struct Listener {
+    pub position: Vector3,
+    pub right: Vector3,
+}
+
+/// One-shot sound effects
+#[derive(Clone, Copy)]
+pub enum SoundEffect {
+    Warp,
+    Collision,
+}
+
+struct Voice {
+    effect: SoundEffect,
+    time: f32,
+    phase: f32,
+}
+
+/// Procedural sounds mixed into a raylib audio stream, plus the music. Call `update` once per frame
+pub struct Audio<'a> {
+    stream: AudioStream<'a>,
+    synth: Synth,
+    buffer: Vec<f32>,
+    music: Option<MusicPlayer<'a>>,
+}
+
+impl<'a> Audio<'a> {
+    /// Opens the stream and the music; if the music fails to load, the error is returned with
+    /// the rest of the sound working
+    pub fn new(device: &'a RaylibAudio) -> (Self, Option<String>) {
+        let (music, error) = match MusicPlayer::load(device) {
+            Ok(music) => (Some(music), None),
+            Err(e) => (None, Some(format!("{}; playing without music", e))),
+        };
+        device.set_audio_stream_buffer_size_default(BUFFER_FRAMES as i32);
+        // Estéreo, muestras f32 intercaladas (izquierda, derecha)
+        let stream = device.new_audio_stream(SAMPLE_RATE, 32, 2);
+        stream.play();
+        (Audio { stream, synth: Synth::new(), buffer: vec![0.0; BUFFER_FRAMES * 2], music }, error)
+    }
+
+    pub fn play(&mut self, effect: SoundEffect) {
+        self.synth.voices.push(Voice { effect, time: 0.0, phase: 0.0 });
+    }
+
+    /// Sets the engine from the current `speed`, the music from how close the camera is to a
+    /// star (`near_star`, 0..1) and the left/right gains of each emitter as heard by `listener`,
+    /// and refills the stream buffers it has consumed; `dt` paces the music crossfade
+    pub fn update(&mut self, speed: f32, near_star: f32, listener: &Listener, emitters: &[Emitter], params: &AudioParams, dt: f32) {
+        if let Some(music) = self.music.as_mut() {
+            music.update(near_star, params.music_volume * params.master_volume, dt);
+        }
+        self.synth.emitters.resize_with(emitters.len(), EmitterVoice::default);
+        for (voice, emitter) in self.synth.emitters.iter_mut().zip(emitters) {
+            voice.sound = Some(emitter.sound);
+            voice.target_gains = spatial_gains(listener, emitter);
+        }
+        let throttle = (speed / ENGINE_FULL_SPEED).min(1.0);
+        self.synth.engine_target = (
+            ENGINE_BASE_FREQUENCY + speed.min(ENGINE_FULL_SPEED * 2.0) * ENGINE_FREQUENCY_PER_SPEED,
+            0.2 + 0.8 * throttle,
+        );
+        // raylib reparte el stream en dos mitades: como mucho hay dos por rellenar
+        for _ in 0..2 {
+            if !self.stream.is_processed() {
+                break;
+            }
+            for frame in self.buffer.chunks_exact_mut(2) {
+                let (left, right) = self.synth.next_sample(params);
+                frame[0] = left;
+                frame[1] = right;
+            }
+            self.stream.update(&self.buffer);
+        }
+    }
+}
+
+// Efectos que disparan los eventos del frame
+impl Observer for Audio<'_> {
+    fn on_event(&mut self, event: &Event, _world: &World) {
+        match event {
+            Event::WarpStarted { .. } => self.play(SoundEffect::Warp),
+            Event::CollisionOccurred { .. } => self.play(SoundEffect::Collision),
+            _ => {}
+        }
+    }
+}
+
+// Volumen por canal de un sonido con posición: atenuación con la distancia (inversa a partir
+// de unos radios del cuerpo) y panorama de potencia constante según el lado de la cámara
+fn spatial_gains(listener: &Listener, emitter: &Emitter) -> (f32, f32) {
+    let offset = emitter.position - listener.position;
+    let distance = dot(offset, offset).sqrt();
+    let reference = (emitter.radius * 4.0).max(1.0);
+    let attenuation = reference / distance.max(reference);
+    let pan = if distance > 0.0 { dot(offset / distance, listener.right).clamp(-1.0, 1.0) } else { 0.0 };
+    let angle = (pan + 1.0) * FRAC_PI_4;
+    (attenuation * angle.cos(), attenuation * angle.sin())
+}
+
+// Estado de un sonido con posición; los volúmenes se acercan poco a poco a los objetivos
+#[derive(Default)]
+struct EmitterVoice {
+    sound: Option<BodySound>,
+    gains: (f32, f32),
+    target_gains: (f32, f32),
+    slow_noise: f32, // Ruido muy filtrado (retumbe)
+    wind_noise: f32, // Ruido algo filtrado (viento)
+}
+
+impl EmitterVoice {
+    fn next_sample(&mut self, white: f32, time: f32) -> (f32, f32) {
+        self.gains.0 += (self.target_gains.0 - self.gains.0) * 0.001;
+        self.gains.1 += (self.target_gains.1 - self.gains.1) * 0.001;
+        self.slow_noise += (white - self.slow_noise) * 0.004;
+        self.wind_noise += (white - self.wind_noise) * 0.05;
+        let value = match self.sound {
+            // Retumbe grave que crece y se apaga a ratos, como erupciones lejanas
+            Some(BodySound::Rumble) => {
+                let bursts = value_noise(time * 0.4, 3.0, 0.0);
+                self.slow_noise * 5.0 * bursts * bursts
+            }
+            // Viento: ruido sin la parte más grave, con ráfagas
+            Some(BodySound::Storm) => {
+                let gusts = value_noise(time * 0.3, 7.0, 0.0);
+                (self.wind_noise - self.slow_noise) * 1.5 * (0.2 + gusts)
+            }
+            None => 0.0,
+        };
+        (value * self.gains.0, value * self.gains.1)
+    }
+}
+
+struct Synth {
+    time: f32,
+    drone_phases: [f32; 3],
+    engine_phase: f32,
+    // Frecuencia y volumen actuales del motor; se acercan poco a poco al objetivo sin saltos
+    engine_frequency: f32,
+    engine_volume: f32,
+    engine_target: (f32, f32),
+    voices: Vec<Voice>,
+    noise_seed: u32,
+    filtered_noise: f32,
+    emitters: Vec<EmitterVoice>,
+}
+
+impl Synth {
+    fn new() -> Self {
+        Synth {
+            time: 0.0,
+            drone_phases: [0.0; 3],
+            engine_phase: 0.0,
+            engine_frequency: ENGINE_BASE_FREQUENCY,
+            engine_volume: 0.0,
+            engine_target: (ENGINE_BASE_FREQUENCY, 0.0),
+            voices: Vec::new(),
+            noise_seed: 0,
+            filtered_noise: 0.0,
+            emitters: Vec::new(),
+        }
+    }
+
+    // Siguiente muestra (izquierda, derecha)
+    fn next_sample(&mut self, params: &AudioParams) -> (f32, f32) {
+        let step = 1.0 / SAMPLE_RATE as f32;
+        self.time = (self.time + step) % 1000.0;
+
+        // Zumbido ambiental con una ondulación lenta del volumen
+        let mut drone = 0.0;
+        for (phase, frequency) in self.drone_phases.iter_mut().zip(DRONE_FREQUENCIES) {
+            *phase = (*phase + TAU * frequency * step) % TAU;
+            drone += phase.sin();
+        }
+        drone *= 0.2 * (0.7 + 0.3 * (TAU * 0.1 * self.time).sin());
+
+        // Motor: tres armónicos para un timbre más áspero que un seno puro
+        self.engine_frequency += (self.engine_target.0 - self.engine_frequency) * 0.0005;
+        self.engine_volume += (self.engine_target.1 - self.engine_volume) * 0.0005;
+        self.engine_phase = (self.engine_phase + TAU * self.engine_frequency * step) % TAU;
+        let p = self.engine_phase;
+        let engine = (p.sin() + 0.5 * (2.0 * p).sin() + 0.25 * (3.0 * p).sin()) * 0.2 * self.engine_volume;
+
+        // Ruido blanco filtrado (paso bajo) para las colisiones
+        self.noise_seed = self.noise_seed.wrapping_add(1);
+        let white = (hash(self.noise_seed) >> 8) as f32 / (1 << 23) as f32 - 1.0;
+        self.filtered_noise += (white - self.filtered_noise) * 0.1;
+
+        let mut effects = 0.0;
+        for voice in &mut self.voices {
+            voice.time += step;
+            let t = voice.time;
+            effects += match voice.effect {
+                // Barrido ascendente de tres octavas que se desvanece al llegar
+                SoundEffect::Warp => {
+                    let frequency = 150.0 * 8.0_f32.powf(t / WARP_DURATION);
+                    voice.phase = (voice.phase + TAU * frequency * step) % TAU;
+                    voice.phase.sin() * (t * 20.0).min(1.0) * (1.0 - t / WARP_DURATION).max(0.0) * 0.5
+                }
+                // Golpe grave más un chasquido de ruido
+                SoundEffect::Collision => {
+                    voice.phase = (voice.phase + TAU * 60.0 * step) % TAU;
+                    voice.phase.sin() * (-t * 8.0).exp() * 0.6 + self.filtered_noise * (-t * 12.0).exp() * 0.8
+                }
+            };
+        }
+        self.voices.retain(|voice| voice.time < match voice.effect {
+            SoundEffect::Warp => WARP_DURATION,
+            SoundEffect::Collision => COLLISION_DURATION,
+        });
+
+        let (mut left, mut right) = (0.0, 0.0);
+        for emitter in &mut self.emitters {
+            let (l, r) = emitter.next_sample(white, self.time);
+            left += l;
+            right += r;
+        }
+
+        // Lo que no tiene posición suena igual por los dos canales
+        let center = drone * params.ambient_volume + engine * params.engine_volume + effects * params.effects_volume;
+        // Saturación suave para que la suma de todo nunca recorte
+        (
+            ((center + left * params.bodies_volume) * params.master_volume).tanh(),
+            ((center + right * params.bodies_volume) * params.master_volume).tanh(),
+        )
+    }
+}
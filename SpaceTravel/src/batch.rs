@@ -0,0 +1,359 @@
+// batch.rs
+// Acumula los triángulos de todos los cuerpos del frame y los procesa en una sola pasada:
+// rasterización en paralelo, resolución de visibilidad por píxel y sombreado solo de lo visible.
+// Las esferas trazadas por rayos (raytrace.rs) y los cuerpos de distancia con signo (sdf.rs)
+// entran en la misma pasada como un triángulo más
+use crate::fragment::Fragment;
+use crate::framebuffer::{Framebuffer, PixelSource};
+use crate::light::Light;
+use crate::line::line;
+use crate::lighting::{Surface, apply_lighting, irradiance};
+use crate::raytrace::sphere;
+use crate::renderer::Model;
+use crate::sdf::{Sdf, march};
+use crate::shader_params::{LightingQuality, ShaderParams};
+use crate::shader_registry::ShaderRegistry;
+use crate::shaders::*;
+use crate::shadow::{ShadowMap, Shadows};
+use crate::stats::PipelineStats;
+use crate::triangle::triangle;
+use crate::uniforms::Uniforms;
+use crate::vertex::Vertex;
+use rayon::prelude::*;
+use raylib::prelude::*;
+use std::ops::Range;
+use std::sync::Arc;
+
+// Color de las aristas en modo alambre y cuánto se acercan a la cámara para no quedar
+// ocultas por su propia superficie
+const WIREFRAME_COLOR: Vector3 = Vector3::new(0.3, 1.0, 0.4);
+const WIREFRAME_DEPTH_BIAS: f32 = 1e-5;
+
+// Una llamada de dibujo: uniforms y shader comunes a sus triángulos
+struct Draw {
+    uniforms: Uniforms,
+    shader: String,
+    // Nombre para el inspector de píxeles y primer triángulo de la malla en el lote
+    label: Arc<str>,
+    first_triangle: usize,
+    // Vértices de su parte de la malla y los de la geometría que emitió
+    vertices: Range<usize>,
+    emitted: Range<usize>,
+    surface: Surface,
+    // false: el cuerpo está fuera de la cámara y solo se usa para proyectar sombras
+    visible: bool,
+    // Some: su único triángulo es simbólico y en su lugar se traza la forma exacta
+    traced: Option<Traced>,
+    // Malla cerrada con las caras hacia fuera: sus triángulos de espaldas se descartan
+    cull_back_faces: bool,
+}
+
+// Formas que se trazan por píxel en lugar de rasterizarse
+#[derive(Clone, Copy)]
+enum Traced {
+    Sphere,
+    Sdf(Sdf),
+}
+
+/// Transformed triangles from every submitted mesh, tagged with the draw they belong to
+#[derive(Default)]
+pub struct RenderBatch {
+    draws: Vec<Draw>,
+    vertices: Vec<Vertex>,
+    triangles: Vec<([usize; 3], usize)>,
+    // Fragmento visible por píxel (se reutiliza entre frames)
+    winners: Vec<u32>,
+    touched: Vec<usize>,
+    /// Draw the edges of the visible triangles on top of the shaded result
+    pub wireframe: bool,
+}
+
+impl RenderBatch {
+    pub fn new() -> Self {
+        RenderBatch {
+            draws: Vec::new(),
+            vertices: Vec::new(),
+            triangles: Vec::new(),
+            winners: Vec::new(),
+            touched: Vec::new(),
+            wireframe: false,
+        }
+    }
+
+    /// Runs the vertex shader on the mesh of `model` and queues its triangles, one draw per
+    /// submesh with the shader and surface of its part; the label of the model names the draws
+    /// in the pixel inspector
+    pub fn submit(&mut self, uniforms: Uniforms, model: &Model) {
+        self.push_draws(uniforms, model, true);
+    }
+
+    /// Queues a mesh that is outside the camera but can still cast shadows into view;
+    /// it only takes part in the shadow pass
+    pub fn submit_shadow_caster(&mut self, uniforms: Uniforms, model: &Model) {
+        self.push_draws(uniforms, model, false);
+    }
+
+    // Una llamada de dibujo por parte de la malla: los vértices se transforman una sola vez y
+    // cada parte toma sus triángulos con el shader y la superficie que le da el modelo
+    fn push_draws(&mut self, uniforms: Uniforms, model: &Model, visible: bool) {
+        profile_scope!("vertex");
+        let mesh = model.mesh;
+
+        // Vertex Shader Stage (una vez por vértice único)
+        let base = self.vertices.len();
+        self.vertices.extend(mesh.vertices.iter().map(|vertex| vertex_shader(vertex, &uniforms)));
+
+        for submesh in &mesh.submeshes {
+            let draw = self.draws.len();
+            let first_triangle = self.triangles.len();
+
+            // Primitive Assembly Stage (a partir del index buffer)
+            for face in mesh.indices[submesh.indices.clone()].chunks_exact(3) {
+                self.triangles.push(([base + face[0] as usize, base + face[1] as usize, base + face[2] as usize], draw));
+            }
+
+            // Geometry Stage (opcional): triángulos extra a partir de los ensamblados, en el espacio
+            // del modelo; se proyectan sin volver a pasar por el relieve ni la animación
+            let first_emitted = self.vertices.len();
+            if let Some(geometry) = uniforms.geometry_shader {
+                let mut emitted = Vec::new();
+                for &([a, b, c], _) in &self.triangles[first_triangle..] {
+                    geometry([&self.vertices[a], &self.vertices[b], &self.vertices[c]], &uniforms, &mut emitted);
+                }
+                for triangle in emitted {
+                    let first = self.vertices.len();
+                    self.vertices.extend(triangle.iter().map(|vertex| project_vertex(vertex, &uniforms)));
+                    self.triangles.push(([first, first + 1, first + 2], draw));
+                }
+            }
+
+            let part = model.parts.iter().find(|part| part.name == submesh.name);
+            let (shader, surface) = part.map_or((model.shader, model.surface), |part| (part.shader, part.surface));
+            // En el inspector de píxeles las partes con estilo propio llevan su nombre
+            let label: Arc<str> = match part {
+                Some(part) => format!("{}/{}", model.label, part.name).into(),
+                None => model.label.into(),
+            };
+            let vertices = base + submesh.vertices.start..base + submesh.vertices.end;
+            let emitted = first_emitted..self.vertices.len();
+            self.draws.push(Draw {
+                uniforms: uniforms.clone(),
+                shader: shader.to_string(),
+                label,
+                first_triangle,
+                vertices,
+                emitted,
+                surface,
+                visible,
+                traced: None,
+                cull_back_faces: mesh.back_faces_hidden,
+            });
+        }
+    }
+
+    /// Queues `model` as the exact sphere its mesh approximates, ray-traced per pixel in the
+    /// same pass as the triangles. It takes the material of the first vertex of the mesh and,
+    /// without vertices to light, is always lit per pixel. It does not cast shadows into the
+    /// shadow map: submit its mesh as a shadow caster for that
+    pub fn submit_sphere(&mut self, uniforms: Uniforms, model: &Model) {
+        self.submit_traced(uniforms, model, Traced::Sphere);
+    }
+
+    /// Queues `model` as the surface of `sdf`, raymarched per pixel inside the radius 0.5 sphere
+    /// of its mesh, with the material of its first vertex and per-pixel lighting
+    pub fn submit_sdf(&mut self, uniforms: Uniforms, model: &Model, sdf: Sdf) {
+        self.submit_traced(uniforms, model, Traced::Sdf(sdf));
+    }
+
+    fn submit_traced(&mut self, uniforms: Uniforms, model: &Model, traced: Traced) {
+        let Some(material) = model.mesh.vertices.first() else {
+            return;
+        };
+        let vertex = self.vertices.len();
+        self.vertices.push(material.clone());
+        // Un triángulo simbólico lleva la forma por la rasterización y el inspector de píxeles
+        let first_triangle = self.triangles.len();
+        self.triangles.push(([vertex; 3], self.draws.len()));
+        self.draws.push(Draw {
+            uniforms,
+            shader: model.shader.to_string(),
+            label: model.label.into(),
+            first_triangle,
+            vertices: vertex..vertex + 1,
+            emitted: vertex + 1..vertex + 1,
+            surface: Surface { lighting: Some(LightingQuality::Phong), ..model.surface },
+            visible: true,
+            traced: Some(traced),
+            cull_back_faces: false,
+        });
+    }
+
+    /// Renders the queued triangles into `shadow_map` as seen from `light_position`. Emissive
+    /// draws (the stars themselves) do not cast shadows
+    pub fn render_shadows(&self, shadow_map: &mut ShadowMap, light_position: Vector3, size: usize) {
+        profile_scope!("shadow");
+        let casters: Vec<[Vector3; 3]> = self.triangles
+            .iter()
+            .filter(|(_, draw)| self.draws[*draw].surface.emissive <= 0.0 && self.draws[*draw].traced.is_none())
+            .map(|&([a, b, c], _)| [self.vertices[a].world_position, self.vertices[b].world_position, self.vertices[c].world_position])
+            .collect();
+        shadow_map.render(light_position, size, &casters);
+    }
+
+    // Iluminación Gouraud: la luz de los cuerpos que la usan se calcula una vez por vértice
+    // y el rasterizador la interpola
+    fn light_vertices(&mut self, lights: &[Light], shadow: Option<&Shadows>, params: &ShaderParams) {
+        profile_scope!("gouraud");
+        for draw in &self.draws {
+            if !draw.visible || draw.surface.emissive > 0.0 || draw.surface.quality(&params.lighting) != LightingQuality::Gouraud {
+                continue;
+            }
+            for range in [draw.vertices.clone(), draw.emitted.clone()] {
+                self.vertices[range].par_iter_mut().for_each(|vertex| {
+                    let normal = vertex.transformed_normal.normalized();
+                    let light = irradiance(vertex.world_position, normal, &draw.surface, draw.uniforms.camera_position, lights, shadow, &params.lighting);
+                    vertex.light_diffuse = light.diffuse;
+                    vertex.light_specular = light.specular;
+                });
+            }
+        }
+    }
+
+    /// Rasterizes, shades and writes every queued triangle, leaving the batch empty. Lights that
+    /// cast shadows are looked up in `shadow`, if shadows are enabled this frame, and each draw
+    /// is shaded with its shader from `shaders`. Returns what each stage did with them
+    pub fn flush(&mut self, framebuffer: &mut Framebuffer, lights: &[Light], shadow: Option<&Shadows>, shaders: &ShaderRegistry, params: &ShaderParams) -> PipelineStats {
+        profile_scope!("flush");
+        self.light_vertices(lights, shadow, params);
+        // Rasterization Stage: cada triángulo es independiente; el orden del resultado se conserva
+        let depth_view = framebuffer.depth_view();
+        let vertices = &self.vertices;
+        let draws = &self.draws;
+        // Cada fragmento lleva el índice del triángulo que lo generó
+        let mut stats = PipelineStats::default();
+        let fragments: Vec<(Fragment, usize)> = {
+            profile_scope!("raster");
+            let rasterized: Vec<(Vec<Fragment>, PipelineStats)> = self.triangles
+                .par_iter()
+                .map(|&([a, b, c], draw)| {
+                    let draw = &draws[draw];
+                    if !draw.visible {
+                        return (Vec::new(), PipelineStats::default());
+                    }
+                    let mut triangle_stats = PipelineStats::default();
+                    let fragments = match draw.traced {
+                        Some(Traced::Sphere) => sphere(&draw.uniforms, &vertices[a], &depth_view, &mut triangle_stats),
+                        Some(Traced::Sdf(sdf)) => march(&draw.uniforms, &sdf, &vertices[a], &depth_view, &mut triangle_stats),
+                        None => {
+                            // Con iluminación de dos caras se ven los interiores; lo que emite la
+                            // etapa de geometría no forma parte de la superficie cerrada
+                            let cull = draw.cull_back_faces && !params.lighting.two_sided && a < draw.emitted.start;
+                            triangle(&vertices[a], &vertices[b], &vertices[c], cull, &depth_view, &mut triangle_stats)
+                        }
+                    };
+                    (fragments, triangle_stats)
+                })
+                .collect();
+            let mut fragments = Vec::new();
+            for (index, (triangle_fragments, triangle_stats)) in rasterized.into_iter().enumerate() {
+                stats += triangle_stats;
+                fragments.extend(triangle_fragments.into_iter().map(|fragment| (fragment, index)));
+            }
+            fragments
+        };
+
+        // Visibilidad: por cada píxel queda solo el fragmento más cercano (el primero en caso de empate)
+        let visible: Vec<usize> = {
+            profile_scope!("resolve");
+            let pixel_count = (depth_view.width * depth_view.height) as usize;
+            if self.winners.len() != pixel_count {
+                self.winners = vec![u32::MAX; pixel_count];
+            }
+            for (i, (fragment, _)) in fragments.iter().enumerate() {
+                let x = fragment.position.x as i32;
+                let y = fragment.position.y as i32;
+                if x < 0 || x >= depth_view.width || y < 0 || y >= depth_view.height {
+                    continue;
+                }
+                let pixel = (y * depth_view.width + x) as usize;
+                let winner = self.winners[pixel];
+                if winner == u32::MAX {
+                    self.winners[pixel] = i as u32;
+                    self.touched.push(pixel);
+                } else if fragment.depth < fragments[winner as usize].0.depth {
+                    self.winners[pixel] = i as u32;
+                }
+            }
+            let visible: Vec<usize> = self.touched.iter().map(|&pixel| self.winners[pixel] as usize).collect();
+            for &pixel in &self.touched {
+                self.winners[pixel] = u32::MAX;
+            }
+            self.touched.clear();
+            stats.fragments_depth_rejected += (fragments.len() - visible.len()) as u32;
+            visible
+        };
+
+        // Fragment Processing Stage e iluminación: solo los fragmentos visibles, en paralelo
+        let triangles = &self.triangles;
+        let colors: Vec<Vector3> = {
+            profile_scope!("fragment");
+            visible
+                .par_iter()
+                .with_min_len(256)
+                .map(|&i| {
+                    let (fragment, index) = &fragments[i];
+                    let draw = &draws[triangles[*index].1];
+                    let color = shaders.shade(fragment, &draw.uniforms, &draw.shader, params);
+                    apply_lighting(color, fragment, &draw.surface, draw.uniforms.camera_position, lights, shadow, &params.lighting)
+                })
+                .collect()
+        };
+
+        // Escritura al framebuffer en serie
+        profile_scope!("write");
+        let record_sources = framebuffer.is_recording_sources();
+        for (&i, final_color) in visible.iter().zip(colors) {
+            let (fragment, index) = &fragments[i];
+            let x = fragment.position.x as i32;
+            let y = fragment.position.y as i32;
+            let written = if record_sources {
+                let draw = &draws[triangles[*index].1];
+                let source = PixelSource {
+                    label: draw.label.clone(),
+                    triangle: index - draw.first_triangle,
+                    color: final_color,
+                    normal: fragment.normal,
+                    world_position: fragment.world_position,
+                };
+                framebuffer.point_from(x, y, final_color, fragment.depth, source)
+            } else {
+                framebuffer.point(
+                    x,
+                    y,
+                    final_color, //poner fragment.color si no se quiere nada de shading 
+                    fragment.depth,
+                )
+            };
+            stats.pixels_written += written as u32;
+        }
+
+        if self.wireframe {
+            profile_scope!("wireframe");
+            for &([a, b, c], draw) in &self.triangles {
+                if !self.draws[draw].visible || self.draws[draw].traced.is_some() {
+                    continue;
+                }
+                for (from, to) in [(a, b), (b, c), (c, a)] {
+                    for fragment in line(&self.vertices[from], &self.vertices[to]) {
+                        framebuffer.point(fragment.position.x as i32, fragment.position.y as i32, WIREFRAME_COLOR, fragment.depth - WIREFRAME_DEPTH_BIAS);
+                    }
+                }
+            }
+        }
+
+        self.draws.clear();
+        self.vertices.clear();
+        self.triangles.clear();
+        stats
+    }
+}
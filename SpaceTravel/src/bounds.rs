@@ -0,0 +1,84 @@
+// bounds.rs
+use crate::matrix::multiply_matrix_vector4;
+use raylib::prelude::*;
+
+/// Axis-aligned bounding box
+#[derive(Clone, Copy, Debug)]
+pub struct Aabb {
+    pub min: Vector3,
+    pub max: Vector3,
+}
+
+/// Bounding sphere enclosing every vertex of a mesh
+#[derive(Clone, Copy, Debug)]
+pub struct BoundingSphere {
+    pub center: Vector3,
+    pub radius: f32,
+}
+
+fn transform_point(point: Vector3, matrix: &Matrix) -> Vector3 {
+    let p = multiply_matrix_vector4(matrix, &Vector4::new(point.x, point.y, point.z, 1.0));
+    Vector3::new(p.x, p.y, p.z)
+}
+
+impl Aabb {
+    /// Smallest box containing all the points (a zero-size box at the origin if there are none)
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Vector3>) -> Self {
+        let mut points = points.into_iter();
+        let Some(&first) = points.next() else {
+            return Aabb { min: Vector3::zero(), max: Vector3::zero() };
+        };
+        points.fold(Aabb { min: first, max: first }, |aabb, p| Aabb {
+            min: Vector3::new(aabb.min.x.min(p.x), aabb.min.y.min(p.y), aabb.min.z.min(p.z)),
+            max: Vector3::new(aabb.max.x.max(p.x), aabb.max.y.max(p.y), aabb.max.z.max(p.z)),
+        })
+    }
+
+    pub fn center(&self) -> Vector3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn size(&self) -> Vector3 {
+        self.max - self.min
+    }
+
+    pub fn corners(&self) -> [Vector3; 8] {
+        let (a, b) = (self.min, self.max);
+        [
+            Vector3::new(a.x, a.y, a.z), Vector3::new(b.x, a.y, a.z),
+            Vector3::new(a.x, b.y, a.z), Vector3::new(b.x, b.y, a.z),
+            Vector3::new(a.x, a.y, b.z), Vector3::new(b.x, a.y, b.z),
+            Vector3::new(a.x, b.y, b.z), Vector3::new(b.x, b.y, b.z),
+        ]
+    }
+
+    /// World-space box enclosing this box after applying the model matrix
+    pub fn transformed(&self, model_matrix: &Matrix) -> Aabb {
+        let corners = self.corners().map(|corner| transform_point(corner, model_matrix));
+        Aabb::from_points(&corners)
+    }
+}
+
+impl BoundingSphere {
+    /// Sphere centered on the box center with the radius of the farthest point
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Vector3> + Clone) -> Self {
+        let center = Aabb::from_points(points.clone()).center();
+        let radius = points.into_iter()
+            .map(|&p| (p - center).length())
+            .fold(0.0, f32::max);
+        BoundingSphere { center, radius }
+    }
+
+    /// World-space sphere after applying the model matrix.
+    /// The radius grows by the largest axis scale, so it stays conservative under non-uniform scaling
+    pub fn transformed(&self, model_matrix: &Matrix) -> BoundingSphere {
+        let m = model_matrix;
+        let scale = Vector3::new(m.m0, m.m1, m.m2).length()
+            .max(Vector3::new(m.m4, m.m5, m.m6).length())
+            .max(Vector3::new(m.m8, m.m9, m.m10).length());
+        BoundingSphere {
+            center: transform_point(self.center, model_matrix),
+            radius: self.radius * scale,
+        }
+    }
+}
@@ -2,9 +2,25 @@
 #![allow(dead_code)]
 
 use raylib::prelude::*;
-use crate::matrix::create_view_matrix;
-use std::f32::consts::PI;
+use crate::matrix::{Quaternion, create_view_matrix};
+use crate::noise::value_noise;
 
+/// Near clipping plane of the perspective projection used by the app and the offline renders
+pub const NEAR_PLANE: f32 = 0.1;
+/// Far clipping plane of that projection; it only sets the depth range, nothing is culled by it
+pub const FAR_PLANE: f32 = 100.0;
+
+// Velocidades con sensibilidad 1
+const ROTATION_SPEED: f32 = 0.05;
+const ZOOM_SPEED: f32 = 0.5;
+const PAN_SPEED: f32 = 0.1;
+
+// Sacudida: desplazamiento máximo (unidades), cuánto se calma por segundo y rapidez del temblor
+const SHAKE_AMPLITUDE: f32 = 0.6;
+const SHAKE_DECAY: f32 = 1.5;
+const SHAKE_FREQUENCY: f32 = 25.0;
+
+#[derive(Clone)]
 pub struct Camera {
     // Camera position/orientation
     pub eye: Vector3,        // Camera position
@@ -14,6 +30,7 @@ pub struct Camera {
     // Orbit camera parameters
     pub yaw: f32,            // Rotation around Y axis (left/right)
     pub pitch: f32,          // Rotation around X axis (up/down)
+    pub roll: f32,           // Rotation around the view axis
     pub distance: f32,       // Distance from target
 
     // Movement speed
@@ -41,25 +58,67 @@ impl Camera {
             up,
             yaw,
             pitch,
+            roll: 0.0,
             distance,
-            rotation_speed: 0.05,
-            zoom_speed: 0.5,
-            pan_speed: 0.1,
+            rotation_speed: ROTATION_SPEED,
+            zoom_speed: ZOOM_SPEED,
+            pan_speed: PAN_SPEED,
         }
     }
 
-    /// Update camera eye position based on yaw, pitch, and distance
+    /// Update camera eye position and up vector based on yaw, pitch, roll, and distance
     fn update_eye_position(&mut self) {
-        // Clamp pitch to avoid gimbal lock
-        self.pitch = self.pitch.clamp(-PI / 2.0 + 0.1, PI / 2.0 - 0.1);
-
-        // Calculate camera position using spherical coordinates
+        // Con un cuaternión el vector arriba gira con la cámara: se puede pasar por encima de los
+        // polos y alabear sin el bloqueo de cardán del lookAt con el Y del mundo.
+        // Sin alabeo coincide con las coordenadas esféricas de siempre:
         // x = distance * cos(pitch) * cos(yaw)
         // y = distance * sin(pitch)
         // z = distance * cos(pitch) * sin(yaw)
-        self.eye.x = self.target.x + self.distance * self.pitch.cos() * self.yaw.cos();
-        self.eye.y = self.target.y + self.distance * self.pitch.sin();
-        self.eye.z = self.target.z + self.distance * self.pitch.cos() * self.yaw.sin();
+        let orientation = self.orientation();
+        self.eye = self.target + orientation.rotate_vector(Vector3::new(self.distance, 0.0, 0.0));
+        self.up = orientation.rotate_vector(Vector3::new(0.0, 1.0, 0.0));
+    }
+
+    /// Orientation for the given angles: yaw around the world Y axis, then pitch, then roll
+    /// around the view axis. It rotates +X onto the direction from the target to the eye
+    pub fn orientation_from_angles(yaw: f32, pitch: f32, roll: f32) -> Quaternion {
+        let yaw = Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), -yaw);
+        let pitch = Quaternion::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), pitch);
+        let roll = Quaternion::from_axis_angle(Vector3::new(1.0, 0.0, 0.0), roll);
+        yaw * pitch * roll
+    }
+
+    /// Current orientation of the camera (see `orientation_from_angles`)
+    pub fn orientation(&self) -> Quaternion {
+        Camera::orientation_from_angles(self.yaw, self.pitch, self.roll)
+    }
+
+    /// Turns the camera to `orientation`, keeping its target and distance
+    pub fn set_orientation(&mut self, orientation: Quaternion) {
+        let back = orientation.rotate_vector(Vector3::new(1.0, 0.0, 0.0));
+        self.pitch = back.y.clamp(-1.0, 1.0).asin();
+        self.yaw = back.z.atan2(back.x);
+        // El alabeo es el giro que queda entre el arriba pedido y el de la cámara nivelada
+        let level = Camera::orientation_from_angles(self.yaw, self.pitch, 0.0);
+        let up = orientation.rotate_vector(Vector3::new(0.0, 1.0, 0.0));
+        let level_up = level.rotate_vector(Vector3::new(0.0, 1.0, 0.0));
+        let level_side = level.rotate_vector(Vector3::new(0.0, 0.0, 1.0));
+        self.roll = up.dot(level_side).atan2(up.dot(level_up));
+        self.update_eye_position();
+    }
+
+    /// Orbit around `target` at `distance`, keeping the current yaw and pitch
+    pub fn look_at(&mut self, target: Vector3, distance: f32) {
+        self.target = target;
+        self.distance = distance;
+        self.update_eye_position();
+    }
+
+    /// Scale the rotation, zoom and pan speeds (1 = default)
+    pub fn set_sensitivity(&mut self, sensitivity: f32) {
+        self.rotation_speed = ROTATION_SPEED * sensitivity;
+        self.zoom_speed = ZOOM_SPEED * sensitivity;
+        self.pan_speed = PAN_SPEED * sensitivity;
     }
 
     /// Get the view matrix for this camera
@@ -69,38 +128,23 @@ impl Camera {
 
     /// Process keyboard input to control the camera
     pub fn process_input(&mut self, window: &RaylibHandle) {
-        // Rotation controls (yaw)
-        if window.is_key_down(KeyboardKey::KEY_A) {
-            self.yaw += self.rotation_speed;
-            self.update_eye_position();
-        }
-        if window.is_key_down(KeyboardKey::KEY_D) {
-            self.yaw -= self.rotation_speed;
-            self.update_eye_position();
-        }
-
-        // Rotation controls (pitch)
-        if window.is_key_down(KeyboardKey::KEY_W) {
-            self.pitch += self.rotation_speed;
-            self.update_eye_position();
-        }
-        if window.is_key_down(KeyboardKey::KEY_S) {
-            self.pitch -= self.rotation_speed;
-            self.update_eye_position();
-        }
+        self.apply_input(&CameraInput::read(window), 1.0);
+    }
 
-        // Zoom controls (distance from target) - arrow keys
-        if window.is_key_down(KeyboardKey::KEY_UP) {
-            self.distance -= self.zoom_speed;
-            if self.distance < 0.5 {
-                self.distance = 0.5; // Prevent camera from going too close
-            }
-            self.update_eye_position();
-        }
-        if window.is_key_down(KeyboardKey::KEY_DOWN) {
-            self.distance += self.zoom_speed;
-            self.update_eye_position();
+    /// Moves the camera by the controls held in `input` during `frames` frames (the speeds are
+    /// per frame at INPUT_RATE frames per second)
+    pub fn apply_input(&mut self, input: &CameraInput, frames: f32) {
+        // Rotation controls (yaw, pitch and roll around the view axis)
+        self.yaw += input.yaw * self.rotation_speed * frames;
+        self.pitch += input.pitch * self.rotation_speed * frames;
+        self.roll += input.roll * self.rotation_speed * frames;
+
+        // Zoom controls (distance from target)
+        self.distance += input.zoom * self.zoom_speed * frames;
+        if input.zoom < 0.0 && self.distance < 0.5 {
+            self.distance = 0.5; // Prevent camera from going too close
         }
+        self.update_eye_position();
 
         // Pan controls (move target/center point)
         // Calculate right and forward vectors for panning
@@ -122,38 +166,81 @@ impl Camera {
             -forward_normalized.x,
         );
 
-        // Q/E keys for horizontal panning
-        if window.is_key_down(KeyboardKey::KEY_Q) {
-            self.target.x += right.x * self.pan_speed;
-            self.target.z += right.z * self.pan_speed;
-            self.update_eye_position();
-        }
-        if window.is_key_down(KeyboardKey::KEY_E) {
-            self.target.x -= right.x * self.pan_speed;
-            self.target.z -= right.z * self.pan_speed;
-            self.update_eye_position();
-        }
+        self.target.x += right.x * input.pan * self.pan_speed * frames;
+        self.target.z += right.z * input.pan * self.pan_speed * frames;
+        self.target.y += input.lift * self.pan_speed * frames;
+        self.update_eye_position();
+    }
+}
 
-        // Left/Right arrow keys for horizontal panning
-        if window.is_key_down(KeyboardKey::KEY_LEFT) {
-            self.target.x += right.x * self.pan_speed;
-            self.target.z += right.z * self.pan_speed;
-            self.update_eye_position();
-        }
-        if window.is_key_down(KeyboardKey::KEY_RIGHT) {
-            self.target.x -= right.x * self.pan_speed;
-            self.target.z -= right.z * self.pan_speed;
-            self.update_eye_position();
-        }
+/// Frames per second the camera speeds are tuned for
+pub const INPUT_RATE: f32 = 60.0;
+
+/// Camera controls held down in a frame, so they can be applied away from the window (on the
+/// simulation thread). Each axis adds +1 or -1 per key
+#[derive(Clone, Copy, Default)]
+pub struct CameraInput {
+    pub yaw: f32,   // A / D
+    pub pitch: f32, // W / S
+    pub roll: f32,  // Z / X
+    pub zoom: f32,  // Flecha abajo / arriba (aleja / acerca)
+    pub pan: f32,   // Q o flecha izquierda / E o flecha derecha
+    pub lift: f32,  // R / F
+}
 
-        // Vertical panning
-        if window.is_key_down(KeyboardKey::KEY_R) {
-            self.target.y += self.pan_speed;
-            self.update_eye_position();
+impl CameraInput {
+    /// Reads the camera keys held down right now
+    pub fn read(window: &RaylibHandle) -> Self {
+        let axis = |positive: &[KeyboardKey], negative: &[KeyboardKey]| {
+            let held = |keys: &[KeyboardKey]| keys.iter().filter(|&&key| window.is_key_down(key)).count() as f32;
+            held(positive) - held(negative)
+        };
+        CameraInput {
+            yaw: axis(&[KeyboardKey::KEY_A], &[KeyboardKey::KEY_D]),
+            pitch: axis(&[KeyboardKey::KEY_W], &[KeyboardKey::KEY_S]),
+            roll: axis(&[KeyboardKey::KEY_Z], &[KeyboardKey::KEY_X]),
+            zoom: axis(&[KeyboardKey::KEY_DOWN], &[KeyboardKey::KEY_UP]),
+            pan: axis(&[KeyboardKey::KEY_Q, KeyboardKey::KEY_LEFT], &[KeyboardKey::KEY_E, KeyboardKey::KEY_RIGHT]),
+            lift: axis(&[KeyboardKey::KEY_R], &[KeyboardKey::KEY_F]),
         }
-        if window.is_key_down(KeyboardKey::KEY_F) {
-            self.target.y -= self.pan_speed;
-            self.update_eye_position();
+    }
+}
+
+/// Short tremble of the camera, e.g. after a collision or the start of a warp
+#[derive(Default)]
+pub struct CameraShake {
+    trauma: f32, // 0..1; se reduce con el tiempo
+}
+
+impl CameraShake {
+    pub fn new() -> Self {
+        CameraShake { trauma: 0.0 }
+    }
+
+    /// Adds `amount` (0..1) to the shake, up to the maximum
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    /// Raises the shake to at least `amount` (0..1)
+    pub fn raise_trauma(&mut self, amount: f32) {
+        self.trauma = self.trauma.max(amount.min(1.0));
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.trauma = (self.trauma - dt * SHAKE_DECAY).max(0.0);
+    }
+
+    /// View matrix of `camera` with the eye and the target moved together by the shake
+    pub fn view_matrix(&self, camera: &Camera, time: f32) -> Matrix {
+        if self.trauma <= 0.0 {
+            return camera.get_view_matrix();
         }
+        // Al cuadrado: los golpes suaves apenas se notan y los fuertes sacuden mucho más
+        let amount = self.trauma * self.trauma * SHAKE_AMPLITUDE;
+        let t = time * SHAKE_FREQUENCY;
+        let offset = Vector3::new(value_noise(t, 0.0, 0.0), value_noise(0.0, t, 0.0), value_noise(0.0, 0.0, t)) * 2.0
+            - Vector3::one();
+        create_view_matrix(camera.eye + offset * amount, camera.target + offset * amount, camera.up)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,104 @@
+// cockpit.rs
+// Vista desde la cabina: el interior (marco de la cúpula y tablero) se construye en el espacio
+// de la cámara y va fijo a ella. Se dibuja en una pasada aparte con su propia proyección de
+// rango cercano y DepthMode::Overlay, así queda delante de todo sin pelear por la profundidad
+// con el sistema, que se ve a través de la cúpula. Sustituye a la nave flotante del HUD
+use space_travel::Model;
+use space_travel::light::Light;
+use space_travel::lighting::Surface;
+use space_travel::matrix::{create_projection_matrix, inverse};
+use space_travel::mesh::Mesh;
+use space_travel::obj::compute_tangents;
+use space_travel::vertex::Vertex;
+use raylib::prelude::*;
+
+// Planos de la proyección del interior: todo está a menos de un par de unidades de la cámara
+const NEAR_PLANE: f32 = 0.05;
+const FAR_PLANE: f32 = 5.0;
+
+// Grosor de los montantes de la cúpula
+const STRUT: f32 = 0.05;
+
+// Luz de cortesía de la cabina, para que el tablero no quede negro de espaldas a la estrella
+const INTERIOR_LIGHT_COLOR: Vector3 = Vector3::new(0.6, 0.75, 1.0);
+const INTERIOR_LIGHT_INTENSITY: f32 = 0.5;
+
+/// The interior of the cockpit, in camera space (looking down -Z, +Y up)
+pub struct Cockpit {
+    mesh: Mesh,
+}
+
+impl Cockpit {
+    pub fn new() -> Self {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        // Tablero: una losa inclinada hacia la cámara en la parte baja de la vista
+        add_beam(&mut vertices, &mut indices, Vector3::new(-1.4, -0.46, -0.9), Vector3::new(1.4, -0.46, -0.9), 0.5, 0.1, Vector3::new(0.0, 1.0, 0.4));
+        // Consola central sobre el tablero
+        add_beam(&mut vertices, &mut indices, Vector3::new(-0.22, -0.4, -0.85), Vector3::new(0.22, -0.4, -0.85), 0.2, 0.08, Vector3::new(0.0, 1.0, 0.6));
+        // Montantes laterales de la cúpula, del tablero hacia el techo
+        add_beam(&mut vertices, &mut indices, Vector3::new(-1.05, -0.42, -0.95), Vector3::new(-0.55, 0.7, -0.9), STRUT, STRUT, Vector3::new(0.0, 0.0, 1.0));
+        add_beam(&mut vertices, &mut indices, Vector3::new(1.05, -0.42, -0.95), Vector3::new(0.55, 0.7, -0.9), STRUT, STRUT, Vector3::new(0.0, 0.0, 1.0));
+        // Travesaño superior
+        add_beam(&mut vertices, &mut indices, Vector3::new(-0.65, 0.52, -0.9), Vector3::new(0.65, 0.52, -0.9), STRUT, STRUT, Vector3::new(0.0, 1.0, 0.0));
+        compute_tangents(&mut vertices, &indices);
+        Cockpit { mesh: Mesh::new(vertices, indices) }
+    }
+
+    /// The interior placed in front of the camera whose view matrix is `view_matrix`
+    pub fn model<'a>(&'a self, view_matrix: &Matrix) -> Model<'a> {
+        Model {
+            mesh: &self.mesh,
+            model_matrix: inverse(view_matrix).unwrap_or_else(Matrix::identity),
+            shader: "cockpit",
+            label: "cockpit",
+            surface: Surface::glossy(0.4, 24.0, 0.6),
+            displacement: 0.0,
+            vertex_animation: "",
+            geometry_shader: "",
+            parts: &[],
+            ray_traced: false,
+            rings: None,
+            sdf: None,
+        }
+    }
+
+    /// Near-range projection the interior is drawn with (field of view `fov`, in radians)
+    pub fn projection(fov: f32, aspect: f32) -> Matrix {
+        create_projection_matrix(fov, aspect, NEAR_PLANE, FAR_PLANE)
+    }
+
+    /// Dim light inside the cockpit, at the eye
+    pub fn interior_light(eye: Vector3) -> Light {
+        Light::new(eye).with_color(INTERIOR_LIGHT_COLOR).with_intensity(INTERIOR_LIGHT_INTENSITY)
+    }
+}
+
+// Añade una viga de sección rectangular de `from` a `to`: `width` de ancho y `height` de alto
+// (en la dirección de `up` que queda perpendicular a la viga), con normales por cara
+fn add_beam(vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>, from: Vector3, to: Vector3, width: f32, height: f32, up: Vector3) {
+    let along = to - from;
+    let direction = along.normalized();
+    let side = direction.cross(up).normalized();
+    let up = side.cross(direction);
+    let center = (from + to) * 0.5;
+    // Ejes en orden diestro (side x direction = up) con la mitad de la viga en cada uno
+    let axes = [(side, width * 0.5), (direction, along.length() * 0.5), (up, height * 0.5)];
+    for axis in 0..3 {
+        let (u, half_u) = axes[(axis + 1) % 3];
+        let (v, half_v) = axes[(axis + 2) % 3];
+        let (normal, half) = axes[axis];
+        for sign in [1.0, -1.0] {
+            let normal = normal * sign;
+            // En la cara opuesta se invierte u para que el giro siga siendo antihorario desde fuera
+            let u = u * sign;
+            let face_center = center + normal * half;
+            let base = vertices.len() as u32;
+            for (du, dv, uv) in [(-1.0, -1.0, (0.0, 0.0)), (1.0, -1.0, (1.0, 0.0)), (1.0, 1.0, (1.0, 1.0)), (-1.0, 1.0, (0.0, 1.0))] {
+                let position = face_center + u * (du * half_u) + v * (dv * half_v);
+                vertices.push(Vertex::new(position, normal, Vector2::new(uv.0, uv.1)));
+            }
+            indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+    }
+}
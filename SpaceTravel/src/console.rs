@@ -0,0 +1,234 @@
+// console.rs
+// Consola de depuración desplegable (tecla ~): una línea de texto que se ejecuta contra un
+// registro de comandos. Cada subsistema registra los suyos (warp.rs "warp", world.rs "set",
+// main.rs "timescale" y "toggle"); la consola solo conoce el registro y el contexto.
+// Además registra los eventos del bus que van ocurriendo, siempre en inglés como los comandos
+use crate::events::{Event, EventBus, Observer};
+use crate::locale::{Language, Strings};
+use crate::ui::blend_rect;
+use crate::warp::Warp;
+use crate::world::World;
+use space_travel::camera::Camera;
+use space_travel::framebuffer::Framebuffer;
+use raylib::prelude::*;
+
+// Líneas de salida que se conservan y fracción de la pantalla que ocupa la consola
+const MAX_OUTPUT_LINES: usize = 200;
+const HEIGHT_FRACTION: f32 = 0.4;
+// Tamaño de letra para un framebuffer de 720 de alto (se escala con él)
+const FONT_SIZE: f32 = 18.0;
+const BACKGROUND: Vector3 = Vector3::new(0.02, 0.03, 0.06);
+const INPUT_COLOR: Color = Color::new(230, 235, 255, 255);
+const OUTPUT_COLOR: Color = Color::new(170, 180, 200, 255);
+const ERROR_COLOR: Color = Color::new(255, 120, 100, 255);
+
+/// State the commands can read and change, borrowed from the main loop while a command runs
+pub struct ConsoleContext<'a> {
+    pub world: &'a mut World,
+    pub camera: &'a mut Camera,
+    pub warp: &'a mut Option<Warp>,
+    pub time: f32,
+    pub time_scale: &'a mut f32,
+    /// Switches `toggle` can flip, by name
+    pub flags: Vec<(&'static str, &'a mut bool)>,
+    /// Where the commands publish what they caused (a warp starting...)
+    pub events: &'a mut EventBus,
+}
+
+// Función de un comando: recibe los argumentos y devuelve el mensaje a mostrar o un error
+type Handler = Box<dyn Fn(&mut ConsoleContext<'_>, &[&str]) -> Result<String, String>>;
+
+struct Command {
+    name: &'static str,
+    usage: &'static str,
+    handler: Handler,
+}
+
+/// Commands the console can run; `help` is always available and lists the rest
+pub struct CommandRegistry {
+    commands: Vec<Command>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        CommandRegistry { commands: Vec::new() }
+    }
+
+    /// Adds a command; `usage` is shown by `help` and when the arguments are wrong.
+    /// A later registration with the same name replaces the earlier one
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        handler: impl Fn(&mut ConsoleContext<'_>, &[&str]) -> Result<String, String> + 'static,
+    ) {
+        self.commands.retain(|command| command.name != name);
+        self.commands.push(Command { name, usage, handler: Box::new(handler) });
+    }
+
+    /// Runs a command line; the result is the text to print
+    pub fn execute(&self, context: &mut ConsoleContext<'_>, line: &str) -> Result<String, String> {
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            return Ok(String::new());
+        };
+        let arguments: Vec<&str> = words.collect();
+        if name == "help" {
+            let mut lines: Vec<&str> = self.commands.iter().map(|command| command.usage).collect();
+            lines.sort_unstable();
+            return Ok(lines.join("\n"));
+        }
+        let command = self.commands.iter().find(|command| command.name == name)
+            .ok_or_else(|| format!("Unknown command '{}' (try 'help')", name))?;
+        (command.handler)(context, &arguments).map_err(|e| format!("{}\nusage: {}", e, command.usage))
+    }
+
+    // Nombres de comando que empiezan por `prefix`, para completar con Tab
+    fn completions(&self, prefix: &str) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = self.commands.iter().map(|command| command.name).chain(["help"])
+            .filter(|name| name.starts_with(prefix))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// Parses the single numeric argument of a command
+pub fn parse_number(arguments: &[&str]) -> Result<f32, String> {
+    match arguments {
+        [value] => value.parse().map_err(|_| format!("'{}' is not a number", value)),
+        _ => Err("Expected one number".to_string()),
+    }
+}
+
+/// Drop-down console: input line, history and output
+pub struct Console {
+    open: bool,
+    input: String,
+    output: Vec<(String, bool)>, // Texto y si es un error
+    history: Vec<String>,
+    history_index: usize,         // history.len() = la línea nueva
+    english: Strings,             // Para describir los eventos
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Console { open: false, input: String::new(), output: Vec::new(), history: Vec::new(), history_index: 0, english: Strings::new(Language::En) }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Opens the console, to show output the player asked for with a key
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// Reads the keyboard: ~ opens and closes the console and, while open, the typed text goes
+    /// to the input line. Returns the line to run when Enter is pressed
+    pub fn update(&mut self, window: &mut RaylibHandle, registry: &CommandRegistry) -> Option<String> {
+        if window.is_key_pressed(KeyboardKey::KEY_GRAVE) {
+            self.open = !self.open;
+        }
+        if !self.open {
+            // Vaciar la cola de caracteres para que lo escrito con la consola cerrada no aparezca al abrirla
+            while window.get_char_pressed().is_some() {}
+            return None;
+        }
+
+        while let Some(character) = window.get_char_pressed() {
+            if character != '`' && character != '~' && !character.is_control() {
+                self.input.push(character);
+            }
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_BACKSPACE) || window.is_key_pressed_repeat(KeyboardKey::KEY_BACKSPACE) {
+            self.input.pop();
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            self.open = false;
+        }
+
+        // Flechas: recorrer los comandos anteriores
+        if window.is_key_pressed(KeyboardKey::KEY_UP) && self.history_index > 0 {
+            self.history_index -= 1;
+            self.input = self.history[self.history_index].clone();
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_DOWN) && self.history_index < self.history.len() {
+            self.history_index += 1;
+            self.input = self.history.get(self.history_index).cloned().unwrap_or_default();
+        }
+
+        // Tab completa el nombre del comando si solo hay una opción, o las lista
+        if window.is_key_pressed(KeyboardKey::KEY_TAB) && !self.input.contains(' ') {
+            match self.completions(registry).as_slice() {
+                [name] => self.input = format!("{} ", name),
+                [] => {}
+                names => self.print(&names.join("  "), false),
+            }
+        }
+
+        if window.is_key_pressed(KeyboardKey::KEY_ENTER) && !self.input.trim().is_empty() {
+            let line = std::mem::take(&mut self.input);
+            self.print(&format!("> {}", line), false);
+            if self.history.last() != Some(&line) {
+                self.history.push(line.clone());
+            }
+            self.history_index = self.history.len();
+            return Some(line);
+        }
+        None
+    }
+
+    fn completions(&self, registry: &CommandRegistry) -> Vec<&'static str> {
+        registry.completions(self.input.trim())
+    }
+
+    /// Runs `line` and prints what it returned
+    pub fn execute(&mut self, registry: &CommandRegistry, context: &mut ConsoleContext<'_>, line: &str) {
+        match registry.execute(context, line) {
+            Ok(message) => self.print(&message, false),
+            Err(message) => self.print(&message, true),
+        }
+    }
+
+    /// Adds `text` to the output, one entry per line; errors are shown in red
+    pub fn print(&mut self, text: &str, error: bool) {
+        self.output.extend(text.lines().map(|line| (line.to_string(), error)));
+        let excess = self.output.len().saturating_sub(MAX_OUTPUT_LINES);
+        self.output.drain(..excess);
+    }
+
+    /// Draws the console over the top of the frame, newest output just above the input line
+    pub fn draw(&self, framebuffer: &mut Framebuffer) {
+        if !self.open {
+            return;
+        }
+        let height = (framebuffer.height as f32 * HEIGHT_FRACTION) as i32;
+        blend_rect(framebuffer, (0, 0, framebuffer.width, height), BACKGROUND, 0.85);
+
+        let font_size = ((FONT_SIZE * framebuffer.height as f32 / 720.0) as i32).max(10);
+        let line_height = font_size + font_size / 4;
+        let margin = font_size / 2;
+        let input_y = height - line_height - margin / 2;
+        let image = &mut framebuffer.color_buffer;
+        image.draw_text(&format!("> {}_", self.input), margin, input_y, font_size, INPUT_COLOR);
+
+        let mut y = input_y - line_height;
+        for (line, error) in self.output.iter().rev() {
+            if y < 0 {
+                break;
+            }
+            image.draw_text(line, margin, y, font_size, if *error { ERROR_COLOR } else { OUTPUT_COLOR });
+            y -= line_height;
+        }
+        image.draw_rectangle(0, height - 1, framebuffer.width, 1, INPUT_COLOR);
+    }
+}
+
+impl Observer for Console {
+    fn on_event(&mut self, event: &Event, world: &World) {
+        let text = event.describe(world, &self.english);
+        self.print(&text, false);
+    }
+}
@@ -0,0 +1,86 @@
+// dust.rs
+// Polvo espacial alrededor de la cámara: partículas fijas en el mundo dentro de una caja que
+// acompaña a la cámara. Se dibujan como estelas en sentido contrario a su velocidad, más largas
+// cuanto más rápido se mueve, para notar el movimiento en el espacio vacío
+use space_travel::framebuffer::Framebuffer;
+use space_travel::matrix::{DepthMode, multiply_matrix_vector4};
+use space_travel::noise::hash;
+use space_travel::shader_params::DustParams;
+use raylib::prelude::*;
+
+/// Dust particles that wrap around a box centered on the camera
+pub struct SpaceDust {
+    // Posiciones dentro de una caja de lado 1; se escalan con `box_size`
+    particles: Vec<Vector3>,
+    previous_eye: Option<Vector3>,
+    velocity: Vector3,
+}
+
+impl SpaceDust {
+    pub fn new() -> Self {
+        SpaceDust { particles: Vec::new(), previous_eye: None, velocity: Vector3::zero() }
+    }
+
+    /// Updates the camera velocity from its movement since the last frame
+    pub fn update(&mut self, eye: Vector3, dt: f32, params: &DustParams) {
+        if self.particles.len() != params.count {
+            self.particles = (0..params.count as u32).map(|i| {
+                let random = |k: u32| (hash(i.wrapping_mul(4).wrapping_add(k) ^ 0x5D_u32) >> 8) as f32 / (1 << 24) as f32;
+                Vector3::new(random(0), random(1), random(2))
+            }).collect();
+        }
+        self.velocity = match self.previous_eye {
+            Some(previous) if dt > 0.0 => (eye - previous) / dt,
+            _ => Vector3::zero(),
+        };
+        self.previous_eye = Some(eye);
+    }
+
+    /// Draws every particle as a streak from where it is to where it was `streak_time`
+    /// seconds ago relative to the camera. Particles leaving the box reappear on the opposite side
+    pub fn draw(&self, framebuffer: &mut Framebuffer, eye: Vector3, view_projection: &Matrix, viewport: &Matrix, depth_mode: DepthMode, params: &DustParams) {
+        let size = params.box_size.max(1.0);
+        let half = size * 0.5;
+        // La estela no pasa de media caja aunque la cámara salte (teletransporte, warp)
+        let mut trail = self.velocity * params.streak_time;
+        let length = trail.length();
+        if length > half {
+            trail *= half / length;
+        }
+
+        let project = |point: Vector3| {
+            let clip = multiply_matrix_vector4(view_projection, &Vector4::new(point.x, point.y, point.z, 1.0));
+            if clip.w <= 0.1 {
+                return None;
+            }
+            let ndc = Vector4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+            let screen = multiply_matrix_vector4(viewport, &ndc);
+            Some((screen.x as i32, screen.y as i32, depth_mode.depth(ndc.z, clip.w)))
+        };
+
+        for particle in &self.particles {
+            // Posición relativa a la cámara, envuelta dentro de la caja
+            let wrap = |offset: f32, eye: f32| (offset * size - eye + half).rem_euclid(size) - half;
+            let relative = Vector3::new(wrap(particle.x, eye.x), wrap(particle.y, eye.y), wrap(particle.z, eye.z));
+            // Más tenue hacia el borde de la caja para que no aparezcan de golpe
+            let fade = 1.0 - relative.length() / half;
+            if fade <= 0.0 {
+                continue;
+            }
+            let Some((x0, y0, depth)) = project(eye + relative) else {
+                continue;
+            };
+            if x0 < 0 || x0 >= framebuffer.width || y0 < 0 || y0 >= framebuffer.height {
+                continue;
+            }
+            // Cola detrás de la cámara o desproporcionada (partícula pegada a la cámara): solo el punto
+            let (x1, y1) = match project(eye + relative + trail) {
+                Some((x, y, _)) if (x - x0).abs() + (y - y0).abs() <= framebuffer.width => (x, y),
+                _ => (x0, y0),
+            };
+            let value = (params.brightness * fade).clamp(0.0, 1.0);
+            let color = Color::new((value * 200.0) as u8, (value * 210.0) as u8, (value * 230.0) as u8, 255);
+            framebuffer.draw_line_with_depth(x0, y0, x1, y1, color, depth);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+// dynamic_resolution.rs
+// Resolución dinámica: con un objetivo de FPS en los ajustes se vigila la duración media de los
+// frames y se baja la escala de render cuando pasa del objetivo, o se sube cuando sobra tiempo,
+// sin salir de MIN_SCALE y de la escala elegida en los ajustes. Entre bajar y subir queda un
+// margen amplio, y tras cada cambio se espera a que la media refleje la nueva resolución, para
+// que la escala no oscile
+
+// Escala mínima y cuánto cambia cada vez
+const MIN_SCALE: f32 = 0.35;
+const STEP: f32 = 0.05;
+
+// Se baja con la media por encima del objetivo por este factor y se sube por debajo de este otro
+const LOWER_ABOVE: f32 = 1.1;
+const RAISE_BELOW: f32 = 0.75;
+
+// Peso de cada frame en la media y segundos de espera tras un cambio
+const SMOOTHING: f32 = 0.1;
+const COOLDOWN: f32 = 1.0;
+
+/// Render scale that follows the frame time towards a target frame rate
+pub struct DynamicResolution {
+    scale: Option<f32>,     // None: la de los ajustes
+    average_frame_time: f32,
+    cooldown: f32,
+}
+
+impl DynamicResolution {
+    pub fn new() -> Self {
+        DynamicResolution { scale: None, average_frame_time: 0.0, cooldown: COOLDOWN }
+    }
+
+    /// The scale in use, given the one from the settings
+    pub fn scale(&self, max_scale: f32) -> f32 {
+        self.scale.unwrap_or(max_scale).min(max_scale)
+    }
+
+    /// Whether the scale is below the one from the settings
+    pub fn is_active(&self, max_scale: f32) -> bool {
+        self.scale(max_scale) < max_scale - STEP * 0.5
+    }
+
+    /// Goes back to the scale of the settings and starts measuring again
+    pub fn reset(&mut self) {
+        *self = DynamicResolution::new();
+    }
+
+    /// Adds the duration of the last frame; returns the new scale when it has to change
+    pub fn update(&mut self, frame_time: f32, target_fps: u32, max_scale: f32) -> Option<f32> {
+        if target_fps == 0 || frame_time <= 0.0 {
+            return None;
+        }
+        self.average_frame_time = if self.average_frame_time > 0.0 {
+            self.average_frame_time + (frame_time - self.average_frame_time) * SMOOTHING
+        } else {
+            frame_time
+        };
+        self.cooldown -= frame_time;
+        if self.cooldown > 0.0 {
+            return None;
+        }
+
+        let target = 1.0 / target_fps as f32;
+        let current = self.scale(max_scale);
+        let next = if self.average_frame_time > target * LOWER_ABOVE {
+            (current - STEP).max(MIN_SCALE.min(max_scale))
+        } else if self.average_frame_time < target * RAISE_BELOW {
+            (current + STEP).min(max_scale)
+        } else {
+            current
+        };
+        if (next - current).abs() < f32::EPSILON {
+            return None;
+        }
+        self.scale = Some(next);
+        self.cooldown = COOLDOWN;
+        Some(next)
+    }
+}
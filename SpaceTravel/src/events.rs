@@ -0,0 +1,115 @@
+// events.rs
+// Bus de eventos: cada subsistema publica lo que pasó (un cuerpo seleccionado, un warp, un
+// choque, un eclipse, una visita o una vuelta a un cuerpo, un evento de un script) y el bucle
+// principal los reparte una vez por frame a los observadores (audio, sacudida de cámara, avisos
+// en pantalla, consola, scripts y misiones).
+// Quien publica no sabe quién reacciona, y añadir una reacción no toca a quien publica
+use crate::locale::Strings;
+use crate::world::World;
+use space_travel::camera::CameraShake;
+
+// Velocidad de choque (unidades/s) que da la sacudida máxima
+const SHAKE_FULL_SPEED: f32 = 30.0;
+
+/// Something that happened during a frame
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// A body was clicked
+    BodySelected { body: usize },
+    /// A warp towards a body began
+    WarpStarted { body: usize },
+    /// The camera ran into a body at `speed` units per second
+    CollisionOccurred { body: usize, speed: f32 },
+    /// `occluder` began covering the star `light`, as seen from the camera
+    EclipseBegan { occluder: usize, light: usize },
+    /// The camera came close to a body for the first time since it left it
+    BodyVisited { body: usize },
+    /// The camera went once around a body, at most `radius` units from its center
+    OrbitCompleted { body: usize, radius: f32 },
+    /// A body script called emit(name)
+    Script { body: usize, name: String },
+}
+
+impl Event {
+    /// Short name of the event, as scripts see it
+    pub fn name(&self) -> &str {
+        match self {
+            Event::BodySelected { .. } => "body_selected",
+            Event::WarpStarted { .. } => "warp_started",
+            Event::CollisionOccurred { .. } => "collision",
+            Event::EclipseBegan { .. } => "eclipse_began",
+            Event::BodyVisited { .. } => "body_visited",
+            Event::OrbitCompleted { .. } => "orbit_completed",
+            Event::Script { name, .. } => name,
+        }
+    }
+
+    /// The body the event is about
+    pub fn body(&self) -> usize {
+        match self {
+            Event::BodySelected { body }
+            | Event::WarpStarted { body }
+            | Event::CollisionOccurred { body, .. }
+            | Event::BodyVisited { body }
+            | Event::OrbitCompleted { body, .. }
+            | Event::Script { body, .. } => *body,
+            Event::EclipseBegan { occluder, .. } => *occluder,
+        }
+    }
+
+    /// Readable description in the language of `strings`, with the names of the bodies involved
+    pub fn describe(&self, world: &World, strings: &Strings) -> String {
+        let name = |entity: usize| world.names.get(entity).map_or("?", String::as_str);
+        let about = |key: &str, body: usize| strings.format(key, &[("body", &name(body))]);
+        match self {
+            Event::BodySelected { body } => about("events.selected", *body),
+            Event::WarpStarted { body } => about("events.warp", *body),
+            Event::CollisionOccurred { body, .. } => about("events.collision", *body),
+            Event::EclipseBegan { occluder, light } => strings.format("events.eclipse", &[("occluder", &name(*occluder)), ("light", &name(*light))]),
+            Event::BodyVisited { body } => about("events.visited", *body),
+            Event::OrbitCompleted { body, radius } => strings.format("events.orbited", &[("body", &name(*body)), ("radius", &format!("{:.1}", radius))]),
+            // El nombre lo eligió el script: no se traduce
+            Event::Script { body, name: event } => format!("[{}] {}", name(*body), event),
+        }
+    }
+}
+
+/// Reacts to the events of a frame
+pub trait Observer {
+    fn on_event(&mut self, event: &Event, world: &World);
+}
+
+/// Queue of the events published since the last dispatch
+pub struct EventBus {
+    pending: Vec<Event>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        EventBus { pending: Vec::new() }
+    }
+
+    pub fn publish(&mut self, event: Event) {
+        self.pending.push(event);
+    }
+
+    /// Hands every pending event, in publishing order, to each observer, and empties the queue
+    pub fn dispatch(&mut self, observers: &mut [&mut dyn Observer], world: &World) {
+        for event in self.pending.drain(..) {
+            for observer in observers.iter_mut() {
+                observer.on_event(&event, world);
+            }
+        }
+    }
+}
+
+// La cámara de la biblioteca no conoce los eventos; la sacudida se decide aquí
+impl Observer for CameraShake {
+    fn on_event(&mut self, event: &Event, _world: &World) {
+        match event {
+            Event::CollisionOccurred { speed, .. } => self.add_trauma(0.3 + 0.7 * (speed / SHAKE_FULL_SPEED).min(1.0)),
+            Event::WarpStarted { .. } => self.raise_trauma(0.3),
+            _ => {}
+        }
+    }
+}
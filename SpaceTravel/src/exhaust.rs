@@ -0,0 +1,134 @@
+// exhaust.rs
+// Partículas de los motores de la nave: nacen en la parte trasera del modelo (la nariz apunta
+// hacia +X), salen hacia atrás y se apagan en poco tiempo. Se suman al color de la escena
+// (mezcla aditiva) respetando la profundidad, sin ocultarse entre ellas
+use space_travel::bounds::Aabb;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::matrix::{DepthMode, multiply_matrix_vector4};
+use space_travel::noise::hash;
+use space_travel::shader_params::ExhaustParams;
+use space_travel::simd::dot;
+use raylib::prelude::*;
+
+// Colores de una partícula recién salida (casi blanca) y al apagarse (naranja)
+const HOT: Vector3 = Vector3::new(1.0, 0.9, 0.65);
+const COOL: Vector3 = Vector3::new(1.0, 0.35, 0.08);
+// Velocidad de la nave (unidades/s) a la que los motores van a pleno rendimiento
+const FULL_THROTTLE_SPEED: f32 = 30.0;
+
+struct Particle {
+    position: Vector3,
+    velocity: Vector3,
+    age: f32,
+    lifetime: f32,
+}
+
+/// Exhaust particles of the ship's engines, simulated in world space
+pub struct Exhaust {
+    particles: Vec<Particle>,
+    previous_center: Option<Vector3>,
+    pending: f32, // Fracción de partícula que quedó por emitir en el frame anterior
+    seed: u32,
+}
+
+impl Exhaust {
+    pub fn new() -> Self {
+        Exhaust { particles: Vec::new(), previous_center: None, pending: 0.0, seed: 0 }
+    }
+
+    // Valor pseudoaleatorio en 0..1; cada llamada avanza la semilla
+    fn random(&mut self) -> f32 {
+        self.seed = self.seed.wrapping_add(1);
+        (hash(self.seed ^ 0xE4) >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// Ages the particles and emits new ones from the two nozzles at the back of `bounds`
+    /// (model space) placed with `model_matrix`. The rate follows the throttle, taken from
+    /// how fast the ship moved since the last frame (for now, its HUD bobbing)
+    pub fn update(&mut self, model_matrix: &Matrix, bounds: &Aabb, dt: f32, params: &ExhaustParams) {
+        let transform = |p: Vector3, w: f32| {
+            let v = multiply_matrix_vector4(model_matrix, &Vector4::new(p.x, p.y, p.z, w));
+            Vector3::new(v.x, v.y, v.z)
+        };
+        let center = transform(bounds.center(), 1.0);
+        let speed = match self.previous_center {
+            Some(previous) if dt > 0.0 => {
+                let moved = center - previous;
+                dot(moved, moved).sqrt() / dt
+            }
+            _ => 0.0,
+        };
+        self.previous_center = Some(center);
+        let throttle = (0.25 + speed / FULL_THROTTLE_SPEED).min(1.0);
+
+        self.age(dt);
+
+        let size = bounds.size();
+        let back = transform(Vector3::new(-1.0, 0.0, 0.0), 0.0);
+        let back = back / dot(back, back).sqrt().max(1e-6);
+        let nozzles = [-0.15, 0.15].map(|side| {
+            transform(Vector3::new(bounds.min.x, bounds.center().y, bounds.center().z + size.z * side), 1.0)
+        });
+
+        self.pending += params.rate * throttle * dt;
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+            for nozzle in nozzles {
+                let spread = Vector3::new(self.random() - 0.5, self.random() - 0.5, self.random() - 0.5) * (params.speed * 0.3);
+                let velocity = back * (params.speed * (0.6 + 0.4 * self.random())) + spread;
+                let lifetime = params.lifetime * (0.5 + 0.5 * self.random());
+                self.particles.push(Particle { position: nozzle, velocity, age: 0.0, lifetime });
+            }
+        }
+    }
+
+    /// Moves the particles and removes the ones that went out, without emitting new ones
+    pub fn age(&mut self, dt: f32) {
+        for particle in &mut self.particles {
+            particle.age += dt;
+            particle.position += particle.velocity * dt;
+        }
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+    }
+
+    /// Emits `count` particles at once from `center` in every direction, as a spark burst
+    pub fn burst(&mut self, center: Vector3, count: usize, params: &ExhaustParams) {
+        for _ in 0..count {
+            let direction = Vector3::new(self.random() - 0.5, self.random() - 0.5, self.random() - 0.5);
+            let direction = direction / dot(direction, direction).sqrt().max(1e-6);
+            let velocity = direction * (params.speed * (1.0 + self.random()));
+            let lifetime = params.lifetime * (1.0 + 2.0 * self.random());
+            self.particles.push(Particle { position: center, velocity, age: 0.0, lifetime });
+        }
+    }
+
+    /// Adds every particle to the frame as a small glowing disc, hidden only by what is in
+    /// front of it
+    pub fn draw(&self, framebuffer: &mut Framebuffer, view_projection: &Matrix, viewport: &Matrix, depth_mode: DepthMode, params: &ExhaustParams) {
+        for particle in &self.particles {
+            let p = particle.position;
+            let clip = multiply_matrix_vector4(view_projection, &Vector4::new(p.x, p.y, p.z, 1.0));
+            if clip.w <= 0.1 {
+                continue;
+            }
+            let ndc = Vector4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+            let screen = multiply_matrix_vector4(viewport, &ndc);
+            let depth = depth_mode.depth(ndc.z, clip.w);
+
+            let life = particle.age / particle.lifetime;
+            let color = (HOT + (COOL - HOT) * life) * (params.brightness * (1.0 - life) * (1.0 - life));
+            // Radio en píxeles según la distancia (la escala vertical del viewport es la mitad del alto)
+            let radius = (params.size * framebuffer.height as f32 * 0.5 / clip.w).clamp(0.5, 6.0);
+            let reach = radius.ceil() as i32;
+            let (cx, cy) = (screen.x as i32, screen.y as i32);
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    let falloff = 1.0 - ((dx * dx + dy * dy) as f32).sqrt() / (radius + 0.5);
+                    if falloff > 0.0 {
+                        framebuffer.add_point(cx + dx, cy + dy, color * falloff, depth);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -10,6 +10,19 @@ pub struct Fragment {
     pub color: Vector3,
     pub depth: f32,
     pub world_position: Vector3,
+    pub object_position: Vector3, // Espacio del modelo: los patrones procedurales giran con el cuerpo
+    pub normal: Vector3,    // Espacio de mundo
+    pub tangent: Vector3,   // Espacio de mundo, para normal mapping
+    pub bitangent: Vector3, // Espacio de mundo, para normal mapping
+    // Material de la cara (ver material.rs)
+    pub diffuse: Vector3,
+    pub specular: Vector3,
+    pub shininess: f32,
+    pub emissive: Vector3,
+    pub material_id: Option<usize>,
+    // Luz interpolada desde los vértices (iluminación Gouraud)
+    pub light_diffuse: Vector3,
+    pub light_specular: Vector3,
 }
 
 impl Fragment {
@@ -19,6 +32,17 @@ impl Fragment {
             color,
             depth,
             world_position,
+            object_position: world_position,
+            normal: Vector3::new(0.0, 0.0, 0.0),
+            tangent: Vector3::new(0.0, 0.0, 0.0),
+            bitangent: Vector3::new(0.0, 0.0, 0.0),
+            diffuse: Vector3::new(1.0, 1.0, 1.0),
+            specular: Vector3::new(0.0, 0.0, 0.0),
+            shininess: 0.0,
+            emissive: Vector3::new(0.0, 0.0, 0.0),
+            material_id: None,
+            light_diffuse: Vector3::new(0.0, 0.0, 0.0),
+            light_specular: Vector3::new(0.0, 0.0, 0.0),
         }
     }
 }
\ No newline at end of file
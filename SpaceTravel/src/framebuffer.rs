@@ -1,4 +1,31 @@
 use raylib::prelude::*;
+use std::sync::Arc;
+
+/// Read-only view of the depth buffer, used by the rasterizer to discard hidden pixels early
+pub struct DepthView<'a> {
+    pub width: i32,
+    pub height: i32,
+    pub depth: &'a [f32],
+}
+
+/// What produced a pixel, recorded only while the pixel inspector is on
+#[derive(Clone)]
+pub struct PixelSource {
+    pub label: Arc<str>,   // Cuerpo o malla que lo dibujó
+    pub triangle: usize,   // Índice del triángulo dentro de su malla
+    pub color: Vector3,
+    pub normal: Vector3,
+    pub world_position: Vector3,
+}
+
+/// Depth of a pixel and, if it came from a mesh, what wrote it
+pub struct PixelInfo<'a> {
+    pub depth: f32,
+    pub source: Option<&'a PixelSource>,
+}
+
+// Tamaño (en píxeles) de los bloques en los que se registra qué se dibujó
+const TILE_SIZE: i32 = 32;
 
 pub struct Framebuffer {
     pub width: i32,
@@ -7,6 +34,19 @@ pub struct Framebuffer {
     background_color: Color,
     current_color: Color,
     depth_buffer: Vec<f32>,
+    // Fondo que restaura el clear: el color de fondo más las capas pintadas con
+    // `add_to_background` (la nebulosa), que solo se repintan al cambiar la cámara
+    background: Vec<Color>,
+    background_painted: bool,
+    // Bloques escritos desde el último clear; solo esos se limpian en el siguiente
+    tiles_x: i32,
+    tiles_y: i32,
+    dirty_tiles: Vec<bool>,
+    full_clear: bool,
+    // Píxeles sueltos sumados al fondo (las estrellas) desde el último clear
+    background_pixels: Vec<usize>,
+    // Procedencia por píxel para el inspector (None cuando está desactivado)
+    sources: Option<Vec<Option<PixelSource>>>,
 }
 
 impl Framebuffer {
@@ -14,6 +54,8 @@ impl Framebuffer {
         let background_color = Color::BLACK; // Un color por defecto
         let color_buffer = Image::gen_image_color(width, height, background_color);
         let depth_buffer = vec![f32::INFINITY; (width * height) as usize];
+        let tiles_x = (width + TILE_SIZE - 1) / TILE_SIZE;
+        let tiles_y = (height + TILE_SIZE - 1) / TILE_SIZE;
         Framebuffer {
             width,
             height,
@@ -21,30 +63,242 @@ impl Framebuffer {
             background_color,
             current_color: Color::WHITE,
             depth_buffer,
+            background: vec![background_color; (width * height) as usize],
+            background_painted: false,
+            tiles_x,
+            tiles_y,
+            dirty_tiles: vec![false; (tiles_x * tiles_y) as usize],
+            full_clear: true,
+            background_pixels: Vec::new(),
+            sources: None,
         }
     }
 
+    pub fn depth_view(&self) -> DepthView<'_> {
+        DepthView { width: self.width, height: self.height, depth: &self.depth_buffer }
+    }
+
+    /// Restores the background and resets depth. Only the tiles written since the last
+    /// clear and the pixels added to the background are touched, unless the whole buffer is
+    /// invalid (first frame, new background color or a repainted background layer)
     pub fn clear(&mut self) {
-        self.color_buffer.clear_background(self.background_color);
-        self.depth_buffer.fill(f32::INFINITY);
+        if let Some(sources) = self.sources.as_mut() {
+            sources.fill(None);
+        }
+
+        if self.full_clear {
+            if self.background_painted {
+                self.restore_background(0, 0, self.width, self.height);
+            } else {
+                self.color_buffer.clear_background(self.background_color);
+            }
+            self.depth_buffer.fill(f32::INFINITY);
+            self.dirty_tiles.fill(false);
+            self.background_pixels.clear();
+            self.full_clear = false;
+            return;
+        }
+
+        for tile_y in 0..self.tiles_y {
+            for tile_x in 0..self.tiles_x {
+                let tile = (tile_y * self.tiles_x + tile_x) as usize;
+                if !self.dirty_tiles[tile] {
+                    continue;
+                }
+                self.dirty_tiles[tile] = false;
+
+                let x0 = tile_x * TILE_SIZE;
+                let y0 = tile_y * TILE_SIZE;
+                let x1 = (x0 + TILE_SIZE).min(self.width);
+                let y1 = (y0 + TILE_SIZE).min(self.height);
+                self.restore_background(x0, y0, x1, y1);
+                for y in y0..y1 {
+                    let row = (y * self.width) as usize;
+                    self.depth_buffer[row + x0 as usize..row + x1 as usize].fill(f32::INFINITY);
+                }
+            }
+        }
+
+        // Las estrellas no escriben profundidad: basta con reponer su color
+        for index in std::mem::take(&mut self.background_pixels) {
+            let (x, y) = (index as i32 % self.width, index as i32 / self.width);
+            self.color_buffer.draw_pixel(x, y, self.background[index]);
+        }
     }
-    
-    pub fn point(&mut self, x: i32, y: i32, color: Vector3, depth: f32) {
-        if x >= 0 && x < self.width && y >= 0 && y < self.height {
-            let index = (y * self.width + x) as usize;
-
-            if depth < self.depth_buffer[index] {
-                self.depth_buffer[index] = depth;
-                let pixel_color = Color::new(
-                    (color.x.clamp(0.0, 1.0) * 255.0) as u8,
-                    (color.y.clamp(0.0, 1.0) * 255.0) as u8,
-                    (color.z.clamp(0.0, 1.0) * 255.0) as u8,
-                    255,
-                );
-                self.color_buffer.draw_pixel(x, y, pixel_color);
+
+    // Copia el fondo guardado en el rectángulo [x0, x1) x [y0, y1)
+    fn restore_background(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        if !self.background_painted {
+            self.color_buffer.draw_rectangle(x0, y0, x1 - x0, y1 - y0, self.background_color);
+            return;
+        }
+        for y in y0..y1 {
+            for x in x0..x1 {
+                self.color_buffer.draw_pixel(x, y, self.background[(y * self.width + x) as usize]);
             }
         }
     }
+
+    /// Starts a new background layer of the plain background color; the next clear restores
+    /// the whole buffer from it
+    pub fn reset_background(&mut self) {
+        self.background.fill(self.background_color);
+        self.background_painted = true;
+        self.full_clear = true;
+    }
+
+    /// Adds `color` to a pixel of the background layer, which every clear restores until the
+    /// next `reset_background`
+    pub fn add_to_background(&mut self, x: i32, y: i32, color: Vector3) {
+        if x < 0 || x >= self.width || y < 0 || y >= self.height {
+            return;
+        }
+        let index = (y * self.width + x) as usize;
+        self.background[index] = add_color(self.background[index], color);
+    }
+
+    /// Whether the background layer holds something painted since the buffer was created
+    /// or its background color changed
+    pub fn has_painted_background(&self) -> bool {
+        self.background_painted
+    }
+    
+    /// Writes the pixel if it passes the depth test; returns whether it was written
+    pub fn point(&mut self, x: i32, y: i32, color: Vector3, depth: f32) -> bool {
+        let Some(index) = self.write(x, y, color, depth) else {
+            return false;
+        };
+        if let Some(sources) = self.sources.as_mut() {
+            sources[index] = None;
+        }
+        true
+    }
+
+    /// Like `point`, but also records where the pixel came from when the inspector is on
+    pub fn point_from(&mut self, x: i32, y: i32, color: Vector3, depth: f32, source: PixelSource) -> bool {
+        let Some(index) = self.write(x, y, color, depth) else {
+            return false;
+        };
+        if let Some(sources) = self.sources.as_mut() {
+            sources[index] = Some(source);
+        }
+        true
+    }
+
+    /// Mixes `color` into the pixel by `coverage` (0..1) if `depth` passes the depth test;
+    /// only pixels at least half covered write their depth. Returns whether it was drawn
+    pub fn blend_point(&mut self, x: i32, y: i32, color: Vector3, coverage: f32, depth: f32) -> bool {
+        if x < 0 || x >= self.width || y < 0 || y >= self.height {
+            return false;
+        }
+        let index = (y * self.width + x) as usize;
+        if depth >= self.depth_buffer[index] {
+            return false;
+        }
+        if coverage >= 0.5 {
+            self.depth_buffer[index] = depth;
+        }
+        let current = self.color_buffer.get_color(x, y);
+        let mix = |channel: u8, value: f32| {
+            let channel = channel as f32 / 255.0;
+            ((channel + (value.clamp(0.0, 1.0) - channel) * coverage) * 255.0) as u8
+        };
+        self.set_pixel(x, y, Color::new(mix(current.r, color.x), mix(current.g, color.y), mix(current.b, color.z), 255));
+        if let Some(sources) = self.sources.as_mut() {
+            sources[index] = None;
+        }
+        true
+    }
+
+    /// Adds `color` to a pixel nothing was drawn on (depth still at infinity), without
+    /// touching the depth buffer; used for the stars behind the scene. The pixel is put back
+    /// by the next clear without marking its tile
+    pub fn add_background(&mut self, x: i32, y: i32, color: Vector3) {
+        if x < 0 || x >= self.width || y < 0 || y >= self.height {
+            return;
+        }
+        let index = (y * self.width + x) as usize;
+        if self.depth_buffer[index] != f32::INFINITY {
+            return;
+        }
+        self.background_pixels.push(index);
+        let current = self.color_buffer.get_color(x, y);
+        self.color_buffer.draw_pixel(x, y, add_color(current, color));
+    }
+
+    /// Adds `color` to a pixel regardless of depth (overlays drawn on top of the scene)
+    pub fn add_pixel(&mut self, x: i32, y: i32, color: Vector3) {
+        if x < 0 || x >= self.width || y < 0 || y >= self.height {
+            return;
+        }
+        let current = self.color_buffer.get_color(x, y);
+        self.set_pixel(x, y, add_color(current, color));
+    }
+
+    /// Adds `color` to a pixel if `depth` passes the depth test, without writing the depth
+    /// (additive particles glow over the scene and never hide each other)
+    pub fn add_point(&mut self, x: i32, y: i32, color: Vector3, depth: f32) {
+        if x < 0 || x >= self.width || y < 0 || y >= self.height {
+            return;
+        }
+        if depth >= self.depth_buffer[(y * self.width + x) as usize] {
+            return;
+        }
+        self.add_pixel(x, y, color);
+    }
+
+    /// Replaces the color of a pixel without touching the depth buffer (post-processing).
+    /// The tile is marked so the next clear restores it
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: Color) {
+        if x < 0 || x >= self.width || y < 0 || y >= self.height {
+            return;
+        }
+        self.dirty_tiles[((y / TILE_SIZE) * self.tiles_x + x / TILE_SIZE) as usize] = true;
+        self.color_buffer.draw_pixel(x, y, color);
+    }
+
+    // Escribe el píxel si pasa la prueba de profundidad y devuelve su índice
+    fn write(&mut self, x: i32, y: i32, color: Vector3, depth: f32) -> Option<usize> {
+        if x < 0 || x >= self.width || y < 0 || y >= self.height {
+            return None;
+        }
+        let index = (y * self.width + x) as usize;
+        if depth >= self.depth_buffer[index] {
+            return None;
+        }
+
+        self.depth_buffer[index] = depth;
+        self.dirty_tiles[((y / TILE_SIZE) * self.tiles_x + x / TILE_SIZE) as usize] = true;
+        let pixel_color = Color::new(
+            (color.x.clamp(0.0, 1.0) * 255.0) as u8,
+            (color.y.clamp(0.0, 1.0) * 255.0) as u8,
+            (color.z.clamp(0.0, 1.0) * 255.0) as u8,
+            255,
+        );
+        self.color_buffer.draw_pixel(x, y, pixel_color);
+        Some(index)
+    }
+
+    /// Turns per-pixel provenance recording on or off (it costs memory and a write per pixel)
+    pub fn record_sources(&mut self, enabled: bool) {
+        self.sources = enabled.then(|| vec![None; (self.width * self.height) as usize]);
+    }
+
+    pub fn is_recording_sources(&self) -> bool {
+        self.sources.is_some()
+    }
+
+    /// Depth and provenance of the pixel at (x, y) in the last rendered frame
+    pub fn inspect(&self, x: i32, y: i32) -> Option<PixelInfo<'_>> {
+        if x < 0 || x >= self.width || y < 0 || y >= self.height {
+            return None;
+        }
+        let index = (y * self.width + x) as usize;
+        Some(PixelInfo {
+            depth: self.depth_buffer[index],
+            source: self.sources.as_ref().and_then(|sources| sources[index].as_ref()),
+        })
+    }
     
     // Método para dibujar una línea con profundidad específica
     pub fn draw_line_with_depth(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color, depth: f32) {
@@ -87,18 +341,90 @@ impl Framebuffer {
     }
     
     pub fn set_background_color(&mut self, color: Color) {
+        if color != self.background_color {
+            self.background.fill(color);
+            self.background_painted = false;
+            self.full_clear = true;
+        }
         self.background_color = color;
     }
 
+    pub fn background_color(&self) -> Color {
+        self.background_color
+    }
+
     pub fn set_current_color(&mut self, color: Color) {
         self.current_color = color;
     }
 
-    pub fn swap_buffers(&self, d: &mut RaylibHandle, thread: &RaylibThread) {
+    /// Presents the color buffer scaled to fill the window width; `overlay` lines are drawn on
+    /// top as debug text
+    pub fn swap_buffers(&self, d: &mut RaylibHandle, thread: &RaylibThread, overlay: &[String]) {
         if let Ok(texture) = d.load_texture_from_image(thread, &self.color_buffer) {
+            let scale = d.get_screen_width() as f32 / self.width as f32;
             let mut d = d.begin_drawing(thread);
             d.clear_background(self.background_color);
-            d.draw_texture(&texture, 0, 0, Color::WHITE);
+            d.draw_texture_ex(&texture, Vector2::zero(), 0.0, scale, Color::WHITE);
+            for (i, line) in overlay.iter().enumerate() {
+                d.draw_text(line, 10, 10 + i as i32 * 20, 18, Color::LIGHTGRAY);
+            }
         }
     } 
-}
\ No newline at end of file
+}
+
+// Suma un color (0..1 por canal) a un píxel, saturando en 255
+fn add_color(current: Color, color: Vector3) -> Color {
+    let add = |channel: u8, value: f32| (channel as f32 + value.max(0.0) * 255.0).min(255.0) as u8;
+    Color::new(add(current.r, color.x), add(current.g, color.y), add(current.b, color.z), 255)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Framebuffer de 64x64 (2x2 bloques) ya limpio por primera vez
+    fn cleared() -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(64, 64);
+        framebuffer.clear();
+        framebuffer
+    }
+
+    #[test]
+    fn clear_touches_only_the_written_tile() {
+        let mut framebuffer = cleared();
+        assert!(framebuffer.point(5, 5, Vector3::one(), 0.5));
+        // Marca pintada por fuera del registro en otro bloque: un clear completo la borraría
+        framebuffer.color_buffer.draw_pixel(40, 40, Color::RED);
+
+        framebuffer.clear();
+        assert!(framebuffer.color_buffer.get_color(5, 5) == Color::BLACK);
+        assert_eq!(framebuffer.depth_view().depth[5 * 64 + 5], f32::INFINITY);
+        assert!(framebuffer.color_buffer.get_color(40, 40) == Color::RED);
+    }
+
+    #[test]
+    fn stars_are_restored_without_dirtying_their_tile() {
+        let mut framebuffer = cleared();
+        framebuffer.add_background(3, 3, Vector3::one());
+        framebuffer.color_buffer.draw_pixel(10, 10, Color::RED);
+        assert!(framebuffer.dirty_tiles.iter().all(|&dirty| !dirty));
+
+        framebuffer.clear();
+        assert!(framebuffer.color_buffer.get_color(3, 3) == Color::BLACK);
+        assert!(framebuffer.color_buffer.get_color(10, 10) == Color::RED);
+    }
+
+    #[test]
+    fn clear_restores_the_painted_background() {
+        let mut framebuffer = cleared();
+        framebuffer.reset_background();
+        framebuffer.add_to_background(50, 2, Vector3::new(0.0, 0.0, 1.0));
+        framebuffer.clear();
+        assert!(framebuffer.color_buffer.get_color(50, 2) == Color::new(0, 0, 255, 255));
+
+        // Lo que tapa la escena vuelve a la capa de fondo, no al color liso
+        assert!(framebuffer.point(50, 2, Vector3::one(), 0.5));
+        framebuffer.clear();
+        assert!(framebuffer.color_buffer.get_color(50, 2) == Color::new(0, 0, 255, 255));
+    }
+}
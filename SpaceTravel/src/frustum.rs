@@ -0,0 +1,113 @@
+// frustum.rs
+use crate::bounds::BoundingSphere;
+use crate::matrix::multiply_matrices;
+use raylib::prelude::*;
+
+/// Plane in the form `normal · p + distance = 0`; points with a positive value are inside
+#[derive(Clone, Copy, Debug)]
+pub struct Plane {
+    pub normal: Vector3,
+    pub distance: f32,
+}
+
+impl Plane {
+    fn new(a: f32, b: f32, c: f32, d: f32) -> Self {
+        // Normalizar para que signed_distance devuelva unidades de mundo
+        let length = (a * a + b * b + c * c).sqrt();
+        Plane {
+            normal: Vector3::new(a / length, b / length, c / length),
+            distance: d / length,
+        }
+    }
+
+    pub fn signed_distance(&self, point: Vector3) -> f32 {
+        self.normal.x * point.x + self.normal.y * point.y + self.normal.z * point.z + self.distance
+    }
+}
+
+/// View frustum in world space: left, right, bottom, top and near planes. There is no far
+/// plane: the rasterizer still draws what lies beyond it, so it must not be culled
+#[derive(Clone, Copy, Debug)]
+pub struct Frustum {
+    pub planes: [Plane; 5],
+}
+
+impl Frustum {
+    /// Extracts the planes from the view and projection matrices of the camera
+    pub fn from_matrices(view_matrix: &Matrix, projection_matrix: &Matrix) -> Self {
+        Frustum::from_clip_matrix(&multiply_matrices(projection_matrix, view_matrix))
+    }
+
+    /// Extracts the planes from a combined projection * view matrix (Gribb-Hartmann)
+    pub fn from_clip_matrix(m: &Matrix) -> Self {
+        // Filas de la matriz (Matrix de raylib guarda columnas)
+        let row0 = [m.m0, m.m4, m.m8, m.m12];
+        let row1 = [m.m1, m.m5, m.m9, m.m13];
+        let row2 = [m.m2, m.m6, m.m10, m.m14];
+        let row3 = [m.m3, m.m7, m.m11, m.m15];
+
+        let plane = |row: [f32; 4], sign: f32| Plane::new(
+            row3[0] + sign * row[0],
+            row3[1] + sign * row[1],
+            row3[2] + sign * row[2],
+            row3[3] + sign * row[3],
+        );
+
+        Frustum {
+            planes: [
+                plane(row0, 1.0),  // left
+                plane(row0, -1.0), // right
+                plane(row1, 1.0),  // bottom
+                plane(row1, -1.0), // top
+                plane(row2, 1.0),  // near
+            ],
+        }
+    }
+
+    /// Conservative test: true if the sphere is at least partially inside the frustum
+    pub fn intersects_sphere(&self, center: Vector3, radius: f32) -> bool {
+        self.planes.iter().all(|plane| plane.signed_distance(center) >= -radius)
+    }
+
+    pub fn intersects_bounding_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.intersects_sphere(sphere.center, sphere.radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::{FAR_PLANE, NEAR_PLANE};
+    use crate::matrix::{create_projection_matrix, create_view_matrix};
+    use std::f32::consts::PI;
+
+    // Cámara en el origen mirando hacia -Z
+    fn frustum() -> Frustum {
+        let view = create_view_matrix(Vector3::zero(), Vector3::new(0.0, 0.0, -1.0), Vector3::new(0.0, 1.0, 0.0));
+        let projection = create_projection_matrix(PI / 3.0, 16.0 / 9.0, NEAR_PLANE, FAR_PLANE);
+        Frustum::from_matrices(&view, &projection)
+    }
+
+    #[test]
+    fn sphere_in_front_is_inside() {
+        assert!(frustum().intersects_sphere(Vector3::new(0.0, 0.0, -10.0), 1.0));
+    }
+
+    #[test]
+    fn sphere_behind_is_outside() {
+        assert!(!frustum().intersects_sphere(Vector3::new(0.0, 0.0, 10.0), 1.0));
+    }
+
+    #[test]
+    fn sphere_to_the_side_is_outside_unless_it_reaches_in() {
+        let frustum = frustum();
+        assert!(!frustum.intersects_sphere(Vector3::new(50.0, 0.0, -10.0), 1.0));
+        assert!(frustum.intersects_sphere(Vector3::new(50.0, 0.0, -10.0), 45.0));
+    }
+
+    #[test]
+    fn far_plane_does_not_cull() {
+        // Sin plano lejano: lo que está más allá de FAR_PLANE sigue dentro
+        assert!(frustum().intersects_sphere(Vector3::new(0.0, 0.0, -FAR_PLANE * 10.0), 1.0));
+    }
+}
@@ -0,0 +1,172 @@
+// ghost.rs
+// Repetición de un vuelo anterior: mientras se graba (una contrarreloj, o H) se guarda cada poco
+// la posición y la dirección de la cámara, y a la vez se reproduce la grabación anterior como
+// una nave "fantasma" que vuela a su lado. El fantasma es translúcido: sus aristas se suman al
+// color de la escena con prueba de profundidad pero sin escribirla, como las partículas. La
+// grabación que se conserva se escribe en un archivo para compararla en otra ejecución
+use space_travel::framebuffer::Framebuffer;
+use space_travel::matrix::{DepthMode, Quaternion, create_model_matrix_from_quaternion, multiply_matrices, multiply_matrix_vector4};
+use space_travel::mesh::Mesh;
+use raylib::prelude::*;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+// Segundos entre muestras de la grabación
+const SAMPLE_INTERVAL: f32 = 0.1;
+
+// Tamaño de la nave fantasma (el de la nave del HUD) y color que suma cada arista
+const GHOST_SCALE: f32 = 2.0;
+const GHOST_COLOR: Vector3 = Vector3::new(0.25, 0.55, 0.7);
+
+#[derive(Clone, Copy)]
+struct Sample {
+    time: f32,
+    position: Vector3,
+    forward: Vector3,
+}
+
+/// Records the camera path and plays the previous recording back as a ghost ship
+pub struct Ghost {
+    recording: Option<Vec<Sample>>,
+    replay: Vec<Sample>,
+    clock: f32,
+    edges: Vec<(Vector3, Vector3)>, // Aristas de la nave en espacio de modelo
+}
+
+impl Ghost {
+    /// A ghost drawn with the edges of `mesh`; starts with the recording saved at `path`, if any
+    pub fn new(mesh: &Mesh, path: impl AsRef<Path>) -> Self {
+        let mut seen = HashSet::new();
+        let mut edges = Vec::new();
+        for face in mesh.indices.chunks_exact(3) {
+            for (a, b) in [(face[0], face[1]), (face[1], face[2]), (face[2], face[0])] {
+                if seen.insert((a.min(b), a.max(b))) {
+                    edges.push((mesh.vertices[a as usize].position, mesh.vertices[b as usize].position));
+                }
+            }
+        }
+        let path = path.as_ref();
+        let replay = if path.exists() {
+            load(path).unwrap_or_else(|e| {
+                eprintln!("Ignoring ghost recording: {}", e);
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+        Ghost { recording: None, replay, clock: 0.0, edges }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Length in seconds of the recording played back, if there is one
+    pub fn replay_duration(&self) -> Option<f32> {
+        self.replay.last().map(|sample| sample.time)
+    }
+
+    /// Starts a new recording and the playback of the previous one from its beginning
+    pub fn start(&mut self) {
+        self.recording = Some(Vec::new());
+        self.clock = 0.0;
+    }
+
+    /// Ends the recording; with `keep` it becomes the ghost of the next one and is saved at `path`
+    pub fn stop(&mut self, keep: bool, path: impl AsRef<Path>) {
+        let Some(recording) = self.recording.take() else {
+            return;
+        };
+        if !keep || recording.len() < 2 {
+            return;
+        }
+        self.replay = recording;
+        if let Err(e) = save(&self.replay, path) {
+            eprintln!("Failed to save ghost recording: {}", e);
+        }
+    }
+
+    /// Advances the clock and samples the camera at `eye` looking along `forward`
+    pub fn update(&mut self, eye: Vector3, forward: Vector3, dt: f32) {
+        let Some(recording) = self.recording.as_mut() else {
+            return;
+        };
+        if recording.last().is_none_or(|last| self.clock - last.time >= SAMPLE_INTERVAL) {
+            recording.push(Sample { time: self.clock, position: eye, forward: forward.normalized() });
+        }
+        self.clock += dt;
+    }
+
+    // Posición y dirección del fantasma ahora: entre las dos muestras que rodean al reloj
+    fn pose(&self) -> Option<(Vector3, Vector3)> {
+        if self.recording.is_none() || self.replay.len() < 2 {
+            return None;
+        }
+        let next = self.replay.iter().position(|sample| sample.time > self.clock)?;
+        let (a, b) = (self.replay[next.max(1) - 1], self.replay[next.max(1)]);
+        let t = ((self.clock - a.time) / (b.time - a.time).max(f32::EPSILON)).clamp(0.0, 1.0);
+        let forward = a.forward.lerp(b.forward, t);
+        Some((a.position.lerp(b.position, t), if forward.length() > 1e-4 { forward.normalized() } else { a.forward }))
+    }
+
+    /// Adds the ghost ship to the frame while a recording is playing back
+    pub fn draw(&self, framebuffer: &mut Framebuffer, view_projection: &Matrix, viewport: &Matrix, depth_mode: DepthMode) {
+        let Some((position, forward)) = self.pose() else {
+            return;
+        };
+        // Misma orientación que la nave del HUD mirando hacia `forward`, sin el balanceo
+        let rotation = Vector3::new((-forward.y).clamp(-1.0, 1.0).asin(), forward.x.atan2(forward.z), 0.0);
+        let model_matrix = create_model_matrix_from_quaternion(position, GHOST_SCALE, Quaternion::from_euler(rotation));
+        let transform = multiply_matrices(view_projection, &model_matrix);
+        let project = |point: Vector3| {
+            let clip = multiply_matrix_vector4(&transform, &Vector4::new(point.x, point.y, point.z, 1.0));
+            if clip.w <= 0.1 {
+                return None;
+            }
+            let ndc = Vector4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+            let screen = multiply_matrix_vector4(viewport, &ndc);
+            Some((screen.x, screen.y, depth_mode.depth(ndc.z, clip.w)))
+        };
+
+        for &(start, end) in &self.edges {
+            let (Some((x0, y0, depth0)), Some((x1, y1, depth1))) = (project(start), project(end)) else {
+                continue;
+            };
+            // Aristas que cruzan media pantalla o más están pegadas al plano cercano
+            let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil();
+            if steps > framebuffer.width as f32 {
+                continue;
+            }
+            let steps = steps.max(1.0) as i32;
+            for step in 0..=steps {
+                let t = step as f32 / steps as f32;
+                let depth = depth0 + (depth1 - depth0) * t;
+                framebuffer.add_point((x0 + (x1 - x0) * t).round() as i32, (y0 + (y1 - y0) * t).round() as i32, GHOST_COLOR, depth);
+            }
+        }
+    }
+}
+
+// Una muestra por línea: tiempo, posición y dirección separados por comas
+fn save(samples: &[Sample], path: impl AsRef<Path>) -> Result<(), String> {
+    let path = path.as_ref();
+    let mut text = String::from("time,x,y,z,forward_x,forward_y,forward_z\n");
+    for sample in samples {
+        let (p, f) = (sample.position, sample.forward);
+        text.push_str(&format!("{},{},{},{},{},{},{}\n", sample.time, p.x, p.y, p.z, f.x, f.y, f.z));
+    }
+    fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn load(path: &Path) -> Result<Vec<Sample>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    text.lines().skip(1).enumerate().filter(|(_, line)| !line.trim().is_empty()).map(|(index, line)| {
+        let values = line.split(',').map(|value| value.trim().parse::<f32>()).collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("{}:{}: {}", path.display(), index + 2, e))?;
+        let [time, x, y, z, fx, fy, fz] = values[..] else {
+            return Err(format!("{}:{}: expected 7 values", path.display(), index + 2));
+        };
+        Ok(Sample { time, position: Vector3::new(x, y, z), forward: Vector3::new(fx, fy, fz) })
+    }).collect()
+}
@@ -0,0 +1,200 @@
+// impostor.rs
+// Impostores para los cuerpos a media distancia: de vez en cuando el modelo se dibuja solo en
+// una imagen pequeña fuera de pantalla, y entre una captura y la siguiente se pega esa imagen
+// de frente a la cámara (billboard) en lugar de pasar su malla por el pipeline. Se vuelve a
+// capturar cada REFRESH_FRAMES dibujos, o antes si la vista del cuerpo cambió bastante
+use crate::batch::RenderBatch;
+use crate::bounds::BoundingSphere;
+use crate::framebuffer::Framebuffer;
+use crate::light::Light;
+use crate::matrix::{DepthMode, create_projection_matrix, create_view_matrix, create_viewport_matrix};
+use crate::renderer::Model;
+use crate::shader_params::ShaderParams;
+use crate::shader_registry::ShaderRegistry;
+use crate::shadow::Shadows;
+use crate::sprite::project_bounds;
+use crate::stats::PipelineStats;
+use crate::uniforms::Uniforms;
+use raylib::prelude::*;
+use std::collections::HashMap;
+
+/// Models whose projected radius is below this many pixels (and above the point sprites)
+/// are drawn as impostors
+pub const IMPOSTOR_RADIUS: f32 = 24.0;
+
+// Dibujos que puede durar una captura, cuánto puede girar la dirección desde el cuerpo a la
+// cámara (coseno del ángulo) y cuánto puede cambiar su tamaño en pantalla antes de repetirla
+const REFRESH_FRAMES: u64 = 12;
+const MIN_VIEW_ALIGNMENT: f32 = 0.998;
+const MAX_SIZE_CHANGE: f32 = 1.25;
+
+// Margen alrededor de la esfera envolvente en la imagen, y lado mínimo y máximo de esta
+const MARGIN: f32 = 1.1;
+const MIN_SIZE: i32 = 8;
+const MAX_SIZE: i32 = 64;
+
+/// Where a model lands on screen this frame, and the view it is seen from
+pub struct Placement {
+    center: Vector2, // En pantalla
+    depth: f32,
+    radius: f32,     // En píxeles
+    world_center: Vector3,
+    world_radius: f32,
+    direction: Vector3, // Del cuerpo a la cámara
+    distance: f32,
+}
+
+/// A model waiting to be captured before its impostor is drawn
+pub struct Capture<'a> {
+    pub model: &'a Model<'a>,
+    pub uniforms: Uniforms,
+    pub placement: Placement,
+}
+
+// Imagen capturada de un modelo
+struct Impostor {
+    size: i32,
+    texels: Vec<Option<Vector3>>, // None = transparente
+    direction: Vector3,           // Del cuerpo a la cámara en la captura
+    radius: f32,                  // Radio en pantalla en la captura
+    captured: u64,
+    used: u64,
+}
+
+/// Captured images of the models drawn as impostors, by label, and what renders them
+pub struct ImpostorCache {
+    impostors: HashMap<String, Impostor>,
+    batch: RenderBatch,
+    framebuffer: Framebuffer, // Imagen fuera de pantalla; se usa su esquina de size x size
+    frame: u64,
+}
+
+impl Default for ImpostorCache {
+    fn default() -> Self {
+        ImpostorCache::new()
+    }
+}
+
+impl ImpostorCache {
+    pub fn new() -> Self {
+        ImpostorCache {
+            impostors: HashMap::new(),
+            batch: RenderBatch::new(),
+            framebuffer: Framebuffer::new(MAX_SIZE, MAX_SIZE),
+            frame: 0,
+        }
+    }
+
+    /// Starts a new frame: captures age by one
+    pub fn begin_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Where to draw a model bounded by `bounds` (world space) as an impostor, if it is in
+    /// front of the camera and its projected radius falls between `min_radius` and
+    /// IMPOSTOR_RADIUS pixels
+    pub fn place(bounds: &BoundingSphere, uniforms: &Uniforms, min_radius: f32) -> Option<Placement> {
+        let (center, depth, radius) = project_bounds(bounds, uniforms)?;
+        let offset = uniforms.camera_position - bounds.center;
+        let distance = offset.length();
+        if radius < min_radius || radius >= IMPOSTOR_RADIUS || distance <= bounds.radius {
+            return None;
+        }
+        Some(Placement {
+            center,
+            depth,
+            radius,
+            world_center: bounds.center,
+            world_radius: bounds.radius,
+            direction: offset / distance,
+            distance,
+        })
+    }
+
+    /// Whether the image of `label` can still be drawn at `placement`, or it has to be
+    /// captured again first
+    pub fn is_fresh(&self, label: &str, placement: &Placement) -> bool {
+        self.impostors.get(label).is_some_and(|impostor| {
+            let size_change = placement.radius.max(impostor.radius) / placement.radius.min(impostor.radius).max(f32::EPSILON);
+            self.frame - impostor.captured < REFRESH_FRAMES
+                && impostor.direction.dot(placement.direction) >= MIN_VIEW_ALIGNMENT
+                && size_change <= MAX_SIZE_CHANGE
+        })
+    }
+
+    /// Renders the model of `capture` alone, from the camera's direction and framed by its
+    /// bounding sphere, with the lights and shadows of the frame, and keeps the image
+    pub fn capture(&mut self, capture: &Capture, lights: &[Light], shadow: Option<&Shadows>, shaders: &ShaderRegistry, params: &ShaderParams) -> PipelineStats {
+        let placement = &capture.placement;
+        let size = ((placement.radius * MARGIN * 2.0).ceil() as i32).clamp(MIN_SIZE, MAX_SIZE);
+
+        // Cámara en el mismo sitio que la de la escena, mirando al centro del cuerpo, con el
+        // "arriba" de la pantalla (segunda fila de la view matrix) para que la imagen no gire
+        let view_matrix = &capture.uniforms.view_matrix;
+        let up = Vector3::new(view_matrix.m1, view_matrix.m5, view_matrix.m9);
+        let eye = placement.world_center + placement.direction * placement.distance;
+        let half_extent = placement.world_radius * MARGIN;
+        let fov = 2.0 * (half_extent / placement.distance).atan();
+        let near = (placement.distance - half_extent).max(placement.distance * 0.01);
+        let uniforms = Uniforms {
+            view_matrix: create_view_matrix(eye, placement.world_center, up),
+            projection_matrix: create_projection_matrix(fov, 1.0, near, placement.distance + half_extent),
+            viewport_matrix: create_viewport_matrix(0.0, 0.0, size as f32, size as f32),
+            depth_mode: DepthMode::Projective,
+            ..capture.uniforms.clone()
+        };
+
+        self.framebuffer.clear();
+        self.batch.submit(uniforms, capture.model);
+        let stats = self.batch.flush(&mut self.framebuffer, lights, shadow, shaders, params);
+
+        // Lo que no tocó ningún fragmento queda transparente
+        let depth = self.framebuffer.depth_view().depth.to_vec();
+        let mut texels = Vec::with_capacity((size * size) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let covered = depth[(y * self.framebuffer.width + x) as usize] != f32::INFINITY;
+                texels.push(covered.then(|| {
+                    let color = self.framebuffer.color_buffer.get_color(x, y);
+                    Vector3::new(color.r as f32 / 255.0, color.g as f32 / 255.0, color.b as f32 / 255.0)
+                }));
+            }
+        }
+        let impostor = Impostor { size, texels, direction: placement.direction, radius: placement.radius, captured: self.frame, used: self.frame };
+        self.impostors.insert(capture.model.label.to_string(), impostor);
+        stats
+    }
+
+    /// Draws the image of `label` as a camera-facing billboard at `placement`, scaled to its
+    /// current size, at the depth of its center. Returns the number of pixels written
+    pub fn draw(&mut self, label: &str, placement: &Placement, framebuffer: &mut Framebuffer) -> u32 {
+        let Some(impostor) = self.impostors.get_mut(label) else {
+            return 0;
+        };
+        impostor.used = self.frame;
+        let half = placement.radius * MARGIN;
+        let (left, top) = (placement.center.x - half, placement.center.y - half);
+        let scale = impostor.size as f32 / (half * 2.0);
+        let mut written = 0;
+        for y in top.floor() as i32..(top + half * 2.0).ceil() as i32 {
+            for x in left.floor() as i32..(left + half * 2.0).ceil() as i32 {
+                // Muestreo del texel más cercano
+                let u = ((x as f32 + 0.5 - left) * scale) as i32;
+                let v = ((y as f32 + 0.5 - top) * scale) as i32;
+                if u < 0 || u >= impostor.size || v < 0 || v >= impostor.size {
+                    continue;
+                }
+                if let Some(color) = impostor.texels[(v * impostor.size + u) as usize] {
+                    written += framebuffer.point(x, y, color, placement.depth) as u32;
+                }
+            }
+        }
+        written
+    }
+
+    /// Forgets the images that were not drawn this frame
+    pub fn end_frame(&mut self) {
+        let frame = self.frame;
+        self.impostors.retain(|_, impostor| impostor.used == frame);
+    }
+}
@@ -0,0 +1,197 @@
+// instruments.rs
+// Instrumentos del HUD dibujados sobre el framebuffer: la velocidad de la cámara, una cinta con
+// el rumbo, la distancia y la velocidad relativa al cuerpo seleccionado y una escalera de cabeceo
+// como la del horizonte artificial de un avión, medida contra el plano de la eclíptica (XZ, donde
+// orbitan los cuerpos). Las velocidades salen de la diferencia de posiciones entre frames
+use crate::locale::Strings;
+use crate::palette::Palette;
+use crate::world::{Entity, World};
+use space_travel::camera::Camera;
+use space_travel::framebuffer::Framebuffer;
+use raylib::prelude::*;
+
+// Medidas en píxeles para un framebuffer de 720 de alto (se escalan con él)
+const REFERENCE_HEIGHT: f32 = 720.0;
+const TEXT_SIZE: f32 = 18.0;
+const SMALL_TEXT_SIZE: f32 = 14.0;
+const MARGIN: f32 = 20.0;
+const TAPE_WIDTH: f32 = 360.0;
+const TAPE_TICK: f32 = 8.0;
+const LADDER_WIDTH: f32 = 120.0;
+const LADDER_GAP: f32 = 30.0; // Hueco central de cada peldaño
+
+// Grados que abarca la cinta de rumbo, separación de sus marcas y de sus números
+const TAPE_SPAN: f32 = 90.0;
+const TAPE_STEP: i32 = 10;
+const TAPE_LABEL_STEP: i32 = 30;
+
+// Grados entre peldaños de la escalera y cuántos se ven por encima y por debajo del centro
+const LADDER_STEP: i32 = 10;
+const LADDER_RANGE: f32 = 25.0;
+
+// Campo de visión vertical con el que se dibuja la escena, para que la escalera se mueva con ella
+const FIELD_OF_VIEW_DEGREES: f32 = 60.0;
+
+// Qué tan rápido siguen las velocidades mostradas a las medidas (1/s): el paso de la cámara
+// con el teclado es irregular y sin suavizar los números bailan
+const SMOOTHING_RATE: f32 = 6.0;
+
+
+// Cuerpo que siguen los instrumentos y su movimiento medido
+struct Target {
+    body: Entity,
+    position: Vector3,
+    velocity: Vector3,
+}
+
+/// Flight instruments of the HUD and the motion they measure
+pub struct Instruments {
+    previous_eye: Option<Vector3>,
+    velocity: Vector3,
+    target: Option<Target>,
+}
+
+impl Instruments {
+    pub fn new() -> Self {
+        Instruments { previous_eye: None, velocity: Vector3::zero(), target: None }
+    }
+
+    /// Measures how the camera and the body `selected` moved in the last `dt` seconds
+    pub fn update(&mut self, camera: &Camera, world: &World, selected: Option<Entity>, dt: f32) {
+        if dt <= 0.0 {
+            return;
+        }
+        let follow = 1.0 - (-SMOOTHING_RATE * dt).exp();
+        if let Some(previous) = self.previous_eye {
+            self.velocity = self.velocity.lerp((camera.eye - previous) / dt, follow);
+        }
+        self.previous_eye = Some(camera.eye);
+
+        // Al cambiar de cuerpo (o si una recarga lo quitó) la medida empieza de cero
+        let Some(body) = selected.filter(|&body| body < world.transforms.len()) else {
+            self.target = None;
+            return;
+        };
+        let position = world.transforms[body].position;
+        match self.target.as_mut() {
+            Some(target) if target.body == body => {
+                target.velocity = target.velocity.lerp((position - target.position) / dt, follow);
+                target.position = position;
+            }
+            _ => self.target = Some(Target { body, position, velocity: Vector3::zero() }),
+        }
+    }
+
+    /// Measured velocity of the camera
+    pub fn velocity(&self) -> Vector3 {
+        self.velocity
+    }
+
+    /// Position and measured velocity of the selected body
+    pub fn target_motion(&self) -> Option<(Vector3, Vector3)> {
+        self.target.as_ref().map(|target| (target.position, target.velocity))
+    }
+
+    /// Draws the instruments over the frame, for the camera looking from `camera`, with the
+    /// labels in the language of `strings` and the accent colors of `palette`
+    pub fn draw(&self, framebuffer: &mut Framebuffer, camera: &Camera, world: &World, strings: &Strings, palette: &Palette) {
+        let (hud_color, dim_color) = (palette.accent, palette.accent_dim);
+        let unit = framebuffer.height as f32 / REFERENCE_HEIGHT;
+        let px = |value: f32| (value * unit).round() as i32;
+        let (width, height) = (framebuffer.width, framebuffer.height);
+
+        // Ejes de la cámara: adelante, derecha y arriba de la pantalla
+        let forward = (camera.target - camera.eye).normalized();
+        let right = forward.cross(camera.up).normalized();
+        let up = right.cross(forward);
+        let image = &mut framebuffer.color_buffer;
+
+        // Velocidad, abajo a la izquierda
+        let speed = format!("{} {:>8.1} u/s", strings.get("instruments.speed"), self.velocity.length());
+        image.draw_text(&speed, px(MARGIN), height - px(MARGIN + TEXT_SIZE), px(TEXT_SIZE), hud_color);
+
+        // Rumbo sobre la eclíptica, 0° hacia +X y creciendo hacia +Z como los ángulos de las órbitas
+        let heading = forward.z.atan2(forward.x).to_degrees().rem_euclid(360.0);
+        let (center_x, tape_y) = (width / 2, px(MARGIN + SMALL_TEXT_SIZE));
+        let half_tape = px(TAPE_WIDTH) as f32 / 2.0;
+        let degree = px(TAPE_WIDTH) as f32 / TAPE_SPAN;
+        image.draw_line(center_x - half_tape as i32, tape_y, center_x + half_tape as i32, tape_y, dim_color);
+        let first = ((heading - TAPE_SPAN / 2.0) / TAPE_STEP as f32).ceil() as i32 * TAPE_STEP;
+        for mark in (first..=(heading + TAPE_SPAN / 2.0) as i32).step_by(TAPE_STEP as usize) {
+            let x = center_x + ((mark as f32 - heading) * degree) as i32;
+            let labeled = mark.rem_euclid(TAPE_LABEL_STEP) == 0;
+            let tick = px(if labeled { TAPE_TICK } else { TAPE_TICK / 2.0 });
+            image.draw_line(x, tape_y, x, tape_y + tick, hud_color);
+            if labeled {
+                let label = format!("{:03}", mark.rem_euclid(360));
+                image.draw_text(&label, x - px(SMALL_TEXT_SIZE), tape_y + tick + px(2.0), px(SMALL_TEXT_SIZE), dim_color);
+            }
+        }
+        // Marca central y el rumbo exacto encima
+        image.draw_line(center_x, tape_y - px(TAPE_TICK), center_x, tape_y, hud_color);
+        let readout = format!("{} {:03}", strings.get("instruments.heading"), (heading.round() as i32).rem_euclid(360));
+        image.draw_text(&readout, center_x - px(TEXT_SIZE * 2.0), tape_y - px(TAPE_TICK + TEXT_SIZE), px(TEXT_SIZE), hud_color);
+
+        // Escalera de cabeceo: cada peldaño es un ángulo sobre la eclíptica, girado con el alabeo.
+        // El "arriba" del mundo en pantalla da la dirección de la escalera; mirando en vertical
+        // no hay horizonte y se deja derecha
+        let pitch = forward.y.clamp(-1.0, 1.0).asin().to_degrees();
+        let world_up = Vector2::new(right.y, -up.y);
+        let ladder_up = if world_up.length() > 1e-3 { world_up.normalized() } else { Vector2::new(0.0, -1.0) };
+        let ladder_side = Vector2::new(-ladder_up.y, ladder_up.x);
+        let center = Vector2::new(width as f32 / 2.0, height as f32 / 2.0);
+        let pixels_per_degree = height as f32 / FIELD_OF_VIEW_DEGREES;
+        let (half_width, half_gap) = (px(LADDER_WIDTH) as f32 / 2.0, px(LADDER_GAP) as f32 / 2.0);
+        let lowest = ((pitch - LADDER_RANGE) / LADDER_STEP as f32).ceil() as i32 * LADDER_STEP;
+        for rung in (lowest.max(-90)..=((pitch + LADDER_RANGE) as i32).min(90)).step_by(LADDER_STEP as usize) {
+            let middle = center + ladder_up * ((rung as f32 - pitch) * pixels_per_degree);
+            // El horizonte es continuo y más largo; bajo la eclíptica los peldaños son más tenues
+            let (reach, gap, color) = match rung {
+                0 => (half_width * 1.6, 0.0, hud_color),
+                r if r > 0 => (half_width, half_gap, hud_color),
+                _ => (half_width, half_gap, dim_color),
+            };
+            for side in [-1.0, 1.0] {
+                let inner = middle + ladder_side * (gap * side);
+                let outer = middle + ladder_side * (reach * side);
+                image.draw_line_v(inner, outer, color);
+            }
+            if rung != 0 {
+                let label = middle + ladder_side * (half_width + px(6.0) as f32);
+                image.draw_text(&format!("{}", rung), label.x as i32, label.y as i32 - px(SMALL_TEXT_SIZE) / 2, px(SMALL_TEXT_SIZE), color);
+            }
+        }
+        // Retícula fija en el centro
+        let (cx, cy) = (center.x as i32, center.y as i32);
+        image.draw_line(cx - px(12.0), cy, cx - px(4.0), cy, hud_color);
+        image.draw_line(cx + px(4.0), cy, cx + px(12.0), cy, hud_color);
+        image.draw_line(cx, cy + px(4.0), cx, cy + px(8.0), hud_color);
+
+        // Cuerpo seleccionado, abajo a la derecha: distancia a su superficie, velocidad relativa
+        // y con qué rapidez se acerca (positiva) o se aleja la cámara
+        let Some(target) = &self.target else {
+            return;
+        };
+        let Some(name) = world.names.get(target.body) else {
+            return;
+        };
+        let offset = target.position - camera.eye;
+        let distance = offset.length();
+        let radius = world.transforms[target.body].scale * 0.5;
+        let relative = self.velocity - target.velocity;
+        let closing = if distance > 0.0 { relative.dot(offset / distance) } else { 0.0 };
+        let lines = [
+            name.to_uppercase(),
+            format!("{} {:>8.1}", strings.get("instruments.distance"), (distance - radius).max(0.0)),
+            format!("{} {:>8.1} u/s", strings.get("instruments.relative"), relative.length()),
+            format!("{} {:>+8.1} u/s", strings.get("instruments.closing"), closing),
+        ];
+        let line_height = px(TEXT_SIZE + 4.0);
+        let x = width - px(MARGIN + TEXT_SIZE * 9.0);
+        let top = height - px(MARGIN) - line_height * lines.len() as i32;
+        for (index, line) in lines.iter().enumerate() {
+            let color = if index == 0 { hud_color } else { dim_color };
+            image.draw_text(line, x, top + line_height * index as i32, px(TEXT_SIZE), color);
+        }
+    }
+}
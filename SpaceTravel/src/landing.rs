@@ -0,0 +1,189 @@
+// landing.rs
+// Escena en la superficie de un planeta (se aterriza desde el primer plano): un parche curvo de
+// terreno con el relieve del mismo ruido que usa el shader del planeta, que además lo pinta, y
+// en el cielo la estrella y los demás cuerpos en la dirección en que se ven desde ese punto. El
+// parche está fijo en el espacio del planeta, así que al girar este la estrella sale y se pone
+use crate::world::{Entity, World};
+use space_travel::Model;
+use space_travel::camera::Camera;
+use space_travel::light::Light;
+use space_travel::matrix::{create_model_matrix, inverse, multiply_matrix_vector4, new_matrix4};
+use space_travel::mesh::{Mesh, generate_terrain_patch};
+use space_travel::noise::animated_noise;
+use space_travel::shader_params::ShaderParams;
+use raylib::prelude::*;
+
+// Radio del planeta en la escena de superficie; el parche cubre PATCH_HALF_ANGLE radianes a
+// cada lado del punto de aterrizaje (más allá del horizonte)
+const PLANET_RADIUS: f32 = 150.0;
+const PATCH_HALF_ANGLE: f32 = 0.3;
+const PATCH_RESOLUTION: u32 = 96;
+
+// Altura del relieve por unidad de ruido, en radios del planeta
+const RELIEF: f32 = 0.03;
+
+// Altura de la cámara sobre el suelo y límites de su órbita alrededor del punto de aterrizaje
+const EYE_HEIGHT: f32 = 2.0;
+const MIN_DISTANCE: f32 = 2.0;
+const MAX_DISTANCE: f32 = 20.0;
+const MIN_PITCH: f32 = 0.05;
+const MAX_PITCH: f32 = 1.2;
+
+// Los cuerpos del cielo se dibujan a esta distancia (dentro del plano lejano) con su tamaño
+// aparente, pero nunca más pequeños que MIN_SKY_SIZE
+const SKY_DISTANCE: f32 = 80.0;
+const MIN_SKY_SIZE: f32 = 1.0;
+const MAX_SKY_SIZE: f32 = 30.0;
+
+/// The surface of a body seen from the ground
+pub struct Landing {
+    pub body: Entity,
+    direction: Vector3, // Punto de aterrizaje: dirección unitaria en el espacio del planeta
+    ground: f32,        // Relieve en el punto de aterrizaje, en radios
+    terrain: Mesh,
+    frame: Matrix, // Del espacio del planeta al de la superficie (punto de aterrizaje en el origen, Y hacia arriba)
+    pub camera: Camera,
+}
+
+impl Landing {
+    /// Lands on `body` at the point under `eye` (a position in the world). None for bodies
+    /// that can't be landed on: stars and entities that aren't drawn
+    pub fn new(world: &World, body: Entity, eye: Vector3, params: &ShaderParams) -> Option<Self> {
+        let renderable = world.renderables[body].as_ref()?;
+        if world.lights[body].is_some() {
+            return None;
+        }
+        let to_object = inverse(&body_matrix(world, body))?;
+        let local = multiply_matrix_vector4(&to_object, &Vector4::new(eye.x, eye.y, eye.z, 1.0));
+        let local = Vector3::new(local.x, local.y, local.z);
+        let direction = if local.length() > 0.0 { local.normalized() } else { Vector3::new(0.0, 1.0, 0.0) };
+
+        // El mismo patrón principal que pinta el shader, congelado en el tiempo 0
+        let frequency = params.noise_frequency(&renderable.shader);
+        let height = |direction: Vector3| {
+            let p = direction * 0.5;
+            animated_noise(p.x, p.y, p.z, 0.0, frequency) * RELIEF
+        };
+        let ground = height(direction);
+        let terrain = generate_terrain_patch(direction, PATCH_HALF_ANGLE, PATCH_RESOLUTION, height);
+
+        // Base de la superficie: tangent x direction = bitangent (rotación sin reflejo)
+        let axis = if direction.y.abs() < 0.9 { Vector3::new(0.0, 1.0, 0.0) } else { Vector3::new(1.0, 0.0, 0.0) };
+        let tangent = direction.cross(axis).normalized();
+        let bitangent = tangent.cross(direction);
+        // La malla tiene radio 0.5: se escala al radio de la escena y se baja para que el
+        // punto de aterrizaje quede en el origen
+        let s = PLANET_RADIUS * 2.0;
+        let frame = new_matrix4(
+            tangent.x * s,   tangent.y * s,   tangent.z * s,   0.0,
+            direction.x * s, direction.y * s, direction.z * s, -PLANET_RADIUS,
+            bitangent.x * s, bitangent.y * s, bitangent.z * s, 0.0,
+            0.0,             0.0,             0.0,             1.0,
+        );
+
+        let target = Vector3::new(0.0, ground * PLANET_RADIUS + EYE_HEIGHT, 0.0);
+        let camera = Camera::new(target + Vector3::new(0.0, 1.0, 8.0), target, Vector3::new(0.0, 1.0, 0.0));
+        Some(Landing { body, direction, ground, terrain, frame, camera })
+    }
+
+    /// Looks around the landing point (A/D, W/S, arrows to zoom), never below the ground
+    pub fn process_input(&mut self, window: &RaylibHandle) {
+        let target = self.camera.target;
+        self.camera.process_input(window);
+        // El paneo no mueve el punto de aterrizaje, y de pie no se alabea
+        self.camera.pitch = self.camera.pitch.clamp(MIN_PITCH, MAX_PITCH);
+        self.camera.roll = 0.0;
+        self.camera.look_at(target, self.camera.distance.clamp(MIN_DISTANCE, MAX_DISTANCE));
+    }
+
+    /// The terrain and the bodies in the sky, in the space of the surface
+    pub fn models<'a>(&'a self, world: &'a World, sphere: &'a Mesh) -> Vec<Model<'a>> {
+        let mut models = Vec::new();
+        if let Some(renderable) = world.renderables[self.body].as_ref() {
+            models.push(Model {
+                mesh: &self.terrain,
+                model_matrix: self.frame,
+                shader: &renderable.shader,
+                label: &world.names[self.body],
+                surface: renderable.surface,
+                displacement: 0.0, // El parche ya tiene su relieve
+                vertex_animation: "",
+                geometry_shader: "",
+                parts: &[],
+                ray_traced: false,
+                rings: None,
+                sdf: None,
+            });
+        }
+        for (entity, renderable) in world.renderables.iter().enumerate() {
+            let Some(renderable) = renderable.as_ref().filter(|_| entity != self.body) else {
+                continue;
+            };
+            let transform = &world.transforms[entity];
+            let Some((direction, distance)) = self.sky_direction(world, transform.position) else {
+                continue;
+            };
+            // Bajo el horizonte lo taparía el terreno
+            if direction.y < -0.2 {
+                continue;
+            }
+            let size = (transform.scale * SKY_DISTANCE / distance).clamp(MIN_SKY_SIZE, MAX_SKY_SIZE);
+            let mut model_matrix = create_model_matrix(Vector3::zero(), size, transform.rotation);
+            let position = direction * SKY_DISTANCE;
+            model_matrix.m12 = position.x;
+            model_matrix.m13 = position.y;
+            model_matrix.m14 = position.z;
+            models.push(Model {
+                mesh: sphere,
+                model_matrix,
+                shader: &renderable.shader,
+                label: &world.names[entity],
+                surface: renderable.surface,
+                displacement: renderable.displacement,
+                vertex_animation: &renderable.vertex_animation,
+                geometry_shader: &renderable.geometry_shader,
+                parts: &[],
+                ray_traced: renderable.ray_traced,
+                rings: renderable.rings,
+                sdf: renderable.sdf,
+            });
+        }
+        models
+    }
+
+    /// Light of the stars of the scene, arriving along the direction they are seen from here
+    pub fn lights(&self, world: &World) -> Vec<Light> {
+        world.lights.iter().enumerate()
+            .filter_map(|(entity, light)| {
+                let light = light.as_ref()?;
+                let (direction, _) = self.sky_direction(world, world.transforms[entity].position)?;
+                Some(Light::directional(-direction).with_color(light.color).with_intensity(light.intensity))
+            })
+            .collect()
+    }
+
+    // Dirección (en el espacio de la superficie) y distancia a la que se ve `position` desde
+    // el punto de aterrizaje, con el planeta donde está ahora
+    fn sky_direction(&self, world: &World, position: Vector3) -> Option<(Vector3, f32)> {
+        let model = body_matrix(world, self.body);
+        let landing = self.direction * (0.5 * (1.0 + self.ground));
+        let landing = multiply_matrix_vector4(&model, &Vector4::new(landing.x, landing.y, landing.z, 1.0));
+        let offset = position - Vector3::new(landing.x, landing.y, landing.z);
+        let distance = offset.length();
+        if distance <= 0.0 {
+            return None;
+        }
+        // Al espacio del planeta y de ahí al de la superficie (la escala no importa: se normaliza)
+        let to_object = inverse(&model)?;
+        let local = multiply_matrix_vector4(&to_object, &Vector4::new(offset.x, offset.y, offset.z, 0.0));
+        let surface = multiply_matrix_vector4(&self.frame, &Vector4::new(local.x, local.y, local.z, 0.0));
+        let direction = Vector3::new(surface.x, surface.y, surface.z);
+        (direction.length() > 0.0).then(|| (direction.normalized(), distance))
+    }
+}
+
+// Matriz de modelo del cuerpo tal como lo dibuja el RenderSystem
+fn body_matrix(world: &World, body: Entity) -> Matrix {
+    let transform = &world.transforms[body];
+    create_model_matrix(transform.position, transform.scale, transform.rotation)
+}
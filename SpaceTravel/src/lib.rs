@@ -0,0 +1,91 @@
+// lib.rs
+// Biblioteca con el pipeline de render por software: framebuffer, matrices, rasterizador,
+// shaders, iluminación, sombras, post-proceso, cámara y las escenas que se leen de archivo. El
+// binario (main.rs) la usa para el sistema Xerion; otros proyectos, los benchmarks, las pruebas
+// y Python (python.rs, característica "python") pueden usarla igual
+//! Software rasterizer built on raylib: load or generate meshes, describe a [`Scene`] of
+//! models and lights, and let a [`Renderer`] draw it into its framebuffer.
+//!
+//! ```no_run
+//! use space_travel::{Model, Renderer, Scene, View};
+//! use space_travel::camera::{FAR_PLANE, NEAR_PLANE};
+//! use space_travel::lighting::Surface;
+//! use space_travel::matrix::{DepthMode, create_model_matrix, create_projection_matrix, create_view_matrix};
+//! use space_travel::mesh::generate_uv_sphere;
+//! use space_travel::light::Light;
+//! use space_travel::shader_params::ShaderParams;
+//! use raylib::prelude::*;
+//!
+//! let mut renderer = Renderer::new(640, 360);
+//! let sphere = generate_uv_sphere(20, 24);
+//! let scene = Scene {
+//!     models: vec![Model {
+//!         mesh: &sphere,
+//!         model_matrix: create_model_matrix(Vector3::zero(), 10.0, Vector3::zero()),
+//!         shader: "earth",
+//!         label: "earth",
+//!         surface: Surface::default(),
+//!         displacement: 0.0,
+//!         vertex_animation: "",
+//!         geometry_shader: "",
+//!         parts: &[],
+//!         ray_traced: false,
+//!         rings: None,
+//!         sdf: None,
+//!     }],
+//!     lights: vec![Light::new(Vector3::new(30.0, 10.0, 0.0))],
+//! };
+//! let eye = Vector3::new(0.0, 5.0, 30.0);
+//! let view = View {
+//!     view_matrix: create_view_matrix(eye, Vector3::zero(), Vector3::new(0.0, 1.0, 0.0)),
+//!     projection_matrix: create_projection_matrix(std::f32::consts::PI / 3.0, 16.0 / 9.0, NEAR_PLANE, FAR_PLANE),
+//!     depth_mode: DepthMode::Projective,
+//!     eye,
+//!     time: 0.0,
+//!     dt: 0.0,
+//! };
+//! renderer.framebuffer.clear();
+//! let stats = renderer.draw_frame(&scene, &view, &ShaderParams::default());
+//! println!("{} pixels written", stats.pixels_written);
+//! ```
+
+#[macro_use]
+pub mod profiler;
+pub mod assets;
+pub mod batch;
+pub mod bounds;
+pub mod camera;
+pub mod fragment;
+pub mod framebuffer;
+pub mod frustum;
+pub mod impostor;
+pub mod light;
+pub mod lighting;
+pub mod line;
+pub mod material;
+pub mod matrix;
+pub mod mesh;
+pub mod noise;
+pub mod obj;
+pub mod offline;
+pub mod pathtrace;
+pub mod post;
+#[cfg(feature = "python")]
+mod python;
+pub mod raytrace;
+pub mod renderer;
+pub mod scene;
+pub mod sdf;
+pub mod shader_params;
+pub mod shader_registry;
+pub mod shaders;
+pub mod shadow;
+pub mod simd;
+pub mod simplify;
+pub mod sprite;
+pub mod stats;
+pub mod triangle;
+pub mod uniforms;
+pub mod vertex;
+
+pub use renderer::{Model, Part, Renderer, Scene, View};
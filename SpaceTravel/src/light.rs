@@ -1,11 +1,66 @@
 use raylib::prelude::*;
 
+/// How the light spreads from its position
+#[derive(Clone, Copy)]
+pub enum LightKind {
+    Point,
+    // Cono alrededor de `direction`; cosenos de los ángulos interior (luz completa) y exterior (sin luz)
+    Spot { direction: Vector3, inner_cos: f32, outer_cos: f32 },
+    // Luz lejana que llega en paralelo a lo largo de `direction`; la posición no se usa
+    Directional { direction: Vector3 },
+}
+
 pub struct Light {
     pub position: Vector3,
+    pub color: Vector3,  // Color de la luz (0..1 por canal)
+    pub intensity: f32,
+    pub kind: LightKind,
+    pub casts_shadows: bool, // Se renderiza un mapa de sombras desde su posición
+    pub radius: f32,         // Tamaño de la fuente; da la penumbra de las sombras analíticas (0 = puntual)
 }
 
 impl Light {
     pub fn new(position: Vector3) -> Self {
-        Light { position }
+        Light { position, color: Vector3::new(1.0, 1.0, 1.0), intensity: 1.0, kind: LightKind::Point, casts_shadows: false, radius: 0.0 }
+    }
+
+    /// Spotlight pointing along `direction`; angles (radians) are measured from the axis of the cone
+    pub fn spot(position: Vector3, direction: Vector3, inner_angle: f32, outer_angle: f32) -> Self {
+        Light {
+            kind: LightKind::Spot {
+                direction: direction.normalized(),
+                inner_cos: inner_angle.cos(),
+                outer_cos: outer_angle.max(inner_angle + 1e-3).cos(),
+            },
+            ..Light::new(position)
+        }
+    }
+
+    /// Distant light travelling along `direction`, equally strong everywhere
+    pub fn directional(direction: Vector3) -> Self {
+        Light {
+            kind: LightKind::Directional { direction: direction.normalized() },
+            ..Light::new(Vector3::zero())
+        }
+    }
+
+    pub fn with_color(mut self, color: Vector3) -> Self {
+        self.color = color;
+        self
     }
-}
\ No newline at end of file
+
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn with_shadows(mut self, casts_shadows: bool) -> Self {
+        self.casts_shadows = casts_shadows;
+        self
+    }
+
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+}
@@ -0,0 +1,173 @@
+// lighting.rs
+// Etapa de iluminación: se aplica al color que devuelve el fragment shader de cada cuerpo
+use crate::fragment::Fragment;
+use crate::light::{Light, LightKind};
+use crate::shader_params::{LightingParams, LightingQuality, rgb};
+use crate::shadow::Shadows;
+use crate::simd::dot;
+use raylib::prelude::*;
+
+/// How a surface responds to light
+#[derive(Clone, Copy, Debug)]
+pub struct Surface {
+    // > 0: el cuerpo emite luz propia (estrellas); su color se multiplica por este valor y no
+    // pasa por la iluminación. Valores mayores que 1 saturan el color
+    pub emissive: f32,
+    pub specular: f32,  // Intensidad del brillo especular (0 = mate)
+    pub shininess: f32, // Exponente de Blinn-Phong: más alto = brillo más pequeño y nítido
+    pub metallic: f32,  // 0 = brillo blanco, 1 = brillo teñido con el color de la superficie
+    pub lighting: Option<LightingQuality>, // Por vértice o por fragmento; None = la opción global
+}
+
+impl Default for Surface {
+    fn default() -> Self {
+        Surface { emissive: 0.0, specular: 0.0, shininess: 16.0, metallic: 0.0, lighting: None }
+    }
+}
+
+impl Surface {
+    pub fn emissive(strength: f32) -> Self {
+        Surface { emissive: strength, ..Surface::default() }
+    }
+
+    pub fn glossy(specular: f32, shininess: f32, metallic: f32) -> Self {
+        Surface { specular, shininess, metallic, ..Surface::default() }
+    }
+
+    /// Where the light is evaluated for this surface: its own setting or the global one
+    pub fn quality(&self, params: &LightingParams) -> LightingQuality {
+        self.lighting.unwrap_or(params.quality)
+    }
+}
+
+/// Light reaching a fragment, split into the diffuse part (tints the surface color) and the
+/// specular highlight (added on top)
+pub struct Irradiance {
+    pub diffuse: Vector3,
+    pub specular: Vector3,
+}
+
+/// Falloff of a point light: inverse-square beyond `reference_distance` (full intensity
+/// closer than that), faded smoothly to zero at `range`
+pub fn attenuation(distance: f32, params: &LightingParams) -> f32 {
+    let reference = params.reference_distance.max(1e-3);
+    let inverse_square = (reference / distance.max(reference)).powi(2);
+    // Ventana suave para que la luz no se corte de golpe en el alcance
+    let ratio = (distance / params.range.max(1e-3)).min(1.0);
+    let window = (1.0 - ratio.powi(4)).powi(2);
+    inverse_square * window
+}
+
+/// Fraction of a spotlight's intensity that reaches `direction` (unit vector from the light
+/// to the fragment); 1 for point lights
+pub fn cone_factor(kind: &LightKind, direction: Vector3) -> f32 {
+    match *kind {
+        LightKind::Point | LightKind::Directional { .. } => 1.0,
+        LightKind::Spot { direction: axis, inner_cos, outer_cos } => {
+            let t = ((dot(direction, axis) - outer_cos) / (inner_cos - outer_cos)).clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        }
+    }
+}
+
+/// Normal used for lighting. With `two_sided`, a surface seen from behind (inverted normals,
+/// open meshes) is lit as if it faced the camera instead of going black
+pub fn shading_normal(normal: Vector3, position: Vector3, camera_position: Vector3, params: &LightingParams) -> Vector3 {
+    if params.two_sided && dot(normal, camera_position - position) < 0.0 {
+        -normal
+    } else {
+        normal
+    }
+}
+
+/// Light reaching a point with the given world-space normal (a fragment, or a vertex with
+/// Gouraud lighting), summed over every light and tinted by its color. The specular term uses
+/// Blinn-Phong with the surface's shininess, seen from `camera_position`.
+/// Lights that cast shadows (with shadow rays, every light) are dimmed by what `shadow` says
+/// reaches the point
+pub fn irradiance(position: Vector3, normal: Vector3, surface: &Surface, camera_position: Vector3, lights: &[Light], shadow: Option<&Shadows>, params: &LightingParams) -> Irradiance {
+    let mut diffuse = Vector3::zero();
+    let mut specular = Vector3::zero();
+    let to_camera = camera_position - position;
+    let view = to_camera / dot(to_camera, to_camera).sqrt().max(1e-6);
+    let normal = shading_normal(normal, position, camera_position, params);
+
+    for light in lights {
+        let (direction, strength) = match light.kind {
+            LightKind::Directional { direction } => (-direction, light.intensity),
+            _ => {
+                let to_light = light.position - position;
+                let distance = dot(to_light, to_light).sqrt();
+                if distance <= 0.0 {
+                    continue;
+                }
+                let direction = to_light / distance;
+                let cone = cone_factor(&light.kind, -direction);
+                (direction, light.intensity * cone * attenuation(distance, params))
+            }
+        };
+
+        let n_dot_l = dot(normal, direction);
+        if n_dot_l <= 0.0 || strength <= 0.0 {
+            continue;
+        }
+        let strength = match shadow {
+            Some(shadow) if shadow.applies_to(light) => strength * shadow.visibility(position, light, params.shadow_bias),
+            _ => strength,
+        };
+        diffuse += light.color * (strength * n_dot_l);
+
+        if surface.specular > 0.0 {
+            let half = direction + view;
+            let half = half / dot(half, half).sqrt().max(1e-6);
+            let highlight = dot(normal, half).max(0.0).powf(surface.shininess);
+            specular += light.color * (strength * surface.specular * highlight);
+        }
+    }
+    Irradiance { diffuse, specular }
+}
+
+/// Light that reaches every surface regardless of the light sources: a constant color, or
+/// a blend from `ground_color` (facing -Y) to `sky_color` (facing +Y) in hemispheric mode
+pub fn ambient(normal: Vector3, params: &LightingParams) -> Vector3 {
+    if !params.hemispheric {
+        return rgb(params.ambient_color);
+    }
+    let t = normal.y * 0.5 + 0.5;
+    rgb(params.ground_color) * (1.0 - t) + rgb(params.sky_color) * t
+}
+
+/// Directional key light configured in `[lighting]`, if its intensity is above zero
+pub fn key_light(params: &LightingParams) -> Option<Light> {
+    (params.key_light_intensity > 0.0).then(|| {
+        Light::directional(rgb(params.key_light_direction))
+            .with_color(rgb(params.key_light_color))
+            .with_intensity(params.key_light_intensity)
+    })
+}
+
+/// Multiplies the shaded surface color by the light reaching it (direct light after exposure
+/// plus ambient), with a floor so the far side of the system never goes completely dark.
+/// Emissive surfaces skip the lights and output their own color scaled by the emission.
+/// With Gouraud quality the light was computed at the vertices and comes interpolated in the fragment
+pub fn apply_lighting(color: Vector3, fragment: &Fragment, surface: &Surface, camera_position: Vector3, lights: &[Light], shadow: Option<&Shadows>, params: &LightingParams) -> Vector3 {
+    if surface.emissive > 0.0 {
+        let emitted = color * surface.emissive;
+        return Vector3::new(emitted.x.clamp(0.0, 1.0), emitted.y.clamp(0.0, 1.0), emitted.z.clamp(0.0, 1.0));
+    }
+
+    let light = match surface.quality(params) {
+        LightingQuality::Phong => irradiance(fragment.world_position, fragment.normal, surface, camera_position, lights, shadow, params),
+        LightingQuality::Gouraud => Irradiance { diffuse: fragment.light_diffuse, specular: fragment.light_specular },
+    };
+    let normal = shading_normal(fragment.normal, fragment.world_position, camera_position, params);
+    let diffuse = light.diffuse * params.exposure + ambient(normal, params);
+    // El brillo de los metales toma el color de la superficie
+    let tint = Vector3::new(1.0, 1.0, 1.0) * (1.0 - surface.metallic) + color * surface.metallic;
+    let highlight = tint * light.specular * params.exposure;
+    Vector3::new(
+        (color.x * diffuse.x.max(params.min_light) + highlight.x).clamp(0.0, 1.0),
+        (color.y * diffuse.y.max(params.min_light) + highlight.y).clamp(0.0, 1.0),
+        (color.z * diffuse.z.max(params.min_light) + highlight.z).clamp(0.0, 1.0),
+    )
+}
@@ -34,7 +34,8 @@ pub fn line(a: &Vertex, b: &Vertex) -> Vec<Fragment> {
             x0 as f32, 
             y0 as f32, 
             Vector3::new(1.0, 1.0, 1.0), 
-            z
+            z,
+            a.world_position,
         ));
         
         if x0 == x1 && y0 == y1 { break; }
@@ -0,0 +1,105 @@
+// locale.rs
+// Textos del HUD y de los menús en varios idiomas. Cada idioma es un archivo TOML en data/lang,
+// con tablas por pantalla o sistema y un texto por clave; se incluyen en el ejecutable como los
+// datos del sistema solar. Los textos pueden tener huecos con nombre ({body}) que se rellenan al
+// usarlos, y una clave que falte en un idioma se toma del inglés
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+const ENGLISH: &str = include_str!("../data/lang/en.toml");
+const SPANISH: &str = include_str!("../data/lang/es.toml");
+
+/// Language of the HUD and menus
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    En,
+    Es,
+}
+
+impl Language {
+    const ALL: [Language; 2] = [Language::En, Language::Es];
+
+    /// Name of the language in itself, for the settings menu
+    pub fn label(self) -> &'static str {
+        match self {
+            Language::En => "English",
+            Language::Es => "Español",
+        }
+    }
+
+    /// The next language (`forward`) or the previous one, wrapping around
+    pub fn cycle(self, forward: bool) -> Self {
+        let count = Language::ALL.len();
+        let index = Language::ALL.iter().position(|&language| language == self).unwrap_or(0);
+        Language::ALL[if forward { (index + 1) % count } else { (index + count - 1) % count }]
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Language::En => ENGLISH,
+            Language::Es => SPANISH,
+        }
+    }
+}
+
+/// The texts of one language, looked up by keys such as "menu.start"
+pub struct Strings {
+    language: Language,
+    texts: HashMap<String, String>,
+    fallback: HashMap<String, String>, // Inglés, para las claves sin traducir
+}
+
+impl Strings {
+    pub fn new(language: Language) -> Self {
+        let fallback = if language == Language::En { HashMap::new() } else { parse(ENGLISH) };
+        Strings { language, texts: parse(language.source()), fallback }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// The text of `key`; the key itself if no language has it, so a missing text shows up
+    pub fn get<'s>(&'s self, key: &'s str) -> &'s str {
+        self.texts.get(key).or_else(|| self.fallback.get(key)).map_or(key, String::as_str)
+    }
+
+    /// The text of `key` with each {name} replaced by its value in `arguments`
+    pub fn format(&self, key: &str, arguments: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.get(key).to_string();
+        for (name, value) in arguments {
+            text = text.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        text
+    }
+}
+
+// Claves con el nombre de su tabla delante ("menu.start"); un archivo mal escrito deja el idioma
+// vacío y se avisa, no impide arrancar
+fn parse(source: &str) -> HashMap<String, String> {
+    let table: toml::Table = match toml::from_str(source) {
+        Ok(table) => table,
+        Err(e) => {
+            eprintln!("Invalid language file: {}", e);
+            return HashMap::new();
+        }
+    };
+    let mut texts = HashMap::new();
+    flatten("", &table, &mut texts);
+    texts
+}
+
+fn flatten(prefix: &str, table: &toml::Table, texts: &mut HashMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            toml::Value::Table(inner) => flatten(&key, inner, texts),
+            toml::Value::String(text) => {
+                texts.insert(key, text.clone());
+            }
+            _ => eprintln!("Language key '{}' is not text", key),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// material.rs
+use raylib::math::Vector3;
+
+/// Surface properties read from an MTL file
+#[derive(Clone, Debug)]
+pub struct Material {
+    pub name: String,
+    pub diffuse: Vector3,                // Kd
+    pub specular: Vector3,               // Ks
+    pub shininess: f32,                  // Ns
+    pub emissive: Vector3,               // Ke
+    pub diffuse_texture: Option<String>, // map_Kd
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            name: String::from("default"),
+            diffuse: Vector3::new(1.0, 1.0, 1.0),
+            specular: Vector3::new(0.0, 0.0, 0.0),
+            shininess: 0.0,
+            emissive: Vector3::new(0.0, 0.0, 0.0),
+            diffuse_texture: None,
+        }
+    }
+}
+
+impl Material {
+    pub fn from_tobj(material: &tobj::Material) -> Self {
+        let rgb = |c: [f32; 3]| Vector3::new(c[0], c[1], c[2]);
+        let defaults = Material::default();
+
+        // tobj no interpreta Ke; queda entre los parámetros desconocidos como "r g b"
+        let emissive = material.unknown_param.get("Ke")
+            .and_then(|value| {
+                let c: Vec<f32> = value.split_whitespace().filter_map(|n| n.parse().ok()).collect();
+                if c.len() == 3 { Some(Vector3::new(c[0], c[1], c[2])) } else { None }
+            })
+            .unwrap_or(defaults.emissive);
+
+        Material {
+            name: material.name.clone(),
+            diffuse: material.diffuse.map(rgb).unwrap_or(defaults.diffuse),
+            specular: material.specular.map(rgb).unwrap_or(defaults.specular),
+            shininess: material.shininess.unwrap_or(defaults.shininess),
+            emissive,
+            diffuse_texture: material.diffuse_texture.clone(),
+        }
+    }
+}
@@ -2,16 +2,69 @@
 #![allow(dead_code)]
 
 use raylib::prelude::*;
+use crate::simd;
 
+// Implementación escalar o SIMD según la feature "simd" (ver simd.rs)
 pub fn multiply_matrix_vector4(matrix: &Matrix, vector: &Vector4) -> Vector4 {
-    Vector4::new(
-        matrix.m0 * vector.x + matrix.m4 * vector.y + matrix.m8 * vector.z + matrix.m12 * vector.w,
-        matrix.m1 * vector.x + matrix.m5 * vector.y + matrix.m9 * vector.z + matrix.m13 * vector.w,
-        matrix.m2 * vector.x + matrix.m6 * vector.y + matrix.m10 * vector.z + matrix.m14 * vector.w,
-        matrix.m3 * vector.x + matrix.m7 * vector.y + matrix.m11 * vector.z + matrix.m15 * vector.w,
+    simd::multiply_matrix_vector4(matrix, vector)
+}
+
+/// Multiplies two matrices in mathematical order: the result applies `b` first and then `a`.
+/// (raylib's `*` operator composes in the opposite order)
+#[cfg(feature = "math-glam")]
+pub fn multiply_matrices(a: &Matrix, b: &Matrix) -> Matrix {
+    use simd::glam_backend::{from_mat4, to_mat4};
+    from_mat4(to_mat4(a) * to_mat4(b))
+}
+
+/// Multiplies two matrices in mathematical order: the result applies `b` first and then `a`.
+/// (raylib's `*` operator composes in the opposite order)
+#[cfg(not(feature = "math-glam"))]
+pub fn multiply_matrices(a: &Matrix, b: &Matrix) -> Matrix {
+    let row = |m: &Matrix, r: usize| match r {
+        0 => [m.m0, m.m4, m.m8, m.m12],
+        1 => [m.m1, m.m5, m.m9, m.m13],
+        2 => [m.m2, m.m6, m.m10, m.m14],
+        _ => [m.m3, m.m7, m.m11, m.m15],
+    };
+    let column = |m: &Matrix, c: usize| match c {
+        0 => [m.m0, m.m1, m.m2, m.m3],
+        1 => [m.m4, m.m5, m.m6, m.m7],
+        2 => [m.m8, m.m9, m.m10, m.m11],
+        _ => [m.m12, m.m13, m.m14, m.m15],
+    };
+    let cell = |r: usize, c: usize| {
+        let (row, column) = (row(a, r), column(b, c));
+        row[0] * column[0] + row[1] * column[1] + row[2] * column[2] + row[3] * column[3]
+    };
+
+    new_matrix4(
+        cell(0, 0), cell(0, 1), cell(0, 2), cell(0, 3),
+        cell(1, 0), cell(1, 1), cell(1, 2), cell(1, 3),
+        cell(2, 0), cell(2, 1), cell(2, 2), cell(2, 3),
+        cell(3, 0), cell(3, 1), cell(3, 2), cell(3, 3),
     )
 }
 
+/// Returns the inverse of a matrix, or None if it is singular
+pub fn inverse(m: &Matrix) -> Option<Matrix> {
+    // `inverted` de raylib divide por el determinante sin comprobarlo: una matriz singular daría infinitos
+    (m.determinant().abs() >= f32::EPSILON).then(|| m.inverted())
+}
+
+/// Creates the matrix used to transform normals: the inverse-transpose of the model matrix
+/// without its translation. Keeps normals perpendicular to the surface under non-uniform scale.
+/// Falls back to the model's rotation/scale part if the matrix is singular
+pub fn normal_matrix(model_matrix: &Matrix) -> Matrix {
+    let m = model_matrix;
+    let linear = new_matrix3(
+        m.m0, m.m4, m.m8,
+        m.m1, m.m5, m.m9,
+        m.m2, m.m6, m.m10,
+    );
+    inverse(&linear).map(|inv| inv.transposed()).unwrap_or(linear)
+}
+
 /// Creates a 4x4 matrix from 16 float values, specified in traditional row-major order.
 pub fn new_matrix4(
     // Row 0
@@ -160,6 +213,72 @@ pub fn create_projection_matrix(fov_y: f32, aspect: f32, near: f32, far: f32) ->
     )
 }
 
+/// Creates an orthographic projection matrix: the view space box of `half_width` x
+/// `half_height` around the view axis between `near` and `far` fills the screen, with no
+/// perspective (clip w is always 1)
+pub fn create_orthographic_matrix(half_width: f32, half_height: f32, near: f32, far: f32) -> Matrix {
+    new_matrix4(
+        1.0 / half_width, 0.0, 0.0, 0.0,
+        0.0, 1.0 / half_height, 0.0, 0.0,
+        0.0, 0.0, -2.0 / (far - near), -(far + near) / (far - near),
+        0.0, 0.0, 0.0, 1.0,
+    )
+}
+
+/// Shifts the image produced by a projection matrix by (`dx`, `dy`) pixels of a `width` x
+/// `height` viewport, to sample different sub-pixel positions between accumulated frames
+pub fn jitter_projection(projection: &Matrix, dx: f32, dy: f32, width: f32, height: f32) -> Matrix {
+    // La tercera columna se multiplica por z y se divide entre w = -z: desplaza el NDC en -m8, -m9
+    let mut jittered = *projection;
+    jittered.m8 -= 2.0 * dx / width;
+    jittered.m9 += 2.0 * dy / height; // El viewport invierte Y
+    jittered
+}
+
+/// How the depth stored in the framebuffer is computed from the projected position
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DepthMode {
+    /// Standard z/w after the perspective divide (precision concentrated near the camera)
+    Projective,
+    /// log2(1 + w) normalized by the far plane; keeps precision across very large distances
+    Logarithmic { far: f32 },
+    /// Projective depth moved to [-3, -1], in front of everything drawn with the other modes:
+    /// for geometry locked to the camera (a cockpit) drawn with its own near-range projection
+    Overlay,
+}
+
+impl DepthMode {
+    /// Parses "projective" or "log"
+    pub fn parse(text: &str, far: f32) -> Result<Self, String> {
+        match text {
+            "projective" => Ok(DepthMode::Projective),
+            "log" | "logarithmic" => Ok(DepthMode::Logarithmic { far }),
+            _ => Err(format!("unknown depth mode '{}' (expected projective or log)", text)),
+        }
+    }
+
+    /// Depth in the same [-1, 1] range as NDC z, so the framebuffer compare works for both modes.
+    /// clip_w is the clip-space w (the distance in front of the camera)
+    pub fn depth(&self, ndc_z: f32, clip_w: f32) -> f32 {
+        match *self {
+            DepthMode::Projective => ndc_z,
+            DepthMode::Logarithmic { far } => {
+                2.0 * (1.0 + clip_w.max(0.0)).log2() / (1.0 + far).log2() - 1.0
+            }
+            DepthMode::Overlay => ndc_z - 2.0,
+        }
+    }
+
+    /// Inverse of `depth`: distance along the view axis for a stored depth value
+    pub fn linear_depth(&self, depth: f32, near: f32, far: f32) -> f32 {
+        match *self {
+            DepthMode::Projective => 2.0 * near * far / (far + near - depth * (far - near)),
+            DepthMode::Logarithmic { far } => ((depth + 1.0) * 0.5 * (1.0 + far).log2()).exp2() - 1.0,
+            DepthMode::Overlay => 2.0 * near * far / (far + near - (depth + 2.0) * (far - near)),
+        }
+    }
+}
+
 /// Creates a viewport matrix to transform NDC coordinates to screen space
 /// x, y: Viewport position (typically 0, 0)
 /// width, height: Viewport dimensions in pixels
@@ -173,4 +292,250 @@ pub fn create_viewport_matrix(x: f32, y: f32, width: f32, height: f32) -> Matrix
         0.0, 0.0, 1.0, 0.0,
         0.0, 0.0, 0.0, 1.0,
     )
-}
\ No newline at end of file
+}
+/// Unit quaternion representing a rotation (x, y, z vector part, w scalar part).
+/// Avoids the order and gimbal-lock problems of Euler angles
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quaternion {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quaternion {
+    pub fn identity() -> Self {
+        Quaternion { x: 0.0, y: 0.0, z: 0.0, w: 1.0 }
+    }
+
+    /// Rotation of `angle` radians around `axis` (does not need to be normalized)
+    pub fn from_axis_angle(axis: Vector3, angle: f32) -> Self {
+        let length = (axis.x * axis.x + axis.y * axis.y + axis.z * axis.z).sqrt();
+        if length == 0.0 {
+            return Quaternion::identity();
+        }
+        let (sin_half, cos_half) = (angle * 0.5).sin_cos();
+        let s = sin_half / length;
+        Quaternion { x: axis.x * s, y: axis.y * s, z: axis.z * s, w: cos_half }
+    }
+
+    /// Same rotation as `create_model_matrix` builds from Euler angles (X, then Y, then Z)
+    pub fn from_euler(rotation: Vector3) -> Self {
+        let qx = Quaternion::from_axis_angle(Vector3::new(1.0, 0.0, 0.0), rotation.x);
+        let qy = Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), rotation.y);
+        let qz = Quaternion::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), rotation.z);
+        qz * qy * qx
+    }
+
+    pub fn dot(self, other: Quaternion) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn normalized(self) -> Quaternion {
+        let length = self.dot(self).sqrt();
+        if length == 0.0 {
+            return Quaternion::identity();
+        }
+        Quaternion { x: self.x / length, y: self.y / length, z: self.z / length, w: self.w / length }
+    }
+
+    pub fn conjugate(self) -> Quaternion {
+        Quaternion { x: -self.x, y: -self.y, z: -self.z, w: self.w }
+    }
+
+    /// Rotates a vector by this quaternion
+    pub fn rotate_vector(self, v: Vector3) -> Vector3 {
+        let p = Quaternion { x: v.x, y: v.y, z: v.z, w: 0.0 };
+        let r = self * p * self.conjugate();
+        Vector3::new(r.x, r.y, r.z)
+    }
+
+    /// Spherical interpolation along the shortest arc (t = 0 gives self, t = 1 gives target)
+    pub fn slerp(self, target: Quaternion, t: f32) -> Quaternion {
+        let mut target = target;
+        let mut cos_theta = self.dot(target);
+        // q y -q son la misma rotación: tomar el camino corto
+        if cos_theta < 0.0 {
+            target = Quaternion { x: -target.x, y: -target.y, z: -target.z, w: -target.w };
+            cos_theta = -cos_theta;
+        }
+
+        // Casi paralelos: la interpolación lineal es estable y suficiente
+        if cos_theta > 0.9995 {
+            return Quaternion {
+                x: self.x + (target.x - self.x) * t,
+                y: self.y + (target.y - self.y) * t,
+                z: self.z + (target.z - self.z) * t,
+                w: self.w + (target.w - self.w) * t,
+            }.normalized();
+        }
+
+        let theta = cos_theta.acos();
+        let sin_theta = theta.sin();
+        let a = ((1.0 - t) * theta).sin() / sin_theta;
+        let b = (t * theta).sin() / sin_theta;
+        Quaternion {
+            x: self.x * a + target.x * b,
+            y: self.y * a + target.y * b,
+            z: self.z * a + target.z * b,
+            w: self.w * a + target.w * b,
+        }
+    }
+
+    /// Converts the rotation to a 4x4 rotation matrix
+    pub fn to_matrix(self) -> Matrix {
+        let q = self.normalized();
+        let (x, y, z, w) = (q.x, q.y, q.z, q.w);
+        new_matrix3(
+            1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - z * w),       2.0 * (x * z + y * w),
+            2.0 * (x * y + z * w),       1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - x * w),
+            2.0 * (x * z - y * w),       2.0 * (y * z + x * w),       1.0 - 2.0 * (x * x + y * y),
+        )
+    }
+}
+
+// Composición de rotaciones: `a * b` aplica primero `b` y luego `a`
+impl std::ops::Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, other: Quaternion) -> Quaternion {
+        Quaternion {
+            x: self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            y: self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            z: self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+            w: self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+        }
+    }
+}
+
+/// Creates a model matrix from a quaternion orientation.
+/// Composes scale, translation and rotation in the same order as `create_model_matrix`
+pub fn create_model_matrix_from_quaternion(translation: Vector3, scale: f32, rotation: Quaternion) -> Matrix {
+    let scale_matrix = new_matrix4(
+        scale, 0.0,   0.0,   0.0,
+        0.0,   scale, 0.0,   0.0,
+        0.0,   0.0,   scale, 0.0,
+        0.0,   0.0,   0.0,   1.0
+    );
+
+    let translation_matrix = new_matrix4(
+        1.0, 0.0, 0.0, translation.x,
+        0.0, 1.0, 0.0, translation.y,
+        0.0, 0.0, 1.0, translation.z,
+        0.0, 0.0, 0.0, 1.0
+    );
+
+    scale_matrix * translation_matrix * rotation.to_matrix()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn cells(m: &Matrix) -> [f32; 16] {
+        [m.m0, m.m1, m.m2, m.m3, m.m4, m.m5, m.m6, m.m7, m.m8, m.m9, m.m10, m.m11, m.m12, m.m13, m.m14, m.m15]
+    }
+
+    fn assert_matrix_near(a: &Matrix, b: &Matrix) {
+        for (x, y) in cells(a).into_iter().zip(cells(b)) {
+            assert!((x - y).abs() < EPSILON, "{:?} != {:?}", a, b);
+        }
+    }
+
+    fn assert_vector_near(a: Vector3, b: Vector3) {
+        assert!((a - b).length() < EPSILON, "{:?} != {:?}", a, b);
+    }
+
+    fn transform(m: &Matrix, v: Vector3) -> Vector3 {
+        let r = multiply_matrix_vector4(m, &Vector4::new(v.x, v.y, v.z, 0.0));
+        Vector3::new(r.x, r.y, r.z)
+    }
+
+    #[test]
+    fn inverse_undoes_the_matrix() {
+        let rotation = Quaternion::from_axis_angle(Vector3::new(1.0, 2.0, 3.0), 0.7);
+        let m = create_model_matrix_from_quaternion(Vector3::new(4.0, -2.0, 1.5), 2.5, rotation);
+        let inv = inverse(&m).expect("the model matrix is invertible");
+        assert_matrix_near(&multiply_matrices(&inv, &m), &new_matrix3(
+            1.0, 0.0, 0.0,
+            0.0, 1.0, 0.0,
+            0.0, 0.0, 1.0,
+        ));
+    }
+
+    #[test]
+    fn inverse_of_singular_matrix_is_none() {
+        // Escala cero en Y: aplasta el espacio en un plano
+        let flat = new_matrix4(
+            1.0, 0.0, 0.0, 3.0,
+            0.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        );
+        assert!(inverse(&flat).is_none());
+    }
+
+    #[test]
+    fn normal_matrix_keeps_normals_perpendicular_under_non_uniform_scale() {
+        let stretch = new_matrix4(
+            3.0, 0.0, 0.0, 5.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 0.5, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        );
+        let normal = Vector3::new(1.0, 1.0, 0.0).normalized();
+        let tangent = Vector3::new(1.0, -1.0, 0.0).normalized();
+        let transformed_normal = transform(&normal_matrix(&stretch), normal);
+        let transformed_tangent = transform(&stretch, tangent);
+        assert!(transformed_normal.dot(transformed_tangent).abs() < EPSILON);
+    }
+
+    #[test]
+    fn normal_matrix_of_a_rotation_is_the_rotation() {
+        let rotation = Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 1.0), 1.2).to_matrix();
+        assert_matrix_near(&normal_matrix(&rotation), &rotation);
+    }
+
+    #[test]
+    fn axis_angle_rotates_right_handed() {
+        let q = Quaternion::from_axis_angle(Vector3::new(0.0, 2.0, 0.0), PI / 2.0);
+        assert_vector_near(q.rotate_vector(Vector3::new(1.0, 0.0, 0.0)), Vector3::new(0.0, 0.0, -1.0));
+        assert_vector_near(q.rotate_vector(Vector3::new(0.0, 1.0, 0.0)), Vector3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn to_matrix_agrees_with_rotate_vector() {
+        let q = Quaternion::from_euler(Vector3::new(0.3, -1.1, 2.0));
+        let m = q.to_matrix();
+        for v in [Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0), Vector3::new(-0.5, 2.0, 3.0)] {
+            assert_vector_near(transform(&m, v), q.rotate_vector(v));
+        }
+    }
+
+    #[test]
+    fn slerp_hits_both_ends_and_the_middle() {
+        let axis = Vector3::new(0.0, 0.0, 1.0);
+        let start = Quaternion::identity();
+        let end = Quaternion::from_axis_angle(axis, PI / 2.0);
+        let v = Vector3::new(1.0, 0.0, 0.0);
+
+        assert_vector_near(start.slerp(end, 0.0).rotate_vector(v), start.rotate_vector(v));
+        assert_vector_near(start.slerp(end, 1.0).rotate_vector(v), end.rotate_vector(v));
+        let middle = Quaternion::from_axis_angle(axis, PI / 4.0);
+        assert_vector_near(start.slerp(end, 0.5).rotate_vector(v), middle.rotate_vector(v));
+    }
+
+    #[test]
+    fn slerp_takes_the_short_arc() {
+        // -q es la misma rotación que q: el punto medio no debe dar la vuelta larga
+        let axis = Vector3::new(0.0, 1.0, 0.0);
+        let end = Quaternion::from_axis_angle(axis, 0.5);
+        let negated = Quaternion { x: -end.x, y: -end.y, z: -end.z, w: -end.w };
+        let v = Vector3::new(1.0, 0.0, 0.0);
+        let middle = Quaternion::from_axis_angle(axis, 0.25);
+        assert_vector_near(Quaternion::identity().slerp(negated, 0.5).rotate_vector(v), middle.rotate_vector(v));
+    }
+}
@@ -0,0 +1,452 @@
+// mesh.rs
+use crate::bounds::{Aabb, BoundingSphere};
+use crate::obj::compute_tangents;
+use crate::simplify::simplify;
+use crate::vertex::Vertex;
+use raylib::math::{Vector2, Vector3};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::ops::Range;
+
+// Fracción de caras con las normales contra su winding a partir de la cual se invierten
+const REVERSED_NORMAL_LIMIT: f32 = 0.5;
+
+/// Named part of a mesh (an OBJ object or group): its triangles are `indices` of the index
+/// buffer and only use the vertices in `vertices`
+#[derive(Clone, Debug)]
+pub struct Submesh {
+    pub name: String,
+    pub indices: Range<usize>,
+    pub vertices: Range<usize>,
+}
+
+/// Simplified version of a mesh, drawn while the mesh covers less than `max_radius` pixels
+pub struct Lod {
+    pub max_radius: f32,
+    pub mesh: Mesh,
+}
+
+/// Indexed triangle mesh (generated procedurally or converted from an OBJ)
+pub struct Mesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    pub submeshes: Vec<Submesh>,         // Partes que se dibujan por separado; al menos una
+    pub aabb: Aabb,                      // En espacio de modelo
+    pub bounding_sphere: BoundingSphere, // En espacio de modelo
+    pub lods: Vec<Lod>,                  // De más a menos detalle; vacío = siempre esta malla
+    pub back_faces_hidden: bool,         // Cerrada y con las caras hacia fuera: sus caras traseras nunca se ven
+}
+
+impl Mesh {
+    /// Builds a mesh of a single unnamed part and computes its model-space bounds
+    pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>) -> Self {
+        let whole = Submesh { name: String::new(), indices: 0..indices.len(), vertices: 0..vertices.len() };
+        Mesh::with_submeshes(vertices, indices, vec![whole])
+    }
+
+    /// Builds a mesh split into `submeshes`, which must cover the index buffer in order
+    pub fn with_submeshes(vertices: Vec<Vertex>, indices: Vec<u32>, submeshes: Vec<Submesh>) -> Self {
+        let aabb = Aabb::from_points(vertices.iter().map(|v| &v.position));
+        let bounding_sphere = BoundingSphere::from_points(vertices.iter().map(|v| &v.position));
+        let mut mesh = Mesh { vertices, indices, submeshes, aabb, bounding_sphere, lods: Vec::new(), back_faces_hidden: false };
+        mesh.back_faces_hidden = mesh.is_closed() && mesh.signed_volume() > 0.0;
+        mesh
+    }
+
+    /// Adds simplified levels of detail: each (fraction, max_radius) keeps that fraction of the
+    /// triangles and is drawn below that radius in pixels. Levels go from more to less detail
+    pub fn with_lods(mut self, levels: &[(f32, f32)]) -> Self {
+        let triangles = self.indices.len() / 3;
+        self.lods = levels.iter()
+            .map(|&(fraction, max_radius)| Lod {
+                max_radius,
+                mesh: simplify(&self, ((triangles as f32 * fraction) as usize).max(1)),
+            })
+            .collect();
+        self
+    }
+
+    /// The mesh to draw when it covers `radius` pixels on screen: the least detailed level whose
+    /// radius is still above it, or the full mesh
+    pub fn level(&self, radius: f32) -> &Mesh {
+        self.lods.iter().rev()
+            .find(|lod| radius < lod.max_radius)
+            .map_or(self, |lod| &lod.mesh)
+    }
+
+    /// Whether the surface is closed and consistently wound: every edge (joining the vertices
+    /// that share a position) is crossed once in each direction by the two faces beside it
+    pub fn is_closed(&self) -> bool {
+        let mut welded: HashMap<[u32; 3], usize> = HashMap::new();
+        let ids: Vec<usize> = self.vertices.iter().map(|vertex| {
+            let p = vertex.position;
+            let next = welded.len();
+            *welded.entry([p.x.to_bits(), p.y.to_bits(), p.z.to_bits()]).or_insert(next)
+        }).collect();
+        let mut edges: HashMap<(usize, usize), u32> = HashMap::new();
+        for face in self.indices.chunks_exact(3) {
+            let [a, b, c] = [ids[face[0] as usize], ids[face[1] as usize], ids[face[2] as usize]];
+            // Los triángulos que se cierran sobre un punto (los polos de la esfera UV) no tienen lados
+            if a == b || b == c || c == a {
+                continue;
+            }
+            for edge in [(a, b), (b, c), (c, a)] {
+                *edges.entry(edge).or_insert(0) += 1;
+            }
+        }
+        !edges.is_empty() && edges.iter().all(|(&(from, to), &count)| count == 1 && edges.get(&(to, from)) == Some(&1))
+    }
+
+    /// Volume enclosed by the mesh, positive when its counter-clockwise faces look outwards.
+    /// Only meaningful for a closed mesh
+    pub fn signed_volume(&self) -> f32 {
+        self.indices.chunks_exact(3).map(|face| {
+            let [a, b, c] = [0, 1, 2].map(|corner| self.vertices[face[corner] as usize].position);
+            a.dot(b.cross(c))
+        }).sum::<f32>() / 6.0
+    }
+
+    /// Fraction of the triangles whose vertex normals point against the normal of their winding
+    pub fn reversed_normal_fraction(&self) -> f32 {
+        let mut faces = 0;
+        let mut reversed = 0;
+        for face in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|corner| &self.vertices[face[corner] as usize]);
+            let winding = (b.position - a.position).cross(c.position - a.position);
+            if winding.length() == 0.0 {
+                continue;
+            }
+            faces += 1;
+            if winding.dot(a.normal + b.normal + c.normal) < 0.0 {
+                reversed += 1;
+            }
+        }
+        if faces == 0 { 0.0 } else { reversed as f32 / faces as f32 }
+    }
+
+    /// Turns a closed model exported inside out the right way round: the winding is reversed if
+    /// it encloses a negative volume, and then the normals are negated if most of them point
+    /// against it. Open meshes have no inside to tell, so they are left alone.
+    /// Returns the corrections that were made
+    pub fn correct_orientation(&mut self) -> Vec<&'static str> {
+        let mut corrections = Vec::new();
+        if !self.is_closed() {
+            return corrections;
+        }
+        if self.signed_volume() < 0.0 {
+            for face in self.indices.chunks_exact_mut(3) {
+                face.swap(1, 2);
+            }
+            corrections.push("the winding was inside out");
+        }
+        if self.reversed_normal_fraction() > REVERSED_NORMAL_LIMIT {
+            for vertex in self.vertices.iter_mut() {
+                vertex.normal = -vertex.normal;
+            }
+            corrections.push("the normals pointed inwards");
+        }
+        if !corrections.is_empty() {
+            compute_tangents(&mut self.vertices, &self.indices);
+            self.back_faces_hidden = self.signed_volume() > 0.0;
+        }
+        corrections
+    }
+}
+
+/// Generates a UV sphere of radius 0.5 (same size as sphere.obj)
+/// stacks: number of horizontal bands from pole to pole
+/// slices: number of vertical segments around the Y axis
+pub fn generate_uv_sphere(stacks: u32, slices: u32) -> Mesh {
+    let stacks = stacks.max(2);
+    let slices = slices.max(3);
+
+    // Una fila extra de vértices en la costura para que las UV no se solapen
+    let mut vertices = Vec::with_capacity(((stacks + 1) * (slices + 1)) as usize);
+    for stack in 0..=stacks {
+        let v = stack as f32 / stacks as f32;
+        let theta = v * PI; // 0 (polo norte) .. PI (polo sur)
+        for slice in 0..=slices {
+            let u = slice as f32 / slices as f32;
+            let phi = u * 2.0 * PI;
+            let normal = Vector3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+            vertices.push(Vertex::new(normal * 0.5, normal, Vector2::new(u, v)));
+        }
+    }
+
+    let row = slices + 1;
+    let mut indices = Vec::with_capacity((stacks * slices * 6) as usize);
+    for stack in 0..stacks {
+        for slice in 0..slices {
+            let a = stack * row + slice;
+            let b = (stack + 1) * row + slice;
+            let c = (stack + 1) * row + slice + 1;
+            let d = stack * row + slice + 1;
+            // En los polos uno de los dos triángulos es degenerado
+            if stack != 0 {
+                indices.extend_from_slice(&[a, c, d]);
+            }
+            if stack != stacks - 1 {
+                indices.extend_from_slice(&[a, b, c]);
+            }
+        }
+    }
+
+    compute_tangents(&mut vertices, &indices);
+    Mesh::new(vertices, indices)
+}
+
+/// Generates an icosphere of radius 0.5 by subdividing an icosahedron.
+/// Triangles have nearly uniform size, so noise shaders don't stretch at the poles.
+/// Each subdivision level multiplies the triangle count by 4 (level 0 = 20 triangles)
+pub fn generate_icosphere(subdivisions: u32) -> Mesh {
+    // Vértices del icosaedro regular (rectángulos áureos)
+    let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+    let mut positions = vec![
+        Vector3::new(-1.0, t, 0.0), Vector3::new(1.0, t, 0.0), Vector3::new(-1.0, -t, 0.0), Vector3::new(1.0, -t, 0.0),
+        Vector3::new(0.0, -1.0, t), Vector3::new(0.0, 1.0, t), Vector3::new(0.0, -1.0, -t), Vector3::new(0.0, 1.0, -t),
+        Vector3::new(t, 0.0, -1.0), Vector3::new(t, 0.0, 1.0), Vector3::new(-t, 0.0, -1.0), Vector3::new(-t, 0.0, 1.0),
+    ];
+    for p in positions.iter_mut() {
+        p.normalize();
+    }
+
+    let mut faces: Vec<[u32; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    // Cada subdivisión parte cada triángulo en 4, reutilizando los puntos medios compartidos
+    for _ in 0..subdivisions {
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32, positions: &mut Vec<Vector3>| -> u32 {
+            let key = if a < b { (a, b) } else { (b, a) };
+            *midpoints.entry(key).or_insert_with(|| {
+                let mut p = (positions[a as usize] + positions[b as usize]) * 0.5;
+                p.normalize();
+                positions.push(p);
+                (positions.len() - 1) as u32
+            })
+        };
+
+        let mut next_faces = Vec::with_capacity(faces.len() * 4);
+        for [a, b, c] in faces {
+            let ab = midpoint(a, b, &mut positions);
+            let bc = midpoint(b, c, &mut positions);
+            let ca = midpoint(c, a, &mut positions);
+            next_faces.extend([[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+        }
+        faces = next_faces;
+    }
+
+    let mut vertices: Vec<Vertex> = positions.iter().map(|&normal| {
+        // Coordenadas de textura esféricas (igual que la esfera UV)
+        let u = 0.5 + normal.z.atan2(normal.x) / (2.0 * PI);
+        let v = normal.y.clamp(-1.0, 1.0).acos() / PI;
+        Vertex::new(normal * 0.5, normal, Vector2::new(u, v))
+    }).collect();
+    let indices: Vec<u32> = faces.into_iter().flatten().collect();
+
+    compute_tangents(&mut vertices, &indices);
+    Mesh::new(vertices, indices)
+}
+
+/// Generates a ring (torus) around the Y axis that fits in the same unit box as the sphere:
+/// outer radius 0.5 and a tube of `thickness` times that radius
+/// rings: segments around the Y axis
+/// sides: segments around the tube
+pub fn generate_torus(rings: u32, sides: u32, thickness: f32) -> Mesh {
+    let rings = rings.max(3);
+    let sides = sides.max(3);
+    let tube = 0.5 * thickness.clamp(0.02, 0.5);
+    let major = 0.5 - tube;
+
+    // Una fila y una columna extra en las costuras, como la esfera UV
+    let mut vertices = Vec::with_capacity(((rings + 1) * (sides + 1)) as usize);
+    for ring in 0..=rings {
+        let u = ring as f32 / rings as f32;
+        let phi = u * 2.0 * PI;
+        let direction = Vector3::new(phi.cos(), 0.0, phi.sin());
+        for side in 0..=sides {
+            let v = side as f32 / sides as f32;
+            let theta = v * 2.0 * PI;
+            let normal = direction * theta.cos() + Vector3::new(0.0, theta.sin(), 0.0);
+            vertices.push(Vertex::new(direction * major + normal * tube, normal, Vector2::new(u, v)));
+        }
+    }
+
+    // Antihorario visto desde fuera del tubo
+    let row = sides + 1;
+    let mut indices = Vec::with_capacity((rings * sides * 6) as usize);
+    for ring in 0..rings {
+        for side in 0..sides {
+            let a = ring * row + side;
+            let b = (ring + 1) * row + side;
+            let c = (ring + 1) * row + side + 1;
+            let d = ring * row + side + 1;
+            indices.extend_from_slice(&[a, c, b, a, d, c]);
+        }
+    }
+
+    compute_tangents(&mut vertices, &indices);
+    Mesh::new(vertices, indices)
+}
+
+/// Generates a patch of the radius 0.5 sphere around the unit direction `center`, spanning
+/// `half_angle` radians to each side, as a `resolution` x `resolution` grid. Each vertex is
+/// pushed outwards by `height(direction)` times the radius, so the positions stay in the
+/// object space of the planet and its shader paints the patch as it paints the sphere
+pub fn generate_terrain_patch(center: Vector3, half_angle: f32, resolution: u32, height: impl Fn(Vector3) -> f32) -> Mesh {
+    let resolution = resolution.max(1);
+    let center = center.normalized();
+    // Base tangente con tangent x bitangent = center (hacia fuera)
+    let axis = if center.y.abs() < 0.9 { Vector3::new(0.0, 1.0, 0.0) } else { Vector3::new(1.0, 0.0, 0.0) };
+    let tangent = center.cross(axis).normalized();
+    let bitangent = center.cross(tangent);
+
+    // Proyección gnomónica: las celdas tienen casi el mismo tamaño en todo el parche
+    let extent = half_angle.tan();
+    let row = resolution + 1;
+    let mut vertices = Vec::with_capacity((row * row) as usize);
+    for j in 0..=resolution {
+        let v = j as f32 / resolution as f32;
+        for i in 0..=resolution {
+            let u = i as f32 / resolution as f32;
+            let direction = (center + tangent * ((u * 2.0 - 1.0) * extent) + bitangent * ((v * 2.0 - 1.0) * extent)).normalized();
+            let position = direction * (0.5 * (1.0 + height(direction)));
+            vertices.push(Vertex::new(position, direction, Vector2::new(u, v)));
+        }
+    }
+
+    // Mismo sentido de giro que las esferas
+    let mut indices = Vec::with_capacity((resolution * resolution * 6) as usize);
+    for j in 0..resolution {
+        for i in 0..resolution {
+            let a = j * row + i;
+            let b = a + 1;
+            let c = a + row + 1;
+            let d = a + row;
+            indices.extend_from_slice(&[a, c, b, a, d, c]);
+        }
+    }
+
+    // Normales del relieve: promedio de las caras que comparten cada vértice
+    let mut normals = vec![Vector3::zero(); vertices.len()];
+    for face in indices.chunks_exact(3) {
+        let (i0, i1, i2) = (face[0] as usize, face[1] as usize, face[2] as usize);
+        let p0 = vertices[i0].position;
+        // Las caras giran hacia dentro (como las esferas): se invierte el producto cruz
+        let normal = (vertices[i2].position - p0).cross(vertices[i1].position - p0);
+        for i in [i0, i1, i2] {
+            normals[i] += normal;
+        }
+    }
+    for (vertex, normal) in vertices.iter_mut().zip(normals) {
+        if normal.length() > 0.0 {
+            vertex.normal = normal.normalized();
+            vertex.transformed_normal = vertex.normal;
+        }
+    }
+
+    compute_tangents(&mut vertices, &indices);
+    Mesh::new(vertices, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Prisma de altura 1 sobre un contorno antihorario en XY, con normales planas hacia fuera.
+    // Las tapas son abanicos desde el origen: el contorno tiene que verlo entero
+    fn prism(outline: &[(f32, f32)]) -> Mesh {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut add_face = |corners: &[Vector3], normal: Vector3, faces: &[[u32; 3]]| {
+            let base = vertices.len() as u32;
+            vertices.extend(corners.iter().map(|&p| Vertex::new(p, normal, Vector2::new(0.0, 0.0))));
+            indices.extend(faces.iter().flatten().map(|&i| base + i));
+        };
+        let point = |(x, y): (f32, f32), z: f32| Vector3::new(x, y, z);
+        for (i, &from) in outline.iter().enumerate() {
+            let to = outline[(i + 1) % outline.len()];
+            let side = Vector3::new(to.1 - from.1, from.0 - to.0, 0.0).normalized();
+            add_face(&[point(from, -0.5), point(to, -0.5), point(to, 0.5), point(from, 0.5)], side, &[[0, 1, 2], [0, 2, 3]]);
+            let (top, bottom) = (Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, 0.0, -1.0));
+            add_face(&[point((0.0, 0.0), 0.5), point(from, 0.5), point(to, 0.5)], top, &[[0, 1, 2]]);
+            add_face(&[point((0.0, 0.0), -0.5), point(to, -0.5), point(from, -0.5)], bottom, &[[0, 1, 2]]);
+        }
+        Mesh::new(vertices, indices)
+    }
+
+    fn cube() -> Mesh {
+        prism(&[(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)])
+    }
+
+    // Estrella de cinco puntas: las caras de sus entrantes miran hacia el centro
+    fn star() -> Mesh {
+        let outline: Vec<(f32, f32)> = (0..10).map(|i| {
+            let angle = i as f32 * PI / 5.0;
+            let radius = if i % 2 == 0 { 1.0 } else { 0.25 };
+            (radius * angle.cos(), radius * angle.sin())
+        }).collect();
+        prism(&outline)
+    }
+
+    // Exportado al revés: winding y normales hacia dentro
+    fn inside_out(mut mesh: Mesh) -> Mesh {
+        for face in mesh.indices.chunks_exact_mut(3) {
+            face.swap(1, 2);
+        }
+        for vertex in mesh.vertices.iter_mut() {
+            vertex.normal = -vertex.normal;
+        }
+        mesh
+    }
+
+    #[test]
+    fn cube_is_closed_and_outward() {
+        let mut cube = cube();
+        assert!(cube.is_closed());
+        assert!((cube.signed_volume() - 1.0).abs() < 1e-5);
+        assert_eq!(cube.reversed_normal_fraction(), 0.0);
+        assert!(cube.correct_orientation().is_empty());
+    }
+
+    #[test]
+    fn inside_out_cube_is_turned_around() {
+        let mut cube = inside_out(cube());
+        assert!(cube.signed_volume() < 0.0);
+        assert_eq!(cube.correct_orientation().len(), 2);
+        assert!((cube.signed_volume() - 1.0).abs() < 1e-5);
+        assert_eq!(cube.reversed_normal_fraction(), 0.0);
+    }
+
+    #[test]
+    fn flipped_normals_alone_are_negated() {
+        let mut cube = cube();
+        for vertex in cube.vertices.iter_mut() {
+            vertex.normal = -vertex.normal;
+        }
+        assert_eq!(cube.correct_orientation(), ["the normals pointed inwards"]);
+        assert!(cube.signed_volume() > 0.0);
+        assert_eq!(cube.reversed_normal_fraction(), 0.0);
+    }
+
+    #[test]
+    fn concave_mesh_is_left_alone() {
+        let mut star = star();
+        assert!(star.is_closed());
+        assert!(star.signed_volume() > 0.0);
+        assert!(star.correct_orientation().is_empty());
+    }
+
+    #[test]
+    fn open_mesh_is_left_alone() {
+        let mut cube = inside_out(cube());
+        // Sin una cara ya no hay dentro ni fuera
+        cube.indices.truncate(cube.indices.len() - 3);
+        assert!(!cube.is_closed());
+        assert!(cube.correct_orientation().is_empty());
+    }
+}
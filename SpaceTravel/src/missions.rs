@@ -0,0 +1,241 @@
+// missions.rs
+// Objetivos de exploración: visitar todos los cuerpos, ver un eclipse de Lunaris y dar una vuelta
+// a Voidheart por dentro de un radio. Se cumplen con los eventos del bus: el rastreador de este
+// módulo publica cuándo la cámara llega cerca de un cuerpo y cuándo completa una vuelta a su
+// alrededor, y las misiones, como un observador más, los reciben junto a los eclipses. El
+// progreso se dibuja en el HUD y al terminar todas se muestra un resumen (ver states.rs)
+use crate::events::{Event, EventBus, Observer};
+use crate::locale::Strings;
+use crate::palette::Palette;
+use crate::ui::blend_rect;
+use crate::world::{Entity, World};
+use space_travel::framebuffer::Framebuffer;
+use raylib::prelude::*;
+use std::f32::consts::{PI, TAU};
+
+// Un cuerpo cuenta como visitado con la cámara a menos de estos radios de su centro, y las
+// vueltas a su alrededor se miden mientras está a menos de estos otros
+const VISIT_RADII: f32 = 5.0;
+const ORBIT_RADII: f32 = 6.0;
+
+// Radio máximo de la vuelta a Voidheart
+const VOIDHEART_ORBIT_RADIUS: f32 = 18.0;
+
+// Medidas en píxeles para un framebuffer de 720 de alto (se escalan con él)
+const REFERENCE_HEIGHT: f32 = 720.0;
+const TEXT_SIZE: f32 = 16.0;
+const TITLE_SIZE: f32 = 32.0;
+const LINE_HEIGHT: f32 = 22.0;
+const MARGIN: f32 = 20.0;
+const PANEL_WIDTH: f32 = 320.0;
+const SUMMARY_WIDTH: f32 = 520.0;
+
+const PANEL_COLOR: Vector3 = Vector3::new(0.05, 0.06, 0.12);
+const TITLE_COLOR: Color = Color::new(230, 235, 255, 255);
+const PENDING_COLOR: Color = Color::new(180, 190, 215, 255);
+
+// Lo que hay que hacer para cumplir una misión
+enum Objective {
+    VisitAll,
+    Eclipse { occluder: Entity },
+    Orbit { body: Entity, max_radius: f32 },
+}
+
+struct Mission {
+    objective: Objective,
+    completed_at: Option<f32>, // Segundos de exploración al cumplirla
+}
+
+/// The exploration objectives of the scene and how far along they are
+pub struct Missions {
+    missions: Vec<Mission>,
+    visited: Vec<bool>,
+    elapsed: f32,
+    summary_shown: bool,
+}
+
+impl Missions {
+    /// The missions that make sense in `world`: the ones about a named body need that body
+    pub fn new(world: &World) -> Self {
+        let mut missions = vec![Mission { objective: Objective::VisitAll, completed_at: None }];
+        if let Some(occluder) = world.find("Lunaris") {
+            missions.push(Mission { objective: Objective::Eclipse { occluder }, completed_at: None });
+        }
+        if let Some(body) = world.find("Voidheart") {
+            missions.push(Mission { objective: Objective::Orbit { body, max_radius: VOIDHEART_ORBIT_RADIUS }, completed_at: None });
+        }
+        Missions { missions, visited: vec![false; world.names.len()], elapsed: 0.0, summary_shown: false }
+    }
+
+    /// Counts the exploration time
+    pub fn update(&mut self, dt: f32) {
+        if !self.is_complete() {
+            self.elapsed += dt;
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.missions.iter().all(|mission| mission.completed_at.is_some())
+    }
+
+    /// Whether the summary has to be shown now: true once, when the last mission is done
+    pub fn take_summary(&mut self) -> bool {
+        let show = self.is_complete() && !self.summary_shown;
+        self.summary_shown |= show;
+        show
+    }
+
+    // Enunciado de una misión en el idioma de `strings`
+    fn title(&self, mission: &Mission, strings: &Strings) -> String {
+        match mission.objective {
+            Objective::VisitAll => strings.format("missions.visit_all", &[("count", &self.visited.len())]),
+            Objective::Eclipse { .. } => strings.get("missions.eclipse").to_string(),
+            Objective::Orbit { max_radius, .. } => strings.format("missions.orbit", &[("radius", &max_radius)]),
+        }
+    }
+
+    // Texto de una misión con su progreso
+    fn line(&self, mission: &Mission, strings: &Strings) -> String {
+        let mark = if mission.completed_at.is_some() { "[x]" } else { "[ ]" };
+        let title = self.title(mission, strings);
+        match mission.objective {
+            Objective::VisitAll => {
+                let visited = self.visited.iter().filter(|&&visited| visited).count();
+                format!("{} {} ({}/{})", mark, title, visited, self.visited.len())
+            }
+            _ => format!("{} {}", mark, title),
+        }
+    }
+
+    /// Draws the list of missions and their progress in the top right corner of the frame
+    pub fn draw_progress(&self, framebuffer: &mut Framebuffer, strings: &Strings, palette: &Palette) {
+        let unit = framebuffer.height as f32 / REFERENCE_HEIGHT;
+        let px = |value: f32| (value * unit).round() as i32;
+        let done = self.missions.iter().filter(|mission| mission.completed_at.is_some()).count();
+        let (width, height) = (px(PANEL_WIDTH), px(LINE_HEIGHT) * (self.missions.len() as i32 + 1) + px(MARGIN) / 2);
+        let (x, y) = (framebuffer.width - width - px(MARGIN), px(MARGIN));
+        blend_rect(framebuffer, (x, y, width, height), PANEL_COLOR, 0.6);
+
+        let image = &mut framebuffer.color_buffer;
+        let padding = px(MARGIN) / 2;
+        let header = strings.format("missions.header", &[("done", &done), ("total", &self.missions.len())]);
+        image.draw_text(&header, x + padding, y + padding, px(TEXT_SIZE), TITLE_COLOR);
+        for (index, mission) in self.missions.iter().enumerate() {
+            let color = if mission.completed_at.is_some() { palette.accent } else { PENDING_COLOR };
+            let line_y = y + padding + px(LINE_HEIGHT) * (index as i32 + 1);
+            image.draw_text(&self.line(mission, strings), x + padding, line_y, px(TEXT_SIZE), color);
+        }
+    }
+
+    /// Draws the completion summary over the dimmed frame: each mission with the time it was
+    /// done at and the total exploration time
+    pub fn draw_summary(&self, framebuffer: &mut Framebuffer, strings: &Strings, palette: &Palette) {
+        let unit = framebuffer.height as f32 / REFERENCE_HEIGHT;
+        let px = |value: f32| (value * unit).round() as i32;
+        blend_rect(framebuffer, (0, 0, framebuffer.width, framebuffer.height), Vector3::zero(), 0.5);
+        let lines = self.missions.len() as i32 + 4;
+        let (width, height) = (px(SUMMARY_WIDTH), px(TITLE_SIZE) + px(LINE_HEIGHT) * lines + px(MARGIN) * 2);
+        let (x, y) = ((framebuffer.width - width) / 2, (framebuffer.height - height) / 2);
+        blend_rect(framebuffer, (x, y, width, height), PANEL_COLOR, 0.85);
+
+        let image = &mut framebuffer.color_buffer;
+        let padding = px(MARGIN);
+        image.draw_rectangle_lines(Rectangle::new(x as f32, y as f32, width as f32, height as f32), px(2.0).max(1), palette.accent);
+        image.draw_text(strings.get("missions.complete"), x + padding, y + padding, px(TITLE_SIZE), TITLE_COLOR);
+        let mut line_y = y + padding + px(TITLE_SIZE) + px(LINE_HEIGHT);
+        for mission in &self.missions {
+            let time = mission.completed_at.map_or_else(String::new, format_time);
+            image.draw_text(&format!("{}  {}", time, self.title(mission, strings)), x + padding, line_y, px(TEXT_SIZE), palette.accent);
+            line_y += px(LINE_HEIGHT);
+        }
+        line_y += px(LINE_HEIGHT);
+        let total = strings.format("missions.exploration_time", &[("time", &format_time(self.elapsed))]);
+        image.draw_text(&total, x + padding, line_y, px(TEXT_SIZE), TITLE_COLOR);
+        line_y += px(LINE_HEIGHT);
+        image.draw_text(strings.get("missions.keep_exploring"), x + padding, line_y, px(TEXT_SIZE), PENDING_COLOR);
+    }
+}
+
+impl Observer for Missions {
+    fn on_event(&mut self, event: &Event, _world: &World) {
+        if let Event::BodyVisited { body } = *event
+            && let Some(visited) = self.visited.get_mut(body)
+        {
+            *visited = true;
+        }
+        let all_visited = self.visited.iter().all(|&visited| visited);
+        let elapsed = self.elapsed;
+        for mission in self.missions.iter_mut().filter(|mission| mission.completed_at.is_none()) {
+            let done = match (&mission.objective, event) {
+                (Objective::VisitAll, Event::BodyVisited { .. }) => all_visited,
+                (Objective::Eclipse { occluder }, Event::EclipseBegan { occluder: body, .. }) => occluder == body,
+                (Objective::Orbit { body, max_radius }, Event::OrbitCompleted { body: orbited, radius }) => body == orbited && radius <= max_radius,
+                _ => false,
+            };
+            if done {
+                mission.completed_at = Some(elapsed);
+            }
+        }
+    }
+}
+
+// Vuelta en curso alrededor de un cuerpo
+struct Loop {
+    angle: f32,        // Ángulo de la cámara alrededor del cuerpo en el plano XZ
+    swept: f32,        // Ángulo recorrido, con signo
+    max_distance: f32,
+}
+
+/// Watches the camera around the bodies and publishes when it visits one and when it goes
+/// once around one
+pub struct ProximityTracker {
+    near: Vec<bool>,
+    loops: Vec<Option<Loop>>,
+}
+
+impl ProximityTracker {
+    pub fn new() -> Self {
+        ProximityTracker { near: Vec::new(), loops: Vec::new() }
+    }
+
+    /// Compares the camera at `eye` with every body of `world` and publishes what changed
+    pub fn update(&mut self, eye: Vector3, world: &World, events: &mut EventBus) {
+        // Una recarga de la escena cambia los cuerpos: se empieza de cero
+        if self.near.len() != world.transforms.len() {
+            self.near = vec![false; world.transforms.len()];
+            self.loops = (0..world.transforms.len()).map(|_| None).collect();
+        }
+        for (body, transform) in world.transforms.iter().enumerate() {
+            let offset = eye - transform.position;
+            let distance = offset.length();
+            let radius = transform.scale * 0.5;
+
+            let near = distance < radius * VISIT_RADII;
+            if near && !self.near[body] {
+                events.publish(Event::BodyVisited { body });
+            }
+            self.near[body] = near;
+
+            // La vuelta se cuenta en el plano de las órbitas y se pierde al alejarse
+            if distance >= radius * ORBIT_RADII {
+                self.loops[body] = None;
+                continue;
+            }
+            let angle = offset.z.atan2(offset.x);
+            let progress = self.loops[body].get_or_insert(Loop { angle, swept: 0.0, max_distance: distance });
+            progress.swept += (angle - progress.angle + PI).rem_euclid(TAU) - PI;
+            progress.angle = angle;
+            progress.max_distance = progress.max_distance.max(distance);
+            if progress.swept.abs() >= TAU {
+                events.publish(Event::OrbitCompleted { body, radius: progress.max_distance });
+                *progress = Loop { angle, swept: 0.0, max_distance: distance };
+            }
+        }
+    }
+}
+
+// Tiempo como mm:ss
+fn format_time(seconds: f32) -> String {
+    let seconds = seconds.max(0.0) as u32;
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
@@ -0,0 +1,213 @@
+// music.rs
+// Música de fondo con dos pistas que se mezclan según la cercanía a las estrellas:
+// "exploración" (acordes suaves y notas sueltas de una escala pentatónica) y "cerca de una
+// estrella" (un grupo de notas graves disonantes que late). Cada pista es un stream de música
+// de raylib que suena en bucle; si falta su archivo se compone aquí muestra a muestra y se
+// guarda como WAV, así se puede sustituir por cualquier otro WAV con el mismo nombre
+use crate::world::World;
+use space_travel::noise::hash;
+use space_travel::simd::dot;
+use raylib::prelude::*;
+use std::f32::consts::TAU;
+use std::fs;
+use std::path::Path;
+
+// Archivos de las dos pistas
+const EXPLORATION_PATH: &str = "music/exploration.wav";
+const NEAR_STAR_PATH: &str = "music/near_star.wav";
+// Formato de las pistas compuestas: mono, 16 bits
+const SAMPLE_RATE: u32 = 22050;
+// Segundos del fundido del final sobre el principio, para que el bucle no haga clic
+const LOOP_FADE: f32 = 2.0;
+// Segundos que tarda el cambio de una pista a otra
+const CROSSFADE_SECONDS: f32 = 1.0;
+
+// Acordes de la pista de exploración (Am, F, C, G), uno cada CHORD_SECONDS
+const CHORDS: [[f32; 4]; 4] = [
+    [220.0, 261.63, 329.63, 392.0],
+    [174.61, 220.0, 261.63, 329.63],
+    [261.63, 329.63, 392.0, 493.88],
+    [196.0, 246.94, 293.66, 392.0],
+];
+const CHORD_SECONDS: f32 = 8.0;
+// Notas sueltas (La menor pentatónica), una posible cada ARPEGGIO_SECONDS
+const PENTATONIC: [f32; 5] = [440.0, 523.25, 587.33, 659.25, 783.99];
+const ARPEGGIO_SECONDS: f32 = 0.5;
+// Grupo grave de la pista cercana a una estrella (Re, Mi bemol, La)
+const STAR_CLUSTER: [f32; 3] = [73.42, 77.78, 110.0];
+const STAR_PULSE: f32 = 1.25; // Latidos por segundo (40 en cada vuelta del bucle)
+// Duración de las pistas: una vuelta completa a los acordes
+const TRACK_SECONDS: f32 = CHORD_SECONDS * CHORDS.len() as f32;
+
+/// How close the camera is to a star of the scene (0 = far away, 1 = right next to it), used
+/// to crossfade the music. The fade starts at ten radii from the star and is complete at three
+pub fn near_star_weight(eye: Vector3, world: &World) -> f32 {
+    world.transforms.iter().zip(&world.lights)
+        .filter(|(_, light)| light.is_some())
+        .map(|(transform, _)| {
+            let offset = eye - transform.position;
+            let distance = dot(offset, offset).sqrt();
+            let radius = transform.scale * 0.5; // Las mallas de esfera tienen radio 0.5
+            let t = ((radius * 10.0 - distance) / (radius * 7.0)).clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        })
+        .fold(0.0, f32::max)
+}
+
+/// The two music tracks, crossfaded by `update`
+pub struct MusicPlayer<'a> {
+    exploration: Music<'a>,
+    near_star: Music<'a>,
+    mix: f32, // Mezcla actual entre pistas (0 = exploración, 1 = estrella), suavizada
+}
+
+impl<'a> MusicPlayer<'a> {
+    /// Opens both tracks, composing the ones whose file is missing, and starts them
+    pub fn load(device: &'a RaylibAudio) -> Result<Self, String> {
+        let exploration = load_track(device, EXPLORATION_PATH, Track::Exploration)?;
+        let near_star = load_track(device, NEAR_STAR_PATH, Track::NearStar)?;
+        exploration.play_stream();
+        near_star.play_stream();
+        Ok(MusicPlayer { exploration, near_star, mix: 0.0 })
+    }
+
+    /// Moves the mix towards the near-star track as `near_star` goes to 1, sets the volume of
+    /// each track and refills their stream buffers
+    pub fn update(&mut self, near_star: f32, volume: f32, dt: f32) {
+        // Fundido lento para que la música no cambie de golpe
+        self.mix += (near_star - self.mix) * (dt / CROSSFADE_SECONDS).min(1.0);
+        self.exploration.set_volume(volume * (1.0 - self.mix));
+        self.near_star.set_volume(volume * self.mix);
+        self.exploration.update_stream();
+        self.near_star.update_stream();
+    }
+}
+
+// Abre una pista; si su archivo no existe, antes la compone y lo escribe
+fn load_track<'a>(device: &'a RaylibAudio, path: &str, track: Track) -> Result<Music<'a>, String> {
+    if !Path::new(path).exists() {
+        write_wav(path, &track.compose())?;
+    }
+    device.new_music(path).map_err(|e| format!("{}: {}", path, e))
+}
+
+// Guarda muestras mono en -1..1 como WAV de 16 bits
+fn write_wav(path: &str, samples: &[f32]) -> Result<(), String> {
+    let data_size = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_size as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes()); // Tamaño del bloque fmt
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // Canales
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes()); // Bytes por segundo
+    bytes.extend_from_slice(&2u16.to_le_bytes()); // Bytes por muestra
+    bytes.extend_from_slice(&16u16.to_le_bytes()); // Bits por muestra
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes());
+    }
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("{}: {}", parent.display(), e))?;
+    }
+    fs::write(path, bytes).map_err(|e| format!("{}: {}", path, e))
+}
+
+#[derive(Clone, Copy)]
+enum Track {
+    Exploration,
+    NearStar,
+}
+
+impl Track {
+    // Muestras de una vuelta del bucle; se compone un poco más y el sobrante se funde sobre el
+    // principio, así el final enlaza con el principio sin saltos
+    fn compose(self) -> Vec<f32> {
+        let step = 1.0 / SAMPLE_RATE as f32;
+        let length = (TRACK_SECONDS * SAMPLE_RATE as f32) as usize;
+        let fade = (LOOP_FADE * SAMPLE_RATE as f32) as usize;
+        let mut composer = Composer::new();
+        let mut samples: Vec<f32> = (0..length + fade).map(|_| match self {
+            Track::Exploration => composer.exploration(step),
+            Track::NearStar => composer.star(step),
+        }).collect();
+        let (head, tail) = samples.split_at_mut(length);
+        for (index, (sample, extra)) in head.iter_mut().zip(tail.iter()).enumerate() {
+            let t = index as f32 / fade as f32;
+            *sample = *sample * t + extra * (1.0 - t);
+        }
+        samples.truncate(length);
+        samples
+    }
+}
+
+// Sintetizador de las dos pistas
+struct Composer {
+    time: f32,
+    pad_frequencies: [f32; 4],
+    pad_phases: [f32; 4],
+    pluck_frequency: f32,
+    pluck_phase: f32,
+    pluck_time: f32,
+    last_step: i32,
+    cluster_phases: [f32; 3],
+    pulse_phase: f32,
+}
+
+impl Composer {
+    fn new() -> Self {
+        Composer {
+            time: 0.0,
+            pad_frequencies: CHORDS[0],
+            pad_phases: [0.0; 4],
+            pluck_frequency: PENTATONIC[0],
+            pluck_phase: 0.0,
+            pluck_time: f32::INFINITY,
+            last_step: -1,
+            cluster_phases: [0.0; 3],
+            pulse_phase: 0.0,
+        }
+    }
+
+    fn exploration(&mut self, step: f32) -> f32 {
+        self.time = (self.time + step) % TRACK_SECONDS;
+        // Las notas del acorde se deslizan hacia las del siguiente
+        let chord = CHORDS[(self.time / CHORD_SECONDS) as usize % CHORDS.len()];
+        let mut pad = 0.0;
+        for ((frequency, phase), target) in self.pad_frequencies.iter_mut().zip(&mut self.pad_phases).zip(chord) {
+            *frequency += (target - *frequency) * 0.0001;
+            *phase = (*phase + TAU * *frequency * step) % TAU;
+            pad += phase.sin() + 0.3 * (2.0 * *phase).sin();
+        }
+
+        // En cada paso, algunas veces suena una nota pentatónica que se apaga sola
+        let current_step = (self.time / ARPEGGIO_SECONDS) as i32;
+        if current_step != self.last_step {
+            self.last_step = current_step;
+            let random = hash(current_step as u32 ^ 0x3C);
+            if random % 10 < 4 {
+                self.pluck_frequency = PENTATONIC[(random >> 8) as usize % PENTATONIC.len()];
+                self.pluck_time = 0.0;
+            }
+        }
+        self.pluck_time += step;
+        self.pluck_phase = (self.pluck_phase + TAU * self.pluck_frequency * step) % TAU;
+        let pluck = self.pluck_phase.sin() * (-self.pluck_time * 4.0).exp();
+
+        pad * 0.06 + pluck * 0.15
+    }
+
+    fn star(&mut self, step: f32) -> f32 {
+        let mut cluster = 0.0;
+        for (phase, frequency) in self.cluster_phases.iter_mut().zip(STAR_CLUSTER) {
+            *phase = (*phase + TAU * frequency * step) % TAU;
+            cluster += phase.sin() + 0.5 * (3.0 * *phase).sin();
+        }
+        self.pulse_phase = (self.pulse_phase + TAU * STAR_PULSE * step) % TAU;
+        let pulse = 0.5 + 0.5 * self.pulse_phase.sin();
+        cluster * (0.3 + 0.7 * pulse * pulse) * 0.12
+    }
+}
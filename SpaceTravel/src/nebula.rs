@@ -0,0 +1,162 @@
+// nebula.rs
+// Fondo del cielo en tres capas: la banda de la galaxia (en el infinito), la nebulosa y polvo
+// cercano. Cada capa es una esfera de distinto radio alrededor del sistema: al mover la cámara
+// las más cercanas se desplazan más (paralaje) sin geometría 3D. Se pinta en la capa de fondo
+// del framebuffer, que el clear restaura, y solo se repinta al cambiar la cámara o sus ajustes
+use space_travel::framebuffer::Framebuffer;
+use space_travel::matrix::{inverse, multiply_matrix_vector4};
+use space_travel::noise::value_noise;
+use space_travel::scene::NebulaPalette;
+use space_travel::shader_params::{NebulaParams, rgb};
+use space_travel::simd::dot;
+use rayon::prelude::*;
+use raylib::prelude::*;
+
+/// The background layers last painted into the framebuffer and what they were painted with
+#[derive(Default)]
+pub struct Nebula {
+    painted: Option<([f32; 16], NebulaPalette, NebulaParams)>,
+}
+
+impl Nebula {
+    pub fn new() -> Self {
+        Nebula::default()
+    }
+
+    /// Repaints the framebuffer's background layer if the camera, the palette or the settings
+    /// changed since the last call (or the framebuffer lost its layer); otherwise the clear
+    /// keeps restoring the one already painted
+    pub fn update(&mut self, framebuffer: &mut Framebuffer, view_projection: &Matrix, palette: &NebulaPalette, params: &NebulaParams) {
+        let key = (view_projection.to_array(), *palette, params.clone());
+        if framebuffer.has_painted_background() && self.painted.as_ref() == Some(&key) {
+            return;
+        }
+        framebuffer.reset_background();
+        draw(framebuffer, view_projection, palette, params);
+        self.painted = Some(key);
+    }
+}
+
+// Suma las capas a la capa de fondo del framebuffer. El ruido se muestrea cada `step` píxeles
+// y se interpola entre medias, ya que las nubes no tienen detalle fino
+fn draw(framebuffer: &mut Framebuffer, view_projection: &Matrix, palette: &NebulaPalette, params: &NebulaParams) {
+    if params.brightness <= 0.0 && params.galaxy_brightness <= 0.0 && params.dust_opacity <= 0.0 {
+        return;
+    }
+    let Some(inverse_view_projection) = inverse(view_projection) else {
+        return;
+    };
+    let unproject = |x: f32, y: f32, z: f32| {
+        let p = multiply_matrix_vector4(&inverse_view_projection, &Vector4::new(x, y, z, 1.0));
+        Vector3::new(p.x / p.w, p.y / p.w, p.z / p.w)
+    };
+
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let step = params.step.max(1);
+    let columns = width / step + 2;
+    let rows = height / step + 2;
+    let samples: Vec<Vector3> = (0..columns * rows)
+        .into_par_iter()
+        .map(|index| {
+            let x = ((index % columns) * step) as f32 / width as f32 * 2.0 - 1.0;
+            let y = 1.0 - ((index / columns) * step) as f32 / height as f32 * 2.0;
+            // Rayo del píxel: desde el plano cercano (la cámara) hacia el lejano
+            let origin = unproject(x, y, -1.0);
+            let ray = unproject(x, y, 1.0) - origin;
+            sky_color(origin, ray / dot(ray, ray).sqrt().max(1e-6), palette, params)
+        })
+        .collect();
+
+    for y in 0..height {
+        let (row, ty) = (y / step, (y % step) as f32 / step as f32);
+        for x in 0..width {
+            let (column, tx) = (x / step, (x % step) as f32 / step as f32);
+            let sample = |c: i32, r: i32| samples[(r * columns + c) as usize];
+            let top = sample(column, row) * (1.0 - tx) + sample(column + 1, row) * tx;
+            let bottom = sample(column, row + 1) * (1.0 - tx) + sample(column + 1, row + 1) * tx;
+            let color = top * (1.0 - ty) + bottom * ty;
+            // Fuera de las nubes no hay nada que sumar
+            if color.x + color.y + color.z > 1.0 / 255.0 {
+                framebuffer.add_to_background(x, y, color);
+            }
+        }
+    }
+}
+
+// Suma de octavas de ruido, normalizada a 0..1
+fn fbm(p: Vector3, octaves: u32) -> f32 {
+    let (mut sum, mut amplitude, mut total, mut frequency) = (0.0, 0.5, 0.0, 1.0);
+    for octave in 0..octaves.max(1) {
+        // Desplazamiento por octava para que no coincidan los puntos de la red
+        let offset = octave as f32 * 7.31;
+        sum += value_noise(p.x * frequency + offset, p.y * frequency - offset, p.z * frequency + offset * 0.5) * amplitude;
+        total += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    sum / total
+}
+
+// Color del cielo en un rayo: la galaxia y la nebulosa, tapadas en parte por el polvo
+fn sky_color(origin: Vector3, direction: Vector3, palette: &NebulaPalette, params: &NebulaParams) -> Vector3 {
+    let galaxy = galaxy_color(direction, params);
+    let nebula = if params.brightness > 0.0 {
+        nebula_color(layer_direction(origin, direction, params.nebula_distance), palette, params)
+    } else {
+        Vector3::zero()
+    };
+    if params.dust_opacity <= 0.0 {
+        return galaxy + nebula;
+    }
+    // Polvo oscuro que absorbe la luz de detrás, con un leve brillo del color de la nebulosa
+    let p = layer_direction(origin, direction, params.dust_distance) * (params.frequency * 3.0) + Vector3::new(3.7, 9.1, 2.4);
+    let dust = ((fbm(p, params.octaves) - 0.5) * 4.0).clamp(0.0, 1.0) * params.dust_opacity.min(1.0);
+    (galaxy + nebula) * (1.0 - dust) + palette.colors[0] * (dust * 0.3)
+}
+
+// Punto donde el rayo atraviesa la esfera de radio `distance` centrada en el origen, como
+// dirección desde el centro; sin radio (o con la cámara fuera de la esfera) la del propio rayo
+fn layer_direction(origin: Vector3, direction: Vector3, distance: f32) -> Vector3 {
+    let b = dot(origin, direction);
+    let discriminant = b * b - dot(origin, origin) + distance * distance;
+    if distance <= 0.0 || discriminant <= 0.0 {
+        return direction;
+    }
+    let point = origin + direction * (discriminant.sqrt() - b);
+    point / dot(point, point).sqrt().max(1e-6)
+}
+
+// Banda difusa de la galaxia alrededor del círculo máximo perpendicular a `galaxy_normal`,
+// moteada con ruido; está en el infinito y solo gira con la cámara
+fn galaxy_color(direction: Vector3, params: &NebulaParams) -> Vector3 {
+    if params.galaxy_brightness <= 0.0 {
+        return Vector3::zero();
+    }
+    let normal = rgb(params.galaxy_normal);
+    let height = dot(direction, normal / dot(normal, normal).sqrt().max(1e-6)) / params.galaxy_width.max(1e-3);
+    let band = (-height * height).exp();
+    if band < 0.01 {
+        return Vector3::zero();
+    }
+    let p = direction * (params.frequency * 4.0) + Vector3::new(11.0, 4.0, 7.0);
+    let mottling = 0.4 + 0.6 * fbm(p, params.octaves);
+    rgb(params.galaxy_color) * (band * mottling * params.galaxy_brightness)
+}
+
+// Color de la nebulosa en una dirección: la forma de las nubes sale de una capa de ruido y el
+// tono, de otra a mayor frecuencia; los núcleos más densos toman el color más brillante
+fn nebula_color(direction: Vector3, palette: &NebulaPalette, params: &NebulaParams) -> Vector3 {
+    let p = direction * params.frequency + Vector3::new(16.0, 16.0, 16.0);
+    let cloud = fbm(p, params.octaves);
+    let density = palette.density.clamp(0.01, 1.0);
+    // El FBM se concentra alrededor de 0.5: la cobertura decide desde dónde empiezan las nubes
+    let coverage = ((cloud - (0.75 - density * 0.5)) / (density * 0.5)).clamp(0.0, 1.0);
+    if coverage <= 0.0 {
+        return Vector3::zero();
+    }
+    let coverage = coverage * coverage * (3.0 - 2.0 * coverage);
+    let tone = fbm(p * 2.3 + Vector3::new(5.2, 1.3, 8.7), params.octaves.saturating_sub(1));
+    let [thin, mid, core] = palette.colors;
+    let tint = thin + (mid - thin) * tone.clamp(0.0, 1.0);
+    (tint * coverage + core * coverage.powi(3)) * params.brightness
+}
@@ -0,0 +1,70 @@
+// noise.rs
+// Ruido de valor 3D periódico precalculado en una tabla y muestreado con interpolación trilineal.
+// Sustituye a las combinaciones de sin/cos por fragmento de los shaders.
+const SIZE: usize = 32; // Resolución de la tabla por eje (potencia de 2 para envolver con una máscara)
+const MASK: i32 = SIZE as i32 - 1;
+
+pub const fn hash(i: u32) -> u32 {
+    // Hash entero determinista: la misma tabla en cada ejecución
+    let mut h = i.wrapping_mul(0x9E37_79B9) ^ 0x85EB_CA6B;
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7FEB_352D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846C_A68B);
+    h ^= h >> 16;
+    h
+}
+
+// Valores de 8 bits: la tabla ocupa 32 KB y cabe en la caché L1
+const fn build_table() -> [u8; SIZE * SIZE * SIZE] {
+    let mut table = [0; SIZE * SIZE * SIZE];
+    let mut i = 0;
+    while i < table.len() {
+        table[i] = (hash(i as u32) >> 24) as u8;
+        i += 1;
+    }
+    table
+}
+
+// Se calcula en tiempo de compilación
+static TABLE: [u8; SIZE * SIZE * SIZE] = build_table();
+
+#[inline]
+fn lattice(x: i32, y: i32, z: i32) -> f32 {
+    let (x, y, z) = ((x & MASK) as usize, (y & MASK) as usize, (z & MASK) as usize);
+    TABLE[(z * SIZE + y) * SIZE + x] as f32
+}
+
+// Parte entera y fraccionaria sin llamar a floor (más lento en x86_64 sin SSE4.1)
+#[inline]
+fn split(v: f32) -> (i32, f32) {
+    let i = if v < 0.0 { v as i32 - 1 } else { v as i32 };
+    (i, v - i as f32)
+}
+
+/// Periodic value noise in [0, 1] (period 32 on each axis), trilinearly interpolated
+pub fn value_noise(x: f32, y: f32, z: f32) -> f32 {
+    let (ix, tx) = split(x);
+    let (iy, ty) = split(y);
+    let (iz, tz) = split(z);
+
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let x00 = lerp(lattice(ix, iy, iz), lattice(ix + 1, iy, iz), tx);
+    let x10 = lerp(lattice(ix, iy + 1, iz), lattice(ix + 1, iy + 1, iz), tx);
+    let x01 = lerp(lattice(ix, iy, iz + 1), lattice(ix + 1, iy, iz + 1), tx);
+    let x11 = lerp(lattice(ix, iy + 1, iz + 1), lattice(ix + 1, iy + 1, iz + 1), tx);
+    lerp(lerp(x00, x10, ty), lerp(x01, x11, ty), tz) * (1.0 / 255.0)
+}
+
+/// Animated three-octave noise used by the planet shaders.
+/// Same parameters and a similar range as the old sin/cos `exotic_noise` (roughly 0 to 0.6)
+pub fn animated_noise(x: f32, y: f32, z: f32, time: f32, frequency: f32) -> f32 {
+    let freq = frequency * 2.0;
+    // Cada octava se desplaza en el tiempo con una dirección distinta
+    let n1 = value_noise(x * freq * 1.5 + time * 0.7, y * freq + time * 0.5, z * freq * 2.0 + time * 0.3) * 2.0 - 1.0;
+    let n2 = value_noise(x * freq * 3.0 + time * 1.2, y * freq * 1.5 + time * 0.8, z * freq + time * 1.1) * 2.0 - 1.0;
+    let n3 = value_noise(x * freq * 6.0 + time * 2.0, y * freq * 4.0 + time * 1.5, z * freq * 3.0 + time * 0.9) * 2.0 - 1.0;
+
+    // Combinar diferentes frecuencias para efecto más complejo
+    (n1 * 0.5 + n2 * 0.3 + n3 * 0.2).abs()
+}
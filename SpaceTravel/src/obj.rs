@@ -1,31 +1,72 @@
 // obj.rs
+use crate::material::Material;
+use crate::mesh::{Mesh, Submesh};
 use crate::vertex::Vertex;
 use raylib::math::{Vector2, Vector3};
+use std::collections::HashMap;
+use std::fs;
+use std::iter;
 use tobj;
 
 pub struct Obj {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
+    pub materials: Vec<Material>,
+    pub submeshes: Vec<Submesh>, // Un objeto o grupo (o, g) por parte, en orden
+    pub smoothing_groups: Vec<u32>, // Grupo de suavizado (s) de cada triángulo; vacío si el archivo no los usa
 }
 
 impl Obj {
     pub fn load(path: &str) -> Result<Self, tobj::LoadError> {
-        let (models, _materials) = tobj::load_obj(path, &tobj::GPU_LOAD_OPTIONS)?;
+        // tobj resuelve los índices negativos (relativos) y los formatos v, v/vt, v//vn y v/vt/vn;
+        // los quads y n-gonos se triangulan en abanico desde su primer vértice. Con `single_index`
+        // cada combinación v/vt/vn repetida es un solo vértice del buffer de índices, y las que
+        // solo comparten posición (costuras de la textura) siguen separadas
+        let load_options = tobj::LoadOptions {
+            triangulate: true,
+            single_index: true,
+            ignore_points: true,
+            ignore_lines: true,
+        };
+        let (models, materials) = tobj::load_obj(path, &load_options)?;
+
+        // Un .mtl faltante o inválido no impide cargar la geometría
+        let materials: Vec<Material> = match materials {
+            Ok(materials) => materials.iter().map(Material::from_tobj).collect(),
+            Err(e) => {
+                eprintln!("Failed to load materials for {}: {}", path, e);
+                Vec::new()
+            }
+        };
 
         let mut vertices = Vec::new();
         let mut indices = Vec::new();
+        let mut submeshes = Vec::new();
+        let mut missing_normals = false;
 
         for model in models {
             let mesh = &model.mesh;
             let num_vertices = mesh.positions.len() / 3;
 
+            // Si algunas caras no traen vt o vn, tobj omite esos valores y los arreglos quedan
+            // desalineados con las posiciones: en ese caso se ignora el atributo completo
+            let has_normals = mesh.normals.len() == num_vertices * 3;
+            let has_tex_coords = mesh.texcoords.len() == num_vertices * 2;
+            // Colores por vértice (extensión "v x y z r g b"): tiñen el color difuso del material
+            let has_colors = mesh.vertex_color.len() == num_vertices * 3;
+            missing_normals |= !has_normals;
+            // Los índices de cada objeto son relativos a sus propios vértices
+            let base_index = vertices.len() as u32;
+            let material_id = mesh.material_id.filter(|&id| id < materials.len());
+            let material = material_id.map(|id| materials[id].clone()).unwrap_or_default();
+
             for i in 0..num_vertices {
                 let x = mesh.positions[i * 3];
                 let y = mesh.positions[i * 3 + 1];
                 let z = mesh.positions[i * 3 + 2];
                 let position = Vector3::new(x, y, z);
 
-                let normal = if !mesh.normals.is_empty() {
+                let normal = if has_normals {
                     let nx = mesh.normals[i * 3];
                     let ny = mesh.normals[i * 3 + 1];
                     let nz = mesh.normals[i * 3 + 2];
@@ -34,7 +75,7 @@ impl Obj {
                     Vector3::zero()
                 };
 
-                let tex_coords = if !mesh.texcoords.is_empty() {
+                let tex_coords = if has_tex_coords {
                     let u = mesh.texcoords[i * 2];
                     let v = mesh.texcoords[i * 2 + 1];
                     Vector2::new(u, v)
@@ -42,12 +83,124 @@ impl Obj {
                     Vector2::zero()
                 };
 
-                vertices.push(Vertex::new(position, normal, tex_coords));
+                let mut vertex = Vertex::new(position, normal, tex_coords);
+                vertex.color = if has_colors {
+                    let diffuse = material.diffuse;
+                    Vector3::new(
+                        diffuse.x * mesh.vertex_color[i * 3],
+                        diffuse.y * mesh.vertex_color[i * 3 + 1],
+                        diffuse.z * mesh.vertex_color[i * 3 + 2],
+                    )
+                } else {
+                    material.diffuse
+                };
+                vertex.specular = material.specular;
+                vertex.shininess = material.shininess;
+                vertex.emissive = material.emissive;
+                vertex.material_id = material_id;
+                vertices.push(vertex);
             }
-            indices.extend_from_slice(&mesh.indices);
+            // tobj separa los objetos y grupos (y, dentro de ellos, cada material): cada uno es una parte
+            let first_index = indices.len();
+            indices.extend(mesh.indices.iter().map(|&index| base_index + index));
+            submeshes.push(Submesh { name: model.name.clone(), indices: first_index..indices.len(), vertices: base_index as usize..vertices.len() });
+        }
+
+        let smoothing_groups = read_smoothing_groups(path, indices.len() / 3);
+        let mut obj = Obj { vertices, indices, materials, submeshes, smoothing_groups };
+        // Sin entradas vn la iluminación no funciona: generar normales suaves
+        if missing_normals {
+            obj.smooth_normals(None);
+        } else {
+            obj.compute_tangents();
+        }
+        Ok(obj)
+    }
+
+    // Rango de vértices de cada parte a partir de sus índices, tras reordenar los vértices (los
+    // nuevos se crean en el orden de las caras, así que los de cada parte siguen juntos)
+    fn update_submesh_vertices(&mut self) {
+        for submesh in &mut self.submeshes {
+            let indices = &self.indices[submesh.indices.clone()];
+            let start = indices.iter().min().map_or(0, |&index| index as usize);
+            let end = indices.iter().max().map_or(0, |&index| index as usize + 1);
+            submesh.vertices = start..end.max(start);
         }
+    }
+
+    /// Converts the model into an indexed mesh for the renderer (bounds and parts included)
+    pub fn into_mesh(self) -> Mesh {
+        Mesh::with_submeshes(self.vertices, self.indices, self.submeshes)
+    }
 
-        Ok(Obj { vertices, indices })
+    /// Computes per-vertex tangents and bitangents from positions and texture coordinates
+    pub fn compute_tangents(&mut self) {
+        compute_tangents(&mut self.vertices, &self.indices);
+    }
+
+    /// Replaces the vertex normals with smooth, area-weighted normals.
+    /// If the OBJ has smoothing groups, faces sharing a position are averaged only within the
+    /// same group and those with "s off" stay faceted; otherwise they are averaged unless the
+    /// angle between them is greater than `crease_angle` (radians), which keeps hard edges sharp
+    pub fn smooth_normals(&mut self, crease_angle: Option<f32>) {
+        let key = |v: &Vertex| [v.position.x.to_bits(), v.position.y.to_bits(), v.position.z.to_bits()];
+        let face_count = self.indices.len() / 3;
+
+        // Normal de cada cara sin normalizar: su longitud es el doble del área (ponderación por área)
+        let mut face_normals = Vec::with_capacity(face_count);
+        let mut faces_by_position: HashMap<[u32; 3], Vec<usize>> = HashMap::new();
+        for face in 0..face_count {
+            let a = &self.vertices[self.indices[face * 3] as usize];
+            let b = &self.vertices[self.indices[face * 3 + 1] as usize];
+            let c = &self.vertices[self.indices[face * 3 + 2] as usize];
+            face_normals.push((b.position - a.position).cross(c.position - a.position));
+            for v in [a, b, c] {
+                faces_by_position.entry(key(v)).or_default().push(face);
+            }
+        }
+        let unit_normals: Vec<Vector3> = face_normals.iter().map(|n| n.normalized()).collect();
+        let min_cos = crease_angle.map(|angle| angle.cos());
+
+        // Con ángulo de pliegue un mismo vértice puede necesitar varias normales:
+        // se crea un vértice por combinación (vértice original, normal)
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+        let mut indices = Vec::with_capacity(self.indices.len());
+        let mut remap: HashMap<(u32, [u32; 3]), u32> = HashMap::new();
+        for face in 0..face_count {
+            for corner in 0..3 {
+                let index = self.indices[face * 3 + corner];
+                let vertex = &self.vertices[index as usize];
+
+                let mut normal = Vector3::zero();
+                for &other in &faces_by_position[&key(vertex)] {
+                    let shared = if self.smoothing_groups.is_empty() {
+                        min_cos.is_none_or(|min_cos| unit_normals[face].dot(unit_normals[other]) >= min_cos)
+                    } else {
+                        let group = self.smoothing_groups[face];
+                        other == face || (group != 0 && group == self.smoothing_groups[other])
+                    };
+                    if shared {
+                        normal += face_normals[other];
+                    }
+                }
+                let normal = if normal.length() > 0.0 { normal.normalized() } else { unit_normals[face] };
+
+                let normal_key = [normal.x.to_bits(), normal.y.to_bits(), normal.z.to_bits()];
+                let new_index = *remap.entry((index, normal_key)).or_insert_with(|| {
+                    let mut smoothed = vertex.clone();
+                    smoothed.normal = normal;
+                    smoothed.transformed_normal = normal;
+                    vertices.push(smoothed);
+                    (vertices.len() - 1) as u32
+                });
+                indices.push(new_index);
+            }
+        }
+
+        self.vertices = vertices;
+        self.indices = indices;
+        self.update_submesh_vertices();
+        self.compute_tangents();
     }
 
     pub fn get_vertex_array(&self) -> Vec<Vertex> {
@@ -58,3 +211,149 @@ impl Obj {
         vertex_array
     }
 }
+
+// Grupo de suavizado de cada triángulo, que tobj no lee: se recorre el archivo contando los
+// triángulos de cada cara (n - 2, el abanico de tobj) en el mismo orden en que tobj los deja.
+// "s off" y "s 0" son el grupo 0 (facetado). Vacío si ninguna cara tiene grupo o si la cuenta
+// no coincide con la de tobj
+fn read_smoothing_groups(path: &str, triangles: usize) -> Vec<u32> {
+    let Ok(text) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let mut group = 0;
+    let mut groups = Vec::with_capacity(triangles);
+    for line in text.lines() {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("s") => group = tokens.next().and_then(|token| token.parse().ok()).unwrap_or(0),
+            Some("f") => groups.extend(iter::repeat_n(group, tokens.count().saturating_sub(2))),
+            _ => {}
+        }
+    }
+    if groups.iter().all(|&group| group == 0) {
+        return Vec::new();
+    }
+    if groups.len() != triangles {
+        eprintln!("{}: smoothing groups don't match the triangles ({} vs {}); ignoring them", path, groups.len(), triangles);
+        return Vec::new();
+    }
+    groups
+}
+
+/// Accumulates the UV-space tangent of every triangle on its vertices and
+/// orthogonalizes the averages against the vertex normals (MikkTSpace-style)
+pub fn compute_tangents(vertices: &mut [Vertex], indices: &[u32]) {
+    let mut tangents = vec![Vector3::zero(); vertices.len()];
+    let mut bitangents = vec![Vector3::zero(); vertices.len()];
+
+    for face in indices.chunks_exact(3) {
+        let (i0, i1, i2) = (face[0] as usize, face[1] as usize, face[2] as usize);
+        let edge1 = vertices[i1].position - vertices[i0].position;
+        let edge2 = vertices[i2].position - vertices[i0].position;
+        let duv1 = vertices[i1].tex_coords - vertices[i0].tex_coords;
+        let duv2 = vertices[i2].tex_coords - vertices[i0].tex_coords;
+
+        let det = duv1.x * duv2.y - duv2.x * duv1.y;
+        if det.abs() < 1e-10 {
+            continue; // UVs degeneradas: el triángulo no aporta dirección
+        }
+        let r = 1.0 / det;
+        let tangent = (edge1 * duv2.y - edge2 * duv1.y) * r;
+        let bitangent = (edge2 * duv1.x - edge1 * duv2.x) * r;
+
+        for i in [i0, i1, i2] {
+            tangents[i] += tangent;
+            bitangents[i] += bitangent;
+        }
+    }
+
+    for (i, vertex) in vertices.iter_mut().enumerate() {
+        let normal = vertex.normal;
+        // Gram-Schmidt: quitar la componente normal de la tangente
+        let mut tangent = tangents[i] - normal * normal.dot(tangents[i]);
+        if tangent.length() < 1e-6 {
+            // Sin UVs útiles: cualquier dirección perpendicular a la normal
+            let axis = if normal.x.abs() < 0.9 { Vector3::new(1.0, 0.0, 0.0) } else { Vector3::new(0.0, 1.0, 0.0) };
+            tangent = axis - normal * normal.dot(axis);
+        }
+        tangent.normalize();
+
+        // La bitangente conserva la orientación (espejo de UVs) de la acumulada
+        let handedness = if normal.cross(tangent).dot(bitangents[i]) < 0.0 { -1.0 } else { 1.0 };
+        vertex.tangent = tangent;
+        vertex.bitangent = normal.cross(tangent) * handedness;
+        vertex.transformed_tangent = vertex.tangent;
+        vertex.transformed_bitangent = vertex.bitangent;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Escribe `text` en un .obj temporal y lee sus grupos de suavizado
+    fn groups(name: &str, text: &str, triangles: usize) -> Vec<u32> {
+        let path = std::env::temp_dir().join(format!("space_travel_{}_{}.obj", name, std::process::id()));
+        fs::write(&path, text).unwrap();
+        let groups = read_smoothing_groups(path.to_str().unwrap(), triangles);
+        fs::remove_file(&path).ok();
+        groups
+    }
+
+    const VERTICES: &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n";
+
+    // Escribe `text` en un .obj temporal y lo carga
+    fn load(name: &str, text: &str) -> Obj {
+        let path = std::env::temp_dir().join(format!("space_travel_{}_{}.obj", name, std::process::id()));
+        fs::write(&path, text).unwrap();
+        let obj = Obj::load(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).ok();
+        obj
+    }
+
+    // Un cuadrado en dos triángulos, con una coordenada de textura de más para las costuras
+    const ATTRIBUTES: &str = "vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\nvt 0.5 0.5\nvn 0 0 1\n";
+
+    #[test]
+    fn repeated_triplets_share_a_vertex() {
+        let text = format!("{}{}f 1/1/1 2/2/1 3/3/1\nf 1/1/1 3/3/1 4/4/1\n", VERTICES, ATTRIBUTES);
+        let obj = load("shared", &text);
+        assert_eq!(obj.vertices.len(), 4);
+        assert_eq!(obj.indices.len(), 6);
+    }
+
+    #[test]
+    fn seam_vertices_are_kept() {
+        // El vértice 1 aparece con dos coordenadas de textura: son dos vértices distintos
+        let text = format!("{}{}f 1/1/1 2/2/1 3/3/1\nf 1/5/1 3/3/1 4/4/1\n", VERTICES, ATTRIBUTES);
+        let obj = load("seam", &text);
+        assert_eq!(obj.vertices.len(), 5);
+        let seam: Vec<_> = obj.vertices.iter().filter(|vertex| vertex.position == Vector3::zero()).collect();
+        assert_eq!(seam.len(), 2);
+        assert!(seam[0].tex_coords != seam[1].tex_coords);
+    }
+
+    #[test]
+    fn quads_count_as_two_triangles() {
+        let text = format!("{}s 1\nf 1 2 3 4\ns 2\nf 1 2 3\n", VERTICES);
+        assert_eq!(groups("quads", &text, 3), vec![1, 1, 2]);
+    }
+
+    #[test]
+    fn s_off_is_group_zero() {
+        let text = format!("{}s 3\nf 1 2 3\ns off\nf 1 3 4\ns 0\nf 2 3 4\n", VERTICES);
+        assert_eq!(groups("off", &text, 3), vec![3, 0, 0]);
+    }
+
+    #[test]
+    fn no_groups_is_empty() {
+        let text = format!("{}s off\nf 1 2 3 4\n", VERTICES);
+        assert!(groups("none", &text, 2).is_empty());
+    }
+
+    #[test]
+    fn mismatched_count_is_empty() {
+        let text = format!("{}s 1\nf 1 2 3 4\n", VERTICES);
+        assert!(groups("mismatch", &text, 3).is_empty());
+    }
+}
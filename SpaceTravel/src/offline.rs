@@ -0,0 +1,145 @@
+// offline.rs
+// Render sin ventana de una escena con sus órbitas: la cámara se coloca a mano y cada frame
+// sale como bytes RGB (alto x ancho x 3). Lo usan los enlaces de Python (python.rs) y se
+// puede usar igual desde Rust
+use crate::assets::{Assets, PlanetMesh};
+use crate::camera::{FAR_PLANE, NEAR_PLANE};
+use crate::light::Light;
+use crate::lighting::key_light;
+use crate::matrix::{DepthMode, create_model_matrix, create_projection_matrix, create_view_matrix};
+use crate::mesh::Mesh;
+use crate::renderer::{Model, Renderer, Scene, View};
+use crate::scene::{CelestialBody, load_scene, orbits, place_orbits};
+use crate::shader_params::ShaderParams;
+use raylib::prelude::*;
+
+/// Renders a scene without a window: `load_scene`, `set_camera`, then `render_frame`
+pub struct OfflineRenderer {
+    renderer: Renderer,
+    sphere: Mesh,
+    bodies: Vec<CelestialBody>,
+    params: ShaderParams,
+    eye: Vector3,
+    target: Vector3,
+    fov: f32, // Radianes
+}
+
+impl OfflineRenderer {
+    /// Renderer of `width` x `height` pixels with the Xerion system loaded
+    pub fn new(width: i32, height: i32) -> Result<Self, String> {
+        if width <= 0 || height <= 0 {
+            return Err("The image size must be positive".to_string());
+        }
+        let scene = load_scene("xerion")?;
+        Ok(OfflineRenderer {
+            renderer: Renderer::new(width, height),
+            sphere: Assets::load(PlanetMesh::default()).sphere,
+            bodies: scene.bodies,
+            params: ShaderParams::default(),
+            eye: Vector3::new(0.0, 20.0, 75.0),
+            target: Vector3::zero(),
+            fov: std::f32::consts::PI / 3.0,
+        })
+    }
+
+    /// Width and height of the rendered images
+    pub fn size(&self) -> (i32, i32) {
+        (self.renderer.framebuffer.width, self.renderer.framebuffer.height)
+    }
+
+    /// Replaces the bodies with a scene: "xerion", "solar" or the path to a .toml file
+    pub fn load_scene(&mut self, name: &str) -> Result<(), String> {
+        self.bodies = load_scene(name)?.bodies;
+        Ok(())
+    }
+
+    /// Loads the shader parameters (frequencies, palettes, lighting) from a shaders.toml file
+    pub fn load_shader_params(&mut self, path: &str) -> Result<(), String> {
+        self.params = ShaderParams::load(path)?;
+        Ok(())
+    }
+
+    /// Places the camera at `eye` looking at `target`, with a vertical field of view in radians
+    pub fn set_camera(&mut self, eye: Vector3, target: Vector3, fov: f32) {
+        self.eye = eye;
+        self.target = target;
+        self.fov = fov;
+    }
+
+    /// Renders the scene at `time` seconds and returns the image as rows of RGB bytes
+    /// (height x width x 3)
+    pub fn render_frame(&mut self, time: f32) -> Vec<u8> {
+        let positions = body_positions(&self.bodies, time);
+        let models = self.bodies.iter().zip(&positions).map(|(body, &position)| Model {
+            mesh: &self.sphere,
+            model_matrix: create_model_matrix(position, body.scale, body.rotation + Vector3::new(0.0, body.rotation_speed * time, 0.0)),
+            shader: &body.shader,
+            label: &body.name,
+            surface: body.surface,
+            displacement: body.displacement,
+            vertex_animation: &body.vertex_animation,
+            geometry_shader: &body.geometry_shader,
+            parts: &[],
+            ray_traced: body.ray_traced,
+            rings: body.rings,
+            sdf: body.sdf,
+        }).collect();
+        // Las estrellas de la escena iluminan según su color e intensidad, más la luz de fondo
+        let mut lights: Vec<Light> = if self.params.lighting.key_light_only {
+            Vec::new()
+        } else {
+            self.bodies.iter().zip(&positions)
+                .filter_map(|(body, &position)| body.light.map(|light| {
+                    Light::new(position)
+                        .with_color(light.color)
+                        .with_intensity(light.intensity)
+                        .with_shadows(light.shadows)
+                        .with_radius(body.scale * 0.5) // Las mallas de esfera tienen radio 0.5
+                }))
+                .collect()
+        };
+        lights.extend(key_light(&self.params.lighting));
+
+        let (width, height) = self.size();
+        let view = View {
+            view_matrix: create_view_matrix(self.eye, self.target, Vector3::new(0.0, 1.0, 0.0)),
+            projection_matrix: create_projection_matrix(self.fov, width as f32 / height as f32, NEAR_PLANE, FAR_PLANE),
+            depth_mode: DepthMode::Projective,
+            eye: self.eye,
+            time,
+            dt: 0.0,
+        };
+        self.renderer.framebuffer.clear();
+        self.renderer.draw_frame(&Scene { models, lights }, &view, &self.params);
+
+        let colors = self.renderer.framebuffer.color_buffer.get_image_data();
+        colors.iter().flat_map(|color| [color.r, color.g, color.b]).collect()
+    }
+}
+
+// Posición de cada cuerpo en el instante `time`, con las mismas órbitas que el binario
+fn body_positions(bodies: &[CelestialBody], time: f32) -> Vec<Vector3> {
+    let mut positions: Vec<Vector3> = bodies.iter().map(|body| body.translation).collect();
+    place_orbits(&orbits(bodies), &mut positions, time);
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_is_height_by_width_by_three() {
+        let mut renderer = OfflineRenderer::new(64, 36).unwrap();
+        assert_eq!(renderer.size(), (64, 36));
+        let pixels = renderer.render_frame(0.0);
+        assert_eq!(pixels.len(), 36 * 64 * 3);
+        // Desde la cámara por defecto se ve algún cuerpo del sistema
+        assert!(pixels.iter().any(|&channel| channel > 0));
+    }
+
+    #[test]
+    fn empty_size_is_an_error() {
+        assert!(OfflineRenderer::new(0, 36).is_err());
+    }
+}
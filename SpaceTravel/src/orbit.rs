@@ -0,0 +1,223 @@
+// orbit.rs
+// Dibujo de órbitas circulares con la teselación en caché: los puntos en mundo solo se
+// recalculan si cambian el radio o el centro, y los de pantalla si además se mueve la cámara.
+use space_travel::framebuffer::Framebuffer;
+use space_travel::matrix::{multiply_matrices, multiply_matrix_vector4};
+use raylib::prelude::*;
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+const ORBIT_SEGMENTS: usize = 128; // Segmentos por órbita para una línea suave
+
+struct CachedOrbit {
+    radius: f32,
+    center: Vector3,
+    points: Vec<Vector3>,
+    // Segmentos de `points` ya recortados y en pantalla, y las matrices con las que se calcularon
+    screen: Vec<((i32, i32), (i32, i32))>,
+    screen_matrices: Option<[f32; 32]>,
+}
+
+/// Tessellated orbits, keyed by the name of the orbiting body
+pub struct OrbitCache {
+    unit_circle: Vec<(f32, f32)>,
+    orbits: HashMap<String, CachedOrbit>,
+    // Cámara del frame actual (ver set_camera)
+    view_projection: Matrix,
+    viewport_matrix: Matrix,
+    camera_key: [f32; 32],
+}
+
+fn matrix_array(m: &Matrix) -> [f32; 16] {
+    [m.m0, m.m1, m.m2, m.m3, m.m4, m.m5, m.m6, m.m7, m.m8, m.m9, m.m10, m.m11, m.m12, m.m13, m.m14, m.m15]
+}
+
+// Recorta el segmento entre dos puntos en clip space contra el plano cercano (z >= -w); None si
+// queda entero detrás
+fn clip_near(start: Vector4, end: Vector4) -> Option<(Vector4, Vector4)> {
+    let (d_start, d_end) = (start.z + start.w, end.z + end.w);
+    if d_start < 0.0 && d_end < 0.0 {
+        return None;
+    }
+    let lerp = |a: Vector4, b: Vector4, t: f32| Vector4::new(
+        a.x + (b.x - a.x) * t,
+        a.y + (b.y - a.y) * t,
+        a.z + (b.z - a.z) * t,
+        a.w + (b.w - a.w) * t,
+    );
+    let t = d_start / (d_start - d_end);
+    if d_start < 0.0 {
+        Some((lerp(start, end, t), end))
+    } else if d_end < 0.0 {
+        Some((start, lerp(start, end, t)))
+    } else {
+        Some((start, end))
+    }
+}
+
+// División de perspectiva y viewport de un punto delante de la cámara
+fn to_screen(viewport_matrix: &Matrix, clip: Vector4) -> (f32, f32) {
+    let w = if clip.w != 0.0 { clip.w } else { 1.0 };
+    let screen = multiply_matrix_vector4(viewport_matrix, &Vector4::new(clip.x / w, clip.y / w, clip.z / w, 1.0));
+    (screen.x, screen.y)
+}
+
+// Recorta un segmento de pantalla al rectángulo [0, max_x] x [0, max_y] (Liang-Barsky); None si
+// queda fuera
+fn clip_screen(start: (f32, f32), end: (f32, f32), (max_x, max_y): (f32, f32)) -> Option<((f32, f32), (f32, f32))> {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let (mut t0, mut t1) = (0.0_f32, 1.0_f32);
+    for (p, q) in [(-dx, start.0), (dx, max_x - start.0), (-dy, start.1), (dy, max_y - start.1)] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let t = q / p;
+        if p < 0.0 {
+            t0 = t0.max(t);
+        } else {
+            t1 = t1.min(t);
+        }
+        if t0 > t1 {
+            return None;
+        }
+    }
+    Some(((start.0 + dx * t0, start.1 + dy * t0), (start.0 + dx * t1, start.1 + dy * t1)))
+}
+
+impl OrbitCache {
+    pub fn new() -> Self {
+        // Seno y coseno de cada segmento, una sola vez
+        let angle_increment = 2.0 * PI / ORBIT_SEGMENTS as f32;
+        let unit_circle = (0..ORBIT_SEGMENTS)
+            .map(|i| {
+                let (sin, cos) = (i as f32 * angle_increment).sin_cos();
+                (cos, sin)
+            })
+            .collect();
+        OrbitCache {
+            unit_circle,
+            orbits: HashMap::new(),
+            view_projection: Matrix::identity(),
+            viewport_matrix: Matrix::identity(),
+            camera_key: [0.0; 32],
+        }
+    }
+
+    /// Sets the camera used by the following `draw` calls
+    pub fn set_camera(&mut self, view_matrix: &Matrix, projection_matrix: &Matrix, viewport_matrix: &Matrix) {
+        self.view_projection = multiply_matrices(projection_matrix, view_matrix);
+        self.viewport_matrix = *viewport_matrix;
+        self.camera_key[..16].copy_from_slice(&matrix_array(&self.view_projection));
+        self.camera_key[16..].copy_from_slice(&matrix_array(viewport_matrix));
+    }
+
+    /// Draws a circular orbit in the XZ plane around `center`, behind every planet
+    pub fn draw(&mut self, framebuffer: &mut Framebuffer, key: &str, orbit_radius: f32, center: Vector3, orbit_color: Color) {
+        let (view_projection, viewport_matrix) = (&self.view_projection, &self.viewport_matrix);
+        let orbit = self.orbits.entry(key.to_string()).or_insert_with(|| CachedOrbit {
+            radius: f32::NAN,
+            center,
+            points: Vec::with_capacity(ORBIT_SEGMENTS),
+            screen: Vec::with_capacity(ORBIT_SEGMENTS),
+            screen_matrices: None,
+        });
+
+        // Puntos en el plano XZ (Y = 0) relativos al centro
+        if orbit.radius != orbit_radius || orbit.center != center {
+            orbit.radius = orbit_radius;
+            orbit.center = center;
+            orbit.points.clear();
+            orbit.points.extend(self.unit_circle.iter().map(|&(cos, sin)| {
+                Vector3::new(center.x + cos * orbit_radius, center.y, center.z + sin * orbit_radius)
+            }));
+            orbit.screen_matrices = None;
+        }
+
+        // Reproyectar solo si cambió la cámara (o los puntos). Cada segmento se recorta contra el
+        // plano cercano antes de dividir por w (un punto detrás de la cámara se proyectaría al
+        // otro lado de la pantalla y la línea la cruzaría entera) y luego contra la pantalla
+        if orbit.screen_matrices != Some(self.camera_key) {
+            let clip: Vec<Vector4> = orbit.points.iter()
+                .map(|point| multiply_matrix_vector4(view_projection, &Vector4::new(point.x, point.y, point.z, 1.0)))
+                .collect();
+            let bounds = ((framebuffer.width - 1) as f32, (framebuffer.height - 1) as f32);
+            orbit.screen.clear();
+            for (i, &start) in clip.iter().enumerate() {
+                let end = clip[(i + 1) % clip.len()];
+                let Some((start, end)) = clip_near(start, end) else {
+                    continue;
+                };
+                let (start, end) = (to_screen(viewport_matrix, start), to_screen(viewport_matrix, end));
+                if let Some((start, end)) = clip_screen(start, end, bounds) {
+                    orbit.screen.push(((start.0 as i32, start.1 as i32), (end.0 as i32, end.1 as i32)));
+                }
+            }
+            orbit.screen_matrices = Some(self.camera_key);
+        }
+
+        // Dibujar los segmentos visibles con una profundidad mayor (más lejos) que los planetas
+        for &((x0, y0), (x1, y1)) in &orbit.screen {
+            framebuffer.draw_line_with_depth(x0, y0, x1, y1, orbit_color, 1000.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    fn near_distance(v: Vector4) -> f32 {
+        v.z + v.w
+    }
+
+    #[test]
+    fn clip_near_drops_segments_behind_the_camera() {
+        assert!(clip_near(Vector4::new(0.0, 0.0, -3.0, 1.0), Vector4::new(1.0, 0.0, -2.0, 1.0)).is_none());
+    }
+
+    #[test]
+    fn clip_near_keeps_segments_in_front() {
+        let (start, end) = (Vector4::new(0.0, 0.0, 0.5, 1.0), Vector4::new(1.0, 2.0, 0.2, 1.0));
+        assert_eq!(clip_near(start, end), Some((start, end)));
+    }
+
+    #[test]
+    fn clip_near_cuts_crossing_segments_at_the_plane() {
+        let (behind, front) = (Vector4::new(0.0, 0.0, -2.0, 1.0), Vector4::new(3.0, 0.0, 1.0, 1.0));
+
+        let (start, end) = clip_near(behind, front).unwrap();
+        assert!(near_distance(start).abs() < EPSILON);
+        assert!((start.x - 1.0).abs() < EPSILON);
+        assert_eq!(end, front);
+
+        // En el otro sentido se recorta el final
+        let (start, end) = clip_near(front, behind).unwrap();
+        assert_eq!(start, front);
+        assert!(near_distance(end).abs() < EPSILON);
+    }
+
+    #[test]
+    fn clip_screen_keeps_segments_inside() {
+        let segment = ((10.0, 20.0), (30.0, 40.0));
+        assert_eq!(clip_screen(segment.0, segment.1, (100.0, 100.0)), Some(segment));
+    }
+
+    #[test]
+    fn clip_screen_cuts_crossing_segments_at_the_edges() {
+        let (start, end) = clip_screen((-50.0, 50.0), (150.0, 50.0), (100.0, 100.0)).unwrap();
+        assert!(start.0.abs() < EPSILON && (start.1 - 50.0).abs() < EPSILON);
+        assert!((end.0 - 100.0).abs() < EPSILON && (end.1 - 50.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn clip_screen_drops_segments_outside() {
+        assert!(clip_screen((-50.0, -10.0), (150.0, -20.0), (100.0, 100.0)).is_none());
+        // Diagonal que pasa junto a la esquina sin entrar
+        assert!(clip_screen((90.0, -20.0), (120.0, 10.0), (100.0, 100.0)).is_none());
+    }
+}
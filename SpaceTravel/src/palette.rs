@@ -0,0 +1,126 @@
+// palette.rs
+// Colores de lo que se dibuja encima de la escena (órbitas, lo seleccionado, instrumentos,
+// misiones, trayectoria y menús) reunidos en una paleta que se elige en los ajustes. Además de
+// la estándar hay variantes para daltonismo: con deuteranopía y protanopía (rojo-verde) se
+// separan los elementos con azul y naranja o amarillo, con tritanopía (azul-amarillo) con rojo
+// y turquesa. Los colores de las lunas salen de la paleta de Okabe e Ito
+use serde::{Deserialize, Serialize};
+use raylib::prelude::*;
+
+/// Colors of the overlays
+pub struct Palette {
+    pub orbit: Color,                    // Órbitas alrededor del centro
+    pub moon_orbits: &'static [Color],   // Órbitas de las lunas, una por luna; vacío = color de cada luna
+    pub selection: Color,                // Órbita del cuerpo seleccionado
+    pub highlight: Vector3,              // Fondo de la opción elegida en los menús
+    pub accent: Color,                   // Instrumentos y misiones cumplidas
+    pub accent_dim: Color,
+    pub path: Vector3,                   // Trayectoria prevista
+    pub warning: Color,                  // Máximo acercamiento
+}
+
+const OKABE_ITO: [Color; 5] = [
+    Color::new(230, 159, 0, 255),
+    Color::new(86, 180, 233, 255),
+    Color::new(240, 228, 66, 255),
+    Color::new(0, 114, 178, 255),
+    Color::new(204, 121, 167, 255),
+];
+
+const STANDARD: Palette = Palette {
+    orbit: Color::new(150, 255, 120, 255),
+    moon_orbits: &[],
+    selection: Color::new(255, 220, 120, 255),
+    highlight: Vector3::new(0.2, 0.3, 0.6),
+    accent: Color::new(120, 235, 170, 255),
+    accent_dim: Color::new(70, 150, 110, 255),
+    path: Vector3::new(0.45, 0.9, 1.0),
+    warning: Color::new(255, 190, 80, 255),
+};
+
+const DEUTERANOPIA: Palette = Palette {
+    orbit: Color::new(86, 180, 233, 255),
+    moon_orbits: &OKABE_ITO,
+    selection: Color::new(230, 159, 0, 255),
+    highlight: Vector3::new(0.0, 0.3, 0.55),
+    accent: Color::new(86, 180, 233, 255),
+    accent_dim: Color::new(50, 105, 140, 255),
+    path: Vector3::new(0.94, 0.89, 0.26),
+    warning: Color::new(213, 94, 0, 255),
+};
+
+// Con protanopía los rojos se ven oscuros: lo que avisa va en amarillo y naranja claro
+const PROTANOPIA: Palette = Palette {
+    orbit: Color::new(86, 180, 233, 255),
+    moon_orbits: &OKABE_ITO,
+    selection: Color::new(240, 228, 66, 255),
+    highlight: Vector3::new(0.0, 0.3, 0.55),
+    accent: Color::new(86, 180, 233, 255),
+    accent_dim: Color::new(50, 105, 140, 255),
+    path: Vector3::new(0.9, 0.9, 0.9),
+    warning: Color::new(230, 159, 0, 255),
+};
+
+const TRITANOPIA: Palette = Palette {
+    orbit: Color::new(0, 170, 160, 255),
+    moon_orbits: &[
+        Color::new(213, 94, 0, 255),
+        Color::new(0, 158, 115, 255),
+        Color::new(204, 121, 167, 255),
+        Color::new(235, 235, 235, 255),
+        Color::new(0, 114, 178, 255),
+    ],
+    selection: Color::new(240, 70, 60, 255),
+    highlight: Vector3::new(0.45, 0.12, 0.18),
+    accent: Color::new(255, 130, 150, 255),
+    accent_dim: Color::new(150, 75, 90, 255),
+    path: Vector3::new(0.3, 0.85, 0.85),
+    warning: Color::new(255, 80, 60, 255),
+};
+
+impl Palette {
+    /// Color of the orbit of the moon `index` (counting only moons), whose body is `color`
+    pub fn moon_orbit(&self, index: usize, color: Color) -> Color {
+        if self.moon_orbits.is_empty() { color } else { self.moon_orbits[index % self.moon_orbits.len()] }
+    }
+}
+
+/// Palette chosen in the settings
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PalettePreset {
+    Standard,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl PalettePreset {
+    const ALL: [PalettePreset; 4] = [PalettePreset::Standard, PalettePreset::Deuteranopia, PalettePreset::Protanopia, PalettePreset::Tritanopia];
+
+    pub fn colors(self) -> &'static Palette {
+        match self {
+            PalettePreset::Standard => &STANDARD,
+            PalettePreset::Deuteranopia => &DEUTERANOPIA,
+            PalettePreset::Protanopia => &PROTANOPIA,
+            PalettePreset::Tritanopia => &TRITANOPIA,
+        }
+    }
+
+    /// Key of its name in the language files
+    pub fn key(self) -> &'static str {
+        match self {
+            PalettePreset::Standard => "palette.standard",
+            PalettePreset::Deuteranopia => "palette.deuteranopia",
+            PalettePreset::Protanopia => "palette.protanopia",
+            PalettePreset::Tritanopia => "palette.tritanopia",
+        }
+    }
+
+    /// The next preset (`forward`) or the previous one, wrapping around
+    pub fn cycle(self, forward: bool) -> Self {
+        let count = PalettePreset::ALL.len();
+        let index = PalettePreset::ALL.iter().position(|&preset| preset == self).unwrap_or(0);
+        PalettePreset::ALL[if forward { (index + 1) % count } else { (index + count - 1) % count }]
+    }
+}
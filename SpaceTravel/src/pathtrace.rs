@@ -0,0 +1,278 @@
+// pathtrace.rs
+// Render "de calidad" por trazado de caminos: muy lento, para fotos fijas. Cada modelo de la
+// escena es la esfera exacta que aproxima su malla (radio 0.5 escalado por su matriz), con el
+// color que da su fragment shader en el punto del impacto como albedo difuso; los emisivos (las
+// estrellas) son las únicas fuentes de luz y brillan con su color por la emisión. En cada rebote
+// se muestrea directamente el cono de cada estrella (estimación del siguiente evento) y se sigue
+// con un rebote difuso con muestreo coseno, así que salen las sombras suaves de las estrellas
+// grandes, la penumbra de los eclipses y la luz que rebota de un planeta a su luna. Las luces de
+// la escena (y la direccional de fondo) no se usan: la luz sale de las superficies emisivas
+use crate::framebuffer::Framebuffer;
+use crate::matrix::{create_viewport_matrix, inverse, multiply_matrices, multiply_matrix_vector4, normal_matrix};
+use crate::noise::hash;
+use crate::raytrace::{SPHERE_RADIUS, fragment};
+use crate::renderer::{Scene, View};
+use crate::shader_params::ShaderParams;
+use crate::shader_registry::ShaderRegistry;
+use crate::simd::cross;
+use crate::uniforms::Uniforms;
+use crate::vertex::Vertex;
+use rayon::prelude::*;
+use raylib::prelude::*;
+use std::f32::consts::TAU;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Radiancia de una superficie emisiva con emisión 1: desde un planeta a 20 radios de su estrella,
+// la luz directa deja un albedo de 0.5 cerca del blanco
+const STAR_RADIANCE: f32 = 16.0;
+// Lo que llega de los rayos que se pierden en el espacio, el color de fondo del render normal
+const SKY_RADIANCE: Vector3 = Vector3::new(0.04, 0.04, 0.063);
+// Rebotes como mucho, y a partir de cuál la ruleta rusa puede cortar el camino
+const MAX_BOUNCES: usize = 5;
+const ROULETTE_BOUNCE: usize = 2;
+// Separación de los rayos que salen de una superficie para que no choquen con ella misma
+const RAY_OFFSET: f32 = 1e-3;
+
+// Un modelo visto como esfera en el mundo, con lo que necesita su shader
+struct Body<'a> {
+    center: Vector3,
+    radius: f32,
+    object_from_world: Matrix,
+    uniforms: Uniforms,
+    material: &'a Vertex,
+    shader: &'a str,
+    emissive: f32,
+}
+
+// Números pseudoaleatorios de un píxel: cada llamada avanza la semilla, como las partículas
+struct Random(u32);
+
+impl Random {
+    fn next(&mut self) -> f32 {
+        self.0 = self.0.wrapping_add(1);
+        (hash(self.0) >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+// Lo que comparten todos los caminos
+struct Tracer<'a> {
+    bodies: Vec<Body<'a>>,
+    shaders: &'a ShaderRegistry,
+    params: &'a ShaderParams,
+}
+
+/// Path-traces `scene` as seen from `view` into `framebuffer` with `samples` camera rays per
+/// pixel, treating every model as the radius 0.5 sphere its mesh approximates. Only emissive
+/// models give light; the scene lights are ignored. Slow: meant for stills, not frames.
+/// `rows_done` counts the finished rows, so another thread can show the progress
+pub fn render(scene: &Scene, view: &View, shaders: &ShaderRegistry, params: &ShaderParams, framebuffer: &mut Framebuffer, samples: u32, rows_done: &AtomicUsize) {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let viewport_matrix = create_viewport_matrix(0.0, 0.0, width as f32, height as f32);
+    let tracer = Tracer { bodies: bodies(scene, view, &viewport_matrix), shaders, params };
+    let Some(world_from_clip) = inverse(&multiply_matrices(&view.projection_matrix, &view.view_matrix)) else {
+        return;
+    };
+    let samples = samples.max(1);
+
+    // Las filas son independientes: se trazan en paralelo y se cuentan al terminar
+    let rows: Vec<Vec<Vector3>> = (0..height)
+        .into_par_iter()
+        .map(|y| {
+            let row = (0..width).map(|x| {
+                let mut random = Random(hash((y * width + x) as u32));
+                let mut sum = Vector3::zero();
+                for _ in 0..samples {
+                    // Un punto al azar dentro del píxel: suaviza los bordes
+                    let p_x = x as f32 + random.next();
+                    let p_y = y as f32 + random.next();
+                    let (origin, direction) = camera_ray(&world_from_clip, &viewport_matrix, p_x, p_y);
+                    sum += tracer.trace(origin, direction, &mut random);
+                }
+                sum / samples as f32
+            }).collect();
+            rows_done.fetch_add(1, Ordering::Relaxed);
+            row
+        })
+        .collect();
+
+    // La exposición de la iluminación y una curva que comprime las luces en lugar de recortarlas
+    let exposure = params.lighting.exposure;
+    let tone = |value: f32| ((1.0 - (-value * exposure).exp()) * 255.0) as u8;
+    for (y, row) in rows.iter().enumerate() {
+        for (x, radiance) in row.iter().enumerate() {
+            framebuffer.set_pixel(x as i32, y as i32, Color::new(tone(radiance.x), tone(radiance.y), tone(radiance.z), 255));
+        }
+    }
+}
+
+// Las esferas de los modelos, con sus uniforms como los del rasterizador
+fn bodies<'a>(scene: &Scene<'a>, view: &View, viewport_matrix: &Matrix) -> Vec<Body<'a>> {
+    scene.models.iter().filter_map(|model| {
+        let object_from_world = inverse(&model.model_matrix)?;
+        let material = model.mesh.vertices.first()?;
+        let matrix = &model.model_matrix;
+        let scale = Vector3::new(matrix.m0, matrix.m1, matrix.m2).length();
+        Some(Body {
+            center: Vector3::new(matrix.m12, matrix.m13, matrix.m14),
+            radius: SPHERE_RADIUS * scale,
+            object_from_world,
+            uniforms: Uniforms {
+                model_matrix: model.model_matrix,
+                normal_matrix: normal_matrix(&model.model_matrix),
+                view_matrix: view.view_matrix,
+                projection_matrix: view.projection_matrix,
+                viewport_matrix: *viewport_matrix,
+                depth_mode: view.depth_mode,
+                camera_position: view.eye,
+                time: view.time,
+                dt: view.dt,
+                displacement: 0.0,
+                noise_frequency: 0.0,
+                vertex_animation: None,
+                geometry_shader: None,
+            },
+            material,
+            shader: model.shader,
+            emissive: model.surface.emissive,
+        })
+    }).collect()
+}
+
+// Rayo de la cámara por el punto (p_x, p_y) de la imagen: del plano cercano al lejano
+fn camera_ray(world_from_clip: &Matrix, viewport_matrix: &Matrix, p_x: f32, p_y: f32) -> (Vector3, Vector3) {
+    let ndc_x = (p_x - viewport_matrix.m12) / viewport_matrix.m0;
+    let ndc_y = (p_y - viewport_matrix.m13) / viewport_matrix.m5;
+    let unproject = |ndc_z: f32| {
+        let point = multiply_matrix_vector4(world_from_clip, &Vector4::new(ndc_x, ndc_y, ndc_z, 1.0));
+        Vector3::new(point.x, point.y, point.z) / point.w
+    };
+    let origin = unproject(-1.0);
+    (origin, (unproject(1.0) - origin).normalized())
+}
+
+// Dos ejes perpendiculares entre sí y a `axis` (unitario)
+fn basis(axis: Vector3) -> (Vector3, Vector3) {
+    let helper = if axis.x.abs() > 0.9 { Vector3::new(0.0, 1.0, 0.0) } else { Vector3::new(1.0, 0.0, 0.0) };
+    let tangent = cross(helper, axis).normalized();
+    (tangent, cross(axis, tangent))
+}
+
+impl Tracer<'_> {
+    // La esfera más cercana que corta el rayo (dirección unitaria) y la distancia hasta ella
+    fn nearest(&self, origin: Vector3, direction: Vector3) -> Option<(usize, f32)> {
+        let mut nearest: Option<(usize, f32)> = None;
+        for (index, body) in self.bodies.iter().enumerate() {
+            let offset = origin - body.center;
+            let b = offset.dot(direction);
+            let c = offset.dot(offset) - body.radius * body.radius;
+            let discriminant = b * b - c;
+            if discriminant < 0.0 {
+                continue;
+            }
+            let root = discriminant.sqrt();
+            let t = if -b - root > 0.0 { -b - root } else { -b + root };
+            if t > 0.0 && nearest.is_none_or(|(_, best)| t < best) {
+                nearest = Some((index, t));
+            }
+        }
+        nearest
+    }
+
+    // Color del shader de `body` en el punto `point` de su superficie
+    fn shade(&self, body: &Body, point: Vector3) -> Vector3 {
+        let object = multiply_matrix_vector4(&body.object_from_world, &Vector4::new(point.x, point.y, point.z, 1.0));
+        let hit = Vector3::new(object.x, object.y, object.z).normalized() * SPHERE_RADIUS;
+        let fragment = fragment(hit, hit / SPHERE_RADIUS, 0.0, 0.0, 0.0, &body.uniforms, body.material);
+        let color = self.shaders.shade(&fragment, &body.uniforms, body.shader, self.params);
+        Vector3::new(color.x.clamp(0.0, 1.0), color.y.clamp(0.0, 1.0), color.z.clamp(0.0, 1.0))
+    }
+
+    // Luz que sale de una estrella en `point` hacia quien la mira
+    fn emission(&self, body: &Body, point: Vector3) -> Vector3 {
+        self.shade(body, point) * (body.emissive * STAR_RADIANCE)
+    }
+
+    // Radiancia que llega por el rayo de la cámara
+    fn trace(&self, mut origin: Vector3, mut direction: Vector3, random: &mut Random) -> Vector3 {
+        let mut radiance = Vector3::zero();
+        let mut throughput = Vector3::one();
+        for bounce in 0..MAX_BOUNCES {
+            let Some((index, t)) = self.nearest(origin, direction) else {
+                radiance += throughput * SKY_RADIANCE;
+                break;
+            };
+            let body = &self.bodies[index];
+            let point = origin + direction * t;
+
+            // Las estrellas solo cuentan vistas desde la cámara; después ya las suma la luz directa
+            if body.emissive > 0.0 {
+                if bounce == 0 {
+                    radiance += throughput * self.emission(body, point);
+                }
+                break;
+            }
+
+            let mut normal = (point - body.center) / body.radius;
+            if normal.dot(direction) > 0.0 {
+                normal = -normal;
+            }
+            throughput *= self.shade(body, point);
+            let surface = point + normal * RAY_OFFSET;
+            radiance += throughput * self.direct_light(surface, normal, random);
+
+            // Rebote difuso con muestreo coseno: la densidad cancela el coseno y el 1/π del BRDF
+            let (u, v) = (random.next(), random.next());
+            let (tangent, bitangent) = basis(normal);
+            let r = u.sqrt();
+            direction = (tangent * (r * (TAU * v).cos()) + bitangent * (r * (TAU * v).sin()) + normal * (1.0 - u).sqrt()).normalized();
+            origin = surface;
+
+            // Ruleta rusa: los caminos que ya llevan poca energía se cortan o se refuerzan
+            if bounce >= ROULETTE_BOUNCE {
+                let survive = throughput.x.max(throughput.y).max(throughput.z).min(0.95);
+                if random.next() >= survive {
+                    break;
+                }
+                throughput /= survive;
+            }
+        }
+        radiance
+    }
+
+    // Luz directa de las estrellas en un punto difuso (sin el albedo): una dirección al azar
+    // dentro del cono que ocupa cada una, si no la tapa otra esfera
+    fn direct_light(&self, point: Vector3, normal: Vector3, random: &mut Random) -> Vector3 {
+        let mut light = Vector3::zero();
+        for (index, star) in self.bodies.iter().enumerate().filter(|(_, body)| body.emissive > 0.0) {
+            let to_center = star.center - point;
+            let distance_squared = to_center.dot(to_center);
+            let radius_squared = star.radius * star.radius;
+            if distance_squared <= radius_squared {
+                continue;
+            }
+            // 1 - cos del semiángulo del cono, escrito así para no perder precisión con estrellas lejanas
+            let sin_squared = radius_squared / distance_squared;
+            let cone = sin_squared / (1.0 + (1.0 - sin_squared).sqrt());
+            let cos_theta = 1.0 - random.next() * cone;
+            let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+            let phi = TAU * random.next();
+            let axis = to_center / distance_squared.sqrt();
+            let (tangent, bitangent) = basis(axis);
+            let direction = (tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin()) + axis * cos_theta).normalized();
+
+            let cosine = direction.dot(normal);
+            if cosine <= 0.0 {
+                continue;
+            }
+            let Some((hit, t)) = self.nearest(point, direction) else {
+                continue;
+            };
+            if hit != index {
+                continue;
+            }
+            // Radiancia · BRDF (1/π) · coseno / densidad del cono (1 / (2π · cone))
+            light += self.emission(star, point + direction * t) * (cosine * 2.0 * cone);
+        }
+        light
+    }
+}
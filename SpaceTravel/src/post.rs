@@ -0,0 +1,535 @@
+// post.rs
+// Efectos de post-proceso sobre el framebuffer ya rasterizado, antes de presentarlo
+use crate::framebuffer::Framebuffer;
+use crate::matrix::{DepthMode, inverse, multiply_matrix_vector4};
+use crate::shader_params::{OutputDepth, Palette, PostParams};
+use rayon::prelude::*;
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+// Direcciones de muestreo del SSAO (círculo de 8 vecinos)
+const SSAO_DIRECTIONS: [(f32, f32); 8] = [
+    (1.0, 0.0), (0.707, 0.707), (0.0, 1.0), (-0.707, 0.707),
+    (-1.0, 0.0), (-0.707, -0.707), (0.0, -1.0), (0.707, -0.707),
+];
+
+// Matriz de Bayer 4x4: orden en que se encienden los umbrales de cada píxel del patrón
+const BAYER_4X4: [[f32; 4]; 4] = [
+    [0.0, 8.0, 2.0, 10.0],
+    [12.0, 4.0, 14.0, 6.0],
+    [3.0, 11.0, 1.0, 9.0],
+    [15.0, 7.0, 13.0, 5.0],
+];
+
+// Muestras a lo largo del vector de movimiento en el motion blur
+const MOTION_BLUR_SAMPLES: usize = 8;
+// Diferencia máxima entre las matrices de dos frames para considerar la cámara quieta
+const STATIC_CAMERA_EPSILON: f32 = 1e-5;
+// Cada cuántos píxeles (en X y en Y) se mide la luminancia para la adaptación
+const EXPOSURE_SAMPLE_STEP: usize = 4;
+// Paso de tiempo máximo de la adaptación (tras una pausa no salta de golpe)
+const MAX_ADAPTATION_STEP: f32 = 0.1;
+
+/// Post effects that can be toggled at runtime
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PostEffects {
+    pub ssao: bool,
+    pub dither: bool,
+    pub crt: bool,
+    pub motion_blur: bool,
+    pub accumulate: bool,
+    pub auto_exposure: bool,
+}
+
+impl Default for PostEffects {
+    fn default() -> Self {
+        PostEffects { ssao: false, dither: false, crt: false, motion_blur: false, accumulate: false, auto_exposure: true }
+    }
+}
+
+/// Eye adaptation: measures the luminance of each finished frame and eases an exposure
+/// multiplier, in real time, towards the one that would bring it to `exposure_key`. Looking
+/// at a star lowers it and looking into empty space raises it
+pub struct EyeAdaptation {
+    /// Multiplier for the exposure of the lighting
+    pub exposure: f32,
+    last_update: Option<Instant>,
+}
+
+impl Default for EyeAdaptation {
+    fn default() -> Self {
+        EyeAdaptation::new()
+    }
+}
+
+impl EyeAdaptation {
+    pub fn new() -> Self {
+        EyeAdaptation { exposure: 1.0, last_update: None }
+    }
+
+    /// Measures the mean luminance of `framebuffer` and moves the exposure towards its target,
+    /// covering most of the way in `adaptation_time` seconds
+    pub fn update(&mut self, framebuffer: &Framebuffer, params: &PostParams) {
+        let now = Instant::now();
+        let dt = self.last_update.map_or(0.0, |last| now.duration_since(last).as_secs_f32().min(MAX_ADAPTATION_STEP));
+        self.last_update = Some(now);
+
+        let width = framebuffer.width as usize;
+        let colors = framebuffer.color_buffer.get_image_data();
+        // Primera fila de cada franja de EXPOSURE_SAMPLE_STEP filas
+        let (sum, count) = colors
+            .par_chunks(width * EXPOSURE_SAMPLE_STEP)
+            .map(|rows| {
+                rows[..width.min(rows.len())].iter().step_by(EXPOSURE_SAMPLE_STEP).fold((0.0, 0), |(sum, count), color| {
+                    let luminance = (0.2126 * color.r as f32 + 0.7152 * color.g as f32 + 0.0722 * color.b as f32) / 255.0;
+                    (sum + luminance, count + 1)
+                })
+            })
+            .reduce(|| (0.0, 0), |a, b| (a.0 + b.0, a.1 + b.1));
+        if count == 0 {
+            return;
+        }
+        // La imagen se midió con la exposición actual: la luminancia escala con ella
+        let mean = (sum / count as f32).max(f32::EPSILON);
+        let target = (self.exposure * params.exposure_key / mean).clamp(params.min_exposure, params.max_exposure);
+        // En escala logarítmica, para que aclarar y oscurecer tarden lo mismo
+        let blend = 1.0 - (-dt * 3.0 / params.adaptation_time.max(f32::EPSILON)).exp();
+        self.exposure = (self.exposure.ln() + (target.ln() - self.exposure.ln()) * blend).exp();
+    }
+}
+
+/// Camera the frame was rendered with, to turn stored depths back into positions
+pub struct PostCamera {
+    pub view_projection: Matrix, // projection * view, sin el jitter de la acumulación
+    pub depth_mode: DepthMode,
+    pub near: f32,
+    pub far: f32,
+}
+
+/// What the effects keep from one frame to the next
+#[derive(Default)]
+pub struct PostHistory {
+    previous_view_projection: Option<Matrix>,
+    // Media de los frames acumulados con la cámara quieta, y cuántos lleva
+    accumulation: Vec<[f32; 3]>,
+    accumulated: u32,
+    frame: u32,
+}
+
+impl PostHistory {
+    /// Sub-pixel offset (-0.5..0.5 pixels) to render the next frame with while accumulating,
+    /// following the Halton (2, 3) sequence so the samples cover the pixel evenly
+    pub fn next_jitter(&mut self) -> (f32, f32) {
+        self.frame = self.frame % 64 + 1;
+        (halton(self.frame, 2) - 0.5, halton(self.frame, 3) - 0.5)
+    }
+}
+
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut result = 0.0;
+    let mut fraction = 1.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+fn matrices_close(a: &Matrix, b: &Matrix) -> bool {
+    a.to_array().iter().zip(b.to_array()).all(|(x, y)| (x - y).abs() < STATIC_CAMERA_EPSILON)
+}
+
+/// Runs the enabled effects over the framebuffer in a fixed order
+pub fn apply(framebuffer: &mut Framebuffer, effects: &PostEffects, params: &PostParams, camera: &PostCamera, history: &mut PostHistory) {
+    if effects.ssao {
+        profile_scope!("ssao");
+        ssao(framebuffer, params, camera.depth_mode, camera.near, camera.far);
+    }
+    if params.fog_density > 0.0 && params.fog_max > 0.0 {
+        profile_scope!("fog");
+        fog(framebuffer, params, camera.depth_mode, camera.near, camera.far);
+    }
+    if effects.accumulate {
+        profile_scope!("accumulate");
+        let camera_static = history.previous_view_projection.is_some_and(|previous| matrices_close(&previous, &camera.view_projection));
+        accumulate(framebuffer, history, camera_static, params.accumulation_frames);
+    }
+    if effects.motion_blur && let Some(previous) = history.previous_view_projection {
+        profile_scope!("blur");
+        motion_blur(framebuffer, params, camera, &previous);
+    }
+    history.previous_view_projection = Some(camera.view_projection);
+    // Efectos de lente: primero la aberración (remuestrea) y después el oscurecimiento
+    if params.chromatic_aberration > 0.0 {
+        profile_scope!("chromatic");
+        chromatic_aberration(framebuffer, params.chromatic_aberration);
+    }
+    if params.vignette_strength > 0.0 {
+        profile_scope!("vignette");
+        vignette(framebuffer, params.vignette_strength, params.vignette_radius);
+    }
+    if effects.dither || params.output_depth != OutputDepth::Full {
+        profile_scope!("dither");
+        quantize(framebuffer, params.output_depth, effects.dither);
+    }
+    if params.palette != Palette::None {
+        profile_scope!("palette");
+        map_to_palette(framebuffer, params.palette.colors(), params.palette_dither);
+    }
+    if effects.crt {
+        profile_scope!("crt");
+        crt(framebuffer, params);
+    }
+}
+
+// Bayer 4x4 centrado en 0: -0.5..0.5
+fn bayer_offset(x: i32, y: i32) -> f32 {
+    (BAYER_4X4[(y & 3) as usize][(x & 3) as usize] + 0.5) / 16.0 - 0.5
+}
+
+// Aplica `f` a cada píxel en paralelo y reescribe solo los que cambian
+fn map_pixels(framebuffer: &mut Framebuffer, f: impl Fn(i32, i32, Color) -> Color + Sync) {
+    let width = framebuffer.width;
+    let colors = framebuffer.color_buffer.get_image_data();
+    let mapped: Vec<Color> = colors
+        .par_iter()
+        .enumerate()
+        .map(|(index, &color)| f(index as i32 % width, index as i32 / width, color))
+        .collect();
+    write_changed(framebuffer, &colors, &mapped);
+}
+
+// Escribe en el framebuffer los píxeles de `new` que difieren de `old`
+fn write_changed(framebuffer: &mut Framebuffer, old: &[Color], new: &[Color]) {
+    let width = framebuffer.width;
+    for (index, (&before, &after)) in old.iter().zip(new).enumerate() {
+        if before != after {
+            framebuffer.set_pixel(index as i32 % width, index as i32 / width, after);
+        }
+    }
+}
+
+/// Reduces every pixel to `depth` bits per channel. With `dither` a 4x4 Bayer threshold is
+/// used instead of rounding, so the bands of smooth gradients become a fine pattern
+pub fn quantize(framebuffer: &mut Framebuffer, depth: OutputDepth, dither: bool) {
+    let [red, green, blue] = depth.bits();
+    map_pixels(framebuffer, |x, y, color| {
+        let threshold = if dither { bayer_offset(x, y) + 0.5 } else { 0.5 };
+        Color::new(
+            quantize_channel(color.r, red, threshold),
+            quantize_channel(color.g, green, threshold),
+            quantize_channel(color.b, blue, threshold),
+            color.a,
+        )
+    });
+}
+
+/// Replaces every pixel with the closest color of `palette`. With a dither spread above zero
+/// the color is first nudged by the Bayer pattern (in 0..255 units), mixing neighbouring
+/// palette entries instead of leaving flat areas
+pub fn map_to_palette(framebuffer: &mut Framebuffer, palette: &[[u8; 3]], dither_spread: f32) {
+    map_pixels(framebuffer, |x, y, color| {
+        let offset = bayer_offset(x, y) * dither_spread;
+        let target = [color.r as f32 + offset, color.g as f32 + offset, color.b as f32 + offset];
+        let nearest = palette.iter().min_by(|a, b| {
+            distance_squared(a, target).total_cmp(&distance_squared(b, target))
+        });
+        match nearest {
+            Some(&[r, g, b]) => Color::new(r, g, b, color.a),
+            None => color,
+        }
+    });
+}
+
+/// Temporal accumulation: while the camera is static each (jittered) frame is averaged with
+/// the previous ones, up to `max_frames`, converging to a supersampled image. Any camera
+/// movement restarts the history. Orbiting bodies leave a short trail, shorter with fewer frames
+pub fn accumulate(framebuffer: &mut Framebuffer, history: &mut PostHistory, camera_static: bool, max_frames: u32) {
+    let colors = framebuffer.color_buffer.get_image_data();
+    if !camera_static || history.accumulation.len() != colors.len() {
+        history.accumulation = colors.iter().map(|c| [c.r as f32, c.g as f32, c.b as f32]).collect();
+        history.accumulated = 1;
+        return;
+    }
+
+    history.accumulated = (history.accumulated + 1).min(max_frames.max(1));
+    let weight = 1.0 / history.accumulated as f32;
+    let mapped: Vec<Color> = history.accumulation
+        .par_iter_mut()
+        .zip(colors.par_iter())
+        .map(|(average, color)| {
+            for (channel, value) in average.iter_mut().zip([color.r, color.g, color.b]) {
+                *channel += (value as f32 - *channel) * weight;
+            }
+            Color::new(average[0].round() as u8, average[1].round() as u8, average[2].round() as u8, color.a)
+        })
+        .collect();
+    write_changed(framebuffer, &colors, &mapped);
+}
+
+/// Camera motion blur: every pixel is moved back to where the previous frame's camera saw
+/// the same point (reconstructed from its depth), and the colors along that path are
+/// averaged. The streak is `motion_blur_strength` of the frame's motion, at most
+/// `motion_blur_max` pixels. Objects that move with the camera (the HUD ship) are treated as static
+pub fn motion_blur(framebuffer: &mut Framebuffer, params: &PostParams, camera: &PostCamera, previous_view_projection: &Matrix) {
+    let Some(inverse_view_projection) = inverse(&camera.view_projection) else {
+        return;
+    };
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let (near, far) = (camera.near, camera.far);
+    let colors = framebuffer.color_buffer.get_image_data();
+    let depth = framebuffer.depth_view().depth;
+    let sample = |x: f32, y: f32| {
+        let px = (x as i32).clamp(0, width - 1);
+        let py = (y as i32).clamp(0, height - 1);
+        colors[(py * width + px) as usize]
+    };
+
+    let mapped: Vec<Color> = (0..width * height)
+        .into_par_iter()
+        .map(|index| {
+            let color = colors[index as usize];
+            let (x, y) = ((index % width) as f32 + 0.5, (index / width) as f32 + 0.5);
+            // Distancia en la vista; el fondo se trata como si estuviera en el plano lejano
+            let stored = depth[index as usize];
+            let w = if stored.is_finite() { camera.depth_mode.linear_depth(stored, near, far) } else { far };
+
+            // Posición en el mundo a partir de las coordenadas de pantalla y la distancia
+            let ndc_x = x / width as f32 * 2.0 - 1.0;
+            let ndc_y = 1.0 - y / height as f32 * 2.0;
+            let ndc_z = (far + near) / (far - near) - 2.0 * far * near / ((far - near) * w);
+            let world = multiply_matrix_vector4(&inverse_view_projection, &Vector4::new(ndc_x * w, ndc_y * w, ndc_z * w, w));
+            let world = Vector4::new(world.x / world.w, world.y / world.w, world.z / world.w, 1.0);
+
+            // Dónde estaba ese punto en la pantalla del frame anterior
+            let previous = multiply_matrix_vector4(previous_view_projection, &world);
+            if previous.w <= 1e-4 {
+                return color;
+            }
+            let previous_x = (previous.x / previous.w + 1.0) * 0.5 * width as f32;
+            let previous_y = (1.0 - previous.y / previous.w) * 0.5 * height as f32;
+            let (mut vx, mut vy) = ((x - previous_x) * params.motion_blur_strength, (y - previous_y) * params.motion_blur_strength);
+            let length = (vx * vx + vy * vy).sqrt();
+            if length < 0.5 {
+                return color;
+            }
+            if length > params.motion_blur_max {
+                vx *= params.motion_blur_max / length;
+                vy *= params.motion_blur_max / length;
+            }
+
+            let (mut r, mut g, mut b) = (0.0, 0.0, 0.0);
+            for i in 0..MOTION_BLUR_SAMPLES {
+                let t = i as f32 / (MOTION_BLUR_SAMPLES - 1) as f32;
+                let c = sample(x - vx * t, y - vy * t);
+                r += c.r as f32;
+                g += c.g as f32;
+                b += c.b as f32;
+            }
+            let n = MOTION_BLUR_SAMPLES as f32;
+            Color::new((r / n) as u8, (g / n) as u8, (b / n) as u8, color.a)
+        })
+        .collect();
+    write_changed(framebuffer, &colors, &mapped);
+}
+
+/// Lens dispersion: red is sampled further out and blue further in along the line from the
+/// center, up to `amount` pixels at the corners, leaving colored fringes on the edges
+pub fn chromatic_aberration(framebuffer: &mut Framebuffer, amount: f32) {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let colors = framebuffer.color_buffer.get_image_data();
+    let (cx, cy) = (width as f32 * 0.5, height as f32 * 0.5);
+    let corner = (cx * cx + cy * cy).sqrt();
+    let sample = |x: f32, y: f32| {
+        let px = (x as i32).clamp(0, width - 1);
+        let py = (y as i32).clamp(0, height - 1);
+        colors[(py * width + px) as usize]
+    };
+    let mapped: Vec<Color> = (0..width * height)
+        .into_par_iter()
+        .map(|index| {
+            let (x, y) = ((index % width) as f32 + 0.5, (index / width) as f32 + 0.5);
+            // Desplazamiento proporcional a la distancia al centro (en píxeles en la esquina)
+            let (dx, dy) = ((x - cx) / corner * amount, (y - cy) / corner * amount);
+            let color = colors[index as usize];
+            let red = sample(x + dx, y + dy);
+            let blue = sample(x - dx, y - dy);
+            Color::new(red.r, color.g, blue.b, color.a)
+        })
+        .collect();
+    write_changed(framebuffer, &colors, &mapped);
+}
+
+/// Darkens the image towards the corners: full brightness inside `radius` (fraction of the
+/// half-diagonal) and down to `1 - strength` at the corners, with a smooth falloff
+pub fn vignette(framebuffer: &mut Framebuffer, strength: f32, radius: f32) {
+    let (cx, cy) = (framebuffer.width as f32 * 0.5, framebuffer.height as f32 * 0.5);
+    let corner = (cx * cx + cy * cy).sqrt();
+    let radius = radius.clamp(0.0, 0.99);
+    map_pixels(framebuffer, |x, y, color| {
+        let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+        let t = (((dx * dx + dy * dy).sqrt() / corner - radius) / (1.0 - radius)).clamp(0.0, 1.0);
+        let keep = 1.0 - strength.min(1.0) * t * t * (3.0 - 2.0 * t);
+        Color::new(
+            (color.r as f32 * keep) as u8,
+            (color.g as f32 * keep) as u8,
+            (color.b as f32 * keep) as u8,
+            color.a,
+        )
+    });
+}
+
+/// Blue-shift tint of a warp: red fades and blue grows with `intensity` (0..1)
+pub fn blue_shift(framebuffer: &mut Framebuffer, intensity: f32) {
+    let shift = intensity.clamp(0.0, 1.0);
+    map_pixels(framebuffer, |_, _, color| {
+        Color::new(
+            (color.r as f32 * (1.0 - 0.4 * shift)) as u8,
+            (color.g as f32 * (1.0 - 0.1 * shift)) as u8,
+            (color.b as f32 + (255.0 - color.b as f32) * 0.3 * shift) as u8,
+            color.a,
+        )
+    });
+}
+
+/// CRT monitor look: the image is sampled through a barrel distortion (`crt_curvature`),
+/// darkened between scanlines and split into vertical red/green/blue phosphor stripes
+pub fn crt(framebuffer: &mut Framebuffer, params: &PostParams) {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let colors = framebuffer.color_buffer.get_image_data();
+    let mapped: Vec<Color> = (0..width * height)
+        .into_par_iter()
+        .map(|index| {
+            let (x, y) = (index % width, index / width);
+            // Coordenadas -1..1 desde el centro, empujadas hacia fuera cuanto más lejos están
+            let nx = (x as f32 + 0.5) / width as f32 * 2.0 - 1.0;
+            let ny = (y as f32 + 0.5) / height as f32 * 2.0 - 1.0;
+            let bend = 1.0 + params.crt_curvature * (nx * nx + ny * ny);
+            let (sx, sy) = (nx * bend, ny * bend);
+            if sx.abs() > 1.0 || sy.abs() > 1.0 {
+                return Color::new(0, 0, 0, 255); // Fuera de la pantalla curva
+            }
+            let px = (((sx + 1.0) * 0.5 * width as f32) as i32).min(width - 1);
+            let py = (((sy + 1.0) * 0.5 * height as f32) as i32).min(height - 1);
+            let color = colors[(py * width + px) as usize];
+
+            // Una línea oscura cada dos filas y una franja de fósforo por columna
+            let scanline = if y % 2 == 1 { 1.0 - params.crt_scanline_strength } else { 1.0 };
+            let dim = 1.0 - params.crt_mask_strength;
+            let mask = match x % 3 {
+                0 => [1.0, dim, dim],
+                1 => [dim, 1.0, dim],
+                _ => [dim, dim, 1.0],
+            };
+            Color::new(
+                (color.r as f32 * scanline * mask[0]) as u8,
+                (color.g as f32 * scanline * mask[1]) as u8,
+                (color.b as f32 * scanline * mask[2]) as u8,
+                color.a,
+            )
+        })
+        .collect();
+    write_changed(framebuffer, &colors, &mapped);
+}
+
+fn distance_squared(entry: &[u8; 3], target: [f32; 3]) -> f32 {
+    let dr = entry[0] as f32 - target[0];
+    let dg = entry[1] as f32 - target[1];
+    let db = entry[2] as f32 - target[2];
+    dr * dr + dg * dg + db * db
+}
+
+// Nivel más cercano por debajo de valor + umbral (umbral 0.5 = redondeo)
+fn quantize_channel(value: u8, bits: u32, threshold: f32) -> u8 {
+    let levels = ((1u32 << bits) - 1) as f32;
+    let level = (value as f32 / 255.0 * levels + threshold).floor().min(levels);
+    (level / levels * 255.0).round() as u8
+}
+
+/// Exponential distance fog from the depth buffer: past `fog_start` every pixel with geometry
+/// fades towards the background color by `1 - e^(-fog_density * distance)`, at most `fog_max`
+pub fn fog(framebuffer: &mut Framebuffer, params: &PostParams, depth_mode: DepthMode, near: f32, far: f32) {
+    let background = framebuffer.background_color();
+    let amounts: Vec<f32> = framebuffer.depth_view().depth
+        .par_iter()
+        .map(|&depth| {
+            if !depth.is_finite() {
+                return 0.0;
+            }
+            let distance = (depth_mode.linear_depth(depth, near, far) - params.fog_start).max(0.0);
+            (1.0 - (-params.fog_density * distance).exp()).min(params.fog_max)
+        })
+        .collect();
+
+    // Solo se reescriben los píxeles con niebla
+    let width = framebuffer.width;
+    for (index, &amount) in amounts.iter().enumerate() {
+        if amount <= 0.001 {
+            continue;
+        }
+        let (x, y) = (index as i32 % width, index as i32 / width);
+        let color = framebuffer.color_buffer.get_color(x, y);
+        let mix = |channel: u8, target: u8| (channel as f32 + (target as f32 - channel as f32) * amount) as u8;
+        let fogged = Color::new(mix(color.r, background.r), mix(color.g, background.g), mix(color.b, background.b), color.a);
+        framebuffer.color_buffer.draw_pixel(x, y, fogged);
+    }
+}
+
+/// Screen-space ambient occlusion from the depth buffer alone: each pixel is darkened by the
+/// neighbours (at `ssao_radius` and half of it) that sit in front of it within `ssao_range`
+pub fn ssao(framebuffer: &mut Framebuffer, params: &PostParams, depth_mode: DepthMode, near: f32, far: f32) {
+    let depth_view = framebuffer.depth_view();
+    let (width, height) = (depth_view.width, depth_view.height);
+    let linear: Vec<f32> = depth_view.depth
+        .par_iter()
+        .map(|&depth| if depth.is_finite() { depth_mode.linear_depth(depth, near, far) } else { f32::INFINITY })
+        .collect();
+
+    let sample_count = (SSAO_DIRECTIONS.len() * 2) as f32;
+    let occlusion: Vec<f32> = (0..width * height)
+        .into_par_iter()
+        .map(|index| {
+            let center = linear[index as usize];
+            if !center.is_finite() {
+                return 0.0;
+            }
+            let (x, y) = (index % width, index / width);
+            let mut occlusion = 0.0;
+            for &(dx, dy) in &SSAO_DIRECTIONS {
+                for radius in [params.ssao_radius, params.ssao_radius * 0.5] {
+                    let sx = x + (dx * radius) as i32;
+                    let sy = y + (dy * radius) as i32;
+                    if sx < 0 || sx >= width || sy < 0 || sy >= height {
+                        continue;
+                    }
+                    // Solo ocluye lo que está delante y no demasiado lejos (evita halos en las siluetas)
+                    let difference = center - linear[(sy * width + sx) as usize];
+                    if difference > params.ssao_bias && difference < params.ssao_range {
+                        occlusion += 1.0 - difference / params.ssao_range;
+                    }
+                }
+            }
+            (occlusion / sample_count * params.ssao_strength).min(1.0)
+        })
+        .collect();
+
+    // Solo se reescriben los píxeles que quedan ocluidos
+    for (index, &amount) in occlusion.iter().enumerate() {
+        if amount <= 0.001 {
+            continue;
+        }
+        let (x, y) = (index as i32 % width, index as i32 / width);
+        let color = framebuffer.color_buffer.get_color(x, y);
+        let keep = 1.0 - amount;
+        let darkened = Color::new(
+            (color.r as f32 * keep) as u8,
+            (color.g as f32 * keep) as u8,
+            (color.b as f32 * keep) as u8,
+            color.a,
+        );
+        framebuffer.color_buffer.draw_pixel(x, y, darkened);
+    }
+}
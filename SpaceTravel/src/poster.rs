@@ -0,0 +1,157 @@
+// poster.rs
+// Póster del sistema: una sola imagen grande vista desde arriba con proyección ortográfica, con
+// todas las órbitas, cada cuerpo a una escala exagerada (los pequeños crecen hasta un tamaño
+// mínimo en pantalla para que se vean) y su nombre debajo. Se dibuja al doble de tamaño con la
+// calidad Ultra y se reduce al final, así que las órbitas y los bordes salen suavizados. Muestra
+// el sistema tal como está en ese momento; se pide desde la consola con `poster [archivo.png]`
+use crate::console::CommandRegistry;
+use crate::orbit::OrbitCache;
+use crate::palette::PalettePreset;
+use crate::settings::QualityPreset;
+use crate::shader_plugins;
+use crate::systems::RenderSystem;
+use crate::world::World;
+use space_travel::{Renderer, Scene, View};
+use space_travel::lighting::key_light;
+use space_travel::matrix::{DepthMode, create_model_matrix, create_orthographic_matrix, create_view_matrix, create_viewport_matrix, multiply_matrices, multiply_matrix_vector4};
+use space_travel::shader_params::ShaderParams;
+use raylib::prelude::*;
+use std::path::Path;
+use std::time::Instant;
+
+// Lado de la imagen final y cuántas veces más grande se dibuja antes de reducirla
+const SIZE: i32 = 2048;
+const SUPERSAMPLE: i32 = 2;
+
+// Margen alrededor de la órbita más lejana y diámetro mínimo de un cuerpo, en píxeles finales
+const MARGIN: f32 = 1.1;
+const MIN_DIAMETER: f32 = 36.0;
+
+// Nombres: tamaño, separación bajo el cuerpo y ancho aproximado de un carácter de la fuente
+const LABEL_SIZE: i32 = 28;
+const LABEL_GAP: f32 = 8.0;
+const CHAR_WIDTH: f32 = 0.6;
+const LABEL_COLOR: Color = Color::new(230, 230, 240, 255);
+const LABEL_SHADOW: Color = Color::new(0, 0, 0, 200);
+
+// Las órbitas se trazan con varios círculos juntos para que no desaparezcan al reducir
+const ORBIT_STROKES: [f32; 3] = [-0.5, 0.0, 0.5];
+
+const BACKGROUND_COLOR: Color = Color::new(6, 6, 12, 255);
+const SHADER_PARAMS_PATH: &str = "shaders.toml";
+const DEFAULT_PATH: &str = "poster.png";
+
+/// Registers `poster [file.png]`: saves the overview of the current system
+pub fn register_commands(registry: &mut CommandRegistry) {
+    registry.register("poster", "poster [file.png]", |context, arguments| {
+        let path = match arguments {
+            [] => DEFAULT_PATH,
+            [path] => *path,
+            _ => return Err("Expected at most one file name".to_string()),
+        };
+        render(context.world, context.time, path)?;
+        Ok(format!("Poster saved to {}", path))
+    });
+}
+
+// Diámetro con el que sale un cuerpo de tamaño `scale` si cada píxel final mide `pixel`
+fn poster_scale(scale: f32, pixel: f32) -> f32 {
+    scale.max(MIN_DIAMETER * pixel)
+}
+
+/// Renders the whole system seen from above at `time` as a SIZE x SIZE PNG at `path`
+pub fn render(world: &World, time: f32, path: &str) -> Result<(), String> {
+    let start = Instant::now();
+
+    // Lo que tiene que caber: cada cuerpo y cada órbita entera
+    let extent = (0..world.transforms.len()).map(|entity| {
+        let transform = &world.transforms[entity];
+        let body = Vector3::new(transform.position.x, 0.0, transform.position.z).length() + transform.scale * 0.5;
+        let orbit = world.orbits[entity].map_or(0.0, |orbit| {
+            let center = orbit.parent.map_or(Vector3::zero(), |parent| world.transforms[parent].position);
+            Vector3::new(center.x, 0.0, center.z).length() + orbit.radius
+        });
+        body.max(orbit)
+    }).fold(1.0, f32::max) * MARGIN;
+    let pixel = 2.0 * extent / SIZE as f32;
+
+    // Cámara muy por encima del plano de las órbitas mirando hacia abajo, con el -Z del mundo arriba
+    let height = extent * 4.0;
+    let eye = Vector3::new(0.0, height, 0.0);
+    let view_matrix = create_view_matrix(eye, Vector3::zero(), Vector3::new(0.0, 0.0, -1.0));
+    let projection_matrix = create_orthographic_matrix(extent, extent, 0.1, height * 2.0);
+
+    let sphere = QualityPreset::Ultra.planet_mesh().generate();
+    let authored = ShaderParams::load(SHADER_PARAMS_PATH).unwrap_or_else(|e| {
+        eprintln!("Using default shader parameters: {}", e);
+        ShaderParams::default()
+    });
+    let params = QualityPreset::Ultra.apply(&authored);
+    let (width, height_px) = (SIZE * SUPERSAMPLE, SIZE * SUPERSAMPLE);
+    let mut renderer = Renderer::new(width, height_px);
+    renderer.framebuffer.set_background_color(BACKGROUND_COLOR);
+    shader_plugins::register(&mut renderer.shaders);
+    renderer.framebuffer.clear();
+
+    // Órbitas primero, como en la escena; el renderer dibuja los cuerpos encima
+    let palette = PalettePreset::Standard.colors();
+    let mut orbit_cache = OrbitCache::new();
+    orbit_cache.set_camera(&view_matrix, &projection_matrix, &create_viewport_matrix(0.0, 0.0, width as f32, height_px as f32));
+    for (entity, orbit) in world.orbits.iter().enumerate() {
+        let Some(orbit) = orbit.filter(|orbit| orbit.radius > 0.0) else {
+            continue;
+        };
+        let (color, center) = match orbit.parent {
+            None => (Color::new(palette.orbit.r, palette.orbit.g, palette.orbit.b, 200), Vector3::zero()),
+            Some(parent) => {
+                let color = world.renderables[entity].as_ref().map_or(Color::WHITE, |renderable| renderable.color);
+                (Color::new(color.r, color.g, color.b, 140), world.transforms[parent].position)
+            }
+        };
+        for (stroke, offset) in ORBIT_STROKES.iter().enumerate() {
+            let key = format!("{}#{}", world.names[entity], stroke);
+            orbit_cache.draw(&mut renderer.framebuffer, &key, orbit.radius + offset * pixel / SUPERSAMPLE as f32, center, color);
+        }
+    }
+
+    // Los cuerpos, cada uno con su tamaño exagerado
+    let mut models: Vec<_> = RenderSystem.models(world, &sphere).collect();
+    for model in &mut models {
+        if let Some(transform) = world.names.iter().position(|name| name == model.label).map(|entity| world.transforms[entity]) {
+            model.model_matrix = create_model_matrix(transform.position, poster_scale(transform.scale, pixel), transform.rotation);
+        }
+    }
+    let mut lights = if params.lighting.key_light_only { Vec::new() } else { world.scene_lights() };
+    lights.extend(key_light(&params.lighting));
+    let view = View {
+        view_matrix,
+        projection_matrix,
+        depth_mode: DepthMode::Projective,
+        eye,
+        time,
+        dt: 0.0,
+    };
+    renderer.draw_frame(&Scene { models, lights }, &view, &params);
+
+    // Reducir al tamaño final y poner los nombres encima, ya a su resolución
+    let mut image = renderer.framebuffer.color_buffer.clone();
+    image.resize(SIZE, SIZE);
+    let view_projection = multiply_matrices(&projection_matrix, &view_matrix);
+    for (entity, name) in world.names.iter().enumerate() {
+        let transform = &world.transforms[entity];
+        let p = transform.position;
+        let clip = multiply_matrix_vector4(&view_projection, &Vector4::new(p.x, p.y, p.z, 1.0));
+        let x = (clip.x / clip.w + 1.0) * 0.5 * SIZE as f32;
+        let y = (1.0 - clip.y / clip.w) * 0.5 * SIZE as f32 + poster_scale(transform.scale, pixel) * 0.5 / pixel + LABEL_GAP;
+        let x = (x - name.chars().count() as f32 * LABEL_SIZE as f32 * CHAR_WIDTH * 0.5) as i32;
+        image.draw_text(name, x + 2, y as i32 + 2, LABEL_SIZE, LABEL_SHADOW);
+        image.draw_text(name, x, y as i32, LABEL_SIZE, LABEL_COLOR);
+    }
+
+    image.export_image(path);
+    if !Path::new(path).exists() {
+        return Err(format!("Failed to write {}", path));
+    }
+    println!("Poster of {} bodies written to {} in {:.1}s", world.names.len(), path, start.elapsed().as_secs_f32());
+    Ok(())
+}
@@ -0,0 +1,154 @@
+// profiler.rs
+// Instrumentación ligera por etapas. Cada `profile_scope!("nombre")` mide hasta el final del bloque;
+// los scopes anidados forman rutas como "frame/render/raster". Al cerrar cada frame se acumulan
+// min/avg/max por ruta, y opcionalmente se guardan eventos para chrome://tracing.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::time::{Duration, Instant};
+
+// Límite de eventos guardados para el trace (unos minutos a 60 fps)
+const MAX_TRACE_EVENTS: usize = 200_000;
+
+#[derive(Clone, Copy)]
+struct Stats {
+    frames: u32,
+    total: Duration,
+    min: Duration,
+    max: Duration,
+}
+
+struct TraceEvent {
+    name: &'static str,
+    start: Duration,
+    duration: Duration,
+}
+
+struct Profiler {
+    epoch: Instant,
+    stack: Vec<&'static str>,
+    // Tiempo acumulado por ruta en el frame actual (un scope puede repetirse en el mismo frame)
+    frame: BTreeMap<String, Duration>,
+    stats: BTreeMap<String, Stats>,
+    trace: Option<Vec<TraceEvent>>,
+}
+
+thread_local! {
+    static PROFILER: RefCell<Profiler> = RefCell::new(Profiler {
+        epoch: Instant::now(),
+        stack: Vec::new(),
+        frame: BTreeMap::new(),
+        stats: BTreeMap::new(),
+        trace: None,
+    });
+}
+
+/// Measures the time until it is dropped. Created by `profile_scope!`
+pub struct Scope {
+    start: Instant,
+}
+
+impl Scope {
+    pub fn new(name: &'static str) -> Self {
+        PROFILER.with(|p| p.borrow_mut().stack.push(name));
+        Scope { start: Instant::now() }
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        PROFILER.with(|p| {
+            let mut p = p.borrow_mut();
+            let path = p.stack.join("/");
+            let name = p.stack.pop().unwrap_or("?");
+            *p.frame.entry(path).or_default() += elapsed;
+
+            let start = self.start.duration_since(p.epoch);
+            if let Some(trace) = p.trace.as_mut() && trace.len() < MAX_TRACE_EVENTS {
+                trace.push(TraceEvent { name, start, duration: elapsed });
+            }
+        });
+    }
+}
+
+/// Times the rest of the enclosing block under the given stage name
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        let _profile_scope = $crate::profiler::Scope::new($name);
+    };
+}
+
+/// Folds the timings of the current frame into the min/avg/max statistics
+pub fn end_frame() {
+    PROFILER.with(|p| {
+        let mut p = p.borrow_mut();
+        let frame = std::mem::take(&mut p.frame);
+        for (path, time) in frame {
+            let stats = p.stats.entry(path).or_insert(Stats {
+                frames: 0,
+                total: Duration::ZERO,
+                min: Duration::MAX,
+                max: Duration::ZERO,
+            });
+            stats.frames += 1;
+            stats.total += time;
+            stats.min = stats.min.min(time);
+            stats.max = stats.max.max(time);
+        }
+    });
+}
+
+/// Table with min/avg/max milliseconds per stage, indented by nesting level
+pub fn summary() -> String {
+    PROFILER.with(|p| {
+        let p = p.borrow();
+        let mut out = format!("{:<32} {:>9} {:>9} {:>9}\n", "stage", "min ms", "avg ms", "max ms");
+        for (path, stats) in &p.stats {
+            let depth = path.matches('/').count();
+            let name = path.rsplit('/').next().unwrap_or(path);
+            let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+            let _ = writeln!(
+                out,
+                "{:<32} {:>9.3} {:>9.3} {:>9.3}",
+                format!("{}{}", "  ".repeat(depth), name),
+                ms(stats.min),
+                ms(stats.total) / stats.frames as f64,
+                ms(stats.max),
+            );
+        }
+        out
+    })
+}
+
+/// Starts recording every scope as a trace event
+pub fn enable_trace() {
+    PROFILER.with(|p| p.borrow_mut().trace = Some(Vec::new()));
+}
+
+/// Writes the recorded events in Chrome tracing format (open with chrome://tracing or Perfetto)
+pub fn write_trace(path: &str) -> Result<(), String> {
+    PROFILER.with(|p| {
+        let p = p.borrow();
+        let Some(trace) = p.trace.as_ref() else {
+            return Err("tracing is not enabled".to_string());
+        };
+        let mut json = String::from("{\"traceEvents\":[\n");
+        for (i, event) in trace.iter().enumerate() {
+            let separator = if i + 1 < trace.len() { "," } else { "" };
+            let _ = writeln!(
+                json,
+                "{{\"name\":\"{}\",\"ph\":\"X\",\"pid\":1,\"tid\":1,\"ts\":{:.3},\"dur\":{:.3}}}{}",
+                event.name,
+                event.start.as_secs_f64() * 1e6,
+                event.duration.as_secs_f64() * 1e6,
+                separator,
+            );
+        }
+        json.push_str("]}\n");
+        fs::write(path, json).map_err(|e| format!("{}: {}", path, e))
+    })
+}
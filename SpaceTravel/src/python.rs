@@ -0,0 +1,59 @@
+// python.rs
+// Enlaces de Python (pyo3) para renderizar sin ventana desde un notebook: cargar una escena,
+// colocar la cámara y obtener cada frame como un array de numpy (alto x ancho x 3, u8) para
+// figuras y animaciones. Se compilan con la característica "python" (ver pyproject.toml); el
+// render en sí está en offline.rs
+use crate::offline::OfflineRenderer;
+use numpy::{PyArray1, PyArray3, PyArrayMethods};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use raylib::prelude::*;
+
+/// Offline renderer for Python: `load_scene`, `set_camera`, then `render_frame` for each image
+#[pyclass(name = "Renderer", unsendable)]
+struct PyRenderer {
+    inner: OfflineRenderer,
+}
+
+#[pymethods]
+impl PyRenderer {
+    /// Renderer of `width` x `height` pixels with the Xerion system loaded
+    #[new]
+    #[pyo3(signature = (width=640, height=360))]
+    fn new(width: i32, height: i32) -> PyResult<Self> {
+        let inner = OfflineRenderer::new(width, height).map_err(PyValueError::new_err)?;
+        Ok(PyRenderer { inner })
+    }
+
+    /// Replaces the bodies with a scene: "xerion", "solar" or the path to a .toml file
+    fn load_scene(&mut self, name: &str) -> PyResult<()> {
+        self.inner.load_scene(name).map_err(PyValueError::new_err)
+    }
+
+    /// Loads the shader parameters (frequencies, palettes, lighting) from a shaders.toml file
+    fn load_shader_params(&mut self, path: &str) -> PyResult<()> {
+        self.inner.load_shader_params(path).map_err(PyValueError::new_err)
+    }
+
+    /// Places the camera at `eye` looking at `target`, with a vertical field of view in degrees
+    #[pyo3(signature = (eye, target=(0.0, 0.0, 0.0), fov=60.0))]
+    fn set_camera(&mut self, eye: (f32, f32, f32), target: (f32, f32, f32), fov: f32) {
+        self.inner.set_camera(Vector3::new(eye.0, eye.1, eye.2), Vector3::new(target.0, target.1, target.2), fov.to_radians());
+    }
+
+    /// Renders the scene at `time` seconds and returns the image as a height x width x 3
+    /// array of RGB bytes
+    #[pyo3(signature = (time=0.0))]
+    fn render_frame<'py>(&mut self, py: Python<'py>, time: f32) -> PyResult<Bound<'py, PyArray3<u8>>> {
+        let (width, height) = self.inner.size();
+        let pixels = self.inner.render_frame(time);
+        PyArray1::from_vec_bound(py, pixels).reshape([height as usize, width as usize, 3])
+    }
+}
+
+/// Python module `space_travel`
+#[pymodule]
+fn space_travel(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyRenderer>()?;
+    Ok(())
+}
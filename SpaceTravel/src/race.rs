@@ -0,0 +1,206 @@
+// race.rs
+// Contrarreloj por anillos: al empezar (G) se traza una ruta desde la cámara por encima de los
+// planetas más cercanos, uno tras otro, con un anillo sobre cada planeta y otro a mitad de cada
+// tramo. Hay que cruzarlos en orden y en el sentido de la ruta; el anillo siguiente brilla y
+// cada cruce suelta una ráfaga de chispas (las partículas de los motores, exhaust.rs). Los
+// anillos quedan fijos donde se trazaron aunque los planetas sigan su órbita
+use crate::exhaust::Exhaust;
+use crate::locale::Strings;
+use crate::world::World;
+use space_travel::Model;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::lighting::Surface;
+use space_travel::matrix::{DepthMode, Quaternion, create_model_matrix_from_quaternion};
+use space_travel::mesh::{Mesh, generate_torus};
+use space_travel::shader_params::ExhaustParams;
+use raylib::prelude::*;
+
+// Diámetro de los anillos y grosor de su tubo (fracción del radio)
+const GATE_DIAMETER: f32 = 8.0;
+const GATE_THICKNESS: f32 = 0.12;
+
+// Planetas por los que pasa la ruta y altura del anillo sobre cada uno, en radios del planeta
+const ROUTE_PLANETS: usize = 4;
+const CLEARANCE_RADII: f32 = 3.0;
+
+// Chispas al cruzar un anillo y segundos que se muestra el resultado al terminar
+const SPARKS: usize = 80;
+const RESULT_DURATION: f32 = 5.0;
+
+struct Gate {
+    center: Vector3,
+    normal: Vector3, // Sentido en que hay que cruzarlo
+    model_matrix: Matrix,
+    label: String,   // Propio de cada anillo (el renderer guarda impostores por nombre)
+}
+
+/// A time trial through a route of ring gates
+pub struct Race {
+    mesh: Mesh,
+    gates: Vec<Gate>,
+    next: usize,
+    elapsed: f32,
+    previous_eye: Option<Vector3>,
+    best: Option<f32>,
+    result: Option<(f32, f32)>, // Tiempo de la última carrera y segundos que le quedan en pantalla
+    sparks: Exhaust,
+}
+
+impl Race {
+    pub fn new() -> Self {
+        Race {
+            mesh: generate_torus(48, 12, GATE_THICKNESS),
+            gates: Vec::new(),
+            next: 0,
+            elapsed: 0.0,
+            previous_eye: None,
+            best: None,
+            result: None,
+            sparks: Exhaust::new(),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.next < self.gates.len()
+    }
+
+    /// Lays out a route from `eye` over the nearest planets of `world` (bodies that are not
+    /// stars), each leg towards the nearest one not yet on the route, and starts the timer
+    pub fn start(&mut self, eye: Vector3, world: &World) {
+        let mut remaining: Vec<usize> = (0..world.transforms.len()).filter(|&body| world.lights[body].is_none()).collect();
+        let mut waypoints = Vec::new();
+        let mut position = eye;
+        while waypoints.len() < ROUTE_PLANETS {
+            let distance = |body: usize| (world.transforms[body].position - position).length();
+            let Some(index) = (0..remaining.len()).min_by(|&a, &b| distance(remaining[a]).total_cmp(&distance(remaining[b]))) else {
+                break;
+            };
+            let transform = &world.transforms[remaining.swap_remove(index)];
+            position = transform.position + Vector3::new(0.0, transform.scale * 0.5 * CLEARANCE_RADII + GATE_DIAMETER, 0.0);
+            waypoints.push(position);
+        }
+
+        // Un anillo a mitad de cada tramo, de cara al tramo, y otro en cada punto de paso, de
+        // cara a la media entre el tramo que llega y el que sale
+        let mut gates = Vec::new();
+        let mut previous = eye;
+        for (index, &waypoint) in waypoints.iter().enumerate() {
+            let leg = (waypoint - previous).normalized();
+            let outgoing = waypoints.get(index + 1).map_or(leg, |&next| (next - waypoint).normalized());
+            let turn = leg + outgoing;
+            let normal = if turn.length() > 1e-3 { turn.normalized() } else { leg };
+            gates.push((previous.lerp(waypoint, 0.5), leg));
+            gates.push((waypoint, normal));
+            previous = waypoint;
+        }
+        self.gates = gates.into_iter().enumerate().map(|(index, (center, normal))| Gate {
+            center,
+            normal,
+            model_matrix: create_model_matrix_from_quaternion(center, GATE_DIAMETER, facing(normal)),
+            label: format!("gate {}", index + 1),
+        }).collect();
+        self.next = 0;
+        self.elapsed = 0.0;
+        self.previous_eye = Some(eye);
+        self.result = None;
+    }
+
+    /// Removes the gates without a result
+    pub fn abort(&mut self) {
+        self.gates.clear();
+        self.next = 0;
+    }
+
+    /// Runs the timer and checks whether the camera, now at `eye`, went through the next gate
+    /// since the last frame; the sparks move on even after the race. Returns the time of the
+    /// race when it ends in this frame
+    pub fn update(&mut self, eye: Vector3, dt: f32, params: &ExhaustParams) -> Option<f32> {
+        self.sparks.age(dt);
+        if let Some((_, remaining)) = self.result.as_mut() {
+            *remaining -= dt;
+            if *remaining <= 0.0 {
+                self.result = None;
+            }
+        }
+        if !self.is_running() {
+            return None;
+        }
+        self.elapsed += dt;
+        let previous = self.previous_eye.replace(eye).unwrap_or(eye);
+
+        // El tramo recorrido tiene que atravesar el plano del anillo en su sentido y por dentro
+        let gate = &self.gates[self.next];
+        let (before, after) = ((previous - gate.center).dot(gate.normal), (eye - gate.center).dot(gate.normal));
+        if before >= 0.0 || after < 0.0 {
+            return None;
+        }
+        let crossing = previous + (eye - previous) * (before / (before - after));
+        if (crossing - gate.center).length() > GATE_DIAMETER * 0.5 {
+            return None;
+        }
+        self.sparks.burst(gate.center, SPARKS, params);
+        self.next += 1;
+        if !self.is_running() {
+            self.best = Some(self.best.map_or(self.elapsed, |best| best.min(self.elapsed)));
+            self.result = Some((self.elapsed, RESULT_DURATION));
+            self.gates.clear();
+            self.next = 0;
+            return Some(self.elapsed);
+        }
+        None
+    }
+
+    /// The gates still to cross: the next one glows, the rest are lit metal
+    pub fn models(&self) -> impl Iterator<Item = Model<'_>> {
+        self.gates.iter().enumerate().skip(self.next).map(move |(index, gate)| Model {
+            mesh: &self.mesh,
+            model_matrix: gate.model_matrix,
+            shader: "gate",
+            label: &gate.label,
+            surface: if index == self.next { Surface::emissive(1.0) } else { Surface::glossy(0.6, 32.0, 0.8) },
+            displacement: 0.0,
+            vertex_animation: "",
+            geometry_shader: "",
+            parts: &[],
+            ray_traced: false,
+            rings: None,
+            sdf: None,
+        })
+    }
+
+    /// Adds the sparks of the crossed gates to the frame
+    pub fn draw_sparks(&self, framebuffer: &mut Framebuffer, view_projection: &Matrix, viewport: &Matrix, depth_mode: DepthMode, params: &ExhaustParams) {
+        self.sparks.draw(framebuffer, view_projection, viewport, depth_mode, params);
+    }
+
+    /// Timer lines for the HUD: the gate and time of the race in progress, or the result of
+    /// the one just finished
+    pub fn hud_lines(&self, strings: &Strings) -> Vec<String> {
+        let best = self.best.map(|best| strings.format("race.best", &[("time", &format_time(best))])).unwrap_or_default();
+        if self.is_running() {
+            let (gate, total, time) = (self.next + 1, self.gates.len(), format_time(self.elapsed));
+            vec![strings.format("race.running", &[("gate", &gate), ("total", &total), ("time", &time), ("best", &best)])]
+        } else if let Some((time, _)) = self.result {
+            vec![strings.format("race.finished", &[("time", &format_time(time)), ("best", &best)])]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+// Giro que lleva el eje Y del anillo (su agujero) a `normal`
+fn facing(normal: Vector3) -> Quaternion {
+    let up = Vector3::new(0.0, 1.0, 0.0);
+    let axis = up.cross(normal);
+    if axis.length() < 1e-4 {
+        // Ya alineado, o al revés (media vuelta sobre X)
+        return if normal.y > 0.0 { Quaternion::identity() } else { Quaternion::from_axis_angle(Vector3::new(1.0, 0.0, 0.0), std::f32::consts::PI) };
+    }
+    Quaternion::from_axis_angle(axis.normalized(), up.dot(normal).clamp(-1.0, 1.0).acos())
+}
+
+// Tiempo como mm:ss.d
+fn format_time(seconds: f32) -> String {
+    let tenths = (seconds.max(0.0) * 10.0) as u32;
+    format!("{:02}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
@@ -0,0 +1,268 @@
+// raytrace.rs
+// Esferas trazadas por rayos: como todos los planetas son esferas, en lugar de rasterizar su
+// malla teselada se lanza un rayo por píxel contra la esfera exacta de radio 0.5 (la que
+// aproximan las mallas de los planetas) en el espacio del modelo. El contorno sale perfecto a
+// cualquier distancia y la normal es la del punto exacto, sin facetas. Los fragmentos llevan lo
+// mismo que los del rasterizador (posición de mundo y de objeto, normal, tangentes y material),
+// así que pasan por la misma resolución de visibilidad, los mismos shaders y la misma luz. Los
+// anillos de los planetas se trazan igual contra su plano ecuatorial: el rayo corta el plano en
+// un punto y hay anillo si cae entre sus dos radios. Son transparentes, así que se mezclan sobre
+// lo opaco ya dibujado, y cada punto lanza un rayo a las luces para que la sombra del planeta
+// los cruce, algo difícil de sacar bien con geometría translúcida rasterizada
+use crate::fragment::Fragment;
+use crate::framebuffer::{DepthView, Framebuffer};
+use crate::light::Light;
+use crate::lighting::{Surface, apply_lighting};
+use crate::matrix::{inverse, multiply_matrices, multiply_matrix_vector4};
+use crate::noise::value_noise;
+use crate::shader_params::{LightingQuality, ShaderParams};
+use crate::shadow::Shadows;
+use crate::simd::cross;
+use crate::stats::PipelineStats;
+use crate::uniforms::Uniforms;
+use crate::vertex::Vertex;
+use rayon::prelude::*;
+use raylib::prelude::*;
+
+/// Radius of the traced sphere in model space, the one of the planet meshes
+pub const SPHERE_RADIUS: f32 = 0.5;
+
+// Color sin iluminar de los fragmentos, el mismo que usa el rasterizador
+const BASE_COLOR: Vector3 = Vector3::new(0.5, 0.5, 0.5);
+
+// Bandas de los anillos a lo ancho (frecuencia del ruido) y parte del ancho en la que se
+// desvanecen sus bordes
+const RING_BANDS: f32 = 24.0;
+const RING_EDGE: f32 = 0.04;
+
+/// Flat rings around the equator of a body (model space y = 0), only drawn by the ray mode
+#[derive(Clone, Copy, Debug)]
+pub struct Rings {
+    pub inner: f32, // Radio interior y exterior, en radios del cuerpo
+    pub outer: f32,
+    pub color: Vector3,
+    pub opacity: f32, // Opacidad de las bandas más densas (0..1)
+}
+
+/// Traces the model-space sphere of `uniforms` through the pixels it can cover and returns one
+/// fragment per hit in front of `depth_view`, with the material of `material`. Like the
+/// rasterizer, what happened to its pixels is counted in `stats`
+pub fn sphere(uniforms: &Uniforms, material: &Vertex, depth_view: &DepthView, stats: &mut PipelineStats) -> Vec<Fragment> {
+    trace(uniforms, material, depth_view, stats, |origin, direction| {
+        intersect(origin, direction).map(|hit| (hit, hit / SPHERE_RADIUS))
+    })
+}
+
+/// Like [`sphere`], for any surface inside the radius 0.5 sphere: `surface` takes the ray of a
+/// pixel in model space (origin and unit direction) and returns the point it hits and the
+/// unit normal there
+pub(crate) fn trace(uniforms: &Uniforms, material: &Vertex, depth_view: &DepthView, stats: &mut PipelineStats, surface: impl Fn(Vector3, Vector3) -> Option<(Vector3, Vector3)> + Sync) -> Vec<Fragment> {
+    let clip_from_object = multiply_matrices(&uniforms.projection_matrix, &multiply_matrices(&uniforms.view_matrix, &uniforms.model_matrix));
+    let Some(object_from_clip) = inverse(&clip_from_object) else {
+        return Vec::new();
+    };
+    let extent = Vector3::one() * SPHERE_RADIUS;
+    let Some((min_x, max_x, min_y, max_y)) = screen_box(&clip_from_object, extent, uniforms, depth_view) else {
+        return Vec::new();
+    };
+
+    // Las filas son independientes: se trazan en paralelo
+    let rows: Vec<(Vec<Fragment>, u32)> = (min_y..=max_y)
+        .into_par_iter()
+        .map(|y| {
+            let mut fragments = Vec::new();
+            let mut rejected = 0;
+            let p_y = y as f32 + 0.5;
+            let row = (y * depth_view.width) as usize;
+            for x in min_x..=max_x {
+                let p_x = x as f32 + 0.5;
+                let (origin, direction) = pixel_ray(&object_from_clip, uniforms, p_x, p_y);
+                let Some((hit, normal)) = surface(origin, direction) else {
+                    continue;
+                };
+                let clip = multiply_matrix_vector4(&clip_from_object, &Vector4::new(hit.x, hit.y, hit.z, 1.0));
+                if clip.w <= 0.0 {
+                    continue;
+                }
+                let depth = uniforms.depth_mode.depth(clip.z / clip.w, clip.w);
+                let depth = uniforms.viewport_matrix.m10 * depth + uniforms.viewport_matrix.m14;
+                if depth >= depth_view.depth[row + x as usize] {
+                    rejected += 1;
+                    continue;
+                }
+                fragments.push(fragment(hit, normal, p_x, p_y, depth, uniforms, material));
+            }
+            (fragments, rejected)
+        })
+        .collect();
+
+    let mut fragments = Vec::new();
+    for (row, rejected) in rows {
+        stats.fragments_depth_rejected += rejected;
+        fragments.extend(row);
+    }
+    stats.fragments_generated += fragments.len() as u32;
+    fragments
+}
+
+/// Blends the rings of the model of `uniforms` over what `framebuffer` holds, where they are in
+/// front of it: each pixel's ray meets the ring plane at one point, with the opacity of the
+/// band it falls on. They are lit from both sides and take the shadows of `shadow` (the ray
+/// shadows of the bodies, so the planet's shadow crosses them). Returns the pixels written
+pub fn rings(uniforms: &Uniforms, rings: &Rings, framebuffer: &mut Framebuffer, lights: &[Light], shadow: &Shadows, params: &ShaderParams) -> u32 {
+    let clip_from_object = multiply_matrices(&uniforms.projection_matrix, &multiply_matrices(&uniforms.view_matrix, &uniforms.model_matrix));
+    let Some(object_from_clip) = inverse(&clip_from_object) else {
+        return 0;
+    };
+    let reach = rings.outer * SPHERE_RADIUS;
+    let depth_view = framebuffer.depth_view();
+    let Some((min_x, max_x, min_y, max_y)) = screen_box(&clip_from_object, Vector3::new(reach, 0.0, reach), uniforms, &depth_view) else {
+        return 0;
+    };
+
+    // La normal del plano, del lado que mira a la cámara en cada punto
+    let transform = |matrix: &Matrix, v: Vector3, w: f32| {
+        let v = multiply_matrix_vector4(matrix, &Vector4::new(v.x, v.y, v.z, w));
+        Vector3::new(v.x, v.y, v.z)
+    };
+    let plane_normal = transform(&uniforms.normal_matrix, Vector3::new(0.0, 1.0, 0.0), 0.0).normalized();
+    let surface = Surface { lighting: Some(LightingQuality::Phong), ..Surface::default() };
+
+    // Las filas se trazan en paralelo y se mezclan después en serie
+    let pixels: Vec<(i32, i32, Vector3, f32, f32)> = (min_y..=max_y)
+        .into_par_iter()
+        .flat_map_iter(|y| {
+            let depth_view = &depth_view;
+            let row = (y * depth_view.width) as usize;
+            (min_x..=max_x).filter_map(move |x| {
+                let (p_x, p_y) = (x as f32 + 0.5, y as f32 + 0.5);
+                let (origin, direction) = pixel_ray(&object_from_clip, uniforms, p_x, p_y);
+                if direction.y.abs() < 1e-6 {
+                    return None;
+                }
+                let t = -origin.y / direction.y;
+                if t < 0.0 {
+                    return None;
+                }
+                let hit = origin + direction * t;
+                let radius = (hit.x * hit.x + hit.z * hit.z).sqrt() / SPHERE_RADIUS;
+                let (opacity, shade) = ring_band(rings, radius)?;
+
+                let clip = multiply_matrix_vector4(&clip_from_object, &Vector4::new(hit.x, hit.y, hit.z, 1.0));
+                if clip.w <= 0.0 {
+                    return None;
+                }
+                let depth = uniforms.depth_mode.depth(clip.z / clip.w, clip.w);
+                let depth = uniforms.viewport_matrix.m10 * depth + uniforms.viewport_matrix.m14;
+                if depth >= depth_view.depth[row + x as usize] {
+                    return None;
+                }
+
+                let world_position = transform(&uniforms.model_matrix, hit, 1.0);
+                let color = rings.color * shade;
+                let mut fragment = Fragment::new(p_x, p_y, color, depth, world_position);
+                fragment.object_position = hit;
+                fragment.normal = if plane_normal.dot(uniforms.camera_position - world_position) >= 0.0 { plane_normal } else { -plane_normal };
+                let lit = apply_lighting(color, &fragment, &surface, uniforms.camera_position, lights, Some(shadow), &params.lighting);
+                Some((x, y, lit, opacity, depth))
+            })
+        })
+        .collect();
+
+    let mut written = 0;
+    for (x, y, color, opacity, depth) in pixels {
+        written += framebuffer.blend_point(x, y, color, opacity, depth) as u32;
+    }
+    written
+}
+
+// Opacidad y brillo del anillo a `radius` radios del cuerpo, o None fuera de él: bandas de ruido
+// a lo ancho, con los bordes desvanecidos
+fn ring_band(rings: &Rings, radius: f32) -> Option<(f32, f32)> {
+    let width = rings.outer - rings.inner;
+    if width <= 0.0 || radius < rings.inner || radius > rings.outer {
+        return None;
+    }
+    let across = (radius - rings.inner) / width;
+    let edge = (across / RING_EDGE).min((1.0 - across) / RING_EDGE).min(1.0);
+    let density = value_noise(across * RING_BANDS, 0.5, 0.5);
+    let opacity = rings.opacity.clamp(0.0, 1.0) * density * density * edge;
+    (opacity > 0.01).then_some((opacity, 0.7 + 0.5 * density))
+}
+
+// Píxeles que puede cubrir la caja de medio tamaño `extent` centrada en el origen del modelo: la
+// caja de sus esquinas proyectadas. Si alguna queda detrás de la cámara (la cámara está muy cerca
+// o dentro) se recorre la pantalla
+pub(crate) fn screen_box(clip_from_object: &Matrix, extent: Vector3, uniforms: &Uniforms, depth_view: &DepthView) -> Option<(i32, i32, i32, i32)> {
+    let viewport = &uniforms.viewport_matrix;
+    let (mut min_x, mut max_x, mut min_y, mut max_y) = (f32::INFINITY, f32::NEG_INFINITY, f32::INFINITY, f32::NEG_INFINITY);
+    for corner in 0..8 {
+        let sign = |bit: i32, half: f32| if corner & bit == 0 { -half } else { half };
+        let clip = multiply_matrix_vector4(clip_from_object, &Vector4::new(sign(1, extent.x), sign(2, extent.y), sign(4, extent.z), 1.0));
+        if clip.w <= 1e-4 {
+            (min_x, max_x, min_y, max_y) = (0.0, depth_view.width as f32, 0.0, depth_view.height as f32);
+            break;
+        }
+        let x = viewport.m0 * clip.x / clip.w + viewport.m12;
+        let y = viewport.m5 * clip.y / clip.w + viewport.m13;
+        (min_x, max_x, min_y, max_y) = (min_x.min(x), max_x.max(x), min_y.min(y), max_y.max(y));
+    }
+    let min_x = (min_x.floor() as i32).max(0);
+    let max_x = (max_x.ceil() as i32).min(depth_view.width - 1);
+    let min_y = (min_y.floor() as i32).max(0);
+    let max_y = (max_y.ceil() as i32).min(depth_view.height - 1);
+    (min_x <= max_x && min_y <= max_y).then_some((min_x, max_x, min_y, max_y))
+}
+
+/// Point of the radius 0.5 sphere (model space) hit by the ray from `origin` along the unit
+/// vector `direction`. From inside the sphere it is the inner face, as with the mesh
+pub fn intersect(origin: Vector3, direction: Vector3) -> Option<Vector3> {
+    // |origin + t·direction| = r, con direction unitaria
+    let b = origin.dot(direction);
+    let c = origin.dot(origin) - SPHERE_RADIUS * SPHERE_RADIUS;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    let t = if -b - root >= 0.0 { -b - root } else { -b + root };
+    (t >= 0.0).then(|| origin + direction * t)
+}
+
+// Rayo del píxel en el espacio del modelo, del plano cercano al lejano, con dirección unitaria
+pub(crate) fn pixel_ray(object_from_clip: &Matrix, uniforms: &Uniforms, p_x: f32, p_y: f32) -> (Vector3, Vector3) {
+    let viewport = &uniforms.viewport_matrix;
+    let ndc_x = (p_x - viewport.m12) / viewport.m0;
+    let ndc_y = (p_y - viewport.m13) / viewport.m5;
+    let unproject = |ndc_z: f32| {
+        let point = multiply_matrix_vector4(object_from_clip, &Vector4::new(ndc_x, ndc_y, ndc_z, 1.0));
+        Vector3::new(point.x, point.y, point.z) / point.w
+    };
+    let origin = unproject(-1.0);
+    (origin, (unproject(1.0) - origin).normalized())
+}
+
+// Fragmento del punto `hit` con la normal `normal` (unitaria, en el espacio del modelo; en la
+// esfera es la dirección desde el centro). La tangente y la bitangente siguen las UV de las
+// esferas (+U alrededor del eje Y, +V de polo norte a polo sur)
+pub(crate) fn fragment(hit: Vector3, normal: Vector3, p_x: f32, p_y: f32, depth: f32, uniforms: &Uniforms, material: &Vertex) -> Fragment {
+    let tangent = Vector3::new(-normal.z, 0.0, normal.x);
+    let tangent = if tangent.length() > 1e-6 { tangent.normalized() } else { Vector3::new(1.0, 0.0, 0.0) };
+    let bitangent = cross(normal, tangent);
+    let transform = |matrix: &Matrix, v: Vector3, w: f32| {
+        let v = multiply_matrix_vector4(matrix, &Vector4::new(v.x, v.y, v.z, w));
+        Vector3::new(v.x, v.y, v.z)
+    };
+
+    let mut fragment = Fragment::new(p_x, p_y, BASE_COLOR, depth, transform(&uniforms.model_matrix, hit, 1.0));
+    fragment.object_position = hit;
+    fragment.normal = transform(&uniforms.normal_matrix, normal, 0.0).normalized();
+    fragment.tangent = transform(&uniforms.model_matrix, tangent, 0.0).normalized();
+    fragment.bitangent = transform(&uniforms.model_matrix, bitangent, 0.0).normalized();
+    fragment.diffuse = material.color;
+    fragment.specular = material.specular;
+    fragment.shininess = material.shininess;
+    fragment.emissive = material.emissive;
+    fragment.material_id = material.material_id;
+    fragment
+}
@@ -0,0 +1,352 @@
+// renderer.rs
+// Punto de entrada de la biblioteca: reúne el framebuffer, el registro de shaders, el lote de
+// triángulos y el mapa de sombras, y dibuja una escena (modelos y luces) vista desde una cámara.
+// Descarta los modelos fuera del frustum, dibuja los muy lejanos como discos (sprite.rs), los de
+// media distancia como impostores (impostor.rs), las esferas marcadas trazadas por rayos
+// (raytrace.rs) y el resto con el nivel de detalle de su malla que toca por su tamaño en
+// pantalla (simplify.rs), prepara las sombras según los parámetros y hace la pasada única del
+// lote (batch.rs). Los anillos de las esferas trazadas se mezclan al final, sobre lo opaco
+use crate::batch::RenderBatch;
+use crate::bounds::BoundingSphere;
+use crate::framebuffer::Framebuffer;
+use crate::frustum::Frustum;
+use crate::impostor::{Capture, ImpostorCache};
+use crate::light::Light;
+use crate::lighting::Surface;
+use crate::matrix::{DepthMode, create_viewport_matrix, normal_matrix};
+use crate::mesh::Mesh;
+use crate::raytrace::{self, Rings};
+use crate::sdf::Sdf;
+use crate::shader_params::{ShaderParams, ShadowMode};
+use crate::shader_registry::ShaderRegistry;
+use crate::shadow::{ShadowMap, Shadows};
+use crate::sprite::{PointSprite, SPRITE_RADIUS, project_bounds};
+use crate::stats::PipelineStats;
+use crate::uniforms::Uniforms;
+use raylib::prelude::*;
+
+// Cuánto se agranda la esfera envolvente de los modelos animados y de los que emiten geometría
+const ANIMATION_BOUNDS_MARGIN: f32 = 1.1;
+const GEOMETRY_BOUNDS_MARGIN: f32 = 1.5;
+
+/// A mesh placed in the world and the fragment shader it is drawn with
+pub struct Model<'a> {
+    pub mesh: &'a Mesh,
+    pub model_matrix: Matrix,
+    /// Name of the shader in the renderer's registry; unknown names use the default shader
+    pub shader: &'a str,
+    /// Name of the model in the pixel inspector
+    pub label: &'a str,
+    pub surface: Surface,
+    /// Height of the relief raised by the noise of its shader, in mesh units (0 = smooth)
+    pub displacement: f32,
+    /// Name of the vertex animation in the renderer's registry; empty for a rigid model
+    pub vertex_animation: &'a str,
+    /// Name of the geometry stage in the renderer's registry; empty to draw only the mesh
+    pub geometry_shader: &'a str,
+    /// Shader and surface of the submeshes with these names; the rest use `shader` and `surface`
+    pub parts: &'a [Part<'a>],
+    /// Draw it as the exact radius 0.5 sphere its mesh approximates (the planets), ray-traced per
+    /// pixel; ignored with relief, a vertex animation or a geometry stage
+    pub ray_traced: bool,
+    /// Rings around its equator, drawn only while it is ray-traced
+    pub rings: Option<Rings>,
+    /// Raymarch this shape instead of drawing the mesh (it has to fit in the radius 0.5 sphere)
+    pub sdf: Option<Sdf>,
+}
+
+/// Shader and surface for the submesh named `name` (an OBJ object or group) of a model
+#[derive(Clone, Copy)]
+pub struct Part<'a> {
+    pub name: &'a str,
+    pub shader: &'a str,
+    pub surface: Surface,
+}
+
+/// What to draw in a frame: the models and the lights that reach them
+#[derive(Default)]
+pub struct Scene<'a> {
+    pub models: Vec<Model<'a>>,
+    pub lights: Vec<Light>,
+}
+
+/// Where a frame is seen from, and the time the shaders animate with
+pub struct View {
+    pub view_matrix: Matrix,
+    pub projection_matrix: Matrix,
+    pub depth_mode: DepthMode,
+    pub eye: Vector3,
+    pub time: f32,
+    pub dt: f32,
+}
+
+/// The software pipeline: draws scenes into its framebuffer
+pub struct Renderer {
+    pub framebuffer: Framebuffer,
+    /// Shaders the models can name; starts with the built-in ones
+    pub shaders: ShaderRegistry,
+    /// Draw the edges of the visible triangles on top of the shaded result
+    pub wireframe: bool,
+    /// Draw mid-distance models as billboards of an image captured every few frames
+    pub impostors: bool,
+    /// Draw the simplified levels of detail of the meshes that have them when they are small on screen
+    pub lods: bool,
+    batch: RenderBatch,
+    shadow_map: ShadowMap, // Se reutiliza entre frames
+    occluders: Vec<BoundingSphere>,
+    sprites: Vec<PointSprite>,
+    rings: Vec<(Uniforms, Rings)>, // Anillos que se mezclan al final del frame
+    impostor_cache: ImpostorCache,
+}
+
+impl Renderer {
+    /// Renderer with a `width` x `height` framebuffer and the built-in shaders
+    pub fn new(width: i32, height: i32) -> Self {
+        Renderer {
+            framebuffer: Framebuffer::new(width, height),
+            shaders: ShaderRegistry::new(),
+            wireframe: false,
+            impostors: false,
+            lods: true,
+            batch: RenderBatch::new(),
+            shadow_map: ShadowMap::new(),
+            occluders: Vec::new(),
+            sprites: Vec::new(),
+            rings: Vec::new(),
+            impostor_cache: ImpostorCache::new(),
+        }
+    }
+
+    /// Replaces the framebuffer with one of the new size, keeping its background color and
+    /// whether it records pixel sources
+    pub fn resize(&mut self, width: i32, height: i32) {
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer.set_background_color(self.framebuffer.background_color());
+        framebuffer.record_sources(self.framebuffer.is_recording_sources());
+        self.framebuffer = framebuffer;
+    }
+
+    /// Draws `scene` as seen from `view` on top of what the framebuffer holds (clear it first
+    /// to start a new frame). Models outside the view are skipped, although with the shadow
+    /// map they still cast shadows into it, and those only a few pixels across are drawn as
+    /// point sprites (or, with `impostors`, a bit bigger ones as billboards). Returns what each
+    /// stage of the pipeline did
+    pub fn draw_frame(&mut self, scene: &Scene, view: &View, params: &ShaderParams) -> PipelineStats {
+        let viewport_matrix = create_viewport_matrix(0.0, 0.0, self.framebuffer.width as f32, self.framebuffer.height as f32);
+        let frustum = Frustum::from_matrices(&view.view_matrix, &view.projection_matrix);
+        let shadow_mode = params.lighting.shadow_mode;
+
+        self.occluders.clear();
+        self.impostor_cache.begin_frame();
+        let mut culled = 0;
+        let mut impostors = Vec::new();
+        for model in &scene.models {
+            let uniforms = Uniforms {
+                model_matrix: model.model_matrix,
+                normal_matrix: normal_matrix(&model.model_matrix),
+                view_matrix: view.view_matrix,
+                projection_matrix: view.projection_matrix,
+                viewport_matrix,
+                depth_mode: view.depth_mode,
+                camera_position: view.eye,
+                time: view.time,
+                dt: view.dt,
+                displacement: model.displacement,
+                noise_frequency: params.noise_frequency(model.shader),
+                vertex_animation: self.shaders.vertex_animation(model.vertex_animation),
+                geometry_shader: self.shaders.geometry_shader(model.geometry_shader),
+            };
+
+            // Las estrellas no tapan la luz; el resto puede eclipsar a otros modelos. El relieve
+            // sale de la esfera de la malla como mucho su altura, y se deja un margen para lo
+            // que muevan las animaciones de vértices y lo que sobresalga la geometría emitida
+            let mut local_bounds = model.mesh.bounding_sphere;
+            local_bounds.radius += model.displacement;
+            if uniforms.vertex_animation.is_some() {
+                local_bounds.radius *= ANIMATION_BOUNDS_MARGIN;
+            }
+            if uniforms.geometry_shader.is_some() {
+                local_bounds.radius *= GEOMETRY_BOUNDS_MARGIN;
+            }
+            let bounds = local_bounds.transformed(&model.model_matrix);
+            if model.surface.emissive <= 0.0 {
+                self.occluders.push(bounds);
+            }
+
+            // Los anillos de los cuerpos trazados por rayos llegan más lejos que el cuerpo y se
+            // trazan aparte, transparentes, cuando ya está todo lo opaco
+            if model.ray_traced && let Some(rings) = model.rings {
+                let reach = BoundingSphere { center: bounds.center, radius: bounds.radius * rings.outer.max(1.0) };
+                if frustum.intersects_bounding_sphere(&reach) {
+                    self.rings.push((uniforms.clone(), rings));
+                }
+            }
+
+            // Saltar los modelos que quedan fuera de la cámara; con el mapa de sombras siguen proyectándolas
+            if !frustum.intersects_bounding_sphere(&bounds) {
+                culled += 1;
+                if shadow_mode == ShadowMode::Map {
+                    self.batch.submit_shadow_caster(uniforms, model);
+                }
+                continue;
+            }
+
+            // Tan lejos que ocuparía un par de píxeles: un disco de su color en lugar de la malla
+            if let Some(mut sprite) = PointSprite::project(&bounds, &uniforms, model.surface) {
+                sprite.shade(model.mesh, &uniforms, model.shader, &self.shaders, params);
+                self.sprites.push(sprite);
+                continue;
+            }
+
+            // A media distancia: la imagen de su última captura, que solo se repite cuando caduca.
+            // La captura rasteriza la malla, así que las formas SDF y las esferas trazadas se
+            // dibujan siempre por píxel
+            if self.impostors && model.sdf.is_none() && !model.ray_traced && let Some(placement) = ImpostorCache::place(&bounds, &uniforms, SPRITE_RADIUS) {
+                if shadow_mode == ShadowMode::Map {
+                    self.batch.submit_shadow_caster(uniforms.clone(), model);
+                }
+                let fresh = self.impostor_cache.is_fresh(model.label, &placement);
+                impostors.push((fresh, Capture { model, uniforms, placement }));
+                continue;
+            }
+
+            // Forma de distancia con signo marchada por píxel. No entra en el mapa de sombras (su
+            // malla es una esfera); en los eclipses y los rayos cuenta su esfera envolvente
+            if let Some(sdf) = model.sdf {
+                self.batch.submit_sdf(uniforms, model, sdf);
+                continue;
+            }
+
+            // Esfera exacta trazada por píxel; su malla sigue proyectando la sombra. El relieve, las
+            // animaciones y la geometría emitida cambian la forma, así que esos cuerpos usan la malla
+            if model.ray_traced && model.displacement <= 0.0 && uniforms.vertex_animation.is_none() && uniforms.geometry_shader.is_none() {
+                if shadow_mode == ShadowMode::Map {
+                    self.batch.submit_shadow_caster(uniforms.clone(), model);
+                }
+                self.batch.submit_sphere(uniforms, model);
+                continue;
+            }
+
+            // Cuanto más pequeño en pantalla, menos triángulos: el nivel de detalle que le toca
+            let mesh = match project_bounds(&bounds, &uniforms) {
+                Some((_, _, radius)) if self.lods => model.mesh.level(radius),
+                _ => model.mesh,
+            };
+            self.batch.submit(uniforms, &Model { mesh, ..*model });
+        }
+
+        // Sombras de la luz que las proyecta: con el mapa de sombras o con eclipses analíticos
+        // contra las esferas de los modelos; con rayos, las de todas las luces contra esas esferas
+        let shadow = match (scene.lights.iter().find(|light| light.casts_shadows), shadow_mode) {
+            (Some(light), ShadowMode::Map) => {
+                self.batch.render_shadows(&mut self.shadow_map, light.position, params.lighting.shadow_map_size);
+                Some(Shadows::Map(&self.shadow_map))
+            }
+            (Some(_), ShadowMode::Spheres) => Some(Shadows::Spheres(&self.occluders)),
+            (_, ShadowMode::Rays) => Some(Shadows::Rays(&self.occluders)),
+            _ => None,
+        };
+        self.batch.wireframe = self.wireframe;
+        let mut stats = self.batch.flush(&mut self.framebuffer, &scene.lights, shadow.as_ref(), &self.shaders, params);
+        stats.bodies_culled = culled;
+
+        // Los sprites van después del lote, con la prueba de profundidad contra lo ya dibujado
+        for sprite in &self.sprites {
+            stats.pixels_written += sprite.draw(&mut self.framebuffer, view.eye, &scene.lights, shadow.as_ref(), params);
+        }
+        stats.bodies_sprited = self.sprites.len() as u32;
+        self.sprites.clear();
+
+        // Los impostores caducados se capturan con las mismas luces y sombras antes de pegarse
+        for (fresh, capture) in &impostors {
+            if !fresh {
+                let capture_stats = self.impostor_cache.capture(capture, &scene.lights, shadow.as_ref(), &self.shaders, params);
+                stats += capture_stats;
+                stats.impostors_captured += 1;
+            }
+            stats.pixels_written += self.impostor_cache.draw(capture.model.label, &capture.placement, &mut self.framebuffer);
+        }
+        stats.bodies_impostors = impostors.len() as u32;
+        self.impostor_cache.end_frame();
+
+        // Anillos sobre todo lo opaco, con la sombra de su planeta (y de los demás cuerpos) por rayos
+        let ring_shadow = Shadows::Rays(&self.occluders);
+        for (uniforms, rings) in self.rings.drain(..) {
+            stats.pixels_written += raytrace::rings(&uniforms, &rings, &mut self.framebuffer, &scene.lights, &ring_shadow, params);
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::{FAR_PLANE, NEAR_PLANE};
+    use crate::matrix::{create_model_matrix, create_projection_matrix, create_view_matrix};
+    use crate::mesh::generate_icosphere;
+    use std::f32::consts::PI;
+
+    // Un frame de 160x90 con una esfera grande delante de la cámara, recién borrado
+    fn draw_sphere(mesh: &Mesh, params: &ShaderParams) -> PipelineStats {
+        let mut renderer = Renderer::new(160, 90);
+        renderer.lods = false;
+        let scene = Scene {
+            models: vec![Model {
+                mesh,
+                model_matrix: create_model_matrix(Vector3::zero(), 10.0, Vector3::zero()),
+                shader: "earth",
+                label: "earth",
+                surface: Surface::default(),
+                displacement: 0.0,
+                vertex_animation: "",
+                geometry_shader: "",
+                parts: &[],
+                ray_traced: false,
+                rings: None,
+                sdf: None,
+            }],
+            lights: vec![Light::new(Vector3::new(30.0, 10.0, 0.0))],
+        };
+        let eye = Vector3::new(0.0, 0.0, 20.0);
+        let view = View {
+            view_matrix: create_view_matrix(eye, Vector3::zero(), Vector3::new(0.0, 1.0, 0.0)),
+            projection_matrix: create_projection_matrix(PI / 3.0, 16.0 / 9.0, NEAR_PLANE, FAR_PLANE),
+            depth_mode: DepthMode::Projective,
+            eye,
+            time: 0.0,
+            dt: 0.0,
+        };
+        renderer.framebuffer.clear();
+        renderer.draw_frame(&scene, &view, params)
+    }
+
+    #[test]
+    fn pipeline_counters_add_up() {
+        let sphere = generate_icosphere(2);
+        assert!(sphere.back_faces_hidden);
+        let stats = draw_sphere(&sphere, &ShaderParams::default());
+
+        assert_eq!(stats.triangles_submitted as usize, sphere.indices.len() / 3);
+        // Casi la mitad de la esfera mira hacia el otro lado
+        assert!(stats.triangles_backface_culled > stats.triangles_submitted / 3);
+        assert!(stats.triangles_backface_culled + stats.triangles_clipped <= stats.triangles_submitted);
+        // Con el búfer de profundidad vacío nada se descarta antes de la resolución: cada píxel
+        // se queda con un fragmento y el resto son los que perdieron
+        assert!(stats.pixels_written > 0);
+        assert_eq!(stats.fragments_generated - stats.fragments_depth_rejected, stats.pixels_written);
+    }
+
+    #[test]
+    fn two_sided_lighting_keeps_the_back_faces() {
+        let sphere = generate_icosphere(2);
+        let mut params = ShaderParams::default();
+        params.lighting.two_sided = true;
+        let two_sided = draw_sphere(&sphere, &params);
+        let culled = draw_sphere(&sphere, &ShaderParams::default());
+
+        assert_eq!(two_sided.triangles_backface_culled, 0);
+        // Las caras traseras quedan detrás de las delanteras: se generan y se descartan por profundidad
+        assert!(two_sided.fragments_generated > culled.fragments_generated);
+        assert!(two_sided.fragments_depth_rejected > culled.fragments_depth_rejected);
+    }
+}
@@ -0,0 +1,583 @@
+// scene.rs
+use crate::lighting::Surface;
+use crate::raytrace::Rings;
+use crate::sdf::Sdf;
+use crate::shader_params::LightingQuality;
+use raylib::prelude::*;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+// Datos del sistema solar real empaquetados dentro del binario
+const SOLAR_SYSTEM_DATA: &str = include_str!("../data/solar_system.csv");
+
+/// File the Xerion system is loaded (and hot-reloaded) from
+pub const XERION_SCENE_PATH: &str = "scene.toml";
+// Copia de scene.toml dentro del binario, por si el archivo no está junto al programa o no se lee
+const XERION_SCENE_DATA: &str = include_str!("../scene.toml");
+
+// Escalas para convertir unidades reales a unidades de escena
+const EARTH_RADIUS_KM: f32 = 6371.0;
+const EARTH_SCALE: f32 = 0.3;            // Tamaño de la Tierra en la escena (los radios son proporcionales)
+const SUN_CLEARANCE: f32 = 4.0;          // Espacio libre sobre la superficie de la estrella central
+const DISTANCE_SCALE: f32 = 9.0;         // Unidades de escena por raíz de UA
+const MOON_DISTANCE_SCALE: f32 = 0.4;    // Unidades de escena por raíz de radios del planeta
+const EARTH_ORBIT_SPEED: f32 = 0.3;      // rad/s de la Tierra alrededor del Sol
+const EARTH_ROTATION_SPEED: f32 = 1.0;   // rad/s de la Tierra sobre su eje
+
+#[derive(Clone)]
+pub struct CelestialBody {
+    pub name: String,
+    pub translation: Vector3,
+    pub scale: f32,
+    pub rotation: Vector3,
+    pub orbit_radius: f32,
+    pub orbit_speed: f32,
+    pub rotation_speed: f32,
+    pub color: Color,
+    pub parent: Option<String>, // Cuerpo alrededor del cual orbita (None = centro del sistema)
+    pub shader: String,         // Nombre del fragment shader a usar
+    pub light: Option<StarLight>, // Luz que emite el cuerpo (estrellas)
+    pub surface: Surface,         // Respuesta a la iluminación (emisivo, ...)
+    pub displacement: f32,        // Relieve levantado por el ruido de su shader (0 = esfera lisa)
+    pub vertex_animation: String, // Animación de vértices por nombre (vacío = cuerpo rígido)
+    pub geometry_shader: String,  // Etapa de geometría por nombre (vacío = solo la malla)
+    pub ray_traced: bool,         // Se traza la esfera exacta por píxel en lugar de rasterizar la malla
+    pub rings: Option<Rings>,     // Anillos en su ecuador, solo visibles mientras se traza por rayos
+    pub sdf: Option<Sdf>,         // Forma marchada por píxel en lugar de la esfera (ver sdf.rs)
+    pub script: Option<String>,   // Código Rhai que se ejecuta cada frame (ver scripting.rs)
+}
+
+/// Light emitted by a body; it moves with the body and tints everything it reaches
+#[derive(Clone, Copy)]
+pub struct StarLight {
+    pub color: Vector3,
+    pub intensity: f32,
+    pub shadows: bool, // Proyecta sombras (mapa de sombras desde su posición)
+}
+
+/// Colors of the nebula painted behind a scene, from the thin edges of the clouds to
+/// their brightest cores, and how much of the sky it covers (0..1)
+#[derive(Clone, Copy, PartialEq)]
+pub struct NebulaPalette {
+    pub colors: [Vector3; 3],
+    pub density: f32,
+}
+
+impl NebulaPalette {
+    // Nubes rojizas y púrpuras, a juego con Voidheart
+    fn xerion() -> Self {
+        NebulaPalette { colors: [rgb8([70, 12, 60]), rgb8([170, 40, 70]), rgb8([255, 150, 120])], density: 0.5 }
+    }
+
+    // Nubes azuladas y tenues, para no competir con el Sol
+    fn solar() -> Self {
+        NebulaPalette { colors: [rgb8([10, 20, 50]), rgb8([40, 70, 130]), rgb8([180, 200, 255])], density: 0.35 }
+    }
+}
+
+/// Bodies of a scene and the backdrop painted behind them
+pub struct Scene {
+    pub bodies: Vec<CelestialBody>,
+    pub nebula: NebulaPalette,
+}
+
+/// Builds the scene selected by name ("xerion", "solar" or a path to a .toml file). Xerion is
+/// read from XERION_SCENE_PATH, or from the copy built into the program if that fails
+pub fn load_scene(name: &str) -> Result<Scene, String> {
+    let scene = match name {
+        "xerion" => load_scene_file(XERION_SCENE_PATH).or_else(|e| {
+            eprintln!("{}; using the built-in Xerion scene", e);
+            parse_scene(XERION_SCENE_DATA, Path::new(""))
+        })?,
+        "solar" => import_solar_system(SOLAR_SYSTEM_DATA).map(|bodies| Scene { bodies, nebula: NebulaPalette::solar() })?,
+        _ if name.ends_with(".toml") => load_scene_file(name)?,
+        _ => return Err(format!("Unknown scene '{}'", name)),
+    };
+    check_parents(&scene.bodies)?;
+    Ok(scene)
+}
+
+/// File a scene is read from, the one to watch for hot reload (None for the generated ones)
+pub fn scene_path(name: &str) -> Option<&str> {
+    match name {
+        "xerion" => Some(XERION_SCENE_PATH),
+        _ if name.ends_with(".toml") => Some(name),
+        _ => None,
+    }
+}
+
+/// Reads the scene of `path` again after an edit. Unlike load_scene it never falls back to the
+/// built-in copy, so a file that does not parse or has a parent cycle is an error and the
+/// caller keeps the scene it has
+pub fn reload_scene_file(path: &str) -> Result<Scene, String> {
+    let scene = load_scene_file(path)?;
+    check_parents(&scene.bodies)?;
+    Ok(scene)
+}
+
+/// Circular orbit around the parent body (an index into the same list), or the center of the
+/// system; a radius of zero leaves the body where it is
+#[derive(Clone, Copy, Default)]
+pub struct Orbit {
+    pub parent: Option<usize>,
+    pub radius: f32,
+    pub speed: f32,
+}
+
+/// Orbit of each body, in the same order (None for the bodies that do not orbit)
+pub fn orbits(bodies: &[CelestialBody]) -> Vec<Option<Orbit>> {
+    bodies.iter().enumerate().map(|(index, body)| (body.orbit_radius > 0.0).then(|| Orbit {
+        parent: body.parent.as_ref()
+            .and_then(|parent| bodies.iter().position(|b| &b.name == parent))
+            .filter(|&parent| parent != index),
+        radius: body.orbit_radius,
+        speed: body.orbit_speed,
+    })).collect()
+}
+
+/// Moves each body with an orbit to its place at `time`: `positions[i]` goes around the
+/// position of its parent (placed first) or the center; the others keep their position
+pub fn place_orbits(orbits: &[Option<Orbit>], positions: &mut [Vector3], time: f32) {
+    fn place(orbits: &[Option<Orbit>], positions: &mut [Vector3], placed: &mut [bool], index: usize, time: f32) {
+        if placed[index] {
+            return;
+        }
+        // Marcarlo antes de colocar al padre corta los ciclos de padres
+        placed[index] = true;
+        let Some(orbit) = orbits[index] else {
+            return;
+        };
+        if orbit.radius <= 0.0 {
+            return;
+        }
+        let center = match orbit.parent {
+            Some(parent) => {
+                place(orbits, positions, placed, parent, time);
+                positions[parent]
+            }
+            None => Vector3::zero(),
+        };
+        let angle = time * orbit.speed;
+        positions[index] = Vector3::new(
+            center.x + angle.cos() * orbit.radius,
+            center.y,
+            center.z + angle.sin() * orbit.radius,
+        );
+    }
+
+    let mut placed = vec![false; positions.len()];
+    for index in 0..positions.len() {
+        place(orbits, positions, &mut placed, index, time);
+    }
+}
+
+// Rechaza las escenas en las que siguiendo los padres de un cuerpo se vuelve a él (A orbita a B
+// y B a A): ningún cuerpo del ciclo tendría un centro alrededor del que orbitar
+fn check_parents(bodies: &[CelestialBody]) -> Result<(), String> {
+    let parent_of = |index: usize| bodies[index].parent.as_ref().and_then(|parent| bodies.iter().position(|b| &b.name == parent));
+    for start in 0..bodies.len() {
+        // Subir por los padres hasta el centro o hasta repetir un cuerpo
+        let mut visited = vec![start];
+        let mut current = start;
+        while let Some(parent) = parent_of(current) {
+            if let Some(first) = visited.iter().position(|&index| index == parent) {
+                let names: Vec<&str> = visited[first..].iter().chain([&parent]).map(|&index| bodies[index].name.as_str()).collect();
+                return Err(format!("Parent cycle: {}", names.join(" -> ")));
+            }
+            visited.push(parent);
+            current = parent;
+        }
+    }
+    Ok(())
+}
+
+// Color [r, g, b] de 0 a 255 a Vector3 de 0 a 1
+fn rgb8(color: [u8; 3]) -> Vector3 {
+    Vector3::new(color[0] as f32 / 255.0, color[1] as f32 / 255.0, color[2] as f32 / 255.0)
+}
+
+// Formato de scene.toml: una tabla [[body]] por cuerpo celeste y una [nebula] opcional
+#[derive(Deserialize)]
+struct SceneFile {
+    #[serde(rename = "body")]
+    bodies: Vec<BodyConfig>,
+    #[serde(default)]
+    nebula: Option<NebulaConfig>,
+}
+
+// [nebula] colors = [[r, g, b], [r, g, b], [r, g, b]], density = 0.5
+#[derive(Deserialize)]
+struct NebulaConfig {
+    colors: [[u8; 3]; 3],
+    #[serde(default = "default_nebula_density")]
+    density: f32,
+}
+
+fn default_nebula_density() -> f32 {
+    NebulaPalette::xerion().density
+}
+
+#[derive(Deserialize)]
+struct BodyConfig {
+    name: String,
+    #[serde(default)]
+    translation: [f32; 3],
+    scale: f32,
+    #[serde(default)]
+    rotation: [f32; 3],
+    #[serde(default)]
+    orbit_radius: f32,
+    #[serde(default)]
+    orbit_speed: f32,
+    #[serde(default)]
+    rotation_speed: f32,
+    color: [u8; 3],
+    #[serde(default)]
+    parent: Option<String>,
+    #[serde(default)]
+    shader: String, // Vacío = shader por defecto
+    #[serde(default)]
+    light: Option<LightConfig>,
+    #[serde(default)]
+    emissive: f32,
+    #[serde(default)]
+    specular: f32,
+    #[serde(default = "default_shininess")]
+    shininess: f32,
+    #[serde(default)]
+    metallic: f32,
+    #[serde(default)]
+    lighting: Option<LightingQuality>,
+    #[serde(default)]
+    displacement: f32,
+    #[serde(default)]
+    vertex_animation: String, // Vacío = cuerpo rígido
+    #[serde(default)]
+    geometry_shader: String, // Vacío = solo la malla
+    #[serde(default)]
+    ray_traced: bool,
+    #[serde(default)]
+    rings: Option<RingsConfig>,
+    #[serde(default)]
+    sdf: Option<Sdf>,
+    #[serde(default)]
+    script: Option<String>, // Código Rhai o ruta a un archivo .rhai (relativa a la escena)
+}
+
+fn default_shininess() -> f32 {
+    Surface::default().shininess
+}
+
+// light = { color = [r, g, b], intensity = 1.0 }
+#[derive(Deserialize)]
+struct LightConfig {
+    color: [u8; 3],
+    #[serde(default = "default_light_intensity")]
+    intensity: f32,
+    #[serde(default)]
+    shadows: bool,
+}
+
+fn default_light_intensity() -> f32 {
+    1.0
+}
+
+// rings = { inner = 1.3, outer = 2.0, color = [r, g, b], opacity = 0.8 }, radios en radios del cuerpo
+#[derive(Deserialize)]
+struct RingsConfig {
+    inner: f32,
+    outer: f32,
+    color: [u8; 3],
+    #[serde(default = "default_ring_opacity")]
+    opacity: f32,
+}
+
+fn default_ring_opacity() -> f32 {
+    0.8
+}
+
+/// Loads a scene from a TOML file with one `[[body]]` table per celestial body and an
+/// optional `[nebula]` table (the Xerion nebula if missing)
+pub fn load_scene_file(path: impl AsRef<Path>) -> Result<Scene, String> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    parse_scene(&text, path.parent().unwrap_or(Path::new(""))).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Escena a partir del texto de un archivo de escena; los scripts en archivo aparte se leen
+// ahora, relativos a `folder`
+fn parse_scene(text: &str, folder: &Path) -> Result<Scene, String> {
+    let file: SceneFile = toml::from_str(text).map_err(|e| e.to_string())?;
+    let mut bodies = Vec::with_capacity(file.bodies.len());
+    for b in file.bodies {
+        let script = match b.script {
+            Some(script) if script.ends_with(".rhai") => {
+                let script_path = folder.join(&script);
+                Some(fs::read_to_string(&script_path).map_err(|e| format!("{}: {}", script_path.display(), e))?)
+            }
+            script => script,
+        };
+        bodies.push(CelestialBody {
+            name: b.name,
+            translation: Vector3::new(b.translation[0], b.translation[1], b.translation[2]),
+            scale: b.scale,
+            rotation: Vector3::new(b.rotation[0], b.rotation[1], b.rotation[2]),
+            orbit_radius: b.orbit_radius,
+            orbit_speed: b.orbit_speed,
+            rotation_speed: b.rotation_speed,
+            color: Color::new(b.color[0], b.color[1], b.color[2], 255),
+            parent: b.parent,
+            shader: b.shader,
+            light: b.light.map(|light| StarLight {
+                color: rgb8(light.color),
+                intensity: light.intensity,
+                shadows: light.shadows,
+            }),
+            surface: Surface {
+                emissive: b.emissive,
+                specular: b.specular,
+                shininess: b.shininess,
+                metallic: b.metallic,
+                lighting: b.lighting,
+            },
+            displacement: b.displacement,
+            vertex_animation: b.vertex_animation,
+            geometry_shader: b.geometry_shader,
+            ray_traced: b.ray_traced,
+            rings: b.rings.map(|rings| Rings {
+                inner: rings.inner,
+                outer: rings.outer,
+                color: rgb8(rings.color),
+                opacity: rings.opacity,
+            }),
+            sdf: b.sdf,
+            script,
+        });
+    }
+    let nebula = file.nebula.map_or_else(NebulaPalette::xerion, |nebula| NebulaPalette {
+        colors: nebula.colors.map(rgb8),
+        density: nebula.density,
+    });
+
+    Ok(Scene { bodies, nebula })
+}
+
+// Fila del dataset del sistema solar, en unidades reales
+struct SolarRecord {
+    name: String,
+    parent: Option<String>,
+    radius_km: f32,
+    semi_major_axis: f32, // UA (planetas) o km (lunas)
+    orbital_period_days: f32,
+    rotation_period_hours: f32,
+    axial_tilt_deg: f32,
+    shader: String,
+    color: Color,
+}
+
+fn parse_solar_record(line: &str) -> Result<SolarRecord, String> {
+    let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+    if fields.len() != 11 {
+        return Err(format!("expected 11 fields, found {}", fields.len()));
+    }
+
+    let number = |i: usize| -> Result<f32, String> {
+        fields[i].parse::<f32>().map_err(|e| format!("field {} ('{}'): {}", i, fields[i], e))
+    };
+    let channel = |i: usize| -> Result<u8, String> {
+        fields[i].parse::<u8>().map_err(|e| format!("field {} ('{}'): {}", i, fields[i], e))
+    };
+
+    Ok(SolarRecord {
+        name: fields[0].to_string(),
+        parent: if fields[1].is_empty() { None } else { Some(fields[1].to_string()) },
+        radius_km: number(2)?,
+        semi_major_axis: number(3)?,
+        orbital_period_days: number(4)?,
+        rotation_period_hours: number(5)?,
+        axial_tilt_deg: number(6)?,
+        shader: fields[7].to_string(),
+        color: Color::new(channel(8)?, channel(9)?, channel(10)?, 255),
+    })
+}
+
+// Escala (diámetro de la esfera de radio 0.5) de un cuerpo real, proporcional a su radio
+fn solar_scale(record: &SolarRecord) -> f32 {
+    EARTH_SCALE * record.radius_km / EARTH_RADIUS_KM
+}
+
+/// Imports the real solar system from CSV data, converting real radii, distances
+/// and periods into scene units
+pub fn import_solar_system(data: &str) -> Result<Vec<CelestialBody>, String> {
+    let mut records = Vec::new();
+    for (line_number, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let record = parse_solar_record(line)
+            .map_err(|e| format!("solar_system.csv line {}: {}", line_number + 1, e))?;
+        records.push(record);
+    }
+
+    let mut bodies: Vec<CelestialBody> = Vec::with_capacity(records.len());
+    for record in &records {
+        // Radios reales en proporción: solo se comprimen las distancias
+        let scale = solar_scale(record);
+
+        let orbit_radius = match &record.parent {
+            None => 0.0,
+            Some(parent_name) => {
+                let parent = records.iter().find(|r| &r.name == parent_name)
+                    .ok_or_else(|| format!("{}: unknown parent '{}'", record.name, parent_name))?;
+                if parent.parent.is_none() {
+                    // Planeta: distancia en UA alrededor de la estrella central, fuera de su superficie
+                    solar_scale(parent) * 0.5 + SUN_CLEARANCE + DISTANCE_SCALE * record.semi_major_axis.sqrt()
+                } else {
+                    // Luna: distancia en radios del planeta, fuera de su superficie
+                    solar_scale(parent) + MOON_DISTANCE_SCALE * (record.semi_major_axis / parent.radius_km).sqrt()
+                }
+            }
+        };
+
+        // Periodos reales relativos a la Tierra (año = 365.25 días, día = 24 horas)
+        let orbit_speed = if record.orbital_period_days > 0.0 {
+            EARTH_ORBIT_SPEED * 365.25 / record.orbital_period_days
+        } else {
+            0.0
+        };
+        let rotation_speed = if record.rotation_period_hours != 0.0 {
+            EARTH_ROTATION_SPEED * 24.0 / record.rotation_period_hours
+        } else {
+            0.0
+        };
+
+        bodies.push(CelestialBody {
+            name: record.name.clone(),
+            translation: Vector3::new(0.0, 0.0, 0.0),
+            scale,
+            // La inclinación axial se aplica sobre Z, después del giro en Y
+            rotation: Vector3::new(0.0, 0.0, record.axial_tilt_deg.to_radians()),
+            orbit_radius,
+            orbit_speed,
+            rotation_speed,
+            color: record.color,
+            // Los planetas orbitan el centro del sistema, donde está el Sol
+            parent: match &record.parent {
+                Some(parent_name) if records.iter().any(|r| &r.name == parent_name && r.parent.is_some()) => {
+                    Some(parent_name.clone())
+                }
+                _ => None,
+            },
+            shader: record.shader.clone(),
+            // La estrella central ilumina el sistema
+            light: record.parent.is_none().then_some(StarLight { color: Vector3::new(1.0, 0.95, 0.85), intensity: 1.0, shadows: true }),
+            surface: Surface::emissive(if record.parent.is_none() { 1.0 } else { 0.0 }),
+            displacement: 0.0,
+            vertex_animation: String::new(),
+            geometry_shader: String::new(),
+            ray_traced: false,
+            rings: None,
+            sdf: None,
+            script: None,
+        });
+    }
+
+    Ok(bodies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body<'a>(bodies: &'a [CelestialBody], name: &str) -> &'a CelestialBody {
+        bodies.iter().find(|body| body.name == name).unwrap()
+    }
+
+    // Escribe `text` como archivo de escena temporal y devuelve su ruta
+    fn scene_file(name: &str, text: &str) -> String {
+        let path = std::env::temp_dir().join(format!("space_travel_{}_{}.toml", name, std::process::id()));
+        fs::write(&path, text).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    const TWO_BODIES: &str = "[[body]]\nname = \"Star\"\nscale = 2.0\ncolor = [255, 220, 120]\n\n\
+        [[body]]\nname = \"Rock\"\nscale = 0.5\ncolor = [120, 120, 120]\nparent = \"Star\"\norbit_radius = 4.0\n";
+
+    #[test]
+    fn reload_reads_the_edited_file() {
+        let path = scene_file("reload_valid", TWO_BODIES);
+        let scene = reload_scene_file(&path).unwrap();
+        fs::write(&path, TWO_BODIES.replace("orbit_radius = 4.0", "orbit_radius = 6.0")).unwrap();
+        let edited = reload_scene_file(&path).unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(scene.bodies[1].orbit_radius, 4.0);
+        assert_eq!(edited.bodies[1].orbit_radius, 6.0);
+    }
+
+    #[test]
+    fn reload_rejects_cyclic_and_invalid_edits() {
+        // Con un error el llamador se queda con la escena que tenía: no se cae a la copia interna
+        let path = scene_file("reload_invalid", TWO_BODIES);
+        let cyclic = TWO_BODIES.replacen("scale = 2.0", "scale = 2.0\nparent = \"Rock\"", 1);
+        fs::write(&path, &cyclic).unwrap();
+        let cycle = reload_scene_file(&path);
+        fs::write(&path, "[[body]]\nname = \"Broken\"\n").unwrap();
+        let missing_fields = reload_scene_file(&path);
+        fs::remove_file(&path).ok();
+        assert!(cycle.is_err_and(|e| e.contains("Parent cycle")));
+        assert!(missing_fields.is_err());
+    }
+
+    #[test]
+    fn showcase_scene_has_every_sdf_shape() {
+        let scene = load_scene("scenes/showcase.toml").unwrap();
+        let shapes: Vec<&str> = scene.bodies.iter().filter_map(|body| match body.sdf? {
+            Sdf::Torus { .. } => Some("torus"),
+            Sdf::Asteroid { .. } => Some("asteroid"),
+            Sdf::Horizon { .. } => Some("horizon"),
+        }).collect();
+        assert_eq!(shapes, ["horizon", "torus", "asteroid"]);
+    }
+
+    #[test]
+    fn showcase_rings_are_on_a_ray_traced_body() {
+        let scene = load_scene("scenes/showcase.toml").unwrap();
+        let ringed: Vec<&CelestialBody> = scene.bodies.iter().filter(|body| body.rings.is_some()).collect();
+        assert!(!ringed.is_empty());
+        assert!(ringed.iter().all(|body| body.ray_traced));
+    }
+
+    #[test]
+    fn solar_system_imports_every_body() {
+        let bodies = import_solar_system(SOLAR_SYSTEM_DATA).unwrap();
+        assert_eq!(bodies.len(), 10);
+        check_parents(&bodies).unwrap();
+    }
+
+    #[test]
+    fn solar_system_planets_orbit_the_center_and_the_moon_the_earth() {
+        let bodies = import_solar_system(SOLAR_SYSTEM_DATA).unwrap();
+        assert_eq!(body(&bodies, "Moon").parent.as_deref(), Some("Earth"));
+        for planet in ["Mercury", "Earth", "Jupiter", "Neptune"] {
+            assert_eq!(body(&bodies, planet).parent, None, "{}", planet);
+        }
+        assert!(body(&bodies, "Sun").light.is_some());
+    }
+
+    #[test]
+    fn solar_system_keeps_real_relative_radii() {
+        let bodies = import_solar_system(SOLAR_SYSTEM_DATA).unwrap();
+        let ratio = body(&bodies, "Jupiter").scale / body(&bodies, "Earth").scale;
+        assert!((ratio - 69911.0 / 6371.0).abs() < 1e-3, "ratio {}", ratio);
+    }
+
+    #[test]
+    fn solar_system_orbits_clear_the_sun() {
+        let bodies = import_solar_system(SOLAR_SYSTEM_DATA).unwrap();
+        let sun_radius = body(&bodies, "Sun").scale * 0.5;
+        let mercury = body(&bodies, "Mercury");
+        assert!(mercury.orbit_radius - mercury.scale * 0.5 > sun_radius);
+    }
+}
@@ -0,0 +1,144 @@
+// scripting.rs
+// Comportamiento de los cuerpos con scripts Rhai (campo `script` de la escena). Cada frame el
+// script de un cuerpo ve sus valores como variables (scale, orbit_radius, orbit_speed,
+// rotation_speed), los valores con que se cargó (base_scale, ...), time y dt, y puede
+// cambiarlos. Los valores que deban durar entre frames se guardan en el mapa `state`.
+// Funciones disponibles además de las de Rhai: noise(x) (ruido suave en 0..1) y emit(nombre),
+// que publica un evento con ese nombre en el bus. Los eventos del frame anterior llegan en el
+// arreglo `events`, como mapas #{ name, body } (p. ej. "collision", "warp_started" o lo que
+// emitió otro script)
+use crate::events::{Event, EventBus, Observer};
+use crate::world::{Entity, World};
+use space_travel::noise::value_noise;
+use rhai::{AST, Array, Dynamic, Engine, Map, Scope};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Operaciones máximas por ejecución: un bucle infinito en un script no congela el programa
+const MAX_OPERATIONS: u64 = 100_000;
+
+// Valores del cuerpo que el script puede leer y cambiar
+const FIELDS: [&str; 4] = ["scale", "orbit_radius", "orbit_speed", "rotation_speed"];
+
+struct BodyScript {
+    body: Entity,
+    ast: AST,
+    scope: Scope<'static>,
+    variables: usize, // Variables propias del scope; lo que el script declara se descarta tras cada frame
+}
+
+/// Compiled scripts of the bodies of a scene
+pub struct BodyScripts {
+    engine: Engine,
+    scripts: Vec<BodyScript>,
+    emitted: Rc<RefCell<Vec<String>>>,
+    received: Array, // Eventos del bus desde la última ejecución
+}
+
+impl BodyScripts {
+    /// Compiles the scripts of the entities; one that fails to compile is reported and skipped
+    pub fn new(world: &World) -> Self {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.register_fn("noise", |x: f64| value_noise(x as f32, 0.0, 0.0) as f64);
+        let emitted = Rc::new(RefCell::new(Vec::new()));
+        let queue = emitted.clone();
+        engine.register_fn("emit", move |name: &str| queue.borrow_mut().push(name.to_string()));
+
+        let mut scripts = Vec::new();
+        for (entity, script) in world.scripts.iter().enumerate() {
+            let Some(source) = script else {
+                continue;
+            };
+            let ast = match engine.compile(source) {
+                Ok(ast) => ast,
+                Err(e) => {
+                    eprintln!("Script of '{}' disabled: {}", world.names[entity], e);
+                    continue;
+                }
+            };
+            let mut scope = Scope::new();
+            for (field, value) in FIELDS.iter().zip(field_values(world, entity)) {
+                scope.push(*field, value as f64);
+                scope.push_constant(format!("base_{}", field), value as f64);
+            }
+            scope.push("time", 0.0_f64);
+            scope.push("dt", 0.0_f64);
+            scope.push("state", Map::new());
+            scope.push("events", Array::new());
+            let variables = scope.len();
+            scripts.push(BodyScript { body: entity, ast, scope, variables });
+        }
+        BodyScripts { engine, scripts, emitted, received: Array::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    /// Runs every script once and writes back the values they changed. A script that fails
+    /// at runtime is reported and removed. What the scripts emit is published on `events`
+    pub fn update(&mut self, world: &mut World, time: f32, dt: f32, events: &mut EventBus) {
+        let engine = &self.engine;
+        let received = std::mem::take(&mut self.received);
+        self.scripts.retain_mut(|script| {
+            let entity = script.body;
+            if entity >= world.transforms.len() {
+                return false;
+            }
+            let current = field_values(world, entity);
+            for (field, value) in FIELDS.iter().zip(current) {
+                script.scope.set_value(*field, value as f64);
+            }
+            script.scope.set_value("time", time as f64);
+            script.scope.set_value("dt", dt as f64);
+            script.scope.set_value("events", received.clone());
+
+            let result = engine.run_ast_with_scope(&mut script.scope, &script.ast);
+            let values = FIELDS.map(|field| script.scope.get(field).and_then(number));
+            script.scope.rewind(script.variables);
+            for name in self.emitted.borrow_mut().drain(..) {
+                events.publish(Event::Script { body: entity, name });
+            }
+            if let Err(e) = result {
+                eprintln!("Script of '{}' stopped: {}", world.names[entity], e);
+                return false;
+            }
+
+            let [scale, orbit_radius, orbit_speed, rotation_speed] = values;
+            let transform = &mut world.transforms[entity];
+            transform.scale = scale.unwrap_or(transform.scale).max(0.0);
+            transform.spin = rotation_speed.unwrap_or(transform.spin);
+            // La órbita solo se crea si el script le da valores a un cuerpo que no orbitaba
+            let (radius, speed) = (orbit_radius.unwrap_or(current[1]), orbit_speed.unwrap_or(current[2]));
+            if (radius, speed) != (current[1], current[2]) {
+                let orbit = world.orbit_mut(entity);
+                orbit.radius = radius;
+                orbit.speed = speed;
+            }
+            true
+        });
+    }
+}
+
+// Los eventos se guardan para la siguiente ejecución de los scripts
+impl Observer for BodyScripts {
+    fn on_event(&mut self, event: &Event, world: &World) {
+        let mut map = Map::new();
+        map.insert("name".into(), event.name().into());
+        map.insert("body".into(), world.names.get(event.body()).cloned().unwrap_or_default().into());
+        self.received.push(map.into());
+    }
+}
+
+// Valores actuales de la entidad en el orden de FIELDS (una sin órbita tiene radio y velocidad 0)
+fn field_values(world: &World, entity: Entity) -> [f32; 4] {
+    let transform = &world.transforms[entity];
+    let orbit = world.orbits[entity].unwrap_or_default();
+    [transform.scale, orbit.radius, orbit.speed, transform.spin]
+}
+
+// Número de una variable del script, sea entero o decimal
+fn number(value: &Dynamic) -> Option<f32> {
+    value.as_float().ok().or_else(|| value.as_int().ok().map(|int| int as f64)).map(|float| float as f32)
+}
@@ -0,0 +1,146 @@
+// sdf.rs
+// Cuerpos de distancia con signo: formas que no son esferas (una megaestructura alienígena en
+// forma de toro, un asteroide irregular, el horizonte deformado de Voidheart) descritas por una
+// función que da la distancia a su superficie. Se dibujan marchando un rayo por píxel dentro de
+// la esfera de radio 0.5 de la malla del cuerpo (lo que salga de ella se corta): cada paso avanza
+// la distancia a la superficie, que nunca la atraviesa. El impacto se convierte en un fragmento
+// como los de las esferas trazadas (raytrace.rs), así que pasa por los mismos fragment shaders, la
+// misma luz y la misma visibilidad. Se eligen en la escena con `sdf = { shape = "...", ... }`
+use crate::fragment::Fragment;
+use crate::framebuffer::DepthView;
+use crate::noise::value_noise;
+use crate::raytrace::{SPHERE_RADIUS, trace};
+use crate::stats::PipelineStats;
+use crate::uniforms::Uniforms;
+use crate::vertex::Vertex;
+use raylib::prelude::*;
+use serde::Deserialize;
+
+// Pasos como mucho por rayo, distancia a la que se da por tocada la superficie y desplazamiento
+// de las diferencias con las que se calcula la normal
+const MAX_STEPS: usize = 128;
+const HIT_DISTANCE: f32 = 2e-4;
+const NORMAL_OFFSET: f32 = 1e-3;
+
+/// Shape of a raymarched body in model space, where its mesh is the radius 0.5 sphere
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(tag = "shape", rename_all = "lowercase")]
+pub enum Sdf {
+    /// Ring around the Y axis: `major` from the center to the middle of the tube, which has
+    /// radius `minor`
+    Torus { major: f32, minor: f32 },
+    /// Sphere of `radius` with its surface raised and sunk by up to `lumpiness` with noise of
+    /// `frequency`
+    Asteroid { radius: f32, lumpiness: f32, frequency: f32 },
+    /// Sphere of `radius` whose surface ripples by up to `amplitude`, with noise of `frequency`
+    /// that flows at `speed`
+    Horizon { radius: f32, amplitude: f32, frequency: f32, speed: f32 },
+}
+
+impl Sdf {
+    /// Distance from `p` (model space) to the surface at `time`, negative inside. For the noisy
+    /// shapes it is only an estimate, so the march takes shorter steps on them
+    pub fn distance(&self, p: Vector3, time: f32) -> f32 {
+        match *self {
+            Sdf::Torus { major, minor } => {
+                let ring = (p.x * p.x + p.z * p.z).sqrt() - major;
+                (ring * ring + p.y * p.y).sqrt() - minor
+            }
+            Sdf::Asteroid { radius, lumpiness, frequency } => {
+                // Dos octavas: bultos grandes y rugosidad encima
+                let q = p * frequency;
+                let lumps = value_noise(q.x + 11.0, q.y + 23.0, q.z + 5.0) * 0.7
+                    + value_noise(q.x * 3.0 + 7.0, q.y * 3.0 + 2.0, q.z * 3.0 + 19.0) * 0.3;
+                p.length() - radius - lumpiness * (lumps * 2.0 - 1.0)
+            }
+            Sdf::Horizon { radius, amplitude, frequency, speed } => {
+                let q = p * frequency;
+                let flow = time * speed;
+                let warp = value_noise(q.x + flow, q.y - flow * 0.7, q.z + 3.0);
+                p.length() - radius - amplitude * (warp * 2.0 - 1.0)
+            }
+        }
+    }
+
+    // Cuánto más rápido que la distancia real puede cambiar la del ruido: los pasos se dividen
+    // por esto para no atravesar la superficie
+    fn step_scale(&self) -> f32 {
+        match *self {
+            Sdf::Torus { .. } => 1.0,
+            Sdf::Asteroid { lumpiness, frequency, .. } => 1.0 + 5.5 * lumpiness * frequency,
+            Sdf::Horizon { amplitude, frequency, .. } => 1.0 + 3.5 * amplitude * frequency,
+        }
+    }
+
+    // Normal (unitaria) en `p`: el gradiente de la distancia por diferencias centradas
+    fn normal(&self, p: Vector3, time: f32) -> Vector3 {
+        let difference = |axis: Vector3| self.distance(p + axis * NORMAL_OFFSET, time) - self.distance(p - axis * NORMAL_OFFSET, time);
+        let gradient = Vector3::new(
+            difference(Vector3::new(1.0, 0.0, 0.0)),
+            difference(Vector3::new(0.0, 1.0, 0.0)),
+            difference(Vector3::new(0.0, 0.0, 1.0)),
+        );
+        if gradient.length() > 0.0 { gradient.normalized() } else { p.normalized() }
+    }
+
+    /// Point of the surface hit by the ray from `origin` along the unit vector `direction`
+    /// (model space) and the normal there, marching only inside the radius 0.5 sphere
+    pub fn hit(&self, origin: Vector3, direction: Vector3, time: f32) -> Option<(Vector3, Vector3)> {
+        // Tramo del rayo dentro de la esfera envolvente
+        let b = origin.dot(direction);
+        let c = origin.dot(origin) - SPHERE_RADIUS * SPHERE_RADIUS;
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let (mut t, end) = ((-b - root).max(0.0), -b + root);
+
+        let scale = self.step_scale();
+        for _ in 0..MAX_STEPS {
+            if t > end {
+                return None;
+            }
+            let p = origin + direction * t;
+            let distance = self.distance(p, time);
+            if distance < HIT_DISTANCE {
+                return Some((p, self.normal(p, time)));
+            }
+            t += distance / scale;
+        }
+        None
+    }
+}
+
+/// Raymarches `sdf` through the pixels the radius 0.5 sphere of `uniforms` can cover and
+/// returns one fragment per hit in front of `depth_view`, with the material of `material`
+pub fn march(uniforms: &Uniforms, sdf: &Sdf, material: &Vertex, depth_view: &DepthView, stats: &mut PipelineStats) -> Vec<Fragment> {
+    trace(uniforms, material, depth_view, stats, |origin, direction| sdf.hit(origin, direction, uniforms.time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TORUS: Sdf = Sdf::Torus { major: 0.35, minor: 0.1 };
+
+    #[test]
+    fn torus_hit_lands_on_the_surface() {
+        let (point, normal) = TORUS.hit(Vector3::new(1.0, 0.0, 0.0), Vector3::new(-1.0, 0.0, 0.0), 0.0)
+            .expect("the ray crosses the ring");
+        assert!((point - Vector3::new(0.45, 0.0, 0.0)).length() < 1e-3, "{:?}", point);
+        assert!(TORUS.distance(point, 0.0).abs() < 1e-3);
+        assert!((normal.length() - 1.0).abs() < 1e-4);
+        assert!(normal.dot(Vector3::new(1.0, 0.0, 0.0)) > 0.99, "{:?}", normal);
+    }
+
+    #[test]
+    fn ray_through_the_hole_misses() {
+        assert!(TORUS.hit(Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, -1.0, 0.0), 0.0).is_none());
+    }
+
+    #[test]
+    fn ray_outside_the_bounding_sphere_misses() {
+        assert!(TORUS.hit(Vector3::new(-1.0, 0.6, 0.0), Vector3::new(1.0, 0.0, 0.0), 0.0).is_none());
+    }
+}
@@ -0,0 +1,224 @@
+// settings.rs
+// Ajustes del jugador que cambia el menú de ajustes: se aplican en el momento y se guardan en
+// un archivo TOML que se lee al arrancar. A diferencia de shaders.toml (parámetros de arte),
+// aquí solo hay opciones de rendimiento y comodidad. El preset de calidad es el mando único:
+// al elegirlo fija la escala de resolución y los efectos de post-proceso costosos (que luego se
+// pueden retocar uno a uno), y rebaja o sube los parámetros de los shaders, las partículas y
+// la teselación de los planetas
+use crate::locale::Language;
+use crate::palette::PalettePreset;
+use space_travel::assets::{PlanetMesh, SPHERE_SLICES, SPHERE_STACKS};
+use space_travel::post::PostEffects;
+use space_travel::shader_params::{LightingQuality, ShaderParams, ShadowMode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+// Límites de los valores numéricos
+pub const RESOLUTION_SCALES: [f32; 3] = [0.5, 0.75, 1.0];
+const SENSITIVITY_RANGE: (f32, f32) = (0.2, 3.0);
+// Objetivos de la resolución dinámica (0 = resolución fija)
+const TARGET_FPS: [u32; 4] = [0, 30, 45, 60];
+
+/// Rendering cost preset, applied on top of the parameters from shaders.toml
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QualityPreset {
+    Low,
+    Medium,
+    High,
+    Ultra,
+}
+
+impl QualityPreset {
+    const ALL: [QualityPreset; 4] = [QualityPreset::Low, QualityPreset::Medium, QualityPreset::High, QualityPreset::Ultra];
+
+    /// Parses "low", "medium", "high" or "ultra" (the --quality argument)
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "low" => Ok(QualityPreset::Low),
+            "medium" => Ok(QualityPreset::Medium),
+            "high" => Ok(QualityPreset::High),
+            "ultra" => Ok(QualityPreset::Ultra),
+            _ => Err(format!("unknown quality '{}' (expected low, medium, high or ultra)", text)),
+        }
+    }
+
+    /// Key of its name in the language files
+    pub fn key(self) -> &'static str {
+        match self {
+            QualityPreset::Low => "quality.low",
+            QualityPreset::Medium => "quality.medium",
+            QualityPreset::High => "quality.high",
+            QualityPreset::Ultra => "quality.ultra",
+        }
+    }
+
+    /// The next preset (`forward`) or the previous one, wrapping around
+    pub fn cycle(self, forward: bool) -> Self {
+        let count = QualityPreset::ALL.len();
+        let index = QualityPreset::ALL.iter().position(|&preset| preset == self).unwrap_or(0);
+        QualityPreset::ALL[if forward { (index + 1) % count } else { (index + count - 1) % count }]
+    }
+
+    /// Fraction of the window the preset renders at
+    pub fn resolution_scale(self) -> f32 {
+        match self {
+            QualityPreset::Low => RESOLUTION_SCALES[0],
+            QualityPreset::Medium => RESOLUTION_SCALES[1],
+            QualityPreset::High | QualityPreset::Ultra => 1.0,
+        }
+    }
+
+    /// `post` with the costly effects switched for the preset; the look of the image (dither,
+    /// CRT) stays as the player chose
+    pub fn post_effects(self, post: PostEffects) -> PostEffects {
+        match self {
+            QualityPreset::Low => PostEffects { ssao: false, motion_blur: false, accumulate: false, auto_exposure: false, ..post },
+            QualityPreset::Medium => PostEffects { ssao: false, accumulate: false, auto_exposure: true, ..post },
+            QualityPreset::High => PostEffects { ssao: true, accumulate: false, auto_exposure: true, ..post },
+            QualityPreset::Ultra => PostEffects { ssao: true, accumulate: true, auto_exposure: true, ..post },
+        }
+    }
+
+    /// Tessellation of the planets (--planet-mesh overrides it)
+    pub fn planet_mesh(self) -> PlanetMesh {
+        match self {
+            QualityPreset::Low => PlanetMesh::UvSphere { stacks: SPHERE_STACKS / 2, slices: SPHERE_SLICES / 2 },
+            QualityPreset::Medium => PlanetMesh::UvSphere { stacks: SPHERE_STACKS * 3 / 4, slices: SPHERE_SLICES * 3 / 4 },
+            QualityPreset::High => PlanetMesh::default(),
+            QualityPreset::Ultra => PlanetMesh::Icosphere { subdivisions: 4 },
+        }
+    }
+
+    /// Whether mid-distance bodies are drawn as impostors; Ultra always draws their meshes
+    pub fn impostors(self) -> bool {
+        self != QualityPreset::Ultra
+    }
+
+    /// The loaded parameters with their cost lowered (or raised, with Ultra); High keeps them
+    /// as they are in the file
+    pub fn apply(self, params: &ShaderParams) -> ShaderParams {
+        let mut params = params.clone();
+        match self {
+            QualityPreset::High => return params,
+            // Más estrellas y partículas, sombras más finas y la nebulosa con más muestras
+            QualityPreset::Ultra => {
+                params.lighting.shadow_map_size *= 2;
+                params.nebula.step = (params.nebula.step / 2).max(1);
+                params.starfield.count = params.starfield.count * 3 / 2;
+                params.dust.count = params.dust.count * 3 / 2;
+                params.exhaust.rate *= 1.5;
+                return params;
+            }
+            QualityPreset::Low | QualityPreset::Medium => {}
+        }
+        // Las sombras analíticas son bastante más baratas que el cube map
+        if params.lighting.shadow_mode == ShadowMode::Map {
+            params.lighting.shadow_mode = ShadowMode::Spheres;
+        }
+        params.nebula.step = params.nebula.step.max(6);
+        if self == QualityPreset::Low {
+            params.lighting.quality = LightingQuality::Gouraud;
+            params.lighting.shadow_mode = ShadowMode::Off;
+            params.nebula.step = params.nebula.step.max(8);
+            params.starfield.count /= 2;
+            params.dust.count /= 2;
+            params.exhaust.rate *= 0.5;
+        }
+        params
+    }
+}
+
+/// Player options from the settings menu
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub resolution_scale: f32, // Fracción de la ventana a la que se renderiza (uno de RESOLUTION_SCALES)
+    pub post: PostEffects,
+    pub sensitivity: f32,      // Multiplica las velocidades de la cámara
+    pub volume: f32,           // Multiplica el volumen general de [audio]
+    pub music_volume: f32,     // Multiplica el volumen de la música de [audio]
+    pub quality: QualityPreset,
+    pub language: Language,    // De los menús y el HUD
+    pub palette: PalettePreset, // Colores de las órbitas y del HUD (variantes para daltonismo)
+    pub target_fps: u32,       // Resolución dinámica: FPS que se intentan mantener (0 = apagada)
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            resolution_scale: 1.0,
+            post: PostEffects::default(),
+            sensitivity: 1.0,
+            volume: 1.0,
+            music_volume: 1.0,
+            quality: QualityPreset::High,
+            language: Language::Es,
+            palette: PalettePreset::Standard,
+            target_fps: 0,
+        }
+    }
+}
+
+impl Settings {
+    /// Loads the settings; values out of range are brought back into it
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut settings: Settings = toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        settings.resolution_scale = settings.resolution_scale.clamp(RESOLUTION_SCALES[0], 1.0);
+        settings.sensitivity = settings.sensitivity.clamp(SENSITIVITY_RANGE.0, SENSITIVITY_RANGE.1);
+        settings.volume = settings.volume.clamp(0.0, 1.0);
+        settings.music_volume = settings.music_volume.clamp(0.0, 1.0);
+        Ok(settings)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let text = toml::to_string(self).map_err(|e| format!("{}: {}", path.display(), e))?;
+        fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Switches to `quality` with the resolution scale and post-processing effects it bundles
+    pub fn set_quality(&mut self, quality: QualityPreset) {
+        self.quality = quality;
+        self.resolution_scale = quality.resolution_scale();
+        self.post = quality.post_effects(self.post);
+    }
+
+    /// Moves the resolution scale to the next (`forward`) or previous step, wrapping around
+    pub fn cycle_resolution_scale(&mut self, forward: bool) {
+        let current = RESOLUTION_SCALES.iter().position(|scale| *scale >= self.resolution_scale).unwrap_or(RESOLUTION_SCALES.len() - 1);
+        let count = RESOLUTION_SCALES.len();
+        self.resolution_scale = RESOLUTION_SCALES[if forward { (current + 1) % count } else { (current + count - 1) % count }];
+    }
+
+    /// Moves the dynamic resolution target to the next (`forward`) or previous one, wrapping
+    /// around through off
+    pub fn cycle_target_fps(&mut self, forward: bool) {
+        let current = TARGET_FPS.iter().position(|&fps| fps >= self.target_fps).unwrap_or(0);
+        let count = TARGET_FPS.len();
+        self.target_fps = TARGET_FPS[if forward { (current + 1) % count } else { (current + count - 1) % count }];
+    }
+
+    /// Changes the sensitivity by `steps` tenths, within its limits
+    pub fn adjust_sensitivity(&mut self, steps: i32) {
+        self.sensitivity = (((self.sensitivity + steps as f32 * 0.1) * 10.0).round() / 10.0).clamp(SENSITIVITY_RANGE.0, SENSITIVITY_RANGE.1);
+    }
+
+    /// Changes the volume by `steps` tenths, within 0..1
+    pub fn adjust_volume(&mut self, steps: i32) {
+        self.volume = step_volume(self.volume, steps);
+    }
+
+    /// Changes the music volume by `steps` tenths, within 0..1
+    pub fn adjust_music_volume(&mut self, steps: i32) {
+        self.music_volume = step_volume(self.music_volume, steps);
+    }
+}
+
+// Volumen movido `steps` décimas, dentro de 0..1
+fn step_volume(volume: f32, steps: i32) -> f32 {
+    ((volume + steps as f32 * 0.1) * 10.0).round().clamp(0.0, 10.0) / 10.0
+}
@@ -0,0 +1,692 @@
+// shader_params.rs
+// Parámetros ajustables (frecuencias de ruido y paletas) de cada fragment shader.
+// Se cargan desde shaders.toml; los campos que falten usan los valores por defecto.
+use raylib::prelude::*;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Converts an `[r, g, b]` palette entry (0.0 - 1.0) to a Vector3
+pub fn rgb(c: [f32; 3]) -> Vector3 {
+    Vector3::new(c[0], c[1], c[2])
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ShaderParams {
+    pub sun: SunParams,
+    pub mercury: MercuryParams,
+    pub earth: EarthParams,
+    pub mars: MarsParams,
+    pub uranus: UranusParams,
+    pub nave: NaveParams,
+    pub zephyr: ZephyrParams,
+    pub pyrion: PyrionParams,
+    pub glacia: GlaciaParams,
+    pub umbraleth: UmbralethParams,
+    pub verdis: VerdisParams,
+    pub lighting: LightingParams,
+    pub post: PostParams,
+    pub starfield: StarfieldParams,
+    pub nebula: NebulaParams,
+    pub dust: DustParams,
+    pub exhaust: ExhaustParams,
+    pub trajectory: TrajectoryParams,
+    pub audio: AudioParams,
+}
+
+impl ShaderParams {
+    /// Loads shader parameters from a TOML file with one table per shader
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Frequency of the main noise pattern of a built-in shader (continents, dunes, ice...),
+    /// for geometry that has to match what the shader paints; 1 for shaders without one
+    pub fn noise_frequency(&self, shader: &str) -> f32 {
+        match shader {
+            "sun" => self.sun.energy_frequency,
+            "mercury" => self.mercury.crystal_frequency,
+            "earth" => self.earth.continent_frequency,
+            "mars" => self.mars.desert_frequency,
+            "uranus" => self.uranus.band_frequency,
+            "zephyr" => self.zephyr.storm_frequency,
+            "pyrion" => self.pyrion.sulfur_frequency,
+            "glacia" => self.glacia.ice_frequency,
+            "umbraleth" => self.umbraleth.energy_frequency,
+            "verdis" => self.verdis.flora_frequency,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct SunParams {
+    pub energy_frequency: f32,
+    pub energy_detail_frequency: f32,
+    pub energy_fine_frequency: f32,
+    pub burst_frequency: f32,
+    pub core_color: [f32; 3],
+    pub surface_color: [f32; 3],
+    pub corona_color: [f32; 3],
+    pub burst_color: [f32; 3],
+}
+
+impl Default for SunParams {
+    fn default() -> Self {
+        SunParams {
+            energy_frequency: 3.0,
+            energy_detail_frequency: 2.0,
+            energy_fine_frequency: 1.5,
+            burst_frequency: 0.5,
+            core_color: [1.0, 0.1, 0.8],    // Rosa neón central
+            surface_color: [0.2, 0.9, 1.0], // Cian eléctrico
+            corona_color: [0.9, 1.0, 0.1],  // Amarillo neón
+            burst_color: [1.0, 1.0, 0.5],
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct MercuryParams {
+    pub crystal_frequency: f32,
+    pub vein_frequency: f32,
+    pub base_metal: [f32; 3],
+    pub crystal_color: [f32; 3],
+    pub vein_color: [f32; 3],
+    pub iridescent_color: [f32; 3],
+}
+
+impl Default for MercuryParams {
+    fn default() -> Self {
+        MercuryParams {
+            crystal_frequency: 4.0,
+            vein_frequency: 3.0,
+            base_metal: [0.2, 0.3, 0.2],    // Púrpura metálico
+            crystal_color: [0.4, 0.8, 0.9], // Azul cristalino
+            vein_color: [0.9, 0.6, 0.3],    // Naranja metálico
+            iridescent_color: [0.3, 0.9, 0.7],
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct EarthParams {
+    pub continent_frequency: f32,
+    pub river_frequency: f32,
+    pub bio_frequency: f32,
+    pub ocean_color: [f32; 3],
+    pub land_color: [f32; 3],
+    pub river_color: [f32; 3],
+    pub bio_color: [f32; 3],
+}
+
+impl Default for EarthParams {
+    fn default() -> Self {
+        EarthParams {
+            continent_frequency: 2.0,
+            river_frequency: 1.5,
+            bio_frequency: 1.0,
+            ocean_color: [0.1, 0.8, 0.6], // Verde azulado fluorescente
+            land_color: [0.9, 0.4, 0.7],  // Rosa alienígena
+            river_color: [0.3, 0.9, 0.9], // Cian brillante
+            bio_color: [0.8, 0.2, 0.9],   // Púrpura bioluminiscente
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct MarsParams {
+    pub desert_frequency: f32,
+    pub canyon_frequency: f32,
+    pub storm_frequency: f32,
+    pub base_color: [f32; 3],
+    pub canyon_color: [f32; 3],
+    pub storm_color: [f32; 3],
+}
+
+impl Default for MarsParams {
+    fn default() -> Self {
+        MarsParams {
+            desert_frequency: 2.5,
+            canyon_frequency: 2.0,
+            storm_frequency: 0.8,
+            base_color: [0.8, 0.2, 0.4],   // Rosa rojizo
+            canyon_color: [0.6, 0.8, 0.2], // Verde amarillento
+            storm_color: [0.9, 0.7, 0.3],  // Amarillo dorado
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct UranusParams {
+    pub band_frequency: f32,
+    pub vortex_frequency: f32,
+    pub energy_frequency: f32,
+    pub deep_nebula: [f32; 3],
+    pub vortex_color: [f32; 3],
+    pub energy_color: [f32; 3],
+    pub rim_color: [f32; 3],  // Borde iluminado (Fresnel)
+    pub rim_strength: f32,
+    pub rim_power: f32,       // Más alto = borde más delgado
+}
+
+impl Default for UranusParams {
+    fn default() -> Self {
+        UranusParams {
+            band_frequency: 1.2,
+            vortex_frequency: 1.8,
+            energy_frequency: 0.9,
+            deep_nebula: [0.3, 0.1, 0.8],  // Azul profundo
+            vortex_color: [0.7, 0.3, 0.9], // Púrpura vibrante
+            energy_color: [0.1, 0.9, 0.8], // Verde azulado energético
+            rim_color: [0.6, 0.8, 1.0],
+            rim_strength: 0.6,
+            rim_power: 3.0,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct NaveParams {
+    pub circuit_frequency: f32,
+    pub grid_frequency: f32,
+    pub hologram_frequency: f32,
+    pub ship_base: [f32; 3],
+    pub circuit_color: [f32; 3],
+    pub energy_color: [f32; 3],
+    pub hologram_color: [f32; 3],
+}
+
+impl Default for NaveParams {
+    fn default() -> Self {
+        NaveParams {
+            circuit_frequency: 4.0,
+            grid_frequency: 3.0,
+            hologram_frequency: 2.0,
+            ship_base: [0.2, 0.1, 0.4],      // Púrpura oscuro
+            circuit_color: [0.1, 0.9, 0.6],  // Verde neón
+            energy_color: [0.8, 0.3, 0.9],   // Rosa eléctrico
+            hologram_color: [0.3, 0.7, 1.0], // Azul holográfico
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct ZephyrParams {
+    pub storm_frequency: f32,
+    pub wind_frequency: f32,
+    pub electric_frequency: f32,
+    pub storm_base: [f32; 3],
+    pub crystal_color: [f32; 3],
+    pub electric_color: [f32; 3],
+    pub rim_color: [f32; 3],  // Borde iluminado (Fresnel)
+    pub rim_strength: f32,
+    pub rim_power: f32,       // Más alto = borde más delgado
+}
+
+impl Default for ZephyrParams {
+    fn default() -> Self {
+        ZephyrParams {
+            storm_frequency: 2.5,
+            wind_frequency: 2.2,
+            electric_frequency: 1.5,
+            storm_base: [0.1, 0.3, 0.7],     // Azul eléctrico
+            crystal_color: [0.4, 0.9, 0.8],  // Verde azulado cristalino
+            electric_color: [0.9, 0.5, 1.0], // Rosa eléctrico
+            rim_color: [0.5, 0.8, 1.0],
+            rim_strength: 0.7,
+            rim_power: 3.0,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct PyrionParams {
+    pub sulfur_frequency: f32,
+    pub crack_frequency: f32,
+    pub magma_frequency: f32,
+    pub crust_color: [f32; 3],
+    pub sulfur_color: [f32; 3],
+    pub magma_color: [f32; 3],
+    pub crack_color: [f32; 3],
+}
+
+impl Default for PyrionParams {
+    fn default() -> Self {
+        PyrionParams {
+            sulfur_frequency: 2.0,
+            crack_frequency: 1.8,
+            magma_frequency: 1.3,
+            crust_color: [0.8, 0.6, 0.1],  // Amarillo sulfúrico
+            sulfur_color: [0.9, 0.8, 0.2], // Amarillo brillante
+            magma_color: [1.0, 0.4, 0.1],  // Naranja incandescente
+            crack_color: [0.6, 0.3, 0.1],  // Marrón oscuro
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct GlaciaParams {
+    pub ice_frequency: f32,
+    pub gas_frequency: f32,
+    pub crystal_frequency: f32,
+    pub ice_base: [f32; 3],
+    pub alien_ice_color: [f32; 3],
+    pub gas_color: [f32; 3],
+    pub crystal_color: [f32; 3],
+    pub rim_color: [f32; 3],  // Borde iluminado (Fresnel)
+    pub rim_strength: f32,
+    pub rim_power: f32,       // Más alto = borde más delgado
+}
+
+impl Default for GlaciaParams {
+    fn default() -> Self {
+        GlaciaParams {
+            ice_frequency: 2.8,
+            gas_frequency: 2.1,
+            crystal_frequency: 1.4,
+            ice_base: [0.7, 0.9, 1.0],        // Azul hielo
+            alien_ice_color: [0.4, 0.8, 0.5], // Verde hielo
+            gas_color: [0.8, 0.5, 0.9],       // Púrpura congelado
+            crystal_color: [0.3, 0.7, 0.9],   // Azul cristal
+            rim_color: [0.9, 0.97, 1.0],
+            rim_strength: 0.8,
+            rim_power: 3.0,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct UmbralethParams {
+    pub energy_frequency: f32,
+    pub vortex_frequency: f32,
+    pub quantum_frequency: f32,
+    pub void_color: [f32; 3],
+    pub energy_color: [f32; 3],
+    pub vortex_color: [f32; 3],
+    pub quantum_color: [f32; 3],
+}
+
+impl Default for UmbralethParams {
+    fn default() -> Self {
+        UmbralethParams {
+            energy_frequency: 1.5,
+            vortex_frequency: 1.8,
+            quantum_frequency: 0.9,
+            void_color: [0.05, 0.02, 0.1],  // Negro violáceo
+            energy_color: [0.4, 0.1, 0.6],  // Púrpura oscuro energético
+            vortex_color: [0.2, 0.05, 0.4], // Púrpura muy oscuro
+            quantum_color: [0.6, 0.2, 0.8], // Púrpura brillante
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct VerdisParams {
+    pub flora_frequency: f32,
+    pub bio_frequency: f32,
+    pub fungal_frequency: f32,
+    pub flora_base: [f32; 3],
+    pub bio_color: [f32; 3],
+    pub fungal_color: [f32; 3],
+    pub light_color: [f32; 3],
+}
+
+impl Default for VerdisParams {
+    fn default() -> Self {
+        VerdisParams {
+            flora_frequency: 2.3,
+            bio_frequency: 1.9,
+            fungal_frequency: 1.2,
+            flora_base: [0.2, 0.7, 0.3],   // Verde alienígena
+            bio_color: [0.1, 0.9, 0.5],    // Verde neón
+            fungal_color: [0.8, 0.3, 0.6], // Rosa micológico
+            light_color: [0.4, 1.0, 0.7],  // Verde brillante
+        }
+    }
+}
+
+/// Settings of the lighting stage, shared by every lit body
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct LightingParams {
+    pub reference_distance: f32, // Distancia a la que la luz llega con su intensidad completa
+    pub range: f32,              // Más allá de esta distancia la luz no llega
+    pub exposure: f32,
+    pub min_light: f32,          // Luz mínima para que los planetas lejanos sigan siendo visibles
+    // Luz ambiental constante, o hemisférica (cielo arriba, reflejo de las estrellas abajo, según +Y)
+    pub ambient_color: [f32; 3],
+    pub hemispheric: bool,
+    pub sky_color: [f32; 3],
+    pub ground_color: [f32; 3],
+    // Luz direccional lejana (galaxia de fondo); intensidad 0 = apagada
+    pub key_light_direction: [f32; 3],
+    pub key_light_color: [f32; 3],
+    pub key_light_intensity: f32,
+    pub key_light_only: bool,    // Ignorar las estrellas para probar shaders con luz uniforme
+    pub two_sided: bool,         // Iluminar también las caras vistas por detrás (normales invertidas)
+    pub quality: LightingQuality, // Iluminación por vértice (rápida) o por fragmento; cada cuerpo puede cambiarla
+    // Sombras de la estrella con `shadows = true` (con rayos, de todas las luces). Mapa: lado de cada cara del cube map en texels
+    // y sesgo relativo a la distancia para evitar el acné de sombra
+    pub shadow_mode: ShadowMode,
+    pub shadow_map_size: usize,
+    pub shadow_bias: f32,
+}
+
+/// Where the lights are evaluated
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LightingQuality {
+    Gouraud, // En los vértices; la luz se interpola por el triángulo
+    Phong,   // En cada fragmento, con la normal interpolada
+}
+
+/// How the lighting stage computes the shadows of the lights that cast them
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShadowMode {
+    Off,
+    Map,     // Cube map de profundidad desde la luz
+    Spheres, // Eclipses analíticos contra las esferas de los cuerpos, con penumbra (más barato)
+    Rays,    // Rayos de sombra hacia cada luz contra las esferas: sombras duras exactas
+}
+
+impl Default for LightingParams {
+    fn default() -> Self {
+        LightingParams {
+            reference_distance: 25.0,
+            range: 150.0,
+            exposure: 1.0,
+            min_light: 0.08,
+            ambient_color: [0.04, 0.04, 0.06],
+            hemispheric: false,
+            sky_color: [0.05, 0.05, 0.12],
+            ground_color: [0.1, 0.04, 0.03],
+            key_light_direction: [-0.3, -0.5, -1.0],
+            key_light_color: [0.6, 0.7, 1.0],
+            key_light_intensity: 0.0,
+            key_light_only: false,
+            two_sided: false,
+            quality: LightingQuality::Phong,
+            shadow_mode: ShadowMode::Map,
+            shadow_map_size: 512,
+            shadow_bias: 0.01,
+        }
+    }
+}
+
+/// Settings of the post-processing effects
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct PostParams {
+    pub ssao_radius: f32,   // Radio de muestreo en píxeles
+    pub ssao_bias: f32,     // Diferencia mínima de profundidad (unidades de mundo) para ocluir
+    pub ssao_range: f32,    // Diferencia máxima; más allá no se considera oclusión
+    pub ssao_strength: f32,
+    pub accumulation_frames: u32,  // Frames que se promedian con la cámara quieta (más = más suave, más estela)
+    pub motion_blur_strength: f32, // Fracción del movimiento del frame que se emborrona
+    pub motion_blur_max: f32,      // Longitud máxima de la estela, en píxeles
+    pub chromatic_aberration: f32, // Separación de los canales en las esquinas, en píxeles (0 = apagada)
+    pub vignette_strength: f32,    // Oscurecimiento en las esquinas (0 = apagada, 1 = negro)
+    pub vignette_radius: f32,      // Fracción de la media diagonal que queda sin oscurecer
+    pub output_depth: OutputDepth, // Profundidad de color de la salida (estilo retro)
+    pub palette: Palette,          // Paleta fija a la que se reduce la imagen final
+    pub palette_dither: f32,       // Amplitud del dithering Bayer al elegir color (0..255; 0 = el más cercano)
+    pub crt_curvature: f32,        // Distorsión de barril de la pantalla CRT (0 = plana)
+    pub crt_scanline_strength: f32, // Oscurecimiento de las líneas entre scanlines (0..1)
+    pub crt_mask_strength: f32,    // Atenuación de los canales fuera de cada franja de fósforo (0..1)
+    pub fog_density: f32,          // Niebla exponencial por unidad de distancia (0 = apagada)
+    pub fog_start: f32,            // Distancia a la cámara desde la que empieza la niebla
+    pub fog_max: f32,              // Cuánto se acerca al fondo lo más lejano (0..1)
+    pub exposure_key: f32,         // Luminancia media (0..1) a la que la adaptación lleva la imagen
+    pub adaptation_time: f32,      // Segundos que tarda la adaptación en recorrer casi todo el cambio
+    pub min_exposure: f32,         // Límites del multiplicador de exposición de la adaptación
+    pub max_exposure: f32,
+}
+
+/// Bits per channel the final image is reduced to
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+pub enum OutputDepth {
+    #[serde(rename = "full")]
+    Full,   // 8 bits por canal, sin reducir
+    #[serde(rename = "rgb565")]
+    Rgb565, // 5, 6 y 5 bits
+    #[serde(rename = "8color")]
+    Rgb111, // 1 bit por canal: negro, blanco y los 6 primarios/secundarios
+}
+
+impl OutputDepth {
+    pub fn bits(&self) -> [u32; 3] {
+        match self {
+            OutputDepth::Full => [8, 8, 8],
+            OutputDepth::Rgb565 => [5, 6, 5],
+            OutputDepth::Rgb111 => [1, 1, 1],
+        }
+    }
+}
+
+impl Default for PostParams {
+    fn default() -> Self {
+        PostParams {
+            ssao_radius: 6.0,
+            ssao_bias: 0.02,
+            ssao_range: 1.5,
+            ssao_strength: 1.2,
+            accumulation_frames: 32,
+            motion_blur_strength: 0.5,
+            motion_blur_max: 40.0,
+            chromatic_aberration: 0.0,
+            vignette_strength: 0.0,
+            vignette_radius: 0.5,
+            output_depth: OutputDepth::Full,
+            palette: Palette::None,
+            palette_dither: 48.0,
+            crt_curvature: 0.08,
+            crt_scanline_strength: 0.35,
+            crt_mask_strength: 0.25,
+            fog_density: 0.01,
+            fog_start: 150.0,
+            fog_max: 0.9,
+            exposure_key: 0.12,
+            adaptation_time: 2.0,
+            min_exposure: 0.4,
+            max_exposure: 2.5,
+        }
+    }
+}
+
+/// Settings of the background stars
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct StarfieldParams {
+    pub count: usize,
+    pub brightness: f32,       // Multiplica el brillo de todas las estrellas
+    pub twinkle: f32,          // Fracción del brillo que oscila con el centelleo (0 = fijas)
+    pub twinkle_speed: f32,    // Velocidad media del centelleo (radianes por segundo)
+    pub sprite_threshold: f32, // Brillo a partir del cual la estrella se dibuja como una cruz
+}
+
+impl Default for StarfieldParams {
+    fn default() -> Self {
+        StarfieldParams {
+            count: 1500,
+            brightness: 1.0,
+            twinkle: 0.5,
+            twinkle_speed: 3.0,
+            sprite_threshold: 0.8,
+        }
+    }
+}
+
+/// Settings of the background layers: galaxy band, nebula (its colors come from the scene) and dust
+#[derive(Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct NebulaParams {
+    pub frequency: f32,  // Tamaño de las nubes: más alto = nubes más pequeñas
+    pub octaves: u32,    // Capas de ruido sumadas (más = bordes más detallados)
+    pub brightness: f32, // 0 = sin nebulosa
+    pub step: i32,       // Píxeles entre muestras del ruido; el resto se interpola
+    // Radio de cada capa: más cerca = más paralaje al mover la cámara (0 = en el infinito)
+    pub nebula_distance: f32,
+    pub dust_distance: f32,
+    pub dust_opacity: f32,       // Cuánto tapa el polvo cercano lo de detrás (0 = sin polvo)
+    pub galaxy_brightness: f32,  // 0 = sin banda de la galaxia
+    pub galaxy_width: f32,       // Anchura angular de la banda (seno del ángulo)
+    pub galaxy_normal: [f32; 3], // Eje perpendicular al plano de la galaxia
+    pub galaxy_color: [f32; 3],
+}
+
+impl Default for NebulaParams {
+    fn default() -> Self {
+        NebulaParams {
+            frequency: 1.5,
+            octaves: 5,
+            brightness: 0.35,
+            step: 4,
+            nebula_distance: 1000.0,
+            dust_distance: 250.0,
+            dust_opacity: 0.6,
+            galaxy_brightness: 0.25,
+            galaxy_width: 0.15,
+            galaxy_normal: [0.3, 1.0, 0.2],
+            galaxy_color: [0.8, 0.85, 1.0],
+        }
+    }
+}
+
+/// Settings of the dust particles around the camera
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct DustParams {
+    pub count: usize,      // 0 = sin polvo
+    pub box_size: f32,     // Lado de la caja alrededor de la cámara donde viven las partículas
+    pub streak_time: f32,  // Segundos de movimiento que muestra la estela
+    pub brightness: f32,
+}
+
+impl Default for DustParams {
+    fn default() -> Self {
+        DustParams {
+            count: 300,
+            box_size: 40.0,
+            streak_time: 0.08,
+            brightness: 0.6,
+        }
+    }
+}
+
+/// Settings of the ship's engine exhaust particles
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct ExhaustParams {
+    pub rate: f32,       // Partículas por segundo y motor con el acelerador a fondo
+    pub lifetime: f32,   // Segundos que vive como máximo cada partícula
+    pub speed: f32,      // Velocidad de salida (unidades/s)
+    pub size: f32,       // Radio en unidades de mundo
+    pub brightness: f32,
+}
+
+impl Default for ExhaustParams {
+    fn default() -> Self {
+        ExhaustParams {
+            rate: 120.0,
+            lifetime: 0.35,
+            speed: 8.0,
+            size: 0.25,
+            brightness: 0.8,
+        }
+    }
+}
+
+/// Settings of the ship's predicted path
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct TrajectoryParams {
+    pub seconds: f32,       // Cuánto adelante se predice (0 = sin trayectoria)
+    pub steps: usize,       // Tramos de integración
+    pub tick_interval: f32, // Segundos entre marcas de tiempo (0 = sin marcas)
+    pub min_speed: f32,     // Por debajo de esta velocidad no se dibuja
+    pub brightness: f32,
+}
+
+impl Default for TrajectoryParams {
+    fn default() -> Self {
+        TrajectoryParams {
+            seconds: 10.0,
+            steps: 40,
+            tick_interval: 1.0,
+            min_speed: 0.5,
+            brightness: 0.8,
+        }
+    }
+}
+
+/// Volumes of the synthesized sounds (0 = silent, 1 = full)
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct AudioParams {
+    pub master_volume: f32,
+    pub ambient_volume: f32, // Zumbido de fondo
+    pub music_volume: f32,   // Música (exploración / cerca de una estrella)
+    pub engine_volume: f32,  // Motor, según la velocidad
+    pub effects_volume: f32, // Warp y colisiones
+    pub bodies_volume: f32,  // Sonidos de los cuerpos (volcanes de Pyrion, tormentas de Zephyr)
+}
+
+impl Default for AudioParams {
+    fn default() -> Self {
+        AudioParams {
+            master_volume: 0.7,
+            ambient_volume: 0.6,
+            music_volume: 0.5,
+            engine_volume: 0.8,
+            effects_volume: 1.0,
+            bodies_volume: 0.8,
+        }
+    }
+}
+
+/// Fixed palettes of old hardware the final image can be mapped to
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Palette {
+    None,
+    C64,     // Commodore 64 (16 colores)
+    Ega,     // EGA/CGA de 16 colores
+    GameBoy, // Los 4 verdes de la Game Boy original
+}
+
+impl Palette {
+    pub fn colors(&self) -> &'static [[u8; 3]] {
+        match self {
+            Palette::None => &[],
+            Palette::C64 => &[
+                [0x00, 0x00, 0x00], [0xFF, 0xFF, 0xFF], [0x68, 0x37, 0x2B], [0x70, 0xA4, 0xB2],
+                [0x6F, 0x3D, 0x86], [0x58, 0x8D, 0x43], [0x35, 0x28, 0x79], [0xB8, 0xC7, 0x6F],
+                [0x6F, 0x4F, 0x25], [0x43, 0x39, 0x00], [0x9A, 0x67, 0x59], [0x44, 0x44, 0x44],
+                [0x6C, 0x6C, 0x6C], [0x9A, 0xD2, 0x84], [0x6C, 0x5E, 0xB5], [0x95, 0x95, 0x95],
+            ],
+            Palette::Ega => &[
+                [0x00, 0x00, 0x00], [0x00, 0x00, 0xAA], [0x00, 0xAA, 0x00], [0x00, 0xAA, 0xAA],
+                [0xAA, 0x00, 0x00], [0xAA, 0x00, 0xAA], [0xAA, 0x55, 0x00], [0xAA, 0xAA, 0xAA],
+                [0x55, 0x55, 0x55], [0x55, 0x55, 0xFF], [0x55, 0xFF, 0x55], [0x55, 0xFF, 0xFF],
+                [0xFF, 0x55, 0x55], [0xFF, 0x55, 0xFF], [0xFF, 0xFF, 0x55], [0xFF, 0xFF, 0xFF],
+            ],
+            Palette::GameBoy => &[[0x0F, 0x38, 0x0F], [0x30, 0x62, 0x30], [0x8B, 0xAC, 0x0F], [0x9B, 0xBC, 0x0F]],
+        }
+    }
+}
@@ -0,0 +1,56 @@
+// shader_plugins.rs
+// Shaders que no forman parte de shaders.rs y se añaden al registro al arrancar; sirven de
+// ejemplo de cómo contribuir shaders desde fuera del pipeline
+use space_travel::shader_registry::ShaderRegistry;
+use space_travel::shaders::fresnel;
+use raylib::prelude::*;
+
+/// Registers the shaders of this module
+pub fn register(registry: &mut ShaderRegistry) {
+    // Holograma: franjas horizontales que suben con el tiempo y un borde brillante
+    registry.register("hologram", |fragment, uniforms, _params| {
+        let stripes = ((fragment.world_position.y * 6.0 - uniforms.time * 2.0).sin() * 0.5 + 0.5).powf(4.0);
+        let rim = fresnel(fragment, uniforms, 2.0);
+        let base = Vector3::new(0.1, 0.6, 0.9);
+        base * (0.25 + 0.5 * stripes) + Vector3::new(0.6, 0.9, 1.0) * rim
+    });
+
+    // Cabina: metal oscuro con juntas entre paneles y pilotos del tablero que parpadean, en el
+    // espacio del objeto para que no resbalen al mover la cámara
+    registry.register("cockpit", |fragment, uniforms, _params| {
+        let p = fragment.object_position;
+        let seam_x = ((p.x * 8.0).rem_euclid(1.0) - 0.5).abs();
+        let seam_y = ((p.y * 8.0).rem_euclid(1.0) - 0.5).abs();
+        let seam = if seam_x > 0.47 || seam_y > 0.47 { 0.6 } else { 1.0 };
+        let base = Vector3::new(0.16, 0.17, 0.2) * seam;
+        // Pilotos: celdas de una rejilla sobre el tablero y la consola
+        if p.y < -0.35 {
+            let cell = ((p.x * 20.0).floor(), (p.z * 20.0).floor());
+            let inside = ((p.x * 20.0).rem_euclid(1.0) - 0.5).abs() < 0.2 && ((p.z * 20.0).rem_euclid(1.0) - 0.5).abs() < 0.2;
+            let phase = (cell.0 * 12.9898 + cell.1 * 78.233).sin() * 43758.547;
+            if inside && phase.rem_euclid(1.0) > 0.8 && (uniforms.time * 2.0 + phase).sin() > 0.0 {
+                return if (cell.0 as i32).rem_euclid(2) == 0 { Vector3::new(0.2, 1.0, 0.4) } else { Vector3::new(1.0, 0.5, 0.1) };
+            }
+        }
+        base
+    });
+
+    // Partes de la nave (objetos Glass y Engines de nave.obj): cristal de la cabina oscuro con
+    // reflejos en los bordes, y toberas que laten con un núcleo caliente
+    registry.register("ship_glass", |fragment, uniforms, _params| {
+        let rim = fresnel(fragment, uniforms, 3.0);
+        Vector3::new(0.05, 0.1, 0.16) + Vector3::new(0.55, 0.8, 1.0) * rim
+    });
+    registry.register("ship_engine", |fragment, uniforms, _params| {
+        let pulse = 0.8 + 0.2 * (uniforms.time * 9.0).sin();
+        let core = (1.0 - fresnel(fragment, uniforms, 2.0)).powf(2.0);
+        (Vector3::new(1.0, 0.45, 0.15) * (1.0 - core) + Vector3::new(0.7, 0.85, 1.0) * core) * pulse
+    });
+
+    // Anillos de la contrarreloj: luces que recorren el aro para marcar por dónde cruzar
+    registry.register("gate", |fragment, uniforms, _params| {
+        let p = fragment.object_position;
+        let chase = ((p.z.atan2(p.x) * 6.0 - uniforms.time * 4.0).sin() * 0.5 + 0.5).powf(6.0);
+        Vector3::new(0.15, 0.7, 1.0) * (0.5 + 0.5 * chase) + Vector3::new(0.8, 1.0, 1.0) * (0.4 * chase)
+    });
+}
@@ -0,0 +1,107 @@
+// shader_registry.rs
+// Fragment shaders, animaciones de vértices y etapas de geometría por nombre: los de shaders.rs
+// más los que registre otro código en tiempo de ejecución, para que las escenas puedan usar
+// shaders nuevos sin tocar shaders.rs
+use crate::fragment::Fragment;
+use crate::shader_params::ShaderParams;
+use crate::shaders::*;
+use crate::uniforms::Uniforms;
+use crate::vertex::Vertex;
+use raylib::prelude::*;
+use std::collections::HashMap;
+
+// Nombres de los shaders de shaders.rs
+const BUILT_IN: [&str; 12] = ["sun", "mercury", "earth", "mars", "uranus", "zephyr", "pyrion", "glacia", "umbraleth", "verdis", "nave", "vertex_color"];
+
+// Animaciones de vértices de shaders.rs
+const BUILT_IN_ANIMATIONS: [(&str, VertexAnimation); 2] = [("pulse", pulse_vertex_animation), ("ripple", ripple_vertex_animation)];
+
+// Etapas de geometría de shaders.rs
+const BUILT_IN_GEOMETRY: [(&str, GeometryShader); 2] = [("prominences", prominence_geometry_shader), ("spikes", spike_geometry_shader)];
+
+/// Fragment shader registered at runtime. Fragments are shaded in parallel, so it must be
+/// `Send + Sync`
+pub type FragmentShader = Box<dyn Fn(&Fragment, &Uniforms, &ShaderParams) -> Vector3 + Send + Sync>;
+
+/// Programmable vertex stage: takes the model-space position and normal of a vertex (after
+/// the relief) and returns them moved, usually as a function of `uniforms.time`. A plain
+/// function, so the uniforms of every draw can carry it
+pub type VertexAnimation = fn(Vector3, Vector3, &Uniforms) -> (Vector3, Vector3);
+
+/// Optional geometry stage, between primitive assembly and rasterization: takes a triangle
+/// of the mesh after the vertex stage (positions and normals in model space) and appends
+/// extra triangles, in the same space, to the output. The original triangle is always drawn
+pub type GeometryShader = fn([&Vertex; 3], &Uniforms, &mut Vec<[Vertex; 3]>);
+
+/// Fragment shaders the scenes can reference by name
+pub struct ShaderRegistry {
+    custom: HashMap<String, FragmentShader>,
+    animations: HashMap<String, VertexAnimation>,
+    geometry: HashMap<String, GeometryShader>,
+}
+
+impl Default for ShaderRegistry {
+    fn default() -> Self {
+        ShaderRegistry::new()
+    }
+}
+
+impl ShaderRegistry {
+    pub fn new() -> Self {
+        let animations = BUILT_IN_ANIMATIONS.iter().map(|&(name, animation)| (name.to_string(), animation)).collect();
+        let geometry = BUILT_IN_GEOMETRY.iter().map(|&(name, shader)| (name.to_string(), shader)).collect();
+        ShaderRegistry { custom: HashMap::new(), animations, geometry }
+    }
+
+    /// Adds a shader under `name`; it takes precedence over a built-in one with the same name
+    pub fn register(&mut self, name: impl Into<String>, shader: impl Fn(&Fragment, &Uniforms, &ShaderParams) -> Vector3 + Send + Sync + 'static) {
+        self.custom.insert(name.into(), Box::new(shader));
+    }
+
+    /// Whether `name` is a built-in or registered shader (an empty name is the default shader)
+    pub fn contains(&self, name: &str) -> bool {
+        name.is_empty() || BUILT_IN.contains(&name) || self.custom.contains_key(name)
+    }
+
+    /// Adds a vertex animation under `name`, replacing any other with the same name
+    pub fn register_vertex_animation(&mut self, name: impl Into<String>, animation: VertexAnimation) {
+        self.animations.insert(name.into(), animation);
+    }
+
+    /// The vertex animation called `name`; None for an empty name (rigid body) or an unknown one
+    pub fn vertex_animation(&self, name: &str) -> Option<VertexAnimation> {
+        self.animations.get(name).copied()
+    }
+
+    /// Adds a geometry stage under `name`, replacing any other with the same name
+    pub fn register_geometry_shader(&mut self, name: impl Into<String>, shader: GeometryShader) {
+        self.geometry.insert(name.into(), shader);
+    }
+
+    /// The geometry stage called `name`; None for an empty name (no extra triangles) or an unknown one
+    pub fn geometry_shader(&self, name: &str) -> Option<GeometryShader> {
+        self.geometry.get(name).copied()
+    }
+
+    /// Runs the shader called `shader`; unknown names use the default shader
+    pub fn shade(&self, fragment: &Fragment, uniforms: &Uniforms, shader: &str, params: &ShaderParams) -> Vector3 {
+        if let Some(custom) = self.custom.get(shader) {
+            return custom(fragment, uniforms, params);
+        }
+        match shader {
+            "sun" => sun_fragment_shader(fragment, uniforms, &params.sun),
+            "mercury" => mercury_fragment_shader(fragment, uniforms, &params.mercury),
+            "earth" => earth_fragment_shader(fragment, uniforms, &params.earth),
+            "mars" => mars_fragment_shader(fragment, uniforms, &params.mars),
+            "uranus" => uranus_fragment_shader(fragment, uniforms, &params.uranus),
+            "zephyr" => zephyr_fragment_shader(fragment, uniforms, &params.zephyr),
+            "pyrion" => pyrion_fragment_shader(fragment, uniforms, &params.pyrion),
+            "glacia" => glacia_fragment_shader(fragment, uniforms, &params.glacia),
+            "umbraleth" => umbraleth_fragment_shader(fragment, uniforms, &params.umbraleth),
+            "verdis" => verdis_fragment_shader(fragment, uniforms, &params.verdis),
+            "nave" => nave_fragment_shader(fragment, uniforms, &params.nave),
+            "vertex_color" => vertex_color_fragment_shader(fragment, uniforms),
+            _ => fragment_shader(fragment, uniforms), // Default
+        }
+    }
+}
@@ -1,14 +1,19 @@
 use raylib::prelude::*;
 use crate::vertex::Vertex;
-use crate::Uniforms;
+use crate::uniforms::Uniforms;
 use crate::matrix::multiply_matrix_vector4;
 use crate::fragment::Fragment;
+use crate::shader_params::*;
+use crate::noise::animated_noise;
+use std::f32::consts::{PI, TAU};
 
-fn transform_normal(normal: &Vector3, model_matrix: &Matrix) -> Vector3 {
+// Transforma una dirección (w = 0.0) y la normaliza.
+// Para normales se usa la normal matrix; para tangentes, la model matrix
+fn transform_normal(normal: &Vector3, matrix: &Matrix) -> Vector3 {
     // Convierte el normal a coordenadas homogéneas (añade coordenada w = 0.0)
     let normal_vec4 = Vector4::new(normal.x, normal.y, normal.z, 0.0);
 
-    let transformed_normal_vec4 = multiply_matrix_vector4(model_matrix, &normal_vec4);
+    let transformed_normal_vec4 = multiply_matrix_vector4(matrix, &normal_vec4);
 
     // Convierte de vuelta a Vector3 y normaliza
     let mut transformed_normal = Vector3::new(
@@ -21,12 +26,163 @@ fn transform_normal(normal: &Vector3, model_matrix: &Matrix) -> Vector3 {
     transformed_normal
 }
 
+// Paso de las diferencias finitas con las que se estima la pendiente del relieve
+const DISPLACEMENT_EPSILON: f32 = 0.01;
+
+// Animación "pulse": cuánto crece el cuerpo en cada latido y cuántos radianes por segundo late
+const PULSE_AMPLITUDE: f32 = 0.04;
+const PULSE_SPEED: f32 = 2.5;
+
+// Animación "ripple": altura de las ondas (unidades de la malla), bandas por unidad de altura,
+// ondas alrededor del ecuador y velocidad con la que avanzan
+const RIPPLE_AMPLITUDE: f32 = 0.012;
+const RIPPLE_BANDS: f32 = 18.0;
+const RIPPLE_WAVES: f32 = 5.0;
+const RIPPLE_SPEED: f32 = 1.6;
+
+// Geometría "prominences": fracción de triángulos de la superficie de los que brota una
+// llamarada, su altura máxima y ancho (unidades de la malla), cuántos tramos tiene el arco y lo
+// rápido que sube y baja
+const PROMINENCE_CHANCE: f32 = 0.03;
+const PROMINENCE_HEIGHT: f32 = 0.14;
+const PROMINENCE_WIDTH: f32 = 0.012;
+const PROMINENCE_SEGMENTS: usize = 6;
+const PROMINENCE_SPEED: f32 = 0.7;
+
+// Geometría "spikes": fracción de triángulos con un cristal, su altura máxima y el ancho de su
+// base respecto al triángulo del que sale
+const SPIKE_CHANCE: f32 = 0.08;
+const SPIKE_HEIGHT: f32 = 0.08;
+const SPIKE_BASE: f32 = 0.7;
+
+// Relieve del shader: levanta el vértice a lo largo de su normal según el ruido del planeta
+// (congelado en el tiempo 0, para que la silueta no hierva)
+fn displace(position: Vector3, normal: Vector3, uniforms: &Uniforms) -> (Vector3, Vector3) {
+    offset_along_normal(position, normal, |p| animated_noise(p.x, p.y, p.z, 0.0, uniforms.noise_frequency) * uniforms.displacement)
+}
+
+/// Moves `position` along `normal` by `height(position)` and tilts the normal with the slope
+/// of `height` along the surface (finite differences). Helper for vertex animations
+pub fn offset_along_normal(position: Vector3, normal: Vector3, height: impl Fn(Vector3) -> f32) -> (Vector3, Vector3) {
+    let e = DISPLACEMENT_EPSILON;
+    let gradient = Vector3::new(
+        height(position + Vector3::new(e, 0.0, 0.0)) - height(position - Vector3::new(e, 0.0, 0.0)),
+        height(position + Vector3::new(0.0, e, 0.0)) - height(position - Vector3::new(0.0, e, 0.0)),
+        height(position + Vector3::new(0.0, 0.0, e)) - height(position - Vector3::new(0.0, 0.0, e)),
+    ) / (2.0 * e);
+    // Solo cuenta la pendiente a lo largo de la superficie
+    let slope = gradient - normal * gradient.dot(normal);
+    (position + normal * height(position), (normal - slope).normalized())
+}
+
+/// Vertex animation "pulse": the body swells and shrinks like a beating star
+pub fn pulse_vertex_animation(position: Vector3, normal: Vector3, uniforms: &Uniforms) -> (Vector3, Vector3) {
+    let beat = (uniforms.time * PULSE_SPEED).sin() * PULSE_AMPLITUDE;
+    (position * (1.0 + beat), normal)
+}
+
+/// Vertex animation "ripple": waves run across the atmosphere, in bands from pole to pole
+/// that drift around the equator
+pub fn ripple_vertex_animation(position: Vector3, normal: Vector3, uniforms: &Uniforms) -> (Vector3, Vector3) {
+    let time = uniforms.time;
+    offset_along_normal(position, normal, |p| {
+        let bands = (p.y * RIPPLE_BANDS + time * RIPPLE_SPEED).sin();
+        let waves = (p.z.atan2(p.x) * RIPPLE_WAVES - time * RIPPLE_SPEED * 0.5).sin();
+        bands * (0.5 + 0.5 * waves) * RIPPLE_AMPLITUDE
+    })
+}
+
+// Valor pseudoaleatorio estable en [0, 1) para un triángulo, a partir de su centro
+fn triangle_hash(center: Vector3) -> f32 {
+    (center.dot(Vector3::new(12.9898, 78.233, 37.719)).sin() * 43758.547).fract().abs()
+}
+
+// Vértice emitido por la etapa de geometría: los atributos de `source` en otra posición
+fn emitted_vertex(source: &Vertex, position: Vector3, normal: Vector3) -> Vertex {
+    Vertex { position, normal, ..source.clone() }
+}
+
+/// Geometry "prominences": a few surface triangles sprout an arc of flame that rises and
+/// falls over time, as a thin double-sided ribbon between two of their corners
+pub fn prominence_geometry_shader(triangle: [&Vertex; 3], uniforms: &Uniforms, output: &mut Vec<[Vertex; 3]>) {
+    let [a, b, c] = triangle;
+    let center = (a.position + b.position + c.position) / 3.0;
+    let chance = triangle_hash(center);
+    if chance >= PROMINENCE_CHANCE {
+        return;
+    }
+    let normal = (a.normal + b.normal + c.normal).normalized();
+    // Cada llamarada va a su ritmo; nunca desaparece del todo
+    let phase = chance / PROMINENCE_CHANCE * TAU;
+    let height = PROMINENCE_HEIGHT * (0.6 + 0.4 * (uniforms.time * PROMINENCE_SPEED + phase).sin());
+    // Los pies del arco se separan más allá del triángulo para que la llamarada se vea de lejos
+    let (start, end) = (center + (a.position - center) * 3.0, center + (b.position - center) * 3.0);
+    let side = normal.cross(end - start).normalized() * PROMINENCE_WIDTH;
+    let point = |i: usize| {
+        let t = i as f32 / PROMINENCE_SEGMENTS as f32;
+        start + (end - start) * t + normal * ((t * PI).sin() * height)
+    };
+    for i in 0..PROMINENCE_SEGMENTS {
+        let (from, to) = (point(i), point(i + 1));
+        let corners = [from - side, from + side, to + side, to - side].map(|p| emitted_vertex(a, p, normal));
+        output.push([corners[0].clone(), corners[1].clone(), corners[2].clone()]);
+        let [first, _, third, fourth] = corners;
+        output.push([first, third, fourth]);
+    }
+}
+
+/// Geometry "spikes": some surface triangles grow a crystal, a three-sided pyramid standing
+/// on a smaller copy of the triangle
+pub fn spike_geometry_shader(triangle: [&Vertex; 3], _uniforms: &Uniforms, output: &mut Vec<[Vertex; 3]>) {
+    let center = (triangle[0].position + triangle[1].position + triangle[2].position) / 3.0;
+    let chance = triangle_hash(center);
+    if chance >= SPIKE_CHANCE {
+        return;
+    }
+    let normal = (triangle[0].normal + triangle[1].normal + triangle[2].normal).normalized();
+    let apex = center + normal * (SPIKE_HEIGHT * (0.4 + 0.6 * chance / SPIKE_CHANCE));
+    let base = triangle.map(|vertex| center + (vertex.position - center) * SPIKE_BASE);
+    for i in 0..3 {
+        let (from, to) = (base[i], base[(i + 1) % 3]);
+        // Normal de la cara, hacia fuera del cristal
+        let mut face_normal = (to - from).cross(apex - from).normalized();
+        if face_normal.dot((from + to) * 0.5 - center) < 0.0 {
+            face_normal = -face_normal;
+        }
+        output.push([
+            emitted_vertex(triangle[i], from, face_normal),
+            emitted_vertex(triangle[(i + 1) % 3], to, face_normal),
+            emitted_vertex(triangle[i], apex, face_normal),
+        ]);
+    }
+}
+
 pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+    let (mut position, mut normal) = if uniforms.displacement > 0.0 {
+        displace(vertex.position, vertex.normal, uniforms)
+    } else {
+        (vertex.position, vertex.normal)
+    };
+    // Etapa programable: la animación de vértices del cuerpo, sobre el relieve
+    if let Some(animate) = uniforms.vertex_animation {
+        (position, normal) = animate(position, normal, uniforms);
+    }
+    transform_vertex(vertex, position, normal, uniforms)
+}
+
+/// The fixed part of the vertex stage: projects a vertex whose position and normal are
+/// already final in model space. The triangles emitted by the geometry stage go through it
+pub fn project_vertex(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+    transform_vertex(vertex, vertex.position, vertex.normal, uniforms)
+}
+
+// Transformaciones de modelo, vista, proyección y viewport de un vértice ya desplazado
+fn transform_vertex(vertex: &Vertex, position: Vector3, normal: Vector3, uniforms: &Uniforms) -> Vertex {
     // Convert vertex position to homogeneous coordinates (Vec4) by adding a w-component of 1.0
     let position_vec4 = Vector4::new(
-        vertex.position.x,
-        vertex.position.y,
-        vertex.position.z,
+        position.x,
+        position.y,
+        position.z,
         1.0
     );
 
@@ -50,8 +206,11 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
         Vector3::new(clip_position.x, clip_position.y, clip_position.z)
     };
     
+    // Profundidad proyectiva o logarítmica según la configuración
+    let depth = uniforms.depth_mode.depth(ndc.z, clip_position.w);
+
     // Apply Viewport transformation to get screen coordinates
-    let ndc_vec4 = Vector4::new(ndc.x, ndc.y, ndc.z, 1.0);
+    let ndc_vec4 = Vector4::new(ndc.x, ndc.y, depth, 1.0);
     let screen_position = multiply_matrix_vector4(&uniforms.viewport_matrix, &ndc_vec4);
     
     let transformed_position = Vector3::new(
@@ -62,24 +221,42 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     
     // Create a new Vertex with the transformed position
     Vertex {
-        position: vertex.position,
-        normal: vertex.normal,
+        position,
+        normal,
         tex_coords: vertex.tex_coords,
         color: vertex.color,
+        specular: vertex.specular,
+        shininess: vertex.shininess,
+        emissive: vertex.emissive,
+        material_id: vertex.material_id,
+        tangent: vertex.tangent,
+        bitangent: vertex.bitangent,
         transformed_position,
-        transformed_normal: transform_normal(&vertex.normal, &uniforms.model_matrix),
+        world_position: Vector3::new(world_position.x, world_position.y, world_position.z),
+        transformed_normal: transform_normal(&normal, &uniforms.normal_matrix),
+        transformed_tangent: transform_normal(&vertex.tangent, &uniforms.model_matrix),
+        transformed_bitangent: transform_normal(&vertex.bitangent, &uniforms.model_matrix),
+        light_diffuse: vertex.light_diffuse,
+        light_specular: vertex.light_specular,
     }
 }
 
-// Función de ruido pseudoaleatorio mejorada para efectos más exóticos
-fn exotic_noise(x: f32, y: f32, z: f32, time: f32, frequency: f32) -> f32 {
-    let freq = frequency * 2.0;
-    let n1 = (x * freq * 1.5 + time * 0.7).sin() * (y * freq + time * 0.5).cos() * (z * freq * 2.0 + time * 0.3).sin();
-    let n2 = (x * freq * 3.0 + time * 1.2).cos() * (y * freq * 1.5 + time * 0.8).sin() * (z * freq + time * 1.1).cos();
-    let n3 = (x * freq * 6.0 + time * 2.0).sin() * (y * freq * 4.0 + time * 1.5).cos() * (z * freq * 3.0 + time * 0.9).sin();
-    
-    // Combinar diferentes frecuencias para efecto más complejo
-    (n1 * 0.5 + n2 * 0.3 + n3 * 0.2).abs()
+/// View-dependent Fresnel factor: 0 where the surface faces the camera, rising to 1 at the
+/// silhouette. Higher `power` concentrates it on a thinner rim
+pub fn fresnel(fragment: &Fragment, uniforms: &Uniforms, power: f32) -> f32 {
+    let to_camera = uniforms.camera_position - fragment.world_position;
+    let distance = to_camera.length();
+    if distance <= 0.0 {
+        return 0.0;
+    }
+    let facing = fragment.normal.dot(to_camera / distance).clamp(0.0, 1.0);
+    (1.0 - facing).powf(power)
+}
+
+// Mezcla el color con el borde iluminado de la atmósfera o el hielo
+fn with_rim(color: Vector3, fragment: &Fragment, uniforms: &Uniforms, rim_color: [f32; 3], strength: f32, power: f32) -> Vector3 {
+    let rim = fresnel(fragment, uniforms, power) * strength;
+    color * (1.0 - rim) + rgb(rim_color) * rim
 }
 
 // Shader simple para cualquier objeto que no tenga un shader específico
@@ -88,15 +265,20 @@ pub fn fragment_shader(fragment: &Fragment, _uniforms: &Uniforms) -> Vector3 {
     fragment.color
 }
 
+// Modelos pintados a mano: el color de cada vértice (o el del material) sin textura ni ruido
+pub fn vertex_color_fragment_shader(fragment: &Fragment, _uniforms: &Uniforms) -> Vector3 {
+    fragment.diffuse + fragment.emissive
+}
+
 // Shader específico para el sol con efectos exóticos de energía cósmica
-pub fn sun_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
-    let pos = fragment.world_position;
+pub fn sun_fragment_shader(fragment: &Fragment, uniforms: &Uniforms, params: &SunParams) -> Vector3 {
+    let pos = fragment.object_position;
     let time = uniforms.time;
     
     // Calcular ruido en múltiples escalas para efecto de energía cósmica
-    let cosmic_energy = exotic_noise(pos.x, pos.y, pos.z, time, 3.0) * 0.8 +
-                       exotic_noise(pos.x * 2.0, pos.y * 2.0, pos.z * 2.0, time + 100.0, 2.0) * 0.4 +
-                       exotic_noise(pos.x * 4.0, pos.y * 4.0, pos.z * 4.0, time + 200.0, 1.5) * 0.2;
+    let cosmic_energy = animated_noise(pos.x, pos.y, pos.z, time, params.energy_frequency) * 0.8 +
+                       animated_noise(pos.x * 2.0, pos.y * 2.0, pos.z * 2.0, time + 100.0, params.energy_detail_frequency) * 0.4 +
+                       animated_noise(pos.x * 4.0, pos.y * 4.0, pos.z * 4.0, time + 200.0, params.energy_fine_frequency) * 0.2;
     
     // Efecto de pulsación multidimensional
     let pulsation = (time * 1.5).sin().abs() * 0.3 + (time * 2.2).cos().abs() * 0.2 + 0.5;
@@ -105,9 +287,9 @@ pub fn sun_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3
     let distance_from_center = pos.length();
     
     // Colores exóticos de energía cósmica
-    let core_color = Vector3::new(1.0, 0.1, 0.8);      // Rosa neón central
-    let surface_color = Vector3::new(0.2, 0.9, 1.0);   // Cian eléctrico
-    let corona_color = Vector3::new(0.9, 1.0, 0.1);    // Amarillo neón
+    let core_color = rgb(params.core_color);          // Rosa neón central
+    let surface_color = rgb(params.surface_color);    // Cian eléctrico
+    let corona_color = rgb(params.corona_color);      // Amarillo neón
     
     // Determinar zona de la estrella basada en la distancia
     let zone_factor = if distance_from_center < 0.6 {
@@ -133,12 +315,12 @@ pub fn sun_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3
     let intensity = (cosmic_energy * 2.0 + pulsation) * 0.7;
     
     // Efecto de "explosiones" de energía aleatorias
-    let energy_burst = exotic_noise(pos.x * 0.3, pos.y * 0.3, pos.z * 0.3, time * 3.0, 0.5);
+    let energy_burst = animated_noise(pos.x * 0.3, pos.y * 0.3, pos.z * 0.3, time * 3.0, params.burst_frequency);
     let burst_effect = (energy_burst * 3.0 + (time * 4.0).sin().abs() * 0.7).min(1.0);
     
     // Combinar todo para el color final con efectos de energía
     let final_color = base_color * intensity * (1.0 - burst_effect * 0.4) + 
-                     Vector3::new(1.0, 1.0, 0.5) * burst_effect * 0.6;
+                     rgb(params.burst_color) * burst_effect * 0.6;
     
     // Asegurar que los valores estén en el rango [0, 1]
     Vector3::new(
@@ -149,18 +331,18 @@ pub fn sun_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3
 }
 
 // Shader para Mercurio con colores metálicos exóticos
-pub fn mercury_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
-    let pos = fragment.world_position;
+pub fn mercury_fragment_shader(fragment: &Fragment, uniforms: &Uniforms, params: &MercuryParams) -> Vector3 {
+    let pos = fragment.object_position;
     let time = uniforms.time;
     
     // Patrones complejos para superficie alienígena
-    let crystal_pattern = exotic_noise(pos.x, pos.y, pos.z, time, 4.0);
-    let metal_veins = exotic_noise(pos.x * 2.0, pos.y * 2.0, pos.z * 2.0, time + 50.0, 3.0);
+    let crystal_pattern = animated_noise(pos.x, pos.y, pos.z, time, params.crystal_frequency);
+    let metal_veins = animated_noise(pos.x * 2.0, pos.y * 2.0, pos.z * 2.0, time + 50.0, params.vein_frequency);
     
     // Colores metálicos exóticos
-    let base_metal = Vector3::new(0.2, 0.3, 0.2);      // Púrpura metálico
-    let crystal_color = Vector3::new(0.4, 0.8, 0.9);   // Azul cristalino
-    let vein_color = Vector3::new(0.9, 0.6, 0.3);      // Naranja metálico
+    let base_metal = rgb(params.base_metal);          // Púrpura metálico
+    let crystal_color = rgb(params.crystal_color);    // Azul cristalino
+    let vein_color = rgb(params.vein_color);          // Naranja metálico
     
     // Mezclar colores según patrones
     let crystal_factor = (crystal_pattern * 0.6 + 0.4).powf(1.5);
@@ -171,7 +353,7 @@ pub fn mercury_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vect
     
     // Efecto de reflexión iridiscente
     let iridescence = (pos.x * 8.0 + time * 2.0).sin().abs() * 0.2;
-    let iridescent_color = final_color * (1.0 - iridescence) + Vector3::new(0.3, 0.9, 0.7) * iridescence;
+    let iridescent_color = final_color * (1.0 - iridescence) + rgb(params.iridescent_color) * iridescence;
     
     Vector3::new(
         iridescent_color.x.clamp(0.0, 1.0),
@@ -181,20 +363,20 @@ pub fn mercury_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vect
 }
 
 // Shader para la Tierra con colores alienígenas
-pub fn earth_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
-    let pos = fragment.world_position;
+pub fn earth_fragment_shader(fragment: &Fragment, uniforms: &Uniforms, params: &EarthParams) -> Vector3 {
+    let pos = fragment.object_position;
     let time = uniforms.time;
     
     // Patrones alienígenas para continentes y océanos
-    let continent_pattern = exotic_noise(pos.x, pos.y, pos.z, time, 2.0);
-    let alien_rivers = exotic_noise(pos.x * 3.0, pos.y * 3.0, pos.z * 3.0, time + 30.0, 1.5);
-    let bio_luminescence = exotic_noise(pos.x * 5.0, pos.y * 5.0, pos.z * 5.0, time * 2.0, 1.0);
+    let continent_pattern = animated_noise(pos.x, pos.y, pos.z, time, params.continent_frequency);
+    let alien_rivers = animated_noise(pos.x * 3.0, pos.y * 3.0, pos.z * 3.0, time + 30.0, params.river_frequency);
+    let bio_luminescence = animated_noise(pos.x * 5.0, pos.y * 5.0, pos.z * 5.0, time * 2.0, params.bio_frequency);
     
     // Colores alienígenas exóticos
-    let ocean_color = Vector3::new(0.1, 0.8, 0.6);     // Verde azulado fluorescente
-    let land_color = Vector3::new(0.9, 0.4, 0.7);      // Rosa alienígena
-    let river_color = Vector3::new(0.3, 0.9, 0.9);     // Cian brillante
-    let bio_color = Vector3::new(0.8, 0.2, 0.9);       // Púrpura bioluminiscente
+    let ocean_color = rgb(params.ocean_color);        // Verde azulado fluorescente
+    let land_color = rgb(params.land_color);          // Rosa alienígena
+    let river_color = rgb(params.river_color);        // Cian brillante
+    let bio_color = rgb(params.bio_color);            // Púrpura bioluminiscente
     
     // Determinar patrones
     let is_land = (continent_pattern * 0.8 + 0.2).max(0.0).min(1.0);
@@ -217,19 +399,19 @@ pub fn earth_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector
 }
 
 // Shader para Marte con colores de paisaje alienígena
-pub fn mars_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
-    let pos = fragment.world_position;
+pub fn mars_fragment_shader(fragment: &Fragment, uniforms: &Uniforms, params: &MarsParams) -> Vector3 {
+    let pos = fragment.object_position;
     let time = uniforms.time;
     
     // Patrones de terreno alienígena
-    let desert_pattern = exotic_noise(pos.x, pos.y, pos.z, time, 2.5);
-    let canyon_pattern = exotic_noise(pos.x * 1.5, pos.y * 1.5, pos.z * 1.5, time + 20.0, 2.0);
-    let dust_storm = exotic_noise(pos.x * 0.5, pos.y * 0.5, pos.z * 0.5, time * 0.3, 0.8);
+    let desert_pattern = animated_noise(pos.x, pos.y, pos.z, time, params.desert_frequency);
+    let canyon_pattern = animated_noise(pos.x * 1.5, pos.y * 1.5, pos.z * 1.5, time + 20.0, params.canyon_frequency);
+    let dust_storm = animated_noise(pos.x * 0.5, pos.y * 0.5, pos.z * 0.5, time * 0.3, params.storm_frequency);
     
     // Colores de paisaje alienígena
-    let base_color = Vector3::new(0.8, 0.2, 0.4);      // Rosa rojizo
-    let canyon_color = Vector3::new(0.6, 0.8, 0.2);    // Verde amarillento
-    let storm_color = Vector3::new(0.9, 0.7, 0.3);     // Amarillo dorado
+    let base_color = rgb(params.base_color);          // Rosa rojizo
+    let canyon_color = rgb(params.canyon_color);      // Verde amarillento
+    let storm_color = rgb(params.storm_color);        // Amarillo dorado
     
     // Aplicar patrones
     let desert_factor = (desert_pattern * 0.7 + 0.3).powf(1.2);
@@ -248,19 +430,19 @@ pub fn mars_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3
 }
 
 // Shader para Urano con colores de gas nebular
-pub fn uranus_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
-    let pos = fragment.world_position;
+pub fn uranus_fragment_shader(fragment: &Fragment, uniforms: &Uniforms, params: &UranusParams) -> Vector3 {
+    let pos = fragment.object_position;
     let time = uniforms.time;
     
     // Patrones de gas nebular
-    let nebula_bands = exotic_noise(pos.x, pos.y, pos.z, time * 0.2, 1.2);
-    let gas_vortices = exotic_noise(pos.x * 2.0, pos.y * 2.0, pos.z * 2.0, time * 0.5, 1.8);
-    let energy_clouds = exotic_noise(pos.x * 0.7, pos.y * 0.7, pos.z * 0.7, time * 1.5, 0.9);
+    let nebula_bands = animated_noise(pos.x, pos.y, pos.z, time * 0.2, params.band_frequency);
+    let gas_vortices = animated_noise(pos.x * 2.0, pos.y * 2.0, pos.z * 2.0, time * 0.5, params.vortex_frequency);
+    let energy_clouds = animated_noise(pos.x * 0.7, pos.y * 0.7, pos.z * 0.7, time * 1.5, params.energy_frequency);
     
     // Colores de nebulosa
-    let deep_nebula = Vector3::new(0.3, 0.1, 0.8);     // Azul profundo
-    let vortex_color = Vector3::new(0.7, 0.3, 0.9);    // Púrpura vibrante
-    let energy_color = Vector3::new(0.1, 0.9, 0.8);    // Verde azulado energético
+    let deep_nebula = rgb(params.deep_nebula);        // Azul profundo
+    let vortex_color = rgb(params.vortex_color);      // Púrpura vibrante
+    let energy_color = rgb(params.energy_color);      // Verde azulado energético
     
     // Aplicar patrones nebulares
     let band_factor = (nebula_bands * 0.6 + 0.4).powf(1.3);
@@ -270,6 +452,8 @@ pub fn uranus_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vecto
     let banded_gas = deep_nebula * (1.0 - band_factor) + vortex_color * band_factor;
     let vortex_gas = banded_gas * (1.0 - vortex_factor * 0.3) + vortex_color * vortex_factor * 0.3;
     let final_color = vortex_gas * (1.0 - energy_factor * 0.4) + energy_color * energy_factor * 0.4;
+    // Atmósfera visible en el borde del gigante gaseoso
+    let final_color = with_rim(final_color, fragment, uniforms, params.rim_color, params.rim_strength, params.rim_power);
     
     Vector3::new(
         final_color.x.clamp(0.0, 1.0),
@@ -279,20 +463,20 @@ pub fn uranus_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vecto
 }
 
 // Shader para nave espacial con tecnología alienígena
-pub fn nave_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
-    let pos = fragment.world_position;
+pub fn nave_fragment_shader(fragment: &Fragment, uniforms: &Uniforms, params: &NaveParams) -> Vector3 {
+    let pos = fragment.object_position;
     let time = uniforms.time;
     
     // Patrones de tecnología alienígena
-    let circuit_pattern = exotic_noise(pos.x, pos.y, pos.z, time * 2.0, 4.0);
-    let energy_grid = exotic_noise(pos.x * 1.5, pos.y * 1.5, pos.z * 1.5, time * 1.2, 3.0);
-    let hologram_effect = exotic_noise(pos.x * 0.8, pos.y * 0.8, pos.z * 0.8, time * 3.0, 2.0);
+    let circuit_pattern = animated_noise(pos.x, pos.y, pos.z, time * 2.0, params.circuit_frequency);
+    let energy_grid = animated_noise(pos.x * 1.5, pos.y * 1.5, pos.z * 1.5, time * 1.2, params.grid_frequency);
+    let hologram_effect = animated_noise(pos.x * 0.8, pos.y * 0.8, pos.z * 0.8, time * 3.0, params.hologram_frequency);
     
     // Colores de tecnología futurista
-    let ship_base = Vector3::new(0.2, 0.1, 0.4);       // Púrpura oscuro
-    let circuit_color = Vector3::new(0.1, 0.9, 0.6);   // Verde neón
-    let energy_color = Vector3::new(0.8, 0.3, 0.9);    // Rosa eléctrico
-    let hologram_color = Vector3::new(0.3, 0.7, 1.0);  // Azul holográfico
+    let ship_base = rgb(params.ship_base);            // Púrpura oscuro
+    let circuit_color = rgb(params.circuit_color);    // Verde neón
+    let energy_color = rgb(params.energy_color);      // Rosa eléctrico
+    let hologram_color = rgb(params.hologram_color);  // Azul holográfico
     
     // Aplicar patrones tecnológicos
     let circuit_factor = (circuit_pattern * 0.7 + 0.3).powf(2.0);
@@ -305,7 +489,8 @@ pub fn nave_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3
     
     // Efecto de pulsación de energía
     let energy_pulse = (time * 4.0).sin().abs() * 0.4 + 0.6;
-    let pulsed_color = final_color * energy_pulse;
+    // Color de cada sección de la nave (casco, cabina, motores) según su material
+    let pulsed_color = final_color * energy_pulse * fragment.diffuse + fragment.emissive;
     
     Vector3::new(
         pulsed_color.x.clamp(0.0, 1.0),
@@ -315,19 +500,19 @@ pub fn nave_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3
 }
 
 // Shader para Zephyr con colores de tormenta de cristal
-pub fn zephyr_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
-    let pos = fragment.world_position;
+pub fn zephyr_fragment_shader(fragment: &Fragment, uniforms: &Uniforms, params: &ZephyrParams) -> Vector3 {
+    let pos = fragment.object_position;
     let time = uniforms.time;
     
     // Patrones de tormenta de cristal
-    let crystal_storm = exotic_noise(pos.x, pos.y, pos.z, time * 1.5, 2.5);
-    let wind_currents = exotic_noise(pos.x * 1.8, pos.y * 1.8, pos.z * 1.8, time * 0.8, 2.2);
-    let electric_arcs = exotic_noise(pos.x * 0.6, pos.y * 0.6, pos.z * 0.6, time * 2.5, 1.5);
+    let crystal_storm = animated_noise(pos.x, pos.y, pos.z, time * 1.5, params.storm_frequency);
+    let wind_currents = animated_noise(pos.x * 1.8, pos.y * 1.8, pos.z * 1.8, time * 0.8, params.wind_frequency);
+    let electric_arcs = animated_noise(pos.x * 0.6, pos.y * 0.6, pos.z * 0.6, time * 2.5, params.electric_frequency);
     
     // Colores de tormenta exótica
-    let storm_base = Vector3::new(0.1, 0.3, 0.7);      // Azul eléctrico
-    let crystal_color = Vector3::new(0.4, 0.9, 0.8);   // Verde azulado cristalino
-    let electric_color = Vector3::new(0.9, 0.5, 1.0);  // Rosa eléctrico
+    let storm_base = rgb(params.storm_base);          // Azul eléctrico
+    let crystal_color = rgb(params.crystal_color);    // Verde azulado cristalino
+    let electric_color = rgb(params.electric_color);  // Rosa eléctrico
     
     let storm_factor = (crystal_storm * 0.8 + 0.2).powf(1.4);
     let wind_factor = (wind_currents * 0.6 + 0.4).powf(1.6);
@@ -336,6 +521,8 @@ pub fn zephyr_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vecto
     let stormy_sky = storm_base * (1.0 - storm_factor) + crystal_color * storm_factor;
     let with_winds = stormy_sky * (1.0 - wind_factor * 0.3) + crystal_color * wind_factor * 0.3;
     let final_color = with_winds * (1.0 - electric_factor * 0.5) + electric_color * electric_factor * 0.5;
+    // Halo de la tormenta en la silueta
+    let final_color = with_rim(final_color, fragment, uniforms, params.rim_color, params.rim_strength, params.rim_power);
     
     Vector3::new(
         final_color.x.clamp(0.0, 1.0),
@@ -345,20 +532,20 @@ pub fn zephyr_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vecto
 }
 
 // Shader para Pyrion con colores de volcanes de azufre
-pub fn pyrion_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
-    let pos = fragment.world_position;
+pub fn pyrion_fragment_shader(fragment: &Fragment, uniforms: &Uniforms, params: &PyrionParams) -> Vector3 {
+    let pos = fragment.object_position;
     let time = uniforms.time;
     
     // Patrones de volcanes exóticos
-    let sulfur_flows = exotic_noise(pos.x, pos.y, pos.z, time * 0.7, 2.0);
-    let volcanic_cracks = exotic_noise(pos.x * 2.2, pos.y * 2.2, pos.z * 2.2, time * 1.1, 1.8);
-    let magma_pools = exotic_noise(pos.x * 0.9, pos.y * 0.9, pos.z * 0.9, time * 0.5, 1.3);
+    let sulfur_flows = animated_noise(pos.x, pos.y, pos.z, time * 0.7, params.sulfur_frequency);
+    let volcanic_cracks = animated_noise(pos.x * 2.2, pos.y * 2.2, pos.z * 2.2, time * 1.1, params.crack_frequency);
+    let magma_pools = animated_noise(pos.x * 0.9, pos.y * 0.9, pos.z * 0.9, time * 0.5, params.magma_frequency);
     
     // Colores de volcanes alienígenas
-    let crust_color = Vector3::new(0.8, 0.6, 0.1);     // Amarillo sulfúrico
-    let sulfur_color = Vector3::new(0.9, 0.8, 0.2);    // Amarillo brillante
-    let magma_color = Vector3::new(1.0, 0.4, 0.1);     // Naranja incandescente
-    let crack_color = Vector3::new(0.6, 0.3, 0.1);     // Marrón oscuro
+    let crust_color = rgb(params.crust_color);        // Amarillo sulfúrico
+    let sulfur_color = rgb(params.sulfur_color);      // Amarillo brillante
+    let magma_color = rgb(params.magma_color);        // Naranja incandescente
+    let crack_color = rgb(params.crack_color);        // Marrón oscuro
     
     let sulfur_factor = (sulfur_flows * 0.7 + 0.3).powf(1.3);
     let crack_factor = (volcanic_cracks * 0.5 + 0.5).powf(1.8);
@@ -376,20 +563,20 @@ pub fn pyrion_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vecto
 }
 
 // Shader para Glacia con colores de hielo alienígena
-pub fn glacia_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
-    let pos = fragment.world_position;
+pub fn glacia_fragment_shader(fragment: &Fragment, uniforms: &Uniforms, params: &GlaciaParams) -> Vector3 {
+    let pos = fragment.object_position;
     let time = uniforms.time;
     
     // Patrones de hielo exótico
-    let alien_ice = exotic_noise(pos.x, pos.y, pos.z, time * 0.3, 2.8);
-    let frozen_gas = exotic_noise(pos.x * 1.7, pos.y * 1.7, pos.z * 1.7, time * 0.9, 2.1);
-    let crystal_growth = exotic_noise(pos.x * 0.8, pos.y * 0.8, pos.z * 0.8, time * 1.7, 1.4);
+    let alien_ice = animated_noise(pos.x, pos.y, pos.z, time * 0.3, params.ice_frequency);
+    let frozen_gas = animated_noise(pos.x * 1.7, pos.y * 1.7, pos.z * 1.7, time * 0.9, params.gas_frequency);
+    let crystal_growth = animated_noise(pos.x * 0.8, pos.y * 0.8, pos.z * 0.8, time * 1.7, params.crystal_frequency);
     
     // Colores de hielo alienígena
-    let ice_base = Vector3::new(0.7, 0.9, 1.0);        // Azul hielo
-    let alien_ice_color = Vector3::new(0.4, 0.8, 0.5); // Verde hielo
-    let gas_color = Vector3::new(0.8, 0.5, 0.9);       // Púrpura congelado
-    let crystal_color = Vector3::new(0.3, 0.7, 0.9);   // Azul cristal
+    let ice_base = rgb(params.ice_base);              // Azul hielo
+    let alien_ice_color = rgb(params.alien_ice_color); // Verde hielo
+    let gas_color = rgb(params.gas_color);            // Púrpura congelado
+    let crystal_color = rgb(params.crystal_color);    // Azul cristal
     
     let ice_factor = (alien_ice * 0.6 + 0.4).powf(1.2);
     let gas_factor = (frozen_gas * 0.5 + 0.5).powf(1.5);
@@ -398,6 +585,8 @@ pub fn glacia_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vecto
     let icy_surface = ice_base * (1.0 - ice_factor) + alien_ice_color * ice_factor;
     let with_gas = icy_surface * (1.0 - gas_factor * 0.3) + gas_color * gas_factor * 0.3;
     let final_color = with_gas * (1.0 - crystal_factor * 0.4) + crystal_color * crystal_factor * 0.4;
+    // El hielo refleja más en ángulos rasantes
+    let final_color = with_rim(final_color, fragment, uniforms, params.rim_color, params.rim_strength, params.rim_power);
     
     Vector3::new(
         final_color.x.clamp(0.0, 1.0),
@@ -407,20 +596,20 @@ pub fn glacia_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vecto
 }
 
 // Shader para Umbraleth con colores de energía oscura
-pub fn umbraleth_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
-    let pos = fragment.world_position;
+pub fn umbraleth_fragment_shader(fragment: &Fragment, uniforms: &Uniforms, params: &UmbralethParams) -> Vector3 {
+    let pos = fragment.object_position;
     let time = uniforms.time;
     
     // Patrones de energía oscura y materia exótica
-    let dark_energy = exotic_noise(pos.x, pos.y, pos.z, time * 0.4, 1.5);
-    let void_vortices = exotic_noise(pos.x * 1.3, pos.y * 1.3, pos.z * 1.3, time * 0.6, 1.8);
-    let quantum_fluctuations = exotic_noise(pos.x * 0.5, pos.y * 0.5, pos.z * 0.5, time * 2.0, 0.9);
+    let dark_energy = animated_noise(pos.x, pos.y, pos.z, time * 0.4, params.energy_frequency);
+    let void_vortices = animated_noise(pos.x * 1.3, pos.y * 1.3, pos.z * 1.3, time * 0.6, params.vortex_frequency);
+    let quantum_fluctuations = animated_noise(pos.x * 0.5, pos.y * 0.5, pos.z * 0.5, time * 2.0, params.quantum_frequency);
     
     // Colores de energía oscura
-    let void_color = Vector3::new(0.05, 0.02, 0.1);    // Negro violáceo
-    let energy_color = Vector3::new(0.4, 0.1, 0.6);    // Púrpura oscuro energético
-    let vortex_color = Vector3::new(0.2, 0.05, 0.4);   // Púrpura muy oscuro
-    let quantum_color = Vector3::new(0.6, 0.2, 0.8);   // Púrpura brillante
+    let void_color = rgb(params.void_color);          // Negro violáceo
+    let energy_color = rgb(params.energy_color);      // Púrpura oscuro energético
+    let vortex_color = rgb(params.vortex_color);      // Púrpura muy oscuro
+    let quantum_color = rgb(params.quantum_color);    // Púrpura brillante
     
     let energy_factor = (dark_energy * 0.5 + 0.5).powf(1.7);
     let vortex_factor = (void_vortices * 0.4 + 0.6).powf(2.0);
@@ -438,20 +627,20 @@ pub fn umbraleth_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Ve
 }
 
 // Shader para Verdis con colores de bosque bioluminiscente
-pub fn verdis_fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Vector3 {
-    let pos = fragment.world_position;
+pub fn verdis_fragment_shader(fragment: &Fragment, uniforms: &Uniforms, params: &VerdisParams) -> Vector3 {
+    let pos = fragment.object_position;
     let time = uniforms.time;
     
     // Patrones de flora y fauna alienígena
-    let alien_flora = exotic_noise(pos.x, pos.y, pos.z, time * 0.8, 2.3);
-    let bio_lights = exotic_noise(pos.x * 1.6, pos.y * 1.6, pos.z * 1.6, time * 1.4, 1.9);
-    let fungal_networks = exotic_noise(pos.x * 0.7, pos.y * 0.7, pos.z * 0.7, time * 0.9, 1.2);
+    let alien_flora = animated_noise(pos.x, pos.y, pos.z, time * 0.8, params.flora_frequency);
+    let bio_lights = animated_noise(pos.x * 1.6, pos.y * 1.6, pos.z * 1.6, time * 1.4, params.bio_frequency);
+    let fungal_networks = animated_noise(pos.x * 0.7, pos.y * 0.7, pos.z * 0.7, time * 0.9, params.fungal_frequency);
     
     // Colores de bosque alienígena
-    let flora_base = Vector3::new(0.2, 0.7, 0.3);      // Verde alienígena
-    let bio_color = Vector3::new(0.1, 0.9, 0.5);       // Verde neón
-    let fungal_color = Vector3::new(0.8, 0.3, 0.6);    // Rosa micológico
-    let light_color = Vector3::new(0.4, 1.0, 0.7);     // Verde brillante
+    let flora_base = rgb(params.flora_base);          // Verde alienígena
+    let bio_color = rgb(params.bio_color);            // Verde neón
+    let fungal_color = rgb(params.fungal_color);      // Rosa micológico
+    let light_color = rgb(params.light_color);        // Verde brillante
     
     let flora_factor = (alien_flora * 0.7 + 0.3).powf(1.4);
     let bio_factor = (bio_lights * 0.6 + 0.4).powf(1.8);
@@ -0,0 +1,235 @@
+// shadow.rs
+// Sombras de las luces: mapa omnidireccional (seis caras de 90° renderizadas solo con
+// profundidad desde la luz), eclipses analíticos contra las esferas de los cuerpos o rayos de
+// sombra desde cada punto hacia cada luz contra esas mismas esferas
+use crate::bounds::BoundingSphere;
+use crate::light::{Light, LightKind};
+use crate::simd::dot;
+use rayon::prelude::*;
+use raylib::prelude::*;
+
+// Los triángulos más cerca que esto del plano de la luz no se proyectan en esa cara
+const NEAR: f32 = 0.05;
+
+/// Shadow data the lighting stage looks up for the lights that cast shadows
+pub enum Shadows<'a> {
+    Map(&'a ShadowMap),
+    // Esferas de los cuerpos que pueden tapar la luz
+    Spheres(&'a [BoundingSphere]),
+    // Las mismas esferas, con un rayo por punto y luz: sombras duras de todas las luces
+    Rays(&'a [BoundingSphere]),
+}
+
+impl Shadows<'_> {
+    /// Whether `light` is shadowed: with rays every light is, otherwise only those that cast shadows
+    pub fn applies_to(&self, light: &Light) -> bool {
+        matches!(self, Shadows::Rays(_)) || light.casts_shadows
+    }
+
+    /// Fraction (0..1) of `light` reaching `world_position`
+    pub fn visibility(&self, world_position: Vector3, light: &Light, bias: f32) -> f32 {
+        match self {
+            Shadows::Map(map) => map.visibility(world_position, bias),
+            Shadows::Spheres(spheres) => sphere_visibility(world_position, light.position, light.radius, spheres),
+            Shadows::Rays(spheres) => ray_visibility(world_position, light, spheres),
+        }
+    }
+}
+
+/// Fraction of the light's disc left uncovered by the spheres between `position` and the light,
+/// from the angular sizes of both seen from `position`; the partial overlap gives a soft
+/// penumbra. Spheres that contain `position` (its own body) are ignored
+pub fn sphere_visibility(position: Vector3, light_position: Vector3, light_radius: f32, spheres: &[BoundingSphere]) -> f32 {
+    let to_light = light_position - position;
+    let light_distance = dot(to_light, to_light).sqrt();
+    if light_distance <= 0.0 {
+        return 1.0;
+    }
+    let light_direction = to_light / light_distance;
+    // Una luz puntual sigue teniendo un disco mínimo para que el borde no sea de un píxel
+    let light_angle = (light_radius / light_distance).min(1.0).asin().max(1e-3);
+
+    let mut visibility = 1.0;
+    for sphere in spheres {
+        let to_sphere = sphere.center - position;
+        let distance = dot(to_sphere, to_sphere).sqrt();
+        // Los triángulos de la malla quedan algo dentro de su esfera; las esferas tras la luz no tapan
+        if distance <= sphere.radius * 1.05 || distance >= light_distance {
+            continue;
+        }
+        let sphere_angle = (sphere.radius / distance).min(1.0).asin();
+        let separation = dot(to_sphere / distance, light_direction).clamp(-1.0, 1.0).acos();
+        if separation >= light_angle + sphere_angle {
+            continue;
+        }
+        // Cobertura máxima (total, o anular si la esfera se ve más pequeña que la luz), alcanzada
+        // cuando un disco queda dentro del otro; suavizada en la penumbra
+        let full = (sphere_angle / light_angle).powi(2).min(1.0);
+        let inner = (light_angle - sphere_angle).abs();
+        let t = ((light_angle + sphere_angle - separation) / (light_angle + sphere_angle - inner).max(1e-6)).clamp(0.0, 1.0);
+        visibility *= 1.0 - full * t * t * (3.0 - 2.0 * t);
+    }
+    visibility
+}
+
+/// 1 if the ray from `position` towards `light` reaches it (for a directional light, leaves
+/// along its direction) without hitting any of the spheres, 0 otherwise: hard shadows exact
+/// to the pixel. Spheres that contain `position` (its own body) are ignored
+pub fn ray_visibility(position: Vector3, light: &Light, spheres: &[BoundingSphere]) -> f32 {
+    let (direction, light_distance) = match light.kind {
+        LightKind::Directional { direction } => (-direction.normalized(), f32::INFINITY),
+        _ => {
+            let to_light = light.position - position;
+            let distance = dot(to_light, to_light).sqrt();
+            if distance <= 0.0 {
+                return 1.0;
+            }
+            (to_light / distance, distance)
+        }
+    };
+
+    for sphere in spheres {
+        let to_center = sphere.center - position;
+        let distance_squared = dot(to_center, to_center);
+        // Igual que con los eclipses: su propio cuerpo no cuenta
+        if distance_squared <= (sphere.radius * 1.05).powi(2) {
+            continue;
+        }
+        // Punto del rayo más cercano al centro; por detrás del punto la esfera no tapa nada
+        let along = dot(to_center, direction);
+        if along <= 0.0 {
+            continue;
+        }
+        let discriminant = along * along - (distance_squared - sphere.radius * sphere.radius);
+        if discriminant >= 0.0 && along - discriminant.sqrt() < light_distance {
+            return 0.0;
+        }
+    }
+    1.0
+}
+
+/// Depth seen from a point light in every direction, one square face per axis
+/// (+X, -X, +Y, -Y, +Z, -Z). Each texel stores the distance along its face's axis
+#[derive(Default)]
+pub struct ShadowMap {
+    pub position: Vector3,
+    size: usize,
+    faces: Vec<Vec<f32>>,
+}
+
+impl ShadowMap {
+    pub fn new() -> Self {
+        ShadowMap { position: Vector3::zero(), size: 0, faces: Vec::new() }
+    }
+
+    /// Rasterizes the triangles (world-space corners) into every face as seen from `position`,
+    /// keeping the closest depth per texel. `size` is the side of each face in texels
+    pub fn render(&mut self, position: Vector3, size: usize, triangles: &[[Vector3; 3]]) {
+        if self.size != size {
+            self.size = size;
+            self.faces = vec![vec![f32::INFINITY; size * size]; 6];
+        }
+        self.position = position;
+
+        self.faces.par_iter_mut().enumerate().for_each(|(face, depth)| {
+            depth.fill(f32::INFINITY);
+            for corners in triangles {
+                let projected = corners.map(|corner| face_coords(face, corner - position));
+                // El triángulo cae detrás de la cara; lo recoge la cara vecina
+                if projected.iter().any(|&(_, _, z)| z <= NEAR) {
+                    continue;
+                }
+                rasterize_depth(depth, size, projected.map(|(u, v, z)| (to_texel(u / z, size), to_texel(v / z, size), 1.0 / z)));
+            }
+        });
+    }
+
+    /// Fraction (0..1) of the light reaching `world_position`, filtered over the 3x3 texels
+    /// around it. `bias` is relative to the distance, so it grows with the texel footprint
+    pub fn visibility(&self, world_position: Vector3, bias: f32) -> f32 {
+        if self.size == 0 {
+            return 1.0;
+        }
+        let (face, (u, v, z)) = major_face(world_position - self.position);
+        let depth = &self.faces[face];
+        let x = to_texel(u / z, self.size) as i32;
+        let y = to_texel(v / z, self.size) as i32;
+        let limit = self.size as i32 - 1;
+        let threshold = z * (1.0 - bias);
+
+        let mut lit = 0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let sx = (x + dx).clamp(0, limit) as usize;
+                let sy = (y + dy).clamp(0, limit) as usize;
+                if depth[sy * self.size + sx] >= threshold {
+                    lit += 1;
+                }
+            }
+        }
+        lit as f32 / 9.0
+    }
+}
+
+// Coordenadas (u, v, profundidad) de una dirección en la cara indicada
+fn face_coords(face: usize, d: Vector3) -> (f32, f32, f32) {
+    match face {
+        0 => (d.y, d.z, d.x),
+        1 => (d.y, d.z, -d.x),
+        2 => (d.x, d.z, d.y),
+        3 => (d.x, d.z, -d.y),
+        4 => (d.x, d.y, d.z),
+        _ => (d.x, d.y, -d.z),
+    }
+}
+
+// Cara del eje dominante de la dirección, con sus coordenadas
+fn major_face(d: Vector3) -> (usize, (f32, f32, f32)) {
+    let (ax, ay, az) = (d.x.abs(), d.y.abs(), d.z.abs());
+    let face = if ax >= ay && ax >= az {
+        if d.x >= 0.0 { 0 } else { 1 }
+    } else if ay >= az {
+        if d.y >= 0.0 { 2 } else { 3 }
+    } else if d.z >= 0.0 {
+        4
+    } else {
+        5
+    };
+    (face, face_coords(face, d))
+}
+
+// De -1..1 en la cara a coordenadas de texel
+fn to_texel(coordinate: f32, size: usize) -> f32 {
+    (coordinate * 0.5 + 0.5) * size as f32
+}
+
+// Rasteriza un triángulo (x, y, 1/z) en la cara; 1/z es lineal en la proyección
+fn rasterize_depth(depth: &mut [f32], size: usize, [(ax, ay, az), (bx, by, bz), (cx, cy, cz)]: [(f32, f32, f32); 3]) {
+    let area = (bx - ax) * (cy - ay) - (by - ay) * (cx - ax);
+    if area.abs() < 1e-10 {
+        return;
+    }
+    let min_x = ax.min(bx).min(cx).floor().max(0.0) as usize;
+    let min_y = ay.min(by).min(cy).floor().max(0.0) as usize;
+    let max_x = (ax.max(bx).max(cx).ceil() as usize).min(size);
+    let max_y = (ay.max(by).max(cy).ceil() as usize).min(size);
+
+    for y in min_y..max_y {
+        let py = y as f32 + 0.5;
+        for x in min_x..max_x {
+            let px = x as f32 + 0.5;
+            // Baricéntricas con signo normalizado: el orden de los vértices no importa
+            let w1 = ((bx - px) * (cy - py) - (by - py) * (cx - px)) / area;
+            let w2 = ((cx - px) * (ay - py) - (cy - py) * (ax - px)) / area;
+            let w3 = 1.0 - w1 - w2;
+            if w1 < 0.0 || w2 < 0.0 || w3 < 0.0 {
+                continue;
+            }
+            let z = 1.0 / (w1 * az + w2 * bz + w3 * cz);
+            let texel = &mut depth[y * size + x];
+            if z < *texel {
+                *texel = z;
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// ship.rs
+// Nave espacial como elemento del HUD 3D: flota siempre frente a la cámara, en la esquina
+// inferior derecha, con un leve movimiento, y lleva un faro que apunta hacia donde mira la cámara
+use space_travel::camera::Camera;
+use space_travel::light::Light;
+use space_travel::matrix::{create_model_matrix_from_quaternion, Quaternion};
+use raylib::prelude::*;
+
+// Cono del faro de la nave (radianes) e intensidad
+const HEADLIGHT_INNER_ANGLE: f32 = 0.2;
+const HEADLIGHT_OUTER_ANGLE: f32 = 0.35;
+const HEADLIGHT_INTENSITY: f32 = 1.5;
+
+/// The ship that follows the camera
+pub struct ShipHud {
+    orientation: Quaternion, // Sigue a la cámara con slerp para que los giros bruscos se suavicen
+}
+
+impl ShipHud {
+    pub fn new() -> Self {
+        ShipHud { orientation: Quaternion::identity() }
+    }
+
+    /// Model matrix of the ship for this frame and its headlight
+    pub fn update(&mut self, camera: &Camera, time: f32, dt: f32) -> (Matrix, Light) {
+        // Configuración de posición HUD - siempre frente a la cámara
+        let hud_distance = 25.0; // Distancia fija desde la cámara
+        
+        // Calcular vectores de dirección de la cámara
+        let forward_vec = Vector3::new(
+            camera.target.x - camera.eye.x,
+            camera.target.y - camera.eye.y,
+            camera.target.z - camera.eye.z
+        );
+        let forward_len = (forward_vec.x * forward_vec.x + forward_vec.y * forward_vec.y + forward_vec.z * forward_vec.z).sqrt();
+        let camera_forward = Vector3::new(
+            forward_vec.x / forward_len,
+            forward_vec.y / forward_len,
+            forward_vec.z / forward_len
+        );
+        
+        // Cross product: camera_forward x camera.up
+        let right_vec = Vector3::new(
+            camera_forward.y * camera.up.z - camera_forward.z * camera.up.y,
+            camera_forward.z * camera.up.x - camera_forward.x * camera.up.z,
+            camera_forward.x * camera.up.y - camera_forward.y * camera.up.x
+        );
+        let right_len = (right_vec.x * right_vec.x + right_vec.y * right_vec.y + right_vec.z * right_vec.z).sqrt();
+        let camera_right = Vector3::new(
+            right_vec.x / right_len,
+            right_vec.y / right_len,
+            right_vec.z / right_len
+        );
+        
+        // Cross product: camera_right x camera_forward
+        let up_vec = Vector3::new(
+            camera_right.y * camera_forward.z - camera_right.z * camera_forward.y,
+            camera_right.z * camera_forward.x - camera_right.x * camera_forward.z,
+            camera_right.x * camera_forward.y - camera_right.y * camera_forward.x
+        );
+        let up_len = (up_vec.x * up_vec.x + up_vec.y * up_vec.y + up_vec.z * up_vec.z).sqrt();
+        let camera_up_adjusted = Vector3::new(
+            up_vec.x / up_len,
+            up_vec.y / up_len,
+            up_vec.z / up_len
+        );
+        
+        // Offset en la pantalla (esquina inferior derecha)
+        let screen_offset_right = 8.0;    // Más a la derecha
+        let screen_offset_down = -6.0;    // Más abajo (valor negativo)
+        let screen_offset_forward = hud_distance;
+        
+        // Posición base HUD (relativa a la cámara)
+        let hud_base_position = Vector3::new(
+            camera.eye.x + camera_forward.x * screen_offset_forward + camera_right.x * screen_offset_right + camera_up_adjusted.x * screen_offset_down,
+            camera.eye.y + camera_forward.y * screen_offset_forward + camera_right.y * screen_offset_right + camera_up_adjusted.y * screen_offset_down,
+            camera.eye.z + camera_forward.z * screen_offset_forward + camera_right.z * screen_offset_right + camera_up_adjusted.z * screen_offset_down
+        );
+        
+        // Movimiento orbital pequeño para dar vida a la nave
+        let nave_orbit_radius = 2.5;
+        let nave_orbit_speed = 1.5;
+        let nave_angle = time * nave_orbit_speed;
+        
+        // Offset de movimiento suave (flotación en el espacio)
+        let orbit_offset = Vector3::new(
+            (nave_angle * 0.7).cos() * nave_orbit_radius * 0.1,
+            (nave_angle * 1.3).sin() * nave_orbit_radius * 0.15,
+            (nave_angle * 0.9).sin() * nave_orbit_radius * 0.1
+        );
+        
+        // Posición final de la nave
+        let nave_position = Vector3::new(
+            hud_base_position.x + orbit_offset.x,
+            hud_base_position.y + orbit_offset.y,
+            hud_base_position.z + orbit_offset.z
+        );
+        
+        // Calcular rotación para que la nave mire en dirección general de la cámara
+        let look_target = Vector3::new(
+            camera.target.x + 5.0,
+            camera.target.y,
+            camera.target.z + 5.0
+        );
+        let look_vec = Vector3::new(
+            look_target.x - nave_position.x,
+            look_target.y - nave_position.y,
+            look_target.z - nave_position.z
+        );
+        let look_len = (look_vec.x * look_vec.x + look_vec.y * look_vec.y + look_vec.z * look_vec.z).sqrt();
+        let look_direction = Vector3::new(
+            look_vec.x / look_len,
+            look_vec.y / look_len,
+            look_vec.z / look_len
+        );
+        
+        // Calcular rotaciones en Y y X basadas en la dirección de mirada
+        let rotation_y = look_direction.x.atan2(look_direction.z);
+        let rotation_x = (-look_direction.y).asin().max(-0.3).min(0.3); // Limitar inclinación
+        
+        // Rotación adicional para efecto dinámico
+        let additional_roll = (time * 0.5).sin() * 0.1;
+
+        // La orientación sigue a la cámara con slerp para que los giros bruscos se suavicen
+        let target_orientation = Quaternion::from_euler(Vector3::new(rotation_x, rotation_y, additional_roll));
+        self.orientation = self.orientation.slerp(target_orientation, (dt * 8.0).min(1.0));
+
+        // Crear matriz de modelo para la nave HUD
+        let nave_model_matrix = create_model_matrix_from_quaternion(
+            nave_position,
+            2.0, // Escala más pequeña para HUD
+            self.orientation
+        );
+
+        let headlight = Light::spot(nave_position, camera_forward, HEADLIGHT_INNER_ANGLE, HEADLIGHT_OUTER_ANGLE)
+            .with_color(Vector3::new(1.0, 0.95, 0.85))
+            .with_intensity(HEADLIGHT_INTENSITY);
+        (nave_model_matrix, headlight)
+    }
+}
@@ -0,0 +1,270 @@
+// simd.rs
+// Operaciones vectoriales del bucle interno del pipeline.
+// Con la feature "math-glam" delegan en glam; con "simd" (en x86_64) usan instrucciones SSE de 4 lanes;
+// si no, la versión escalar. La API sigue usando los tipos de raylib en todos los casos.
+
+use raylib::prelude::*;
+
+#[cfg(feature = "math-glam")]
+pub mod glam_backend {
+    use glam::{Mat4, Vec3, Vec4};
+    use raylib::prelude::*;
+
+    // Adaptadores entre raylib y glam (ambos guardan las matrices por columnas)
+    #[inline]
+    pub fn to_mat4(m: &Matrix) -> Mat4 {
+        Mat4::from_cols_array(&[
+            m.m0, m.m1, m.m2, m.m3,
+            m.m4, m.m5, m.m6, m.m7,
+            m.m8, m.m9, m.m10, m.m11,
+            m.m12, m.m13, m.m14, m.m15,
+        ])
+    }
+
+    #[inline]
+    pub fn from_mat4(m: Mat4) -> Matrix {
+        let c = m.to_cols_array();
+        Matrix {
+            m0: c[0], m1: c[1], m2: c[2], m3: c[3],
+            m4: c[4], m5: c[5], m6: c[6], m7: c[7],
+            m8: c[8], m9: c[9], m10: c[10], m11: c[11],
+            m12: c[12], m13: c[13], m14: c[14], m15: c[15],
+        }
+    }
+
+    #[inline]
+    fn vec3(v: Vector3) -> Vec3 {
+        Vec3::new(v.x, v.y, v.z)
+    }
+
+    #[inline]
+    pub fn multiply_matrix_vector4(m: &Matrix, v: &Vector4) -> Vector4 {
+        let r = to_mat4(m) * Vec4::new(v.x, v.y, v.z, v.w);
+        Vector4::new(r.x, r.y, r.z, r.w)
+    }
+
+    #[inline]
+    pub fn dot(a: Vector3, b: Vector3) -> f32 {
+        vec3(a).dot(vec3(b))
+    }
+
+    #[inline]
+    pub fn cross(a: Vector3, b: Vector3) -> Vector3 {
+        let r = vec3(a).cross(vec3(b));
+        Vector3::new(r.x, r.y, r.z)
+    }
+
+    #[inline]
+    pub fn interpolate(w1: f32, w2: f32, w3: f32, a: Vector3, b: Vector3, c: Vector3) -> Vector3 {
+        let r = vec3(a) * w1 + vec3(b) * w2 + vec3(c) * w3;
+        Vector3::new(r.x, r.y, r.z)
+    }
+
+    #[inline]
+    pub fn edge4(e: [f32; 3], xs: [f32; 4], y: f32) -> [f32; 4] {
+        (Vec4::from_array(xs) * e[0] + Vec4::splat(e[1] * y + e[2])).to_array()
+    }
+
+    #[inline]
+    pub fn inside_mask4(w1: [f32; 4], w2: [f32; 4], w3: [f32; 4]) -> u32 {
+        let zero = Vec4::ZERO;
+        (Vec4::from_array(w1).cmpge(zero) & Vec4::from_array(w2).cmpge(zero) & Vec4::from_array(w3).cmpge(zero)).bitmask()
+    }
+
+    #[inline]
+    pub fn less_mask4(a: [f32; 4], b: [f32; 4]) -> u32 {
+        Vec4::from_array(a).cmplt(Vec4::from_array(b)).bitmask()
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64", not(feature = "math-glam")))]
+mod sse {
+    use raylib::prelude::*;
+    use std::arch::x86_64::*;
+
+    // SSE forma parte de la base de x86_64, así que las intrínsecas siempre están disponibles
+    #[inline]
+    fn load3(v: Vector3) -> __m128 {
+        unsafe { _mm_set_ps(0.0, v.z, v.y, v.x) }
+    }
+
+    #[inline]
+    fn store(v: __m128) -> [f32; 4] {
+        let mut out = [0.0; 4];
+        unsafe { _mm_storeu_ps(out.as_mut_ptr(), v) };
+        out
+    }
+
+    #[inline]
+    pub fn multiply_matrix_vector4(m: &Matrix, v: &Vector4) -> Vector4 {
+        // Matrix de raylib guarda columnas: resultado = col0*x + col1*y + col2*z + col3*w
+        let r = unsafe {
+            let col0 = _mm_set_ps(m.m3, m.m2, m.m1, m.m0);
+            let col1 = _mm_set_ps(m.m7, m.m6, m.m5, m.m4);
+            let col2 = _mm_set_ps(m.m11, m.m10, m.m9, m.m8);
+            let col3 = _mm_set_ps(m.m15, m.m14, m.m13, m.m12);
+            let sum = _mm_add_ps(
+                _mm_add_ps(_mm_mul_ps(col0, _mm_set1_ps(v.x)), _mm_mul_ps(col1, _mm_set1_ps(v.y))),
+                _mm_add_ps(_mm_mul_ps(col2, _mm_set1_ps(v.z)), _mm_mul_ps(col3, _mm_set1_ps(v.w))),
+            );
+            store(sum)
+        };
+        Vector4::new(r[0], r[1], r[2], r[3])
+    }
+
+    #[inline]
+    pub fn dot(a: Vector3, b: Vector3) -> f32 {
+        let r = store(unsafe { _mm_mul_ps(load3(a), load3(b)) });
+        r[0] + r[1] + r[2]
+    }
+
+    #[inline]
+    pub fn cross(a: Vector3, b: Vector3) -> Vector3 {
+        // a.yzx * b.zxy - a.zxy * b.yzx
+        let r = unsafe {
+            let a = load3(a);
+            let b = load3(b);
+            let a_yzx = _mm_shuffle_ps::<0b11_00_10_01>(a, a);
+            let b_yzx = _mm_shuffle_ps::<0b11_00_10_01>(b, b);
+            let c = _mm_sub_ps(_mm_mul_ps(a, b_yzx), _mm_mul_ps(a_yzx, b));
+            store(_mm_shuffle_ps::<0b11_00_10_01>(c, c))
+        };
+        Vector3::new(r[0], r[1], r[2])
+    }
+
+    #[inline]
+    pub fn interpolate(w1: f32, w2: f32, w3: f32, a: Vector3, b: Vector3, c: Vector3) -> Vector3 {
+        let r = unsafe {
+            store(_mm_add_ps(
+                _mm_add_ps(_mm_mul_ps(load3(a), _mm_set1_ps(w1)), _mm_mul_ps(load3(b), _mm_set1_ps(w2))),
+                _mm_mul_ps(load3(c), _mm_set1_ps(w3)),
+            ))
+        };
+        Vector3::new(r[0], r[1], r[2])
+    }
+
+    #[inline]
+    pub fn edge4(e: [f32; 3], xs: [f32; 4], y: f32) -> [f32; 4] {
+        store(unsafe {
+            _mm_add_ps(_mm_mul_ps(_mm_loadu_ps(xs.as_ptr()), _mm_set1_ps(e[0])), _mm_set1_ps(e[1] * y + e[2]))
+        })
+    }
+
+    #[inline]
+    pub fn inside_mask4(w1: [f32; 4], w2: [f32; 4], w3: [f32; 4]) -> u32 {
+        unsafe {
+            let zero = _mm_setzero_ps();
+            let inside = _mm_and_ps(
+                _mm_and_ps(_mm_cmpge_ps(_mm_loadu_ps(w1.as_ptr()), zero), _mm_cmpge_ps(_mm_loadu_ps(w2.as_ptr()), zero)),
+                _mm_cmpge_ps(_mm_loadu_ps(w3.as_ptr()), zero),
+            );
+            _mm_movemask_ps(inside) as u32
+        }
+    }
+
+    #[inline]
+    pub fn less_mask4(a: [f32; 4], b: [f32; 4]) -> u32 {
+        unsafe { _mm_movemask_ps(_mm_cmplt_ps(_mm_loadu_ps(a.as_ptr()), _mm_loadu_ps(b.as_ptr()))) as u32 }
+    }
+}
+
+#[cfg(not(any(feature = "math-glam", all(feature = "simd", target_arch = "x86_64"))))]
+mod scalar {
+    use raylib::prelude::*;
+
+    #[inline]
+    pub fn multiply_matrix_vector4(m: &Matrix, v: &Vector4) -> Vector4 {
+        Vector4::new(
+            m.m0 * v.x + m.m4 * v.y + m.m8 * v.z + m.m12 * v.w,
+            m.m1 * v.x + m.m5 * v.y + m.m9 * v.z + m.m13 * v.w,
+            m.m2 * v.x + m.m6 * v.y + m.m10 * v.z + m.m14 * v.w,
+            m.m3 * v.x + m.m7 * v.y + m.m11 * v.z + m.m15 * v.w,
+        )
+    }
+
+    #[inline]
+    pub fn dot(a: Vector3, b: Vector3) -> f32 {
+        a.x * b.x + a.y * b.y + a.z * b.z
+    }
+
+    #[inline]
+    pub fn cross(a: Vector3, b: Vector3) -> Vector3 {
+        Vector3::new(
+            a.y * b.z - a.z * b.y,
+            a.z * b.x - a.x * b.z,
+            a.x * b.y - a.y * b.x,
+        )
+    }
+
+    #[inline]
+    pub fn interpolate(w1: f32, w2: f32, w3: f32, a: Vector3, b: Vector3, c: Vector3) -> Vector3 {
+        Vector3::new(
+            w1 * a.x + w2 * b.x + w3 * c.x,
+            w1 * a.y + w2 * b.y + w3 * c.y,
+            w1 * a.z + w2 * b.z + w3 * c.z,
+        )
+    }
+
+    #[inline]
+    pub fn edge4(e: [f32; 3], xs: [f32; 4], y: f32) -> [f32; 4] {
+        let row = e[1] * y + e[2];
+        xs.map(|x| e[0] * x + row)
+    }
+
+    #[inline]
+    pub fn inside_mask4(w1: [f32; 4], w2: [f32; 4], w3: [f32; 4]) -> u32 {
+        (0..4).fold(0, |mask, i| mask | (((w1[i] >= 0.0 && w2[i] >= 0.0 && w3[i] >= 0.0) as u32) << i))
+    }
+
+    #[inline]
+    pub fn less_mask4(a: [f32; 4], b: [f32; 4]) -> u32 {
+        (0..4).fold(0, |mask, i| mask | (((a[i] < b[i]) as u32) << i))
+    }
+}
+
+#[cfg(feature = "math-glam")]
+use glam_backend as backend;
+#[cfg(all(feature = "simd", target_arch = "x86_64", not(feature = "math-glam")))]
+use sse as backend;
+#[cfg(not(any(feature = "math-glam", all(feature = "simd", target_arch = "x86_64"))))]
+use scalar as backend;
+
+/// Multiplies a column-major matrix by a 4D vector
+#[inline]
+pub fn multiply_matrix_vector4(matrix: &Matrix, vector: &Vector4) -> Vector4 {
+    backend::multiply_matrix_vector4(matrix, vector)
+}
+
+#[inline]
+pub fn dot(a: Vector3, b: Vector3) -> f32 {
+    backend::dot(a, b)
+}
+
+#[inline]
+pub fn cross(a: Vector3, b: Vector3) -> Vector3 {
+    backend::cross(a, b)
+}
+
+/// Barycentric interpolation of a per-vertex attribute: w1*a + w2*b + w3*c
+#[inline]
+pub fn interpolate(w1: f32, w2: f32, w3: f32, a: Vector3, b: Vector3, c: Vector3) -> Vector3 {
+    backend::interpolate(w1, w2, w3, a, b, c)
+}
+
+/// Evaluates the linear function `e[0] * x + e[1] * y + e[2]` at 4 pixels of the same row
+#[inline]
+pub fn edge4(e: [f32; 3], xs: [f32; 4], y: f32) -> [f32; 4] {
+    backend::edge4(e, xs, y)
+}
+
+/// Bit i is set when the three barycentric weights of lane i are non-negative
+#[inline]
+pub fn inside_mask4(w1: [f32; 4], w2: [f32; 4], w3: [f32; 4]) -> u32 {
+    backend::inside_mask4(w1, w2, w3)
+}
+
+/// Bit i is set when a[i] < b[i] (depth test for 4 pixels)
+#[inline]
+pub fn less_mask4(a: [f32; 4], b: [f32; 4]) -> u32 {
+    backend::less_mask4(a, b)
+}
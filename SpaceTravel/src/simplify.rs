@@ -0,0 +1,328 @@
+// simplify.rs
+// Simplificación de mallas por colapso de aristas con métricas de error cuádricas (Garland y
+// Heckbert): cada vértice acumula los planos de las caras que lo tocan y se colapsa primero la
+// arista cuyo vértice resultante se aparta menos de ellos. Los vértices con la misma posición
+// (costuras de UV, bordes entre partes) se sueldan antes para que la malla no se abra por ahí, y
+// los bordes abiertos llevan planos perpendiculares de mucho peso para que no se encojan. Sirve
+// para generar los niveles de detalle al cargar en lugar de modelarlos a mano
+use crate::mesh::{Mesh, Submesh};
+use crate::obj::compute_tangents;
+use crate::vertex::Vertex;
+use raylib::math::Vector3;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+// Peso de los planos que sujetan los bordes abiertos
+const BOUNDARY_WEIGHT: f64 = 1000.0;
+
+// Cuádrica simétrica 4x4, guardada como sus 10 coeficientes distintos
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    // Plano n·p + d = 0 con n unitario, multiplicado por `weight`
+    fn plane(normal: [f64; 3], d: f64, weight: f64) -> Self {
+        let [a, b, c] = normal;
+        Quadric([a * a, a * b, a * c, a * d, b * b, b * c, b * d, c * c, c * d, d * d].map(|q| q * weight))
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (q, o) in self.0.iter_mut().zip(other.0) {
+            *q += o;
+        }
+    }
+
+    // Suma de las distancias al cuadrado de `p` a los planos acumulados
+    fn error(&self, p: Vector3) -> f64 {
+        let (x, y, z) = (p.x as f64, p.y as f64, p.z as f64);
+        let q = &self.0;
+        q[0] * x * x + 2.0 * q[1] * x * y + 2.0 * q[2] * x * z + 2.0 * q[3] * x
+            + q[4] * y * y + 2.0 * q[5] * y * z + 2.0 * q[6] * y
+            + q[7] * z * z + 2.0 * q[8] * z
+            + q[9]
+    }
+}
+
+fn to_f64(v: Vector3) -> [f64; 3] {
+    [v.x as f64, v.y as f64, v.z as f64]
+}
+
+fn face_normal(positions: &[Vector3], face: [usize; 3]) -> Vector3 {
+    (positions[face[1]] - positions[face[0]]).cross(positions[face[2]] - positions[face[0]])
+}
+
+// Arista candidata en el montón: coste (sus bits, que para f64 positivos ordenan igual), los dos
+// vértices y sus versiones al calcularla; si alguno ha cambiado desde entonces está caducada
+type Candidate = Reverse<(u64, usize, usize, u32, u32)>;
+
+struct Collapser {
+    positions: Vec<Vector3>,
+    quadrics: Vec<Quadric>,
+    faces: Vec<[usize; 3]>,
+    alive: Vec<bool>,
+    incident: Vec<Vec<usize>>, // Caras que usan cada vértice
+    merged_into: Vec<usize>,   // El propio índice mientras el vértice sigue en la malla
+    version: Vec<u32>,
+    heap: BinaryHeap<Candidate>,
+}
+
+impl Collapser {
+    // Mejor posición para el vértice que junta `a` y `b` (uno de los dos o el punto medio) y su error
+    fn collapse_cost(&self, a: usize, b: usize) -> (f64, Vector3) {
+        let mut quadric = self.quadrics[a];
+        quadric.add(&self.quadrics[b]);
+        let (pa, pb) = (self.positions[a], self.positions[b]);
+        [pa, pb, (pa + pb) * 0.5].into_iter()
+            .map(|p| (quadric.error(p).max(0.0), p))
+            .min_by(|x, y| x.0.total_cmp(&y.0))
+            .unwrap_or((0.0, pa))
+    }
+
+    fn push(&mut self, a: usize, b: usize) {
+        let (cost, _) = self.collapse_cost(a, b);
+        self.heap.push(Reverse((cost.to_bits(), a, b, self.version[a], self.version[b])));
+    }
+
+    fn neighbors(&self, vertex: usize) -> Vec<usize> {
+        let mut neighbors: Vec<usize> = self.incident[vertex].iter()
+            .flat_map(|&f| self.faces[f])
+            .filter(|&v| v != vertex)
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+    }
+
+    // Un colapso no puede dar la vuelta a ninguna de las caras que quedan
+    fn flips(&self, a: usize, b: usize, position: Vector3) -> bool {
+        self.incident[a].iter().chain(&self.incident[b]).any(|&f| {
+            let face = self.faces[f];
+            if !self.alive[f] || (face.contains(&a) && face.contains(&b)) {
+                return false;
+            }
+            let before = face_normal(&self.positions, face);
+            let moved = face.map(|v| if v == a || v == b { position } else { self.positions[v] });
+            let after = (moved[1] - moved[0]).cross(moved[2] - moved[0]);
+            after.dot(before) <= 0.0
+        })
+    }
+
+    // Junta `b` en `a`; devuelve cuántas caras desaparecen
+    fn collapse(&mut self, a: usize, b: usize, position: Vector3) -> usize {
+        let quadric = self.quadrics[b];
+        self.quadrics[a].add(&quadric);
+        self.positions[a] = position;
+        self.merged_into[b] = a;
+        let mut removed = 0;
+        for f in std::mem::take(&mut self.incident[b]) {
+            if !self.alive[f] {
+                continue;
+            }
+            let face = &mut self.faces[f];
+            if face.contains(&a) {
+                self.alive[f] = false;
+                removed += 1;
+            } else {
+                face.iter_mut().filter(|v| **v == b).for_each(|v| *v = a);
+                self.incident[a].push(f);
+            }
+        }
+        let alive = &self.alive;
+        self.incident[a].retain(|&f| alive[f]);
+        self.version[a] += 1;
+        self.version[b] += 1;
+        for neighbor in self.neighbors(a) {
+            self.push(a, neighbor);
+        }
+        removed
+    }
+
+    fn root(&self, mut vertex: usize) -> usize {
+        while self.merged_into[vertex] != vertex {
+            vertex = self.merged_into[vertex];
+        }
+        vertex
+    }
+}
+
+/// Simplified copy of `mesh` with about `target_triangles` triangles, keeping its parts; the
+/// surviving vertices keep the attributes (UV, color, material) of the originals
+pub fn simplify(mesh: &Mesh, target_triangles: usize) -> Mesh {
+    if target_triangles >= mesh.indices.len() / 3 {
+        return Mesh::with_submeshes(mesh.vertices.clone(), mesh.indices.clone(), mesh.submeshes.clone());
+    }
+
+    // Soldar los vértices con la misma posición
+    let mut welded: HashMap<[u32; 3], usize> = HashMap::new();
+    let mut positions = Vec::new();
+    let group_of: Vec<usize> = mesh.vertices.iter().map(|vertex| {
+        let p = vertex.position;
+        *welded.entry([p.x.to_bits(), p.y.to_bits(), p.z.to_bits()]).or_insert_with(|| {
+            positions.push(p);
+            positions.len() - 1
+        })
+    }).collect();
+    let faces: Vec<[usize; 3]> = mesh.indices.chunks_exact(3)
+        .map(|face| [group_of[face[0] as usize], group_of[face[1] as usize], group_of[face[2] as usize]])
+        .collect();
+    let alive: Vec<bool> = faces.iter().map(|&[a, b, c]| a != b && b != c && c != a).collect();
+
+    // Planos de cada cara, con el peso de su área
+    let count = positions.len();
+    let mut quadrics = vec![Quadric::default(); count];
+    let mut incident = vec![Vec::new(); count];
+    let mut edge_faces: HashMap<(usize, usize), u32> = HashMap::new();
+    for (f, &face) in faces.iter().enumerate().filter(|&(f, _)| alive[f]) {
+        for (k, &a) in face.iter().enumerate() {
+            incident[a].push(f);
+            let b = face[(k + 1) % 3];
+            *edge_faces.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+        }
+        let cross = face_normal(&positions, face);
+        let area = cross.length();
+        if area <= 0.0 {
+            continue;
+        }
+        let normal = cross / area;
+        let plane = Quadric::plane(to_f64(normal), -normal.dot(positions[face[0]]) as f64, area as f64 * 0.5);
+        for &v in &face {
+            quadrics[v].add(&plane);
+        }
+    }
+
+    // Bordes abiertos: un plano que contiene la arista y es perpendicular a su cara
+    for (_, &face) in faces.iter().enumerate().filter(|&(f, _)| alive[f]) {
+        let normal = face_normal(&positions, face);
+        for (k, &a) in face.iter().enumerate() {
+            let b = face[(k + 1) % 3];
+            if edge_faces[&(a.min(b), a.max(b))] != 1 {
+                continue;
+            }
+            let side = (positions[b] - positions[a]).cross(normal);
+            if side.length() <= 0.0 {
+                continue;
+            }
+            let side = side.normalized();
+            let plane = Quadric::plane(to_f64(side), -side.dot(positions[a]) as f64, BOUNDARY_WEIGHT);
+            quadrics[a].add(&plane);
+            quadrics[b].add(&plane);
+        }
+    }
+
+    let mut live = alive.iter().filter(|&&alive| alive).count();
+    let mut collapser = Collapser {
+        positions,
+        quadrics,
+        faces,
+        alive,
+        incident,
+        merged_into: (0..count).collect(),
+        version: vec![0; count],
+        heap: BinaryHeap::new(),
+    };
+    for &(a, b) in edge_faces.keys() {
+        collapser.push(a, b);
+    }
+
+    while live > target_triangles {
+        let Some(Reverse((_, a, b, version_a, version_b))) = collapser.heap.pop() else {
+            break;
+        };
+        if collapser.version[a] != version_a || collapser.version[b] != version_b
+            || collapser.merged_into[a] != a || collapser.merged_into[b] != b {
+            continue;
+        }
+        let (_, position) = collapser.collapse_cost(a, b);
+        if collapser.flips(a, b, position) {
+            continue;
+        }
+        live -= collapser.collapse(a, b, position);
+    }
+
+    // Reconstruir: cada esquina usa el vértice original que sobrevive en su posición, de su misma
+    // parte y con la UV más parecida (en las costuras hay varios)
+    let mut members: Vec<Vec<u32>> = vec![Vec::new(); count];
+    for (original, &group) in group_of.iter().enumerate() {
+        members[group].push(original as u32);
+    }
+    let mut part_of = vec![0; mesh.vertices.len()];
+    for (part, submesh) in mesh.submeshes.iter().enumerate() {
+        part_of[submesh.vertices.clone()].fill(part);
+    }
+
+    let mut vertices: Vec<Vertex> = Vec::new();
+    let mut indices = Vec::with_capacity(live * 3);
+    let mut remap: HashMap<(usize, u32), u32> = HashMap::new();
+    let mut submeshes = Vec::with_capacity(mesh.submeshes.len());
+    for submesh in &mesh.submeshes {
+        let (first_index, first_vertex) = (indices.len(), vertices.len());
+        for f in submesh.indices.start / 3..submesh.indices.end / 3 {
+            if !collapser.alive[f] {
+                continue;
+            }
+            for &original in &mesh.indices[f * 3..f * 3 + 3] {
+                let group = collapser.root(group_of[original as usize]);
+                let uv = mesh.vertices[original as usize].tex_coords;
+                let chosen = members[group].iter().copied()
+                    .filter(|&m| part_of[m as usize] == part_of[original as usize])
+                    .min_by(|&x, &y| {
+                        let dx = mesh.vertices[x as usize].tex_coords - uv;
+                        let dy = mesh.vertices[y as usize].tex_coords - uv;
+                        dx.length_sqr().total_cmp(&dy.length_sqr())
+                    })
+                    .unwrap_or(original);
+                let index = *remap.entry((group, chosen)).or_insert_with(|| {
+                    let mut vertex = mesh.vertices[chosen as usize].clone();
+                    vertex.position = collapser.positions[group];
+                    vertex.transformed_position = vertex.position;
+                    vertex.world_position = vertex.position;
+                    vertices.push(vertex);
+                    (vertices.len() - 1) as u32
+                });
+                indices.push(index);
+            }
+        }
+        submeshes.push(Submesh {
+            name: submesh.name.clone(),
+            indices: first_index..indices.len(),
+            vertices: first_vertex..vertices.len(),
+        });
+    }
+
+    compute_tangents(&mut vertices, &indices);
+    Mesh::with_submeshes(vertices, indices, submeshes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::generate_icosphere;
+
+    #[test]
+    fn simplify_reaches_about_the_target() {
+        let sphere = generate_icosphere(3);
+        let simplified = simplify(&sphere, 200);
+        let triangles = simplified.indices.len() / 3;
+        // Cada colapso quita al menos dos caras: puede quedar algo por debajo, nunca por encima
+        assert!((150..=200).contains(&triangles), "{} triangles", triangles);
+        assert!(simplified.indices.iter().all(|&index| (index as usize) < simplified.vertices.len()));
+    }
+
+    #[test]
+    fn simplify_keeps_the_shape() {
+        let simplified = simplify(&generate_icosphere(3), 200);
+        for vertex in &simplified.vertices {
+            let radius = vertex.position.length();
+            assert!((radius - 0.5).abs() < 0.05, "vertex at radius {}", radius);
+        }
+    }
+
+    #[test]
+    fn simplify_above_the_triangle_count_copies_the_mesh() {
+        let sphere = generate_icosphere(2);
+        let copy = simplify(&sphere, sphere.indices.len() / 3);
+        assert_eq!(copy.indices, sphere.indices);
+        assert_eq!(copy.vertices.len(), sphere.vertices.len());
+        assert_eq!(copy.submeshes.len(), sphere.submeshes.len());
+    }
+}
@@ -0,0 +1,260 @@
+// simulation.rs
+// Hilo de simulación: las órbitas y el giro de los cuerpos avanzan en otro hilo a paso fijo
+// (STEP_RATE veces por segundo), para que un frame lento no frene la simulación ni al revés. El
+// hilo lleva su propio reloj, que avanza con la escala de tiempo del render y se va corrigiendo
+// hacia el tiempo que este le pide; el render le pasa también las órbitas y giros actuales (los
+// scripts y la consola los cambian). Las fotos van en doble búfer: el hilo escribe en la suya y la
+// intercambia con la publicada, así que el render solo espera lo que dura el intercambio. Cada
+// foto guarda el paso anterior y el actual, y el render interpola entre los dos según el tiempo
+// pasado desde que se publicó, para que con pantallas de más de STEP_RATE Hz no se noten los
+// pasos. En vuelo libre la nave (la cámara) también se mueve aquí: raylib solo se lee desde el
+// hilo principal, así que el render manda en cada frame las teclas que están pulsadas y el hilo
+// las aplica en cada paso, con las colisiones contra los cuerpos en su posición de ese paso. Fuera
+// del vuelo libre la cámara es del render y el hilo solo se queda con una copia para retomarla
+use crate::systems::{CollisionSystem, OrbitSystem};
+use crate::world::{Collider, Entity, Orbit, Transform, World};
+use space_travel::camera::{Camera, CameraInput, INPUT_RATE};
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// Pasos de simulación por segundo
+const STEP_RATE: f32 = 120.0;
+const STEP: f32 = 1.0 / STEP_RATE;
+// Parte de la diferencia con el tiempo pedido que se corrige en cada paso, y diferencia a partir
+// de la cual se salta directamente a él (otra escena, un cambio brusco del reloj)
+const CORRECTION: f32 = 0.05;
+const MAX_DRIFT: f32 = 0.25;
+
+// Lo que el render le pide a la simulación
+#[derive(Clone, Default)]
+struct Request {
+    generation: u32, // Cambia al cargar otra escena
+    time: f32,
+    rate: f32, // Segundos de simulación por segundo real (0 en pausa)
+    orbits: Vec<Option<Orbit>>,
+    transforms: Vec<Transform>,
+    colliders: Vec<Option<Collider>>,
+    mesh_radius: f32, // Radio de la malla que escalan los colliders
+    camera: Option<Camera>,
+    flight: Option<CameraInput>, // Teclas de vuelo libre (None: la cámara la mueve el render)
+}
+
+// Estado de los cuerpos y de la nave en los dos últimos pasos
+struct Snapshot {
+    generation: u32,
+    time: f32,
+    previous: Vec<Transform>,
+    transforms: Vec<Transform>,
+    flying: bool,
+    previous_camera: Option<Camera>,
+    camera: Option<Camera>,
+    hits: Vec<Entity>, // Colisiones que empezaron y el render aún no ha recogido
+    published: Instant,
+}
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        Snapshot {
+            generation: 0,
+            time: 0.0,
+            previous: Vec::new(),
+            transforms: Vec::new(),
+            flying: false,
+            previous_camera: None,
+            camera: None,
+            hits: Vec::new(),
+            published: Instant::now(),
+        }
+    }
+}
+
+impl Snapshot {
+    // Se muestra un paso por detrás: va de la foto anterior (0) a la última (1)
+    fn alpha(&self) -> f32 {
+        (self.published.elapsed().as_secs_f32() / STEP).clamp(0.0, 1.0)
+    }
+}
+
+struct Shared {
+    request: Mutex<Request>,
+    published: Mutex<Snapshot>,
+    running: AtomicBool,
+}
+
+/// Orbits and spin of the bodies, and the ship in free flight, stepped at a fixed rate on a
+/// background thread
+pub struct SimulationThread {
+    shared: Arc<Shared>,
+    handle: Option<JoinHandle<()>>,
+    generation: u32,
+}
+
+impl SimulationThread {
+    pub fn spawn() -> Result<Self, String> {
+        let shared = Arc::new(Shared {
+            request: Mutex::new(Request::default()),
+            published: Mutex::new(Snapshot::default()),
+            running: AtomicBool::new(true),
+        });
+        let thread_shared = Arc::clone(&shared);
+        let handle = thread::Builder::new()
+            .name("simulation".to_string())
+            .spawn(move || run(&thread_shared))
+            .map_err(|e| format!("Failed to start the simulation thread: {}", e))?;
+        Ok(SimulationThread { shared, handle: Some(handle), generation: 0 })
+    }
+
+    /// Forgets the bodies simulated so far; the next request starts from the world as it is
+    pub fn reset(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Asks the thread to follow `time`, which goes on at `rate` times the real time, with the
+    /// orbits, spins and colliders of `world` (`mesh_radius` is the radius of the mesh the
+    /// colliders scale). With `flight` the thread flies the ship from `camera` with those
+    /// controls until a request comes without them; without it the camera stays as it is sent
+    pub fn request(&self, world: &World, camera: &Camera, flight: Option<CameraInput>, time: f32, rate: f32, mesh_radius: f32) {
+        let mut request = lock(&self.shared.request);
+        request.generation = self.generation;
+        request.time = time;
+        request.rate = rate;
+        request.orbits.clone_from(&world.orbits);
+        request.transforms.clone_from(&world.transforms);
+        request.colliders.clone_from(&world.colliders);
+        request.mesh_radius = mesh_radius;
+        request.camera = Some(camera.clone());
+        request.flight = flight;
+    }
+
+    /// Copies into `world` the positions and rotations of the last snapshot, interpolated from
+    /// the previous step by the time since it was published; false while there is none of the
+    /// current scene yet
+    pub fn read(&self, world: &mut World) -> bool {
+        let snapshot = lock(&self.shared.published);
+        if snapshot.generation != self.generation || snapshot.transforms.len() != world.transforms.len() {
+            return false;
+        }
+        let alpha = snapshot.alpha();
+        let simulated = snapshot.previous.iter().zip(&snapshot.transforms);
+        for (transform, (previous, current)) in world.transforms.iter_mut().zip(simulated) {
+            transform.position = previous.position.lerp(current.position, alpha);
+            transform.rotation = previous.rotation.lerp(current.rotation, alpha);
+        }
+        true
+    }
+
+    /// While the thread flies the ship, moves `camera` to its pose interpolated like the bodies
+    /// and returns the bodies it started to collide with since the last call; None otherwise
+    pub fn read_ship(&self, camera: &mut Camera) -> Option<Vec<Entity>> {
+        let mut snapshot = lock(&self.shared.published);
+        if snapshot.generation != self.generation || !snapshot.flying {
+            return None;
+        }
+        let alpha = snapshot.alpha();
+        let (Some(previous), Some(current)) = (&snapshot.previous_camera, &snapshot.camera) else {
+            return None;
+        };
+        *camera = current.clone();
+        camera.eye = previous.eye.lerp(current.eye, alpha);
+        camera.target = previous.target.lerp(current.target, alpha);
+        camera.up = previous.up.lerp(current.up, alpha).normalized();
+        Some(mem::take(&mut snapshot.hits))
+    }
+}
+
+impl Drop for SimulationThread {
+    fn drop(&mut self) {
+        self.shared.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+// Un hilo que se cae con un candado tomado no debe tumbar al otro: el contenido sigue siendo válido
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// Bucle del hilo: un paso cada STEP, publicando una foto en cada uno
+fn run(shared: &Shared) {
+    let mut orbit_system = OrbitSystem::new();
+    let mut collision_system = CollisionSystem::new();
+    let mut request = Request::default();
+    let mut state = Snapshot::default();
+    let mut back = Snapshot::default();
+    let mut next_tick = Instant::now();
+
+    while shared.running.load(Ordering::Relaxed) {
+        request.clone_from(&lock(&shared.request));
+
+        // Otra escena (o la primera): se empieza desde el mundo que manda el render
+        if request.generation != state.generation || request.transforms.len() != state.transforms.len() {
+            state.generation = request.generation;
+            state.time = request.time;
+            state.transforms.clone_from(&request.transforms);
+            state.flying = false;
+        }
+        // La nave sigue la cámara del render hasta que empieza el vuelo libre; desde entonces
+        // la mueve el hilo
+        let flown_here = state.flying && request.flight.is_some() && state.camera.is_some();
+        if !flown_here {
+            state.camera.clone_from(&request.camera);
+        }
+        state.flying = request.flight.is_some();
+        // Los giros y tamaños los cambian los scripts y la consola; la posición y la rotación son del hilo
+        for (transform, requested) in state.transforms.iter_mut().zip(&request.transforms) {
+            transform.spin = requested.spin;
+            transform.scale = requested.scale;
+        }
+        step(&mut orbit_system, &mut collision_system, &request, &mut state);
+
+        // Publicar: se copia al búfer de atrás y se intercambia con el publicado
+        back.generation = state.generation;
+        back.time = state.time;
+        back.previous.clone_from(&state.previous);
+        back.transforms.clone_from(&state.transforms);
+        back.flying = state.flying;
+        back.previous_camera.clone_from(&state.previous_camera);
+        back.camera.clone_from(&state.camera);
+        // Las colisiones que el render no recogió de la foto anterior siguen en este búfer
+        back.hits.append(&mut state.hits);
+        back.published = Instant::now();
+        mem::swap(&mut *lock(&shared.published), &mut back);
+
+        next_tick += Duration::from_secs_f32(STEP);
+        let now = Instant::now();
+        if next_tick > now {
+            thread::sleep(next_tick - now);
+        } else {
+            next_tick = now;
+        }
+    }
+}
+
+// Un paso: el reloj avanza lo que toca por la escala de tiempo más una parte de lo que le falta
+// (o le sobra) para coincidir con el tiempo pedido; si la diferencia es grande se salta a él y
+// no se interpola entre antes y después del salto. En vuelo libre la nave avanza con las teclas
+// del último frame y se aparta de los cuerpos ya colocados
+fn step(orbit_system: &mut OrbitSystem, collision_system: &mut CollisionSystem, request: &Request, state: &mut Snapshot) {
+    let drift = request.time - state.time;
+    let jump = drift.abs() > MAX_DRIFT;
+    let dt = if jump { drift } else { STEP * request.rate + drift * CORRECTION };
+    state.previous.clone_from(&state.transforms);
+    state.time += dt;
+    orbit_system.run_on(&request.orbits, &mut state.transforms, state.time, dt);
+    if jump {
+        state.previous.clone_from(&state.transforms);
+    }
+
+    state.previous_camera.clone_from(&state.camera);
+    if let (Some(input), Some(camera)) = (request.flight.as_ref(), state.camera.as_mut()) {
+        camera.apply_input(input, STEP * INPUT_RATE);
+        if let Some(hit) = collision_system.run_on(&request.colliders, &state.transforms, camera, request.mesh_radius) {
+            state.hits.push(hit);
+        }
+    }
+}
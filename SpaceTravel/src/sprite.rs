@@ -0,0 +1,129 @@
+// sprite.rs
+// Cuerpos muy lejanos: cuando un modelo ocupa apenas unos píxeles en pantalla no vale la pena
+// pasar su malla por el pipeline. Se dibuja en su lugar un disco suavizado con el color medio
+// de su shader, iluminado como el punto del cuerpo que mira a la cámara, en la posición y la
+// profundidad proyectadas de su centro
+use crate::bounds::BoundingSphere;
+use crate::fragment::Fragment;
+use crate::framebuffer::Framebuffer;
+use crate::light::Light;
+use crate::lighting::{Surface, apply_lighting, irradiance};
+use crate::matrix::multiply_matrix_vector4;
+use crate::mesh::Mesh;
+use crate::shader_params::ShaderParams;
+use crate::shader_registry::ShaderRegistry;
+use crate::shaders::vertex_shader;
+use crate::shadow::Shadows;
+use crate::uniforms::Uniforms;
+use raylib::prelude::*;
+
+/// Models whose projected radius is below this many pixels are drawn as point sprites
+pub const SPRITE_RADIUS: f32 = 1.5;
+
+// Radio mínimo del disco: por lejos que esté, el cuerpo sigue cubriendo un píxel
+const MIN_RADIUS: f32 = 0.75;
+
+// Vértices de la malla con los que se promedia el color del shader
+const COLOR_SAMPLES: usize = 16;
+
+/// A distant model drawn as an anti-aliased disc instead of its mesh
+pub struct PointSprite {
+    pub position: Vector2, // Centro en pantalla
+    pub depth: f32,
+    pub radius: f32,       // En píxeles
+    pub color: Vector3,    // Color medio del shader, antes de la iluminación
+    center: Vector3,       // Centro en el mundo
+    world_radius: f32,
+    surface: Surface,
+}
+
+/// Screen position and depth of the center of `bounds` (world space) and its radius in
+/// pixels, with the matrices of `uniforms`; None if it is behind the camera
+pub fn project_bounds(bounds: &BoundingSphere, uniforms: &Uniforms) -> Option<(Vector2, f32, f32)> {
+    let center = Vector4::new(bounds.center.x, bounds.center.y, bounds.center.z, 1.0);
+    let view_position = multiply_matrix_vector4(&uniforms.view_matrix, &center);
+    let clip_position = multiply_matrix_vector4(&uniforms.projection_matrix, &view_position);
+    if clip_position.w <= 0.0 {
+        return None;
+    }
+    // m5 de la proyección escala Y a NDC y m5 del viewport es media altura de la pantalla
+    let radius = bounds.radius * uniforms.projection_matrix.m5 / clip_position.w * uniforms.viewport_matrix.m5.abs();
+    let ndc = Vector3::new(clip_position.x / clip_position.w, clip_position.y / clip_position.w, clip_position.z / clip_position.w);
+    let depth = uniforms.depth_mode.depth(ndc.z, clip_position.w);
+    let screen = multiply_matrix_vector4(&uniforms.viewport_matrix, &Vector4::new(ndc.x, ndc.y, depth, 1.0));
+    Some((Vector2::new(screen.x, screen.y), screen.z, radius))
+}
+
+impl PointSprite {
+    /// Sprite for a model bounded by `bounds` (world space) if it is in front of the camera
+    /// and projects to less than SPRITE_RADIUS pixels; None if its mesh should be drawn
+    pub fn project(bounds: &BoundingSphere, uniforms: &Uniforms, surface: Surface) -> Option<Self> {
+        let (position, depth, radius) = project_bounds(bounds, uniforms)?;
+        if radius >= SPRITE_RADIUS {
+            return None;
+        }
+        Some(PointSprite {
+            position,
+            depth,
+            radius: radius.max(MIN_RADIUS),
+            color: Vector3::zero(),
+            center: bounds.center,
+            world_radius: bounds.radius,
+            surface,
+        })
+    }
+
+    /// Sets the color to the average of `shader` over a few vertices of `mesh`
+    pub fn shade(&mut self, mesh: &Mesh, uniforms: &Uniforms, shader: &str, shaders: &ShaderRegistry, params: &ShaderParams) {
+        let step = (mesh.vertices.len() / COLOR_SAMPLES).max(1);
+        let mut sum = Vector3::zero();
+        let mut count = 0;
+        for vertex in mesh.vertices.iter().step_by(step) {
+            let vertex = vertex_shader(vertex, uniforms);
+            let mut fragment = Fragment::new(self.position.x, self.position.y, vertex.color, self.depth, vertex.world_position);
+            fragment.object_position = vertex.position;
+            fragment.normal = vertex.transformed_normal;
+            fragment.tangent = vertex.transformed_tangent;
+            fragment.bitangent = vertex.transformed_bitangent;
+            fragment.diffuse = vertex.color;
+            fragment.specular = vertex.specular;
+            fragment.shininess = vertex.shininess;
+            fragment.emissive = vertex.emissive;
+            fragment.material_id = vertex.material_id;
+            sum += shaders.shade(&fragment, uniforms, shader, params);
+            count += 1;
+        }
+        if count > 0 {
+            self.color = sum / count as f32;
+        }
+    }
+
+    /// Lights the sprite at the point of the body facing `camera_position` and blends the disc
+    /// into the framebuffer, depth-tested. Returns the number of pixels written
+    pub fn draw(&self, framebuffer: &mut Framebuffer, camera_position: Vector3, lights: &[Light], shadow: Option<&Shadows>, params: &ShaderParams) -> u32 {
+        let to_camera = camera_position - self.center;
+        let normal = if to_camera.length() > 0.0 { to_camera.normalized() } else { Vector3::new(0.0, 0.0, 1.0) };
+        let surface_point = self.center + normal * self.world_radius;
+        let mut fragment = Fragment::new(self.position.x, self.position.y, self.color, self.depth, surface_point);
+        fragment.normal = normal;
+        // Se calcula aquí también para los cuerpos con iluminación Gouraud
+        let light = irradiance(surface_point, normal, &self.surface, camera_position, lights, shadow, &params.lighting);
+        fragment.light_diffuse = light.diffuse;
+        fragment.light_specular = light.specular;
+        let color = apply_lighting(self.color, &fragment, &self.surface, camera_position, lights, shadow, &params.lighting);
+
+        // Cobertura de cada píxel según su distancia al borde del disco
+        let reach = self.radius + 0.5;
+        let mut written = 0;
+        for y in (self.position.y - reach).floor() as i32..=(self.position.y + reach).ceil() as i32 {
+            for x in (self.position.x - reach).floor() as i32..=(self.position.x + reach).ceil() as i32 {
+                let distance = Vector2::new(x as f32 + 0.5 - self.position.x, y as f32 + 0.5 - self.position.y).length();
+                let coverage = (reach - distance).clamp(0.0, 1.0);
+                if coverage > 0.0 && framebuffer.blend_point(x, y, color, coverage, self.depth) {
+                    written += 1;
+                }
+            }
+        }
+        written
+    }
+}
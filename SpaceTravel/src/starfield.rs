@@ -0,0 +1,95 @@
+// starfield.rs
+// Estrellas del fondo: direcciones fijas (a distancia infinita) generadas con un hash, con su
+// brillo, temperatura de color y fase de centelleo. Se suman al fondo antes que la escena, que las tapa
+use space_travel::framebuffer::Framebuffer;
+use space_travel::matrix::{multiply_matrices, multiply_matrix_vector4};
+use space_travel::noise::hash;
+use space_travel::shader_params::StarfieldParams;
+use raylib::prelude::*;
+use std::f32::consts::TAU;
+
+// Colores de las estrellas frías (rojizas), medias (blancas) y calientes (azuladas)
+const COOL: Vector3 = Vector3::new(1.0, 0.72, 0.5);
+const WHITE: Vector3 = Vector3::new(1.0, 0.97, 0.92);
+const HOT: Vector3 = Vector3::new(0.68, 0.8, 1.0);
+
+struct Star {
+    direction: Vector3,
+    brightness: f32, // 0..1; la mayoría son tenues y unas pocas muy brillantes
+    color: Vector3,
+    phase: f32,
+    speed: f32, // Multiplica la velocidad global del centelleo
+}
+
+/// Stars fixed to directions in world space, drawn behind everything
+pub struct Starfield {
+    stars: Vec<Star>,
+}
+
+impl Starfield {
+    pub fn new(count: usize) -> Self {
+        let stars = (0..count as u32).map(|i| {
+            // Varios valores independientes por estrella a partir del mismo hash
+            let random = |k: u32| (hash(i.wrapping_mul(8).wrapping_add(k)) >> 8) as f32 / (1 << 24) as f32;
+            // Dirección uniforme sobre la esfera
+            let z = random(0) * 2.0 - 1.0;
+            let angle = random(1) * TAU;
+            let ring = (1.0 - z * z).sqrt();
+            Star {
+                direction: Vector3::new(ring * angle.cos(), ring * angle.sin(), z),
+                brightness: random(2).powi(8),
+                color: temperature_color(random(3)),
+                phase: random(4) * TAU,
+                speed: 0.5 + random(5),
+            }
+        }).collect();
+        Starfield { stars }
+    }
+
+    pub fn count(&self) -> usize {
+        self.stars.len()
+    }
+
+    /// Projects every star with the camera's rotation (w = 0, so the translation is ignored)
+    /// and adds it to the background pixels. The brightest ones become small crosses
+    pub fn draw(&self, framebuffer: &mut Framebuffer, view: &Matrix, projection: &Matrix, viewport: &Matrix, time: f32, params: &StarfieldParams) {
+        let view_projection = multiply_matrices(projection, view);
+        for star in &self.stars {
+            let d = star.direction;
+            let clip = multiply_matrix_vector4(&view_projection, &Vector4::new(d.x, d.y, d.z, 0.0));
+            // Detrás de la cámara
+            if clip.w <= 0.0 {
+                continue;
+            }
+            let ndc = Vector4::new(clip.x / clip.w, clip.y / clip.w, 0.0, 1.0);
+            if ndc.x.abs() > 1.0 || ndc.y.abs() > 1.0 {
+                continue;
+            }
+            let screen = multiply_matrix_vector4(viewport, &ndc);
+            let (x, y) = (screen.x as i32, screen.y as i32);
+
+            let twinkle = 1.0 - params.twinkle * (0.5 + 0.5 * (time * params.twinkle_speed * star.speed + star.phase).sin());
+            let color = star.color * (star.brightness * twinkle * params.brightness);
+            framebuffer.add_background(x, y, color);
+            if star.brightness >= params.sprite_threshold {
+                // Brazos de la cruz, más tenues hacia fuera
+                for (distance, falloff) in [(1, 0.5), (2, 0.2)] {
+                    let arm = color * falloff;
+                    framebuffer.add_background(x + distance, y, arm);
+                    framebuffer.add_background(x - distance, y, arm);
+                    framebuffer.add_background(x, y + distance, arm);
+                    framebuffer.add_background(x, y - distance, arm);
+                }
+            }
+        }
+    }
+}
+
+// De 0 (fría) a 1 (caliente), pasando por blanco en el centro
+fn temperature_color(t: f32) -> Vector3 {
+    if t < 0.5 {
+        COOL + (WHITE - COOL) * (t * 2.0)
+    } else {
+        WHITE + (HOT - WHITE) * (t * 2.0 - 1.0)
+    }
+}
@@ -0,0 +1,435 @@
+// states.rs
+// Pantallas del programa y las transiciones entre ellas. Cada estado decide cómo avanza la
+// simulación, qué teclas atiende y qué dibuja: el menú (título, pausa y ajustes de ui.rs), la
+// vista libre del sistema, el primer plano de un cuerpo (la cámara gira a su alrededor), su
+// superficie tras aterrizar (landing.rs), el modo foto (tiempo congelado y sin HUD; Enter guarda
+// una captura y Shift+Enter un render por trazado de caminos) y el resumen de las misiones
+// cumplidas (missions.rs). Los cambios de vista se hacen con un fundido a negro; abrir y cerrar
+// el menú de pausa es inmediato
+use crate::app::App;
+use crate::events::Event;
+use crate::landing::Landing;
+use crate::ui::{MenuAction, blend_rect};
+use crate::warp::warp_target;
+use crate::world::Entity;
+use space_travel::camera::CameraInput;
+use space_travel::profile_scope;
+use raylib::prelude::*;
+
+// Segundos de cada mitad del fundido (oscurecer y aclarar)
+const FADE_DURATION: f32 = 0.25;
+
+// Distancia de la cámara en el primer plano, en radios del cuerpo: al entrar y los límites del zoom
+const CLOSE_UP_DISTANCE: f32 = 4.0;
+const CLOSE_UP_MIN_DISTANCE: f32 = 2.0;
+const CLOSE_UP_MAX_DISTANCE: f32 = 12.0;
+
+/// The screens of the program
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AppState {
+    /// Title screen, pause menu or settings
+    Menu,
+    /// Free flight around the system
+    SystemView,
+    /// The camera orbits one body
+    CloseUp(Entity),
+    /// Standing on the surface of a body
+    Landed(Entity),
+    /// Frozen time, no HUD; Enter saves a capture
+    PhotoMode,
+    /// Frozen time over the summary of the completed missions
+    MissionSummary,
+}
+
+// Lo que pide un estado al terminar su update
+enum Step {
+    Stay,
+    Switch(AppState), // Inmediato
+    FadeTo(AppState), // Con fundido a negro
+    Quit,
+}
+
+// Fundido en curso: primero oscurece hasta negro, cambia de estado y luego aclara
+enum Fade {
+    Out { next: AppState, elapsed: f32 },
+    In { elapsed: f32 },
+}
+
+/// Runs the current screen and the transitions between screens
+pub struct SceneManager {
+    state: AppState,
+    resume: AppState,                   // Estado al que vuelve el menú de pausa
+    photo_return: AppState,             // Estado al que vuelve el modo foto
+    summary_return: AppState,           // Estado al que vuelve el resumen de las misiones
+    saved_view: Option<(Vector3, f32)>, // Objetivo y distancia de la cámara antes del primer plano
+    landing: Option<Landing>,           // Superficie del cuerpo en el que se aterrizó
+    fade: Option<Fade>,
+    capture: bool, // Guardar el próximo frame del modo foto
+    photos: u32,
+}
+
+impl SceneManager {
+    /// Starts on the title screen
+    pub fn new() -> Self {
+        SceneManager {
+            state: AppState::Menu,
+            resume: AppState::SystemView,
+            photo_return: AppState::SystemView,
+            summary_return: AppState::SystemView,
+            saved_view: None,
+            landing: None,
+            fade: None,
+            capture: false,
+            photos: 0,
+        }
+    }
+
+    /// Updates the current screen for this frame (`typing`: the console has the keyboard).
+    /// Returns false when the program has to end
+    pub fn update(&mut self, app: &mut App, window: &RaylibHandle, typing: bool) -> bool {
+        let frame_time = window.get_frame_time();
+        self.update_fade(app, frame_time);
+        if !typing {
+            app.handle_debug_keys(window);
+        }
+        // Mientras oscurece no se atiende la entrada del estado que se va
+        let input = !typing && !matches!(self.fade, Some(Fade::Out { .. }));
+        let step = match self.state {
+            AppState::Menu => self.update_menu(app, window, input, frame_time),
+            AppState::SystemView => self.update_system_view(app, window, input, frame_time),
+            AppState::CloseUp(body) => self.update_close_up(app, window, input, frame_time, body),
+            AppState::Landed(_) => self.update_landed(app, window, input, frame_time),
+            AppState::PhotoMode => self.update_photo_mode(app, window, input),
+            AppState::MissionSummary => self.update_mission_summary(app, window, input),
+        };
+        match step {
+            Step::Stay => {}
+            Step::Switch(next) => self.enter(app, next),
+            Step::FadeTo(next) => self.fade = Some(Fade::Out { next, elapsed: 0.0 }),
+            Step::Quit => return false,
+        }
+        true
+    }
+
+    /// Draws the current screen and shows it in the window
+    pub fn render(&mut self, app: &mut App, window: &mut RaylibHandle, thread: &RaylibThread) {
+        let (frame_stats, overlay) = match self.state {
+            AppState::Menu => {
+                // El menú de pausa tapa la escena de la que se viene
+                let frame_stats = match (&self.landing, self.resume) {
+                    (Some(landing), AppState::Landed(_)) => app.render_landing(landing),
+                    _ => app.render(true),
+                };
+                app.draw_menu();
+                (frame_stats, app.overlay(&frame_stats))
+            }
+            AppState::SystemView => {
+                let frame_stats = app.render(true);
+                (frame_stats, app.overlay(&frame_stats))
+            }
+            AppState::CloseUp(body) => {
+                let frame_stats = app.render(true);
+                let mut overlay = app.overlay(&frame_stats);
+                if let Some(name) = app.world.names.get(body) {
+                    overlay.push(app.strings.format("hud.close_up", &[("body", name)]));
+                }
+                (frame_stats, overlay)
+            }
+            AppState::Landed(body) => {
+                let frame_stats = match &self.landing {
+                    Some(landing) => app.render_landing(landing),
+                    None => app.render(true),
+                };
+                let mut overlay = app.overlay(&frame_stats);
+                if let Some(name) = app.world.names.get(body) {
+                    overlay.push(app.strings.format("hud.surface", &[("body", name)]));
+                }
+                (frame_stats, overlay)
+            }
+            AppState::PhotoMode => {
+                let frame_stats = app.render(false);
+                if std::mem::take(&mut self.capture) {
+                    self.save_capture(app);
+                }
+                // Sin HUD, salvo el avance del render de calidad
+                (frame_stats, app.beauty_progress().into_iter().collect())
+            }
+            AppState::MissionSummary => {
+                let frame_stats = app.render(true);
+                app.draw_mission_summary();
+                (frame_stats, Vec::new())
+            }
+        };
+        self.draw_fade(app);
+        app.present(window, thread, &frame_stats, &overlay);
+    }
+
+    // Cambia de estado preparando la cámara: el primer plano la acerca al cuerpo y al volver a
+    // la vista del sistema recupera donde estaba. Salir del menú de pausa no toca la cámara
+    fn enter(&mut self, app: &mut App, next: AppState) {
+        let previous = std::mem::replace(&mut self.state, next);
+        match next {
+            AppState::Menu => self.resume = previous,
+            AppState::CloseUp(body) if previous == AppState::SystemView => {
+                self.saved_view = Some((app.camera.target, app.camera.distance));
+                let transform = &app.world.transforms[body];
+                app.camera.look_at(transform.position, transform.scale * 0.5 * CLOSE_UP_DISTANCE);
+            }
+            AppState::SystemView => {
+                if let Some((target, distance)) = self.saved_view.take() {
+                    app.camera.look_at(target, distance);
+                }
+                self.landing = None;
+            }
+            AppState::Landed(body) if previous != AppState::Menu => self.landing = app.land(body),
+            AppState::CloseUp(_) if matches!(previous, AppState::Landed(_)) => self.landing = None,
+            AppState::PhotoMode if previous != AppState::Menu => self.photo_return = previous,
+            AppState::MissionSummary if previous != AppState::Menu => self.summary_return = previous,
+            _ => {}
+        }
+    }
+
+    // Avanza el fundido; a mitad (negro total) cambia de estado
+    fn update_fade(&mut self, app: &mut App, frame_time: f32) {
+        match self.fade.as_mut() {
+            Some(Fade::Out { next, elapsed }) => {
+                *elapsed += frame_time;
+                if *elapsed >= FADE_DURATION {
+                    let next = *next;
+                    self.enter(app, next);
+                    self.fade = Some(Fade::In { elapsed: 0.0 });
+                }
+            }
+            Some(Fade::In { elapsed }) => {
+                *elapsed += frame_time;
+                if *elapsed >= FADE_DURATION {
+                    self.fade = None;
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn draw_fade(&self, app: &mut App) {
+        let opacity = match self.fade {
+            Some(Fade::Out { elapsed, .. }) => elapsed / FADE_DURATION,
+            Some(Fade::In { elapsed }) => 1.0 - elapsed / FADE_DURATION,
+            None => return,
+        };
+        let framebuffer = &mut app.renderer.framebuffer;
+        let rect = (0, 0, framebuffer.width, framebuffer.height);
+        blend_rect(framebuffer, rect, Vector3::zero(), opacity.clamp(0.0, 1.0));
+    }
+
+    // Menú: en pausa la simulación se detiene, pero la escena se sigue dibujando detrás; en la
+    // pantalla de título la cámara gira lentamente alrededor del sistema
+    fn update_menu(&mut self, app: &mut App, window: &RaylibHandle, input: bool, frame_time: f32) -> Step {
+        let size = app.render_size();
+        let action = if input { app.menu.update(window, &mut app.settings, &app.strings, size) } else { None };
+        let started = matches!(action, Some(MenuAction::Start { .. }));
+        match action {
+            Some(MenuAction::Quit) => return Step::Quit,
+            Some(action) => app.apply_menu_action(action),
+            None => {}
+        }
+
+        let dt = if app.menu.is_paused() { 0.0 } else { frame_time * app.time_scale };
+        app.advance(dt);
+        if app.menu.on_title() {
+            app.camera.yaw += dt * 0.05;
+            app.camera.look_at(app.camera.target, app.camera.distance);
+        }
+        app.settle(true);
+
+        if self.fade.is_some() {
+            // El fundido hacia la vista del sistema ya está en curso
+            Step::Stay
+        } else if started {
+            self.resume = AppState::SystemView;
+            self.saved_view = None;
+            Step::FadeTo(AppState::SystemView)
+        } else if !app.menu.is_open() {
+            Step::Switch(self.resume)
+        } else {
+            Step::Stay
+        }
+    }
+
+    // Vista del sistema: la cámara se mueve libremente, 1-5 viajan a los planetas, un clic
+    // selecciona un cuerpo y Enter lo mira de cerca; V alterna la vista desde la cabina, G empieza
+    // o abandona la contrarreloj, H graba el vuelo y F12 abre el modo foto
+    fn update_system_view(&mut self, app: &mut App, window: &RaylibHandle, input: bool, frame_time: f32) -> Step {
+        app.advance(frame_time * app.time_scale);
+        let mut step = Step::Stay;
+        if input {
+            app.fly(CameraInput::read(window));
+            let warp_keys = [KeyboardKey::KEY_ONE, KeyboardKey::KEY_TWO, KeyboardKey::KEY_THREE, KeyboardKey::KEY_FOUR, KeyboardKey::KEY_FIVE];
+            for (number, key) in warp_keys.into_iter().enumerate() {
+                if window.is_key_pressed(key) && let Some(body) = warp_target(&app.world, number) {
+                    app.start_warp(body);
+                }
+            }
+            if let Some(body) = app.clicked_body(window) {
+                app.selected = Some(body);
+                app.events.publish(Event::BodySelected { body });
+            }
+            step = common_keys(app, window);
+            if window.is_key_pressed(KeyboardKey::KEY_ENTER) && let Some(body) = app.selected {
+                step = Step::FadeTo(AppState::CloseUp(body));
+            }
+            if window.is_key_pressed(KeyboardKey::KEY_V) {
+                app.cockpit = !app.cockpit;
+            }
+            if window.is_key_pressed(KeyboardKey::KEY_G) {
+                app.toggle_race();
+            }
+            if window.is_key_pressed(KeyboardKey::KEY_H) {
+                app.toggle_recording();
+            }
+        }
+        app.settle(true);
+        summary_when_complete(app, step)
+    }
+
+    // Primer plano: la cámara gira alrededor del cuerpo (A/D, flechas) y se acerca o aleja
+    // dentro de unos límites; L aterriza en él y Backspace vuelve a la vista del sistema
+    fn update_close_up(&mut self, app: &mut App, window: &RaylibHandle, input: bool, frame_time: f32, body: Entity) -> Step {
+        app.advance(frame_time * app.time_scale);
+        // Una recarga de la escena puede haber quitado el cuerpo
+        if body >= app.world.transforms.len() {
+            self.saved_view = None;
+            return Step::Switch(AppState::SystemView);
+        }
+        let mut step = Step::Stay;
+        if input {
+            app.camera.process_input(window);
+            step = common_keys(app, window);
+            if window.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                step = Step::FadeTo(AppState::SystemView);
+            }
+            // En las estrellas no se puede aterrizar
+            if window.is_key_pressed(KeyboardKey::KEY_L) && app.world.lights[body].is_none() {
+                step = Step::FadeTo(AppState::Landed(body));
+            }
+        }
+        // El objetivo sigue al cuerpo en su órbita; la distancia deja la cámara fuera de él
+        let transform = &app.world.transforms[body];
+        let radius = transform.scale * 0.5;
+        let distance = app.camera.distance.clamp(radius * CLOSE_UP_MIN_DISTANCE, radius * CLOSE_UP_MAX_DISTANCE);
+        app.camera.look_at(transform.position, distance);
+        app.settle(false);
+        summary_when_complete(app, step)
+    }
+
+    // Superficie: la cámara mira alrededor del punto de aterrizaje mientras el planeta gira y
+    // los demás cuerpos cruzan el cielo; L o Backspace despegan de vuelta al primer plano
+    fn update_landed(&mut self, app: &mut App, window: &RaylibHandle, input: bool, frame_time: f32) -> Step {
+        app.advance(frame_time * app.time_scale);
+        // Una recarga de la escena puede haber quitado el cuerpo
+        let Some(landing) = self.landing.as_mut().filter(|landing| landing.body < app.world.transforms.len()) else {
+            self.landing = None;
+            self.saved_view = None;
+            return Step::Switch(AppState::SystemView);
+        };
+        let mut step = Step::Stay;
+        if input {
+            landing.process_input(window);
+            if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                app.menu.pause();
+                step = Step::Switch(AppState::Menu);
+            }
+            if window.is_key_pressed(KeyboardKey::KEY_L) || window.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                step = Step::FadeTo(AppState::CloseUp(landing.body));
+            }
+        }
+        app.settle(false);
+        step
+    }
+
+    // Modo foto: el tiempo se congela, la cámara se mueve libremente y Enter guarda el frame
+    // sin HUD (Shift+Enter, trazado de caminos); F12 o Backspace vuelven a la vista anterior
+    fn update_photo_mode(&mut self, app: &mut App, window: &RaylibHandle, input: bool) -> Step {
+        app.advance(0.0);
+        let mut step = Step::Stay;
+        if input {
+            app.camera.process_input(window);
+            if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                app.menu.pause();
+                step = Step::Switch(AppState::Menu);
+            }
+            if window.is_key_pressed(KeyboardKey::KEY_F12) || window.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                step = Step::FadeTo(self.photo_return);
+            }
+            // Sin fundido en curso, para que la captura no salga oscurecida; con Shift, el render
+            // de calidad de la misma vista
+            if window.is_key_pressed(KeyboardKey::KEY_ENTER) && self.fade.is_none() {
+                if window.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) || window.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT) {
+                    self.save_beauty(app);
+                } else {
+                    self.capture = true;
+                }
+            }
+        }
+        app.settle(true);
+        step
+    }
+
+    // Resumen de las misiones: el tiempo se congela tras el panel; Enter o Backspace siguen
+    // explorando desde la vista de la que se vino
+    fn update_mission_summary(&mut self, app: &mut App, window: &RaylibHandle, input: bool) -> Step {
+        app.advance(0.0);
+        let mut step = Step::Stay;
+        if input {
+            if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                app.menu.pause();
+                step = Step::Switch(AppState::Menu);
+            }
+            if window.is_key_pressed(KeyboardKey::KEY_ENTER) || window.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                step = Step::FadeTo(self.summary_return);
+            }
+        }
+        app.settle(false);
+        step
+    }
+
+    // Guarda el frame actual (antes de la consola y los textos) como photo_<n>.png
+    fn save_capture(&mut self, app: &mut App) {
+        profile_scope!("capture");
+        self.photos += 1;
+        let path = format!("photo_{}.png", self.photos);
+        app.renderer.framebuffer.color_buffer.export_image(&path);
+        app.print(&format!("Saved {}", path), false);
+    }
+
+    // Render de calidad de la vista actual como beauty_<n>.png, en otro hilo; el HUD muestra su
+    // avance y la consola avisa al terminar
+    fn save_beauty(&mut self, app: &mut App) {
+        self.photos += 1;
+        let path = format!("beauty_{}.png", self.photos);
+        match app.start_beauty_render(path.clone()) {
+            Ok(()) => app.print(&format!("Rendering {}...", path), false),
+            Err(e) => app.print(&e, true),
+        }
+    }
+}
+
+// Teclas que comparten la vista del sistema y el primer plano: Esc abre el menú de pausa y
+// F12 el modo foto
+fn common_keys(app: &mut App, window: &RaylibHandle) -> Step {
+    if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+        app.menu.pause();
+        return Step::Switch(AppState::Menu);
+    }
+    if window.is_key_pressed(KeyboardKey::KEY_F12) {
+        return Step::FadeTo(AppState::PhotoMode);
+    }
+    Step::Stay
+}
+
+// Al cumplirse la última misión se pasa al resumen, salvo que el estado ya vaya a otro sitio
+fn summary_when_complete(app: &mut App, step: Step) -> Step {
+    match step {
+        Step::Stay if app.missions.take_summary() => Step::FadeTo(AppState::MissionSummary),
+        step => step,
+    }
+}
@@ -0,0 +1,90 @@
+// stats.rs
+// Contadores del pipeline por frame, para medir cuánto trabajo ahorra cada etapa de descarte
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ops::AddAssign;
+
+/// Work done by the pipeline in one frame
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PipelineStats {
+    pub bodies_culled: u32,            // Cuerpos descartados por el frustum antes del vertex shader
+    pub bodies_sprited: u32,           // Cuerpos tan lejanos que se dibujaron como un disco
+    pub bodies_impostors: u32,         // Cuerpos a media distancia dibujados con su imagen capturada
+    pub impostors_captured: u32,       // Imágenes de impostores que se renovaron
+    pub triangles_submitted: u32,
+    pub triangles_backface_culled: u32, // De espaldas a la cámara en mallas cerradas: descartados sin rasterizar
+    pub triangles_clipped: u32,        // Degenerados o completamente fuera de la pantalla
+    pub fragments_generated: u32,
+    pub fragments_depth_rejected: u32, // Early-z en el rasterizador más los que pierden la resolución de visibilidad
+    pub pixels_written: u32,
+}
+
+impl AddAssign for PipelineStats {
+    fn add_assign(&mut self, other: Self) {
+        self.bodies_culled += other.bodies_culled;
+        self.bodies_sprited += other.bodies_sprited;
+        self.bodies_impostors += other.bodies_impostors;
+        self.impostors_captured += other.impostors_captured;
+        self.triangles_submitted += other.triangles_submitted;
+        self.triangles_backface_culled += other.triangles_backface_culled;
+        self.triangles_clipped += other.triangles_clipped;
+        self.fragments_generated += other.fragments_generated;
+        self.fragments_depth_rejected += other.fragments_depth_rejected;
+        self.pixels_written += other.pixels_written;
+    }
+}
+
+impl PipelineStats {
+    /// One line per counter, for the debug HUD
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format!("bodies culled: {}", self.bodies_culled),
+            format!("bodies as sprites: {}", self.bodies_sprited),
+            format!("bodies as impostors: {} ({} captured)", self.bodies_impostors, self.impostors_captured),
+            format!("triangles: {}", self.triangles_submitted),
+            format!("  backface culled: {}", self.triangles_backface_culled),
+            format!("  clipped: {}", self.triangles_clipped),
+            format!("fragments: {}", self.fragments_generated),
+            format!("  depth rejected: {}", self.fragments_depth_rejected),
+            format!("pixels written: {}", self.pixels_written),
+        ]
+    }
+}
+
+/// Writes one CSV row of counters per frame (--stats-csv <file>)
+pub struct StatsExporter {
+    writer: BufWriter<File>,
+    frame: u64,
+}
+
+impl StatsExporter {
+    pub fn create(path: &str) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut writer = BufWriter::new(file);
+        writeln!(
+            writer,
+            "frame,time,bodies_culled,bodies_sprited,bodies_impostors,impostors_captured,triangles_submitted,triangles_backface_culled,triangles_clipped,fragments_generated,fragments_depth_rejected,pixels_written"
+        ).map_err(|e| format!("{}: {}", path, e))?;
+        Ok(StatsExporter { writer, frame: 0 })
+    }
+
+    pub fn record(&mut self, time: f32, stats: &PipelineStats) -> Result<(), String> {
+        self.frame += 1;
+        writeln!(
+            self.writer,
+            "{},{:.4},{},{},{},{},{},{},{},{},{},{}",
+            self.frame,
+            time,
+            stats.bodies_culled,
+            stats.bodies_sprited,
+            stats.bodies_impostors,
+            stats.impostors_captured,
+            stats.triangles_submitted,
+            stats.triangles_backface_culled,
+            stats.triangles_clipped,
+            stats.fragments_generated,
+            stats.fragments_depth_rejected,
+            stats.pixels_written,
+        ).map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,192 @@
+// stream.rs
+// Envío de los frames a otros programas (codificadores, scripts de análisis, agentes) mientras
+// se juega: cada frame sale como una cabecera pequeña seguida de los píxeles RGB sin comprimir.
+// Con una tubería con nombre (pipe:<ruta>, creada antes con mkfifo) los frames van uno detrás de
+// otro; con memoria compartida (shm:<ruta>, p. ej. en /dev/shm) el archivo tiene siempre el último
+// y se sobrescribe en su sitio. La escritura va en otro hilo: si el lector no da abasto se
+// descartan frames en lugar de frenar el render, y el índice de cada frame deja ver los saltos
+use space_travel::framebuffer::Framebuffer;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+// Cabecera, en little endian: MAGIC, ancho (u32), alto (u32), canales (u32), índice del frame
+// (u64) y segundos desde que empezó el envío (f64); 32 bytes
+const MAGIC: &[u8; 4] = b"STFR";
+const CHANNELS: u32 = 3;
+const INDEX_OFFSET: u64 = 16;
+// Índice que tiene la memoria compartida mientras se escribe un frame
+const WRITING: u64 = u64::MAX;
+
+// Frames que pueden esperar al hilo de escritura antes de empezar a descartarlos
+const QUEUE: usize = 2;
+
+/// Where the frames go: a named pipe or a shared memory file
+#[derive(Clone, Debug)]
+pub enum StreamTarget {
+    Pipe(PathBuf),
+    SharedMemory(PathBuf),
+}
+
+impl StreamTarget {
+    /// Parses "pipe:<path>" or "shm:<path>"
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text.split_once(':') {
+            Some(("pipe", path)) if !path.is_empty() => Ok(StreamTarget::Pipe(path.into())),
+            Some(("shm", path)) if !path.is_empty() => Ok(StreamTarget::SharedMemory(path.into())),
+            _ => Err(format!("unknown stream target '{}' (expected pipe:<path> or shm:<path>)", text)),
+        }
+    }
+}
+
+struct Frame {
+    width: u32,
+    height: u32,
+    index: u64,
+    timestamp: f64,
+    pixels: Vec<u8>,
+}
+
+impl Frame {
+    fn header(&self, index: u64) -> [u8; 32] {
+        let mut header = [0; 32];
+        header[0..4].copy_from_slice(MAGIC);
+        header[4..8].copy_from_slice(&self.width.to_le_bytes());
+        header[8..12].copy_from_slice(&self.height.to_le_bytes());
+        header[12..16].copy_from_slice(&CHANNELS.to_le_bytes());
+        header[16..24].copy_from_slice(&index.to_le_bytes());
+        header[24..32].copy_from_slice(&self.timestamp.to_le_bytes());
+        header
+    }
+}
+
+/// Sends every presented frame to an external reader (--stream pipe:<path> | shm:<path>)
+pub struct FrameStream {
+    sender: Option<SyncSender<Frame>>,
+    writer: Option<JoinHandle<()>>,
+    index: u64,
+    dropped: u64,
+    start: Instant,
+}
+
+impl FrameStream {
+    /// Opens the target; opening a named pipe waits until a reader opens the other end
+    pub fn open(target: &StreamTarget) -> Result<Self, String> {
+        let (sender, receiver) = mpsc::sync_channel(QUEUE);
+        let writer = match target {
+            StreamTarget::Pipe(path) => {
+                let file = OpenOptions::new().write(true).open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                thread::Builder::new().name("stream".to_string()).spawn(move || write_pipe(file, receiver))
+            }
+            StreamTarget::SharedMemory(path) => {
+                let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                thread::Builder::new().name("stream".to_string()).spawn(move || write_shared(file, receiver))
+            }
+        }.map_err(|e| format!("Failed to start the stream thread: {}", e))?;
+        Ok(FrameStream { sender: Some(sender), writer: Some(writer), index: 0, dropped: 0, start: Instant::now() })
+    }
+
+    /// Queues the current frame; it is dropped if the reader is still behind. Fails once the
+    /// reader has gone away
+    pub fn send(&mut self, framebuffer: &Framebuffer) -> Result<(), String> {
+        let Some(sender) = self.sender.as_ref() else {
+            return Err("stream closed".to_string());
+        };
+        let colors = framebuffer.color_buffer.get_image_data();
+        let frame = Frame {
+            width: framebuffer.width as u32,
+            height: framebuffer.height as u32,
+            index: self.index,
+            timestamp: self.start.elapsed().as_secs_f64(),
+            pixels: colors.iter().flat_map(|color| [color.r, color.g, color.b]).collect(),
+        };
+        self.index += 1;
+        match sender.try_send(frame) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                self.dropped += 1;
+                Ok(())
+            }
+            Err(TrySendError::Disconnected(_)) => Err("the reader closed the stream".to_string()),
+        }
+    }
+}
+
+impl Drop for FrameStream {
+    fn drop(&mut self) {
+        // Cerrar el canal termina el hilo cuando acaba con lo que tenía en cola
+        self.sender = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+        if self.dropped > 0 {
+            println!("Stream: {} frames sent, {} dropped", self.index - self.dropped, self.dropped);
+        }
+    }
+}
+
+// Tubería: cabecera y píxeles de cada frame seguidos
+fn write_pipe(file: File, receiver: Receiver<Frame>) {
+    let mut writer = BufWriter::new(file);
+    for frame in receiver {
+        let result = writer.write_all(&frame.header(frame.index))
+            .and_then(|()| writer.write_all(&frame.pixels))
+            .and_then(|()| writer.flush());
+        if let Err(e) = result {
+            eprintln!("Failed to write to the stream, streaming stopped: {}", e);
+            return;
+        }
+    }
+}
+
+// Memoria compartida: el índice vale WRITING mientras se reescribe el frame y el del frame al
+// terminar, así que un lector que lee el mismo índice antes y después de copiar tiene un frame entero
+fn write_shared(mut file: File, receiver: Receiver<Frame>) {
+    for frame in receiver {
+        if let Err(e) = write_shared_frame(&mut file, &frame) {
+            eprintln!("Failed to write to the stream, streaming stopped: {}", e);
+            return;
+        }
+    }
+}
+
+fn write_shared_frame(file: &mut File, frame: &Frame) -> io::Result<()> {
+    let size = 32 + frame.pixels.len() as u64;
+    if file.metadata()?.len() != size {
+        file.set_len(size)?;
+    }
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&frame.header(WRITING))?;
+    file.write_all(&frame.pixels)?;
+    file.seek(SeekFrom::Start(INDEX_OFFSET))?;
+    file.write_all(&frame.index.to_le_bytes())?;
+    file.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn parses_pipe_and_shm_targets() {
+        assert!(matches!(StreamTarget::parse("pipe:/tmp/frames"), Ok(StreamTarget::Pipe(path)) if path == Path::new("/tmp/frames")));
+        assert!(matches!(StreamTarget::parse("shm:/dev/shm/frames"), Ok(StreamTarget::SharedMemory(path)) if path == Path::new("/dev/shm/frames")));
+    }
+
+    #[test]
+    fn keeps_colons_in_the_path() {
+        assert!(matches!(StreamTarget::parse(r"pipe:\\.\pipe\frames:1"), Ok(StreamTarget::Pipe(path)) if path == Path::new(r"\\.\pipe\frames:1")));
+    }
+
+    #[test]
+    fn rejects_empty_paths_and_unknown_prefixes() {
+        assert!(StreamTarget::parse("pipe:").is_err());
+        assert!(StreamTarget::parse("shm:").is_err());
+        assert!(StreamTarget::parse("file:/tmp/frames").is_err());
+        assert!(StreamTarget::parse("/tmp/frames").is_err());
+    }
+}
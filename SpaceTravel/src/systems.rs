@@ -0,0 +1,149 @@
+// systems.rs
+// Sistemas que recorren los componentes del mundo (world.rs) cada frame: las órbitas mueven
+// las entidades, las colisiones apartan la cámara de ellas y el render las convierte en modelos
+// para el renderer y dibuja sus órbitas. El bucle principal solo decide en qué orden se ejecutan
+use crate::orbit::OrbitCache;
+use crate::palette::Palette;
+use crate::world::{Collider, Entity, Orbit, Transform, World};
+use space_travel::Model;
+use space_travel::camera::Camera;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::matrix::create_model_matrix;
+use space_travel::mesh::Mesh;
+use space_travel::scene::place_orbits;
+use raylib::prelude::*;
+
+// Radio de colisión de la cámara
+const CAMERA_RADIUS: f32 = 2.0;
+
+/// Places the entities with an orbit for the current time and spins every entity on its axis
+pub struct OrbitSystem {
+    positions: Vec<Vector3>, // Posiciones de las entidades, colocadas por scene::place_orbits
+}
+
+impl OrbitSystem {
+    pub fn new() -> Self {
+        OrbitSystem { positions: Vec::new() }
+    }
+
+    pub fn run(&mut self, world: &mut World, time: f32, dt: f32) {
+        self.run_on(&world.orbits, &mut world.transforms, time, dt);
+    }
+
+    /// Same as `run` on the orbits and transforms of a world kept elsewhere (the simulation thread)
+    pub fn run_on(&mut self, orbits: &[Option<Orbit>], transforms: &mut [Transform], time: f32, dt: f32) {
+        self.positions.clear();
+        self.positions.extend(transforms.iter().map(|transform| transform.position));
+        place_orbits(orbits, &mut self.positions, time);
+        for (transform, &position) in transforms.iter_mut().zip(&self.positions) {
+            transform.position = position;
+            transform.rotation.y += dt * transform.spin;
+        }
+    }
+}
+
+/// Keeps the camera out of the colliders of the entities
+pub struct CollisionSystem {
+    colliding: bool,
+}
+
+impl CollisionSystem {
+    pub fn new() -> Self {
+        CollisionSystem { colliding: false }
+    }
+
+    /// Pushes the eye and the target of the camera out of every collider (`mesh_radius` is the
+    /// radius of the mesh the colliders scale). Returns the entity hit when a contact begins
+    pub fn run(&mut self, world: &World, camera: &mut Camera, mesh_radius: f32) -> Option<Entity> {
+        self.run_on(&world.colliders, &world.transforms, camera, mesh_radius)
+    }
+
+    /// Same as `run` on the colliders and transforms of a world kept elsewhere (the simulation thread)
+    pub fn run_on(&mut self, colliders: &[Option<Collider>], transforms: &[Transform], camera: &mut Camera, mesh_radius: f32) -> Option<Entity> {
+        let mut hit = None;
+        for (entity, collider) in colliders.iter().enumerate() {
+            let Some(collider) = collider else {
+                continue;
+            };
+            let transform = &transforms[entity];
+            let body_radius = mesh_radius * transform.scale * collider.margin;
+            if push_out(&mut camera.eye, transform.position, body_radius) {
+                hit = Some(entity);
+            }
+            push_out(&mut camera.target, transform.position, body_radius);
+        }
+        // Solo cuenta el comienzo del contacto, no mientras dura
+        let began = !self.colliding;
+        self.colliding = hit.is_some();
+        hit.filter(|_| began)
+    }
+}
+
+// Si la esfera de la cámara en `point` toca la del cuerpo, la lleva hasta su borde; devuelve si la tocaba
+fn push_out(point: &mut Vector3, body_pos: Vector3, body_radius: f32) -> bool {
+    let diff_x = point.x - body_pos.x;
+    let diff_y = point.y - body_pos.y;
+    let diff_z = point.z - body_pos.z;
+    let distance = (diff_x.powi(2) + diff_y.powi(2) + diff_z.powi(2)).sqrt();
+    let min_distance = body_radius + CAMERA_RADIUS;
+    if distance >= min_distance {
+        return false;
+    }
+    if distance > 0.0 {
+        // Nueva posición en la dirección de separación, justo fuera del cuerpo
+        point.x = body_pos.x + diff_x / distance * min_distance;
+        point.y = body_pos.y + diff_y / distance * min_distance;
+        point.z = body_pos.z + diff_z / distance * min_distance;
+    }
+    true
+}
+
+/// Turns the renderable entities into models for the renderer and draws their orbits
+pub struct RenderSystem;
+
+impl RenderSystem {
+    /// One model per renderable entity, drawn with `mesh` scaled and placed by its transform
+    pub fn models<'a>(&self, world: &'a World, mesh: &'a Mesh) -> impl Iterator<Item = Model<'a>> {
+        world.renderables.iter().enumerate().filter_map(move |(entity, renderable)| {
+            let renderable = renderable.as_ref()?;
+            let transform = &world.transforms[entity];
+            Some(Model {
+                mesh,
+                model_matrix: create_model_matrix(transform.position, transform.scale, transform.rotation),
+                shader: &renderable.shader,
+                label: &world.names[entity],
+                surface: renderable.surface,
+                displacement: renderable.displacement,
+                vertex_animation: &renderable.vertex_animation,
+                geometry_shader: &renderable.geometry_shader,
+                parts: &[],
+                ray_traced: renderable.ray_traced,
+                rings: renderable.rings,
+                sdf: renderable.sdf,
+            })
+        })
+    }
+
+    /// Draws the orbit of every entity that orbits with the colors of `palette`: the ones
+    /// around the center in its orbit color, moons around their planet in their own color (or
+    /// one of the palette), and the orbit of the `selected` body in the selection color
+    pub fn draw_orbits(&self, world: &World, orbit_cache: &mut OrbitCache, framebuffer: &mut Framebuffer, palette: &Palette, selected: Option<Entity>) {
+        let mut moons = 0;
+        for (entity, orbit) in world.orbits.iter().enumerate() {
+            let Some(orbit) = orbit.filter(|orbit| orbit.radius > 0.0) else {
+                continue;
+            };
+            let name = &world.names[entity];
+            let (color, alpha, center) = match orbit.parent {
+                None => (palette.orbit, 80, Vector3::zero()),
+                Some(parent) => {
+                    let color = world.renderables[entity].as_ref().map_or(Color::WHITE, |renderable| renderable.color);
+                    moons += 1;
+                    (palette.moon_orbit(moons - 1, color), 30, world.transforms[parent].position)
+                }
+            };
+            let (color, alpha) = if selected == Some(entity) { (palette.selection, 160) } else { (color, alpha) };
+            orbit_cache.draw(framebuffer, name, orbit.radius, center, Color::new(color.r, color.g, color.b, alpha));
+        }
+    }
+}
@@ -0,0 +1,227 @@
+// terminal.rs
+// Modo de demostración: cada frame se reduce y se imprime en la terminal con colores ANSI
+// (true color), así el rasterizador se puede ver también desde una sesión SSH. Como el
+// turntable, no abre la ventana: la cámara gira alrededor de toda la escena mientras avanza
+use crate::app::spheres_arg;
+use crate::parse_arg;
+use crate::settings::QualityPreset;
+use crate::shader_plugins;
+use crate::systems::{OrbitSystem, RenderSystem};
+use crate::world::World;
+use space_travel::{Renderer, Scene, View};
+use space_travel::assets::Assets;
+use space_travel::camera::{FAR_PLANE, NEAR_PLANE};
+use space_travel::framebuffer::Framebuffer;
+use space_travel::lighting::key_light;
+use space_travel::matrix::{DepthMode, create_projection_matrix, create_view_matrix};
+use space_travel::scene::load_scene;
+use space_travel::shader_params::ShaderParams;
+use raylib::prelude::*;
+use std::f32::consts::{PI, TAU};
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Caracteres de menor a mayor luminancia para el estilo ASCII
+const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+// Muestras por lado al promediar cada celda (3x3)
+const SAMPLES: i32 = 3;
+
+// Resolución interna del render, ancho por defecto en caracteres y frames por segundo
+const WIDTH: i32 = 640;
+const HEIGHT: i32 = 360;
+const COLUMNS: i32 = 120;
+const FPS: f32 = 15.0;
+
+// La cámara gira una vuelta cada ORBIT_SECONDS, por encima del plano de las órbitas
+const ORBIT_SECONDS: f32 = 60.0;
+const ELEVATION: f32 = 0.45;
+// Margen alrededor de la órbita más lejana al encuadrar la escena
+const FRAMING_MARGIN: f32 = 1.3;
+
+const SHADER_PARAMS_PATH: &str = "shaders.toml";
+const BACKGROUND_COLOR: Color = Color::new(10, 10, 16, 255);
+
+/// How each terminal cell represents the pixels under it
+#[derive(Clone, Copy, Debug)]
+pub enum TerminalStyle {
+    // Medio bloque '▀': el color del texto es la mitad de arriba y el fondo la de abajo
+    Blocks,
+    // Un carácter según la luminancia, coloreado con el color medio
+    Ascii,
+}
+
+impl TerminalStyle {
+    /// Parses "blocks" or "ascii"
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "blocks" => Ok(TerminalStyle::Blocks),
+            "ascii" => Ok(TerminalStyle::Ascii),
+            _ => Err(format!("unknown terminal style '{}' (expected blocks or ascii)", text)),
+        }
+    }
+}
+
+/// Prints the framebuffer to stdout, `columns` characters wide, redrawing in place every frame
+pub struct TerminalOutput {
+    style: TerminalStyle,
+    columns: i32,
+    text: String,
+    first_frame: bool,
+}
+
+impl TerminalOutput {
+    pub fn new(style: TerminalStyle, columns: i32) -> Self {
+        TerminalOutput { style, columns: columns.max(8), text: String::new(), first_frame: true }
+    }
+
+    /// Downsamples the color buffer and writes it as one block of ANSI-colored text
+    pub fn present(&mut self, framebuffer: &Framebuffer) -> io::Result<()> {
+        // Las celdas de la terminal miden aproximadamente el doble de alto que de ancho
+        let cell = framebuffer.width as f32 / self.columns as f32;
+        let rows = (framebuffer.height as f32 / (cell * 2.0)) as i32;
+        let colors = framebuffer.color_buffer.get_image_data();
+        let pixels = Pixels { colors: &colors, width: framebuffer.width, height: framebuffer.height };
+
+        self.text.clear();
+        // Primer frame: limpiar la pantalla; después solo volver al inicio para sobrescribir
+        self.text.push_str(if self.first_frame { "\x1b[2J\x1b[H" } else { "\x1b[H" });
+        self.first_frame = false;
+
+        for row in 0..rows {
+            let top = row as f32 * cell * 2.0;
+            for column in 0..self.columns {
+                let left = column as f32 * cell;
+                match self.style {
+                    TerminalStyle::Blocks => {
+                        let upper = pixels.average(left, top, cell, cell);
+                        let lower = pixels.average(left, top + cell, cell, cell);
+                        let _ = write!(
+                            self.text,
+                            "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m\u{2580}",
+                            upper.r, upper.g, upper.b, lower.r, lower.g, lower.b,
+                        );
+                    }
+                    TerminalStyle::Ascii => {
+                        let color = pixels.average(left, top, cell, cell * 2.0);
+                        let luminance = (0.2126 * color.r as f32 + 0.7152 * color.g as f32 + 0.0722 * color.b as f32) / 255.0;
+                        let index = ((luminance * ASCII_RAMP.len() as f32) as usize).min(ASCII_RAMP.len() - 1);
+                        let _ = write!(self.text, "\x1b[38;2;{};{};{}m{}", color.r, color.g, color.b, ASCII_RAMP[index] as char);
+                    }
+                }
+            }
+            self.text.push_str("\x1b[0m\n");
+        }
+
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(self.text.as_bytes())?;
+        stdout.flush()
+    }
+}
+
+/// Renders the scene without a window and prints every frame to the terminal (--terminal
+/// blocks | ascii, with --terminal-columns, --scene, --spheres and --seconds); runs until
+/// Ctrl+C unless --seconds is given
+pub fn run(style: &str) -> Result<(), String> {
+    let style = TerminalStyle::parse(style)?;
+    let columns = match parse_arg("terminal-columns") {
+        Some(text) => text.parse::<i32>().map_err(|e| format!("Invalid terminal width '{}': {}", text, e))?,
+        None => COLUMNS,
+    };
+    let seconds = match parse_arg("seconds") {
+        Some(text) => Some(text.parse::<f32>().ok().filter(|&seconds| seconds > 0.0)
+            .ok_or_else(|| format!("Invalid duration '{}'", text))?),
+        None => None,
+    };
+    let scene = load_scene(&parse_arg("scene").unwrap_or_else(|| "xerion".to_string()))?;
+    let mut world = World::new(&scene.bodies);
+    if let Some(on) = spheres_arg() {
+        world.set_ray_traced(on);
+    }
+
+    // Calidad baja: la terminal solo muestra unas decenas de filas
+    let quality = QualityPreset::Low;
+    let sphere = Assets::load(quality.planet_mesh()).sphere;
+    let authored = ShaderParams::load(SHADER_PARAMS_PATH).unwrap_or_else(|e| {
+        eprintln!("Using default shader parameters: {}", e);
+        ShaderParams::default()
+    });
+    let params = quality.apply(&authored);
+    let mut renderer = Renderer::new(WIDTH, HEIGHT);
+    renderer.framebuffer.set_background_color(BACKGROUND_COLOR);
+    shader_plugins::register(&mut renderer.shaders);
+
+    let mut orbit_system = OrbitSystem::new();
+    orbit_system.run(&mut world, 0.0, 0.0);
+    // Distancia que encuadra la órbita más lejana; el campo de visión es de 60°
+    let extent = world.transforms.iter().map(|transform| transform.position.length() + transform.scale).fold(1.0, f32::max);
+    let distance = (extent * FRAMING_MARGIN / (PI / 6.0).tan()).min(FAR_PLANE * 0.5);
+    let projection_matrix = create_projection_matrix(PI / 3.0, WIDTH as f32 / HEIGHT as f32, NEAR_PLANE, FAR_PLANE);
+
+    let mut output = TerminalOutput::new(style, columns);
+    let dt = 1.0 / FPS;
+    let mut frame = 0;
+    loop {
+        let frame_start = Instant::now();
+        let time = frame as f32 * dt;
+        if seconds.is_some_and(|seconds| time >= seconds) {
+            return Ok(());
+        }
+        orbit_system.run(&mut world, time, if frame == 0 { 0.0 } else { dt });
+
+        let angle = time / ORBIT_SECONDS * TAU;
+        let eye = Vector3::new(
+            distance * ELEVATION.cos() * angle.cos(),
+            distance * ELEVATION.sin(),
+            distance * ELEVATION.cos() * angle.sin(),
+        );
+        let mut lights = if params.lighting.key_light_only { Vec::new() } else { world.scene_lights() };
+        lights.extend(key_light(&params.lighting));
+        let frame_scene = Scene { models: RenderSystem.models(&world, &sphere).collect(), lights };
+        let view = View {
+            view_matrix: create_view_matrix(eye, Vector3::zero(), Vector3::new(0.0, 1.0, 0.0)),
+            projection_matrix,
+            depth_mode: DepthMode::Projective,
+            eye,
+            time,
+            dt,
+        };
+        renderer.framebuffer.clear();
+        renderer.draw_frame(&frame_scene, &view, &params);
+        output.present(&renderer.framebuffer).map_err(|e| format!("Failed to write to the terminal: {}", e))?;
+
+        frame += 1;
+        let elapsed = frame_start.elapsed();
+        let target = Duration::from_secs_f32(dt);
+        if elapsed < target {
+            thread::sleep(target - elapsed);
+        }
+    }
+}
+
+// Copia de los píxeles del frame (fila a fila)
+struct Pixels<'a> {
+    colors: &'a [Color],
+    width: i32,
+    height: i32,
+}
+
+impl Pixels<'_> {
+    // Color medio de un rectángulo, a partir de una rejilla de muestras
+    fn average(&self, left: f32, top: f32, width: f32, height: f32) -> Color {
+        let (mut r, mut g, mut b) = (0u32, 0u32, 0u32);
+        for sy in 0..SAMPLES {
+            for sx in 0..SAMPLES {
+                let x = ((left + (sx as f32 + 0.5) * width / SAMPLES as f32) as i32).min(self.width - 1);
+                let y = ((top + (sy as f32 + 0.5) * height / SAMPLES as f32) as i32).min(self.height - 1);
+                let color = self.colors[(y * self.width + x) as usize];
+                r += color.r as u32;
+                g += color.g as u32;
+                b += color.b as u32;
+            }
+        }
+        let count = (SAMPLES * SAMPLES) as u32;
+        Color::new((r / count) as u8, (g / count) as u8, (b / count) as u8, 255)
+    }
+}
@@ -0,0 +1,109 @@
+// trajectory.rs
+// Trayectoria prevista de la nave: desde la cámara, la velocidad actual integrada unos segundos
+// hacia adelante y dibujada con líneas con profundidad (los cuerpos la tapan), con una marca por
+// cada `tick_interval` segundos para medir el tiempo. No hay gravedad que integrar: las órbitas
+// del mundo son cinemáticas, así que el camino es recto. Con un cuerpo seleccionado se marca
+// también el punto de máximo acercamiento, suponiendo que él sigue con su velocidad medida
+use crate::palette::Palette;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::matrix::{DepthMode, multiply_matrix_vector4};
+use space_travel::shader_params::TrajectoryParams;
+use raylib::prelude::*;
+
+// Tamaño en píxeles de las marcas de tiempo y de las del máximo acercamiento
+const TICK_SIZE: i32 = 3;
+const APPROACH_SIZE: i32 = 6;
+
+/// Camera matrices the path is projected with
+pub struct PathCamera<'a> {
+    pub view_projection: &'a Matrix,
+    pub viewport: &'a Matrix,
+    pub depth_mode: DepthMode,
+}
+
+/// Draws the path of a ship at `eye` moving with `velocity` for the next `params.seconds`;
+/// `target` (position and velocity of the selected body) adds the closest approach to it. The
+/// path and the approach take their colors from `palette`
+pub fn draw(framebuffer: &mut Framebuffer, eye: Vector3, velocity: Vector3, target: Option<(Vector3, Vector3)>, camera: &PathCamera, params: &TrajectoryParams, palette: &Palette) {
+    if params.seconds <= 0.0 || params.steps == 0 || velocity.length() < params.min_speed {
+        return;
+    }
+    let project = |point: Vector3| {
+        let clip = multiply_matrix_vector4(camera.view_projection, &Vector4::new(point.x, point.y, point.z, 1.0));
+        if clip.w <= 0.1 {
+            return None;
+        }
+        let ndc = Vector4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+        let screen = multiply_matrix_vector4(camera.viewport, &ndc);
+        Some((screen.x as i32, screen.y as i32, camera.depth_mode.depth(ndc.z, clip.w)))
+    };
+    let color = |strength: f32| {
+        let value = (params.brightness * strength).clamp(0.0, 1.0);
+        let path = palette.path;
+        Color::new((path.x * value * 255.0) as u8, (path.y * value * 255.0) as u8, (path.z * value * 255.0) as u8, 255)
+    };
+
+    // Tramos rectos entre pasos de integración, cada uno a la profundidad de su punto más
+    // lejano para que no asome por delante de lo que cruza; se apaga hacia el final
+    let step = params.seconds / params.steps as f32;
+    let position = |time: f32| eye + velocity * time;
+    for index in 0..params.steps {
+        let (start, end) = (index as f32 * step, (index + 1) as f32 * step);
+        let (Some((x0, y0, depth0)), Some((x1, y1, depth1))) = (project(position(start)), project(position(end))) else {
+            continue;
+        };
+        // Tramos que cruzan media pantalla o más están pegados al plano cercano
+        if (x1 - x0).abs() + (y1 - y0).abs() > framebuffer.width {
+            continue;
+        }
+        framebuffer.draw_line_with_depth(x0, y0, x1, y1, color(1.0 - start / params.seconds), depth0.max(depth1));
+    }
+
+    // Marcas de tiempo: una cruz pequeña cada `tick_interval` segundos
+    if params.tick_interval > 0.0 {
+        let mut time = params.tick_interval;
+        while time <= params.seconds {
+            if let Some((x, y, depth)) = project(position(time)) {
+                let tick = color(1.0 - time / params.seconds + 0.25);
+                framebuffer.draw_line_with_depth(x - TICK_SIZE, y, x + TICK_SIZE, y, tick, depth);
+                framebuffer.draw_line_with_depth(x, y - TICK_SIZE, x, y + TICK_SIZE, tick, depth);
+            }
+            time += params.tick_interval;
+        }
+    }
+
+    // Máximo acercamiento: el instante en que la distancia entre los dos movimientos rectos es
+    // mínima, dentro del tiempo previsto. Rombos donde estarán la nave y el cuerpo, unidos
+    let Some((body_position, body_velocity)) = target else {
+        return;
+    };
+    let offset = body_position - eye;
+    let relative_velocity = body_velocity - velocity;
+    let speed_squared = relative_velocity.dot(relative_velocity);
+    if speed_squared <= f32::EPSILON {
+        return;
+    }
+    let time = (-offset.dot(relative_velocity) / speed_squared).clamp(0.0, params.seconds);
+    if time <= 0.0 {
+        return;
+    }
+    // El centro del cuerpo queda dentro de su superficie: ambas marcas van a la profundidad del
+    // punto de la nave para que el cuerpo no tape la suya
+    let Some((ship_x, ship_y, depth)) = project(position(time)) else {
+        return;
+    };
+    let mut markers = vec![(ship_x, ship_y)];
+    if let Some((x, y, _)) = project(body_position + body_velocity * time)
+        && (x - ship_x).abs() + (y - ship_y).abs() <= framebuffer.width
+    {
+        framebuffer.draw_line_with_depth(ship_x, ship_y, x, y, palette.warning, depth);
+        markers.push((x, y));
+    }
+    for (x, y) in markers {
+        let corners = [(x, y - APPROACH_SIZE), (x + APPROACH_SIZE, y), (x, y + APPROACH_SIZE), (x - APPROACH_SIZE, y)];
+        for (index, &(x0, y0)) in corners.iter().enumerate() {
+            let (x1, y1) = corners[(index + 1) % corners.len()];
+            framebuffer.draw_line_with_depth(x0, y0, x1, y1, palette.warning, depth);
+        }
+    }
+}
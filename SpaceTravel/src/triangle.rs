@@ -1,10 +1,14 @@
 // triangle.rs
 use crate::fragment::Fragment;
 use crate::vertex::Vertex;
-use crate::light::Light;
+use crate::framebuffer::DepthView;
+use crate::simd::{dot, interpolate, edge4, inside_mask4, less_mask4};
+use crate::stats::PipelineStats;
 use raylib::prelude::Vector3;
 
-fn barycentric_coordinates(p_x: f32, p_y: f32, a: &Vertex, b: &Vertex, c: &Vertex)  -> (f32, f32, f32) {
+// Coeficientes (a, b, c) de las coordenadas baricéntricas como funciones lineales de pantalla:
+// w = a * x + b * y + c. Devuelve None si el triángulo es degenerado
+fn barycentric_edges(a: &Vertex, b: &Vertex, c: &Vertex) -> Option<[[f32; 3]; 3]> {
     let a_x = a.transformed_position.x;   
     let a_y = a.transformed_position.y;
     let b_x = b.transformed_position.x;
@@ -15,40 +19,91 @@ fn barycentric_coordinates(p_x: f32, p_y: f32, a: &Vertex, b: &Vertex, c: &Verte
     let denom = (b_y - c_y) * (a_x - c_x) + (c_x - b_x) * (a_y - c_y);
 
     if denom.abs() < 1e-10  {
-        return (-1.0, -1.0, -1.0);
+        return None;
     }
-    
-    let w1 = ((b_y - c_y) * (p_x - c_x) + (c_x - b_x) * (p_y - c_y)) / denom;
-    let w2 = ((c_y - a_y) * (p_x - c_x) + (a_x - c_x) * (p_y - c_y)) / denom;
-    let w3 = 1.0 - w1 - w2;
 
-    (w1, w2, w3)
+    let e1 = [(b_y - c_y) / denom, (c_x - b_x) / denom, (-(b_y - c_y) * c_x - (c_x - b_x) * c_y) / denom];
+    let e2 = [(c_y - a_y) / denom, (a_x - c_x) / denom, (-(c_y - a_y) * c_x - (a_x - c_x) * c_y) / denom];
+    // w3 = 1 - w1 - w2
+    let e3 = [-e1[0] - e2[0], -e1[1] - e2[1], 1.0 - e1[2] - e2[2]];
+
+    Some([e1, e2, e3])
 }
 
-pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, light: &Light) -> Vec<Fragment> {
+/// Rasterizes a triangle into fragments. Pixels are evaluated 4 at a time; those hidden behind
+/// the current contents of `depth_view` are discarded before any per-fragment work. With
+/// `cull_back_faces` a triangle seen from behind produces nothing.
+/// The triangle and what happened to its pixels are counted in `stats`
+pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, cull_back_faces: bool, depth_view: &DepthView, stats: &mut PipelineStats) -> Vec<Fragment> {
     let mut fragments = Vec::new();
+    stats.triangles_submitted += 1;
     
 /*     //rgb colors demo
     let color_a = Vector3::new(1.0, 0.0, 0.0);
     let color_b = Vector3::new(0.0, 1.0, 0.0);
     let color_c = Vector3::new(0.0, 0.0, 1.0);  */
 
+    // Color sin iluminar; la luz se aplica después del fragment shader (ver lighting.rs)
     let base_color = Vector3::new(0.5, 0.5, 0.5);
 
-    let min_x = v1.transformed_position.x.min(v2.transformed_position.x).min(v3.transformed_position.x).floor() as i32;
-    let max_x = v1.transformed_position.x.max(v2.transformed_position.x).max(v3.transformed_position.x).ceil() as i32;
-    let min_y = v1.transformed_position.y.min(v2.transformed_position.y).min(v3.transformed_position.y).floor() as i32;
-    let max_y = v1.transformed_position.y.max(v2.transformed_position.y).max(v3.transformed_position.y).ceil() as i32;
+    // El viewport invierte Y: las caras frontales (antihorario en NDC) tienen área negativa en pantalla
+    let (p1, p2, p3) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
+    if cull_back_faces && (p2.x - p1.x) * (p3.y - p1.y) - (p2.y - p1.y) * (p3.x - p1.x) > 0.0 {
+        stats.triangles_backface_culled += 1;
+        return fragments;
+    }
 
-    for y in min_y..=max_y {
-        for x in min_x..=max_x {
-            let p_x = x as f32 + 0.5; //sample at pixel center
-            let p_y = y as f32 + 0.5;
+    let Some([e1, e2, e3]) = barycentric_edges(v1, v2, v3) else {
+        stats.triangles_clipped += 1;
+        return fragments;
+    };
+    // La profundidad también es lineal en pantalla
+    let (z1, z2, z3) = (v1.transformed_position.z, v2.transformed_position.z, v3.transformed_position.z);
+    let ez = [
+        z1 * e1[0] + z2 * e2[0] + z3 * e3[0],
+        z1 * e1[1] + z2 * e2[1] + z3 * e3[1],
+        z1 * e1[2] + z2 * e2[2] + z3 * e3[2],
+    ];
+
+    // Caja envolvente recortada a la pantalla
+    let min_x = (v1.transformed_position.x.min(v2.transformed_position.x).min(v3.transformed_position.x).floor() as i32).max(0);
+    let max_x = (v1.transformed_position.x.max(v2.transformed_position.x).max(v3.transformed_position.x).ceil() as i32).min(depth_view.width - 1);
+    let min_y = (v1.transformed_position.y.min(v2.transformed_position.y).min(v3.transformed_position.y).floor() as i32).max(0);
+    let max_y = (v1.transformed_position.y.max(v2.transformed_position.y).max(v3.transformed_position.y).ceil() as i32).min(depth_view.height - 1);
+    if min_x > max_x || min_y > max_y {
+        stats.triangles_clipped += 1;
+        return fragments;
+    }
 
-            // Calculate barycentric coordinates
-            let (w1, w2, w3) = barycentric_coordinates(p_x, p_y, v1, v2, v3);
+    for y in min_y..=max_y {
+        let p_y = y as f32 + 0.5; //sample at pixel center
+        let row = (y * depth_view.width) as usize;
+
+        for x0 in (min_x..=max_x).step_by(4) {
+            let xs = [0, 1, 2, 3].map(|i| (x0 + i) as f32 + 0.5);
+
+            // Evaluar 4 píxeles a la vez: cobertura y prueba de profundidad temprana
+            let w1s = edge4(e1, xs, p_y);
+            let w2s = edge4(e2, xs, p_y);
+            let w3s = edge4(e3, xs, p_y);
+            let depths = edge4(ez, xs, p_y);
+            // Los carriles fuera de la caja nunca pasan la prueba de profundidad
+            let stored = [0, 1, 2, 3].map(|i| {
+                let x = x0 + i;
+                if x <= max_x { depth_view.depth[row + x as usize] } else { f32::NEG_INFINITY }
+            });
+            let inside = inside_mask4(w1s, w2s, w3s) & lanes_in_box(x0, max_x);
+            let mask = inside & less_mask4(depths, stored);
+            stats.fragments_depth_rejected += (inside & !mask).count_ones();
+            if mask == 0 {
+                continue;
+            }
 
-            if w1 >= 0.0 && w2 >= 0.0 && w3 >= 0.0 {
+            for lane in 0..4 {
+                if mask & (1 << lane) == 0 {
+                    continue;
+                }
+                let (p_x, w1, w2, w3, depth) = (xs[lane], w1s[lane], w2s[lane], w3s[lane], depths[lane]);
 
 /*                 //Interpolate rgb demo
                 let interpolated_color = Vector3::new(
@@ -58,14 +113,10 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, light: &Light) -> Vec<Fra
                 ); */
 
                 // Interpolate normals using barycentric coordinates
-                let interpolated_normal = Vector3::new(
-                    w1 * v1.transformed_normal.x + w2 * v2.transformed_normal.x + w3 * v3.transformed_normal.x,
-                    w1 * v1.transformed_normal.y + w2 * v2.transformed_normal.y + w3 * v3.transformed_normal.y,
-                    w1 * v1.transformed_normal.z + w2 * v2.transformed_normal.z + w3 * v3.transformed_normal.z,
-                );
+                let interpolated_normal = interpolate(w1, w2, w3, v1.transformed_normal, v2.transformed_normal, v3.transformed_normal);
 
                 // Normalize the interpolated normal
-                let normal_length = (interpolated_normal.x * interpolated_normal.x + interpolated_normal.y * interpolated_normal.y + interpolated_normal.z * interpolated_normal.z).sqrt();
+                let normal_length = dot(interpolated_normal, interpolated_normal).sqrt();
 
                 let mut normalized_normal = interpolated_normal;
                 if normal_length > 0.0 {
@@ -73,45 +124,99 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, light: &Light) -> Vec<Fra
                     normalized_normal.y /= normal_length;
                     normalized_normal.z /= normal_length;
                 }
-                
+            
                 // Calculate position in world space for this fragment
-                let world_pos = Vector3::new(
-                    w1 * v1.position.x + w2 * v2.position.x + w3 * v3.position.x,
-                    w1 * v1.position.y + w2 * v2.position.y + w3 * v3.position.y,
-                    w1 * v1.position.z + w2 * v2.position.z + w3 * v3.position.z,
-                );
-
-                // Light direction (from surface to light) for this fragment
-                let mut light_dir = Vector3::new(
-                    light.position.x - world_pos.x,
-                    light.position.y - world_pos.y,
-                    light.position.z - world_pos.z,
-                );
-
-                // Normalize light direction
-                let light_length = (light_dir.x * light_dir.x + light_dir.y * light_dir.y + light_dir.z * light_dir.z).sqrt();
-                if light_length > 0.0 {
-                    light_dir.x /= light_length;
-                    light_dir.y /= light_length;
-                    light_dir.z /= light_length;
-                }
+                let world_pos = interpolate(w1, w2, w3, v1.world_position, v2.world_position, v3.world_position);
+
+                // Interpolate tangent frame for normal mapping
+                let tangent = interpolate(w1, w2, w3, v1.transformed_tangent, v2.transformed_tangent, v3.transformed_tangent).normalized();
+                let bitangent = interpolate(w1, w2, w3, v1.transformed_bitangent, v2.transformed_bitangent, v3.transformed_bitangent).normalized();
+
+                let mut fragment = Fragment::new(p_x, p_y, base_color, depth, world_pos);
+                fragment.object_position = interpolate(w1, w2, w3, v1.position, v2.position, v3.position);
+                fragment.normal = normalized_normal;
+                fragment.tangent = tangent;
+                fragment.bitangent = bitangent;
+                fragment.light_diffuse = interpolate(w1, w2, w3, v1.light_diffuse, v2.light_diffuse, v3.light_diffuse);
+                fragment.light_specular = interpolate(w1, w2, w3, v1.light_specular, v2.light_specular, v3.light_specular);
+
+                // Material: constante por cara, se toma del primer vértice; el color difuso se
+                // interpola porque los modelos con colores por vértice lo cambian dentro de la cara
+                fragment.diffuse = interpolate(w1, w2, w3, v1.color, v2.color, v3.color);
+                fragment.specular = v1.specular;
+                fragment.shininess = v1.shininess;
+                fragment.emissive = v1.emissive;
+                fragment.material_id = v1.material_id;
+                fragments.push(fragment);
+            }
+        }
+    }
+
+    stats.fragments_generated += fragments.len() as u32;
+    fragments
+}
 
-                // Calculate per-fragment lighting intesnsity isuign interpolated normal and light direction
-                let intensity = (normalized_normal.x * light_dir.x + normalized_normal.y * light_dir.y + normalized_normal.z * light_dir.z).max(0.0);
+// Máscara de los carriles x0..x0+3 que caen dentro de la caja (x <= max_x)
+fn lanes_in_box(x0: i32, max_x: i32) -> u32 {
+    match max_x - x0 {
+        0 => 0b0001,
+        1 => 0b0011,
+        2 => 0b0111,
+        _ => 0b1111,
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use raylib::prelude::Vector2;
 
-                let shaded_color = Vector3::new(
-                    base_color.x * intensity,
-                    base_color.y * intensity,
-                    base_color.z * intensity,
-                );
+    const SIZE: i32 = 16;
 
-                // Interpolate depth using barycentric coordinates
-                let depth = w1 * v1.transformed_position.z + w2 * v2.transformed_position.z + w3 * v3.transformed_position.z;
+    // Vértice ya en pantalla
+    fn at(x: f32, y: f32) -> Vertex {
+        Vertex::new(Vector3::new(x, y, 0.5), Vector3::new(0.0, 0.0, 1.0), Vector2::new(0.0, 0.0))
+    }
 
-                fragments.push(Fragment::new(p_x, p_y, shaded_color, depth, world_pos));
-            }
-        }
+    fn rasterize(triangles: &[[Vertex; 3]], cull_back_faces: bool, depth: &[f32]) -> (usize, PipelineStats) {
+        let depth_view = DepthView { width: SIZE, height: SIZE, depth };
+        let mut stats = PipelineStats::default();
+        let fragments = triangles.iter()
+            .map(|[a, b, c]| triangle(a, b, c, cull_back_faces, &depth_view, &mut stats).len())
+            .sum();
+        (fragments, stats)
     }
 
-    fragments
-}
\ No newline at end of file
+    #[test]
+    fn every_submitted_triangle_is_culled_clipped_or_rasterized() {
+        let front = [at(1.0, 1.0), at(1.0, 12.0), at(12.0, 1.0)];
+        let back = [at(1.0, 1.0), at(12.0, 1.0), at(1.0, 12.0)];
+        let degenerate = [at(1.0, 1.0), at(5.0, 5.0), at(9.0, 9.0)];
+        let off_screen = [at(40.0, 40.0), at(40.0, 50.0), at(50.0, 40.0)];
+        let depth = vec![f32::INFINITY; (SIZE * SIZE) as usize];
+
+        let (fragments, stats) = rasterize(&[front.clone(), back.clone(), degenerate, off_screen], true, &depth);
+        assert_eq!(stats.triangles_submitted, 4);
+        assert_eq!(stats.triangles_backface_culled, 1);
+        assert_eq!(stats.triangles_clipped, 2);
+        assert!(fragments > 0);
+        assert_eq!(stats.fragments_generated as usize, fragments);
+
+        // Sin descarte la cara trasera cubre los mismos píxeles que la delantera
+        let (front_fragments, _) = rasterize(&[front], false, &depth);
+        let (back_fragments, stats) = rasterize(&[back], false, &depth);
+        assert_eq!(stats.triangles_backface_culled, 0);
+        assert_eq!(back_fragments, front_fragments);
+    }
+
+    #[test]
+    fn covered_pixels_are_generated_or_depth_rejected() {
+        let front = [at(1.0, 1.0), at(1.0, 14.0), at(14.0, 1.0)];
+        let (covered, _) = rasterize(std::slice::from_ref(&front), true, &vec![f32::INFINITY; (SIZE * SIZE) as usize]);
+
+        // La mitad de arriba ya tiene algo más cerca
+        let depth: Vec<f32> = (0..SIZE * SIZE).map(|i| if i < SIZE * SIZE / 2 { 0.0 } else { f32::INFINITY }).collect();
+        let (fragments, stats) = rasterize(&[front], true, &depth);
+        assert!(stats.fragments_depth_rejected > 0);
+        assert_eq!(fragments + stats.fragments_depth_rejected as usize, covered);
+    }
+}
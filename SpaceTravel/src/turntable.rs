@@ -0,0 +1,113 @@
+// turntable.rs
+// Exportador de "plató giratorio": sin abrir la ventana, la cámara da una vuelta completa
+// alrededor de un cuerpo de la escena a una distancia que lo encuadra y cada frame se guarda
+// como PNG (frame_0000.png, frame_0001.png...), para enseñar el shader de cada planeta. La escena
+// avanza mientras tanto, así que el cuerpo gira y sus shaders se animan; la cámara lo sigue por
+// su órbita. Se dibuja con la calidad Ultra; para un GIF o un vídeo se juntan los PNG con ffmpeg
+use crate::app::spheres_arg;
+use crate::parse_arg;
+use crate::settings::QualityPreset;
+use crate::shader_plugins;
+use crate::systems::{OrbitSystem, RenderSystem};
+use crate::world::World;
+use space_travel::{Renderer, Scene, View};
+use space_travel::assets::{Assets, PlanetMesh};
+use space_travel::camera::{FAR_PLANE, NEAR_PLANE};
+use space_travel::lighting::key_light;
+use space_travel::matrix::{DepthMode, create_projection_matrix, create_view_matrix};
+use space_travel::scene::load_scene;
+use space_travel::shader_params::ShaderParams;
+use raylib::prelude::*;
+use std::f32::consts::{PI, TAU};
+use std::fs;
+use std::path::Path;
+
+// Tamaño de las imágenes y frames por segundo de la animación
+const WIDTH: i32 = 960;
+const HEIGHT: i32 = 540;
+const FPS: f32 = 30.0;
+const DEFAULT_SECONDS: f32 = 10.0;
+
+// Distancia de la cámara en radios del cuerpo y altura sobre su ecuador, en radianes
+const FRAMING_DISTANCE: f32 = 3.5;
+const ELEVATION: f32 = 0.3;
+
+const BACKGROUND_COLOR: Color = Color::new(10, 10, 16, 255);
+const SHADER_PARAMS_PATH: &str = "shaders.toml";
+
+/// Renders one turn of the camera around `body` as a PNG sequence (--turntable <body>, with
+/// --seconds, --scene, --planet-mesh, --spheres and --output); returns the number of frames written
+pub fn export(body: &str) -> Result<usize, String> {
+    let seconds = match parse_arg("seconds") {
+        Some(text) => text.parse::<f32>().ok().filter(|&seconds| seconds > 0.0)
+            .ok_or_else(|| format!("Invalid duration '{}'", text))?,
+        None => DEFAULT_SECONDS,
+    };
+    let scene_name = parse_arg("scene").unwrap_or_else(|| "xerion".to_string());
+    let scene = load_scene(&scene_name)?;
+    let mut world = World::new(&scene.bodies);
+    if let Some(on) = spheres_arg() {
+        world.set_ray_traced(on);
+    }
+    let entity = world.names.iter().position(|name| name.eq_ignore_ascii_case(body)).ok_or_else(|| {
+        format!("No body named '{}' in scene '{}' (bodies: {})", body, scene_name, world.names.join(", "))
+    })?;
+
+    let output = parse_arg("output").unwrap_or_else(|| format!("turntable_{}", world.names[entity].to_lowercase()));
+    fs::create_dir_all(&output).map_err(|e| format!("{}: {}", output, e))?;
+
+    let planet_mesh = match parse_arg("planet-mesh") {
+        Some(text) => PlanetMesh::parse(&text)?,
+        None => QualityPreset::Ultra.planet_mesh(),
+    };
+    let sphere = Assets::load(planet_mesh).sphere;
+    let authored = ShaderParams::load(SHADER_PARAMS_PATH).unwrap_or_else(|e| {
+        eprintln!("Using default shader parameters: {}", e);
+        ShaderParams::default()
+    });
+    let params = QualityPreset::Ultra.apply(&authored);
+    let mut renderer = Renderer::new(WIDTH, HEIGHT);
+    renderer.framebuffer.set_background_color(BACKGROUND_COLOR);
+    shader_plugins::register(&mut renderer.shaders);
+
+    let mut orbit_system = OrbitSystem::new();
+    let frames = (seconds * FPS).round().max(1.0) as usize;
+    let dt = 1.0 / FPS;
+    let projection_matrix = create_projection_matrix(PI / 3.0, WIDTH as f32 / HEIGHT as f32, NEAR_PLANE, FAR_PLANE);
+    for frame in 0..frames {
+        let time = frame as f32 * dt;
+        orbit_system.run(&mut world, time, if frame == 0 { 0.0 } else { dt });
+
+        // Una vuelta entera en la duración pedida, siempre a la misma distancia del cuerpo
+        let transform = world.transforms[entity];
+        let distance = (transform.scale * 0.5 * FRAMING_DISTANCE).max(NEAR_PLANE * 10.0);
+        let angle = frame as f32 / frames as f32 * TAU;
+        let eye = transform.position + Vector3::new(
+            distance * ELEVATION.cos() * angle.cos(),
+            distance * ELEVATION.sin(),
+            distance * ELEVATION.cos() * angle.sin(),
+        );
+
+        let mut lights = if params.lighting.key_light_only { Vec::new() } else { world.scene_lights() };
+        lights.extend(key_light(&params.lighting));
+        let frame_scene = Scene { models: RenderSystem.models(&world, &sphere).collect(), lights };
+        let view = View {
+            view_matrix: create_view_matrix(eye, transform.position, Vector3::new(0.0, 1.0, 0.0)),
+            projection_matrix,
+            depth_mode: DepthMode::Projective,
+            eye,
+            time,
+            dt,
+        };
+        renderer.framebuffer.clear();
+        renderer.draw_frame(&frame_scene, &view, &params);
+
+        let path = Path::new(&output).join(format!("frame_{:04}.png", frame));
+        renderer.framebuffer.color_buffer.export_image(&path.to_string_lossy());
+        if !path.exists() {
+            return Err(format!("Failed to write {}", path.display()));
+        }
+    }
+    println!("Turntable of '{}': {} frames written to {}", world.names[entity], frames, output);
+    Ok(frames)
+}
@@ -0,0 +1,409 @@
+// ui.rs
+// Menús del programa dibujados por software sobre el framebuffer, como el resto del render:
+// la pantalla de título (comenzar la exploración, elegir la escena, ajustes, salir), el menú de
+// pausa que abre Esc y la pantalla de ajustes. Se navegan con las flechas y Enter o con el ratón.
+// También los avisos breves de lo que acaba de pasar (warps, choques, eclipses...). Los textos
+// salen del idioma elegido en los ajustes (ver locale.rs)
+use crate::events::{Event, Observer};
+use crate::locale::Strings;
+use crate::settings::Settings;
+use crate::world::World;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::post::PostEffects;
+use raylib::prelude::*;
+
+// Medidas de los paneles, en píxeles para un framebuffer de 720 de alto (se escalan con él)
+const PANEL_WIDTH: f32 = 440.0;
+const PADDING: f32 = 24.0;
+const TITLE_SIZE: f32 = 36.0;
+const SUBTITLE_SIZE: f32 = 18.0;
+const ITEM_SIZE: f32 = 24.0;
+const ITEM_HEIGHT: f32 = 40.0;
+const HEADER_HEIGHT: f32 = 90.0; // Espacio del título sobre las opciones
+const REFERENCE_HEIGHT: f32 = 720.0;
+
+// Colores de los paneles (la opción elegida se resalta con el de la paleta de los ajustes)
+const PANEL_COLOR: Vector3 = Vector3::new(0.05, 0.06, 0.12);
+const BORDER_COLOR: Color = Color::new(110, 140, 220, 255);
+const TITLE_COLOR: Color = Color::new(230, 235, 255, 255);
+const TEXT_COLOR: Color = Color::new(180, 190, 215, 255);
+const SELECTED_TEXT_COLOR: Color = Color::WHITE;
+
+// Escenas que ofrece la pantalla de título, además de la elegida con --scene
+const BUILT_IN_SCENES: [&str; 2] = ["xerion", "solar"];
+const SCENE_FILE: &str = "scene.toml";
+
+// Segundos que dura un aviso y cuántos se muestran a la vez
+const NOTICE_DURATION: f32 = 3.0;
+const MAX_NOTICES: usize = 4;
+
+// Claves de los nombres de los efectos de post-proceso en el orden de `post_flag`
+const POST_EFFECTS: [&str; 6] = ["post.ssao", "post.dither", "post.crt", "post.motion_blur", "post.accumulate", "post.auto_exposure"];
+
+/// Which screen is showing
+#[derive(Clone, Copy, PartialEq)]
+pub enum Screen {
+    Title,
+    Playing,
+    Paused,
+    Settings,
+}
+
+/// What the main loop has to do after a menu choice
+pub enum MenuAction {
+    Start { scene: String },
+    SettingsChanged,
+    Quit,
+}
+
+// Opciones de los menús
+#[derive(Clone, Copy, PartialEq)]
+enum MenuItem {
+    Start,
+    Scene,
+    Settings,
+    Resume,
+    MainMenu,
+    Quit,
+    ResolutionScale,
+    DynamicResolution,
+    Post(usize),
+    Sensitivity,
+    Volume,
+    MusicVolume,
+    Quality,
+    Language,
+    Palette,
+    Back,
+}
+
+// Interruptor de `PostEffects` correspondiente a POST_EFFECTS[index]
+fn post_flag(post: &mut PostEffects, index: usize) -> &mut bool {
+    match index {
+        0 => &mut post.ssao,
+        1 => &mut post.dither,
+        2 => &mut post.crt,
+        3 => &mut post.motion_blur,
+        4 => &mut post.accumulate,
+        _ => &mut post.auto_exposure,
+    }
+}
+
+// Medidas de los paneles para un tamaño de framebuffer
+struct Layout {
+    width: i32,
+    height: i32,
+    unit: f32,
+}
+
+impl Layout {
+    fn new(width: i32, height: i32) -> Self {
+        Layout { width, height, unit: height as f32 / REFERENCE_HEIGHT }
+    }
+
+    fn px(&self, value: f32) -> i32 {
+        (value * self.unit).round() as i32
+    }
+
+    // Rectángulo (x, y, ancho, alto) del panel centrado con `items` opciones
+    fn panel(&self, items: usize) -> (i32, i32, i32, i32) {
+        let (w, h) = (self.px(PANEL_WIDTH), self.px(HEADER_HEIGHT + items as f32 * ITEM_HEIGHT + PADDING));
+        ((self.width - w) / 2, (self.height - h) / 2, w, h)
+    }
+
+    // Rectángulo de la opción `index` dentro del panel
+    fn item(&self, items: usize, index: usize) -> (i32, i32, i32, i32) {
+        let (x, y, w, _) = self.panel(items);
+        let top = self.px(HEADER_HEIGHT + index as f32 * ITEM_HEIGHT);
+        (x + self.px(PADDING / 2.0), y + top, w - self.px(PADDING), self.px(ITEM_HEIGHT))
+    }
+}
+
+/// Title screen, pause menu and settings
+pub struct Menu {
+    pub screen: Screen,
+    selected: usize,
+    scenes: Vec<String>,
+    scene_index: usize,
+    settings_return: Screen, // Pantalla a la que vuelve "Volver" desde los ajustes
+}
+
+impl Menu {
+    /// Starts on the title screen with `scene` selected
+    pub fn new(scene: &str) -> Self {
+        let mut scenes: Vec<String> = BUILT_IN_SCENES.iter().map(|name| name.to_string()).collect();
+        if std::path::Path::new(SCENE_FILE).exists() {
+            scenes.push(SCENE_FILE.to_string());
+        }
+        if !scenes.iter().any(|name| name == scene) {
+            scenes.push(scene.to_string());
+        }
+        let scene_index = scenes.iter().position(|name| name == scene).unwrap_or(0);
+        Menu { screen: Screen::Title, selected: 0, scenes, scene_index, settings_return: Screen::Title }
+    }
+
+    /// Whether a menu covers the scene (its input goes to the menu, not the camera)
+    pub fn is_open(&self) -> bool {
+        self.screen != Screen::Playing
+    }
+
+    /// Whether the simulation is stopped (pause menu, or settings opened from it)
+    pub fn is_paused(&self) -> bool {
+        self.screen == Screen::Paused || (self.screen == Screen::Settings && self.settings_return == Screen::Paused)
+    }
+
+    /// Opens the pause menu
+    pub fn pause(&mut self) {
+        self.open(Screen::Paused);
+    }
+
+    /// Whether the title screen (or settings opened from it) is showing
+    pub fn on_title(&self) -> bool {
+        self.screen == Screen::Title || (self.screen == Screen::Settings && self.settings_return == Screen::Title)
+    }
+
+    // Opciones de la pantalla actual con su texto
+    fn items(&self, settings: &Settings, strings: &Strings) -> Vec<(MenuItem, String)> {
+        let text = |key: &str| strings.get(key).to_string();
+        let on_off = |enabled: bool| strings.get(if enabled { "menu.on" } else { "menu.off" });
+        match self.screen {
+            Screen::Title => vec![
+                (MenuItem::Start, text("menu.start")),
+                (MenuItem::Scene, strings.format("menu.scene", &[("scene", &self.scenes[self.scene_index])])),
+                (MenuItem::Settings, text("menu.settings")),
+                (MenuItem::Quit, text("menu.quit")),
+            ],
+            Screen::Paused => vec![
+                (MenuItem::Resume, text("menu.resume")),
+                (MenuItem::Settings, text("menu.settings")),
+                (MenuItem::MainMenu, text("menu.main_menu")),
+                (MenuItem::Quit, text("menu.quit")),
+            ],
+            Screen::Settings => {
+                let percent = |value: f32| format!("{:.0}", value * 100.0);
+                let mut items = vec![(MenuItem::ResolutionScale, strings.format("menu.resolution", &[("percent", &percent(settings.resolution_scale))]))];
+                let target = if settings.target_fps == 0 { text("menu.off") } else { strings.format("menu.fps", &[("fps", &settings.target_fps)]) };
+                items.push((MenuItem::DynamicResolution, strings.format("menu.dynamic_resolution", &[("target", &target)])));
+                let mut post = settings.post;
+                for (index, key) in POST_EFFECTS.iter().enumerate() {
+                    let state = on_off(*post_flag(&mut post, index));
+                    items.push((MenuItem::Post(index), strings.format("menu.toggle", &[("name", &strings.get(key)), ("state", &state)])));
+                }
+                items.push((MenuItem::Sensitivity, strings.format("menu.sensitivity", &[("value", &format!("{:.1}", settings.sensitivity))])));
+                items.push((MenuItem::Volume, strings.format("menu.volume", &[("percent", &percent(settings.volume))])));
+                items.push((MenuItem::MusicVolume, strings.format("menu.music_volume", &[("percent", &percent(settings.music_volume))])));
+                items.push((MenuItem::Quality, strings.format("menu.quality", &[("quality", &strings.get(settings.quality.key()))])));
+                items.push((MenuItem::Language, strings.format("menu.language", &[("language", &settings.language.label())])));
+                items.push((MenuItem::Palette, strings.format("menu.palette", &[("palette", &strings.get(settings.palette.key()))])));
+                items.push((MenuItem::Back, text("menu.back")));
+                items
+            }
+            Screen::Playing => Vec::new(),
+        }
+    }
+
+    /// Handles the menu keys and mouse for this frame (`size` is the framebuffer size the menu
+    /// is drawn at). Esc opens the pause menu while playing and goes back from the menus;
+    /// changes to `settings` are made in place and reported with `SettingsChanged`
+    pub fn update(&mut self, window: &RaylibHandle, settings: &mut Settings, strings: &Strings, size: (i32, i32)) -> Option<MenuAction> {
+        if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            match self.screen {
+                Screen::Playing => self.open(Screen::Paused),
+                Screen::Paused => self.screen = Screen::Playing,
+                Screen::Settings => self.open(self.settings_return),
+                Screen::Title => {}
+            }
+            return None;
+        }
+        if self.screen == Screen::Playing {
+            return None;
+        }
+
+        let items = self.items(settings, strings);
+        let count = items.len();
+        if window.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.selected = (self.selected + 1) % count;
+        }
+        if window.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.selected = (self.selected + count - 1) % count;
+        }
+
+        // El ratón selecciona la opción que tiene encima y la activa con un clic; su posición
+        // se lleva a píxeles del framebuffer, que puede ser más pequeño que la ventana
+        let layout = Layout::new(size.0, size.1);
+        let mouse = window.get_mouse_position();
+        let mx = (mouse.x * size.0 as f32 / window.get_screen_width() as f32) as i32;
+        let my = (mouse.y * size.1 as f32 / window.get_screen_height() as f32) as i32;
+        let mut clicked = false;
+        for index in 0..count {
+            let (x, y, w, h) = layout.item(count, index);
+            if mx >= x && mx < x + w && my >= y && my < y + h {
+                if window.get_mouse_delta() != Vector2::zero() {
+                    self.selected = index;
+                }
+                if window.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+                    self.selected = index;
+                    clicked = true;
+                }
+            }
+        }
+        let activate = clicked || window.is_key_pressed(KeyboardKey::KEY_ENTER);
+        let left = window.is_key_pressed(KeyboardKey::KEY_LEFT);
+        let right = window.is_key_pressed(KeyboardKey::KEY_RIGHT);
+        // Las opciones con valor cambian con izquierda/derecha; Enter o un clic avanzan
+        let adjust = activate || left || right;
+
+        match items[self.selected.min(count - 1)].0 {
+            MenuItem::Start if activate => {
+                self.screen = Screen::Playing;
+                Some(MenuAction::Start { scene: self.scenes[self.scene_index].clone() })
+            }
+            MenuItem::Scene if adjust => {
+                let scenes = self.scenes.len();
+                self.scene_index = if left { (self.scene_index + scenes - 1) % scenes } else { (self.scene_index + 1) % scenes };
+                None
+            }
+            MenuItem::Settings if activate => {
+                self.settings_return = self.screen;
+                self.open(Screen::Settings);
+                None
+            }
+            MenuItem::Resume if activate => {
+                self.screen = Screen::Playing;
+                None
+            }
+            MenuItem::MainMenu if activate => {
+                self.open(Screen::Title);
+                None
+            }
+            MenuItem::Back if activate => {
+                self.open(self.settings_return);
+                None
+            }
+            MenuItem::Quit if activate => Some(MenuAction::Quit),
+            MenuItem::ResolutionScale if adjust => {
+                settings.cycle_resolution_scale(!left);
+                Some(MenuAction::SettingsChanged)
+            }
+            MenuItem::DynamicResolution if adjust => {
+                settings.cycle_target_fps(!left);
+                Some(MenuAction::SettingsChanged)
+            }
+            MenuItem::Post(index) if adjust => {
+                let flag = post_flag(&mut settings.post, index);
+                *flag = !*flag;
+                Some(MenuAction::SettingsChanged)
+            }
+            MenuItem::Sensitivity if adjust => {
+                settings.adjust_sensitivity(if left { -1 } else { 1 });
+                Some(MenuAction::SettingsChanged)
+            }
+            MenuItem::Volume if adjust => {
+                settings.adjust_volume(if left { -1 } else { 1 });
+                Some(MenuAction::SettingsChanged)
+            }
+            MenuItem::MusicVolume if adjust => {
+                settings.adjust_music_volume(if left { -1 } else { 1 });
+                Some(MenuAction::SettingsChanged)
+            }
+            MenuItem::Quality if adjust => {
+                settings.set_quality(settings.quality.cycle(!left));
+                Some(MenuAction::SettingsChanged)
+            }
+            MenuItem::Language if adjust => {
+                settings.language = settings.language.cycle(!left);
+                Some(MenuAction::SettingsChanged)
+            }
+            MenuItem::Palette if adjust => {
+                settings.palette = settings.palette.cycle(!left);
+                Some(MenuAction::SettingsChanged)
+            }
+            _ => None,
+        }
+    }
+
+    fn open(&mut self, screen: Screen) {
+        self.screen = screen;
+        self.selected = 0;
+    }
+
+    /// Darkens the frame and draws the panel of the current screen on top
+    pub fn draw(&self, framebuffer: &mut Framebuffer, settings: &Settings, strings: &Strings) {
+        if !self.is_open() {
+            return;
+        }
+        let layout = Layout::new(framebuffer.width, framebuffer.height);
+        // En pausa la escena se oscurece más para que se lea el menú
+        let dim = if self.on_title() { 0.7 } else { 0.45 };
+        blend_rect(framebuffer, (0, 0, layout.width, layout.height), Vector3::zero(), 1.0 - dim);
+
+        let items = self.items(settings, strings);
+        let (x, y, w, h) = layout.panel(items.len());
+        blend_rect(framebuffer, (x, y, w, h), PANEL_COLOR, 0.85);
+        let (title, subtitle) = match self.screen {
+            Screen::Title => ("menu.title", "menu.title_subtitle"),
+            Screen::Settings => ("menu.settings_title", "menu.settings_subtitle"),
+            _ => ("menu.pause_title", "menu.pause_subtitle"),
+        };
+        let (title, subtitle) = (strings.get(title), strings.get(subtitle));
+        let padding = layout.px(PADDING);
+        let image = &mut framebuffer.color_buffer;
+        image.draw_rectangle_lines(Rectangle::new(x as f32, y as f32, w as f32, h as f32), layout.px(2.0).max(1), BORDER_COLOR);
+        image.draw_text(title, x + padding, y + padding - layout.px(6.0), layout.px(TITLE_SIZE), TITLE_COLOR);
+        image.draw_text(subtitle, x + padding, y + padding + layout.px(TITLE_SIZE), layout.px(SUBTITLE_SIZE), TEXT_COLOR);
+
+        for (index, (_, label)) in items.iter().enumerate() {
+            let rect = layout.item(items.len(), index);
+            let selected = index == self.selected;
+            if selected {
+                blend_rect(framebuffer, rect, settings.palette.colors().highlight, 0.9);
+            }
+            let color = if selected { SELECTED_TEXT_COLOR } else { TEXT_COLOR };
+            let text_y = rect.1 + (rect.3 - layout.px(ITEM_SIZE)) / 2;
+            framebuffer.color_buffer.draw_text(label, rect.0 + padding / 2, text_y, layout.px(ITEM_SIZE), color);
+        }
+    }
+}
+
+/// Recent events as short lines of text, shown in the overlay for a few seconds
+pub struct Notices {
+    items: Vec<(Event, f32)>, // Evento y segundos que le quedan; se describe al mostrarlo
+}
+
+impl Notices {
+    pub fn new() -> Self {
+        Notices { items: Vec::new() }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for (_, remaining) in &mut self.items {
+            *remaining -= dt;
+        }
+        self.items.retain(|(_, remaining)| *remaining > 0.0);
+    }
+
+    /// The notices described with the names in `world`, in the language of `strings`
+    pub fn lines<'s>(&'s self, world: &'s World, strings: &'s Strings) -> impl Iterator<Item = String> + 's {
+        self.items.iter().map(move |(event, _)| event.describe(world, strings))
+    }
+}
+
+impl Observer for Notices {
+    fn on_event(&mut self, event: &Event, _world: &World) {
+        self.items.push((event.clone(), NOTICE_DURATION));
+        let excess = self.items.len().saturating_sub(MAX_NOTICES);
+        self.items.drain(..excess);
+    }
+}
+
+/// Blends `color` over the rectangle (x, y, width, height) of the frame with `opacity`
+pub fn blend_rect(framebuffer: &mut Framebuffer, rect: (i32, i32, i32, i32), color: Vector3, opacity: f32) {
+    let (x0, y0, w, h) = rect;
+    for y in y0.max(0)..(y0 + h).min(framebuffer.height) {
+        for x in x0.max(0)..(x0 + w).min(framebuffer.width) {
+            let current = framebuffer.color_buffer.get_color(x, y);
+            let mix = |channel: u8, value: f32| (channel as f32 * (1.0 - opacity) + value * 255.0 * opacity) as u8;
+            framebuffer.set_pixel(x, y, Color::new(mix(current.r, color.x), mix(current.g, color.y), mix(current.b, color.z), 255));
+        }
+    }
+}
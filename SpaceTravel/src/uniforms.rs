@@ -0,0 +1,21 @@
+// uniforms.rs
+use crate::matrix::DepthMode;
+use crate::shader_registry::{GeometryShader, VertexAnimation};
+use raylib::prelude::*;
+
+#[derive(Clone)]
+pub struct Uniforms {
+    pub model_matrix: Matrix,
+    pub normal_matrix: Matrix, // inversa-transpuesta de model_matrix
+    pub view_matrix: Matrix,
+    pub projection_matrix: Matrix,
+    pub viewport_matrix: Matrix,
+    pub depth_mode: DepthMode,
+    pub camera_position: Vector3, // posición de la cámara en el mundo (para términos que dependen de la vista)
+    pub time: f32, // elapsed time in seconds
+    pub dt: f32, // delta time in seconds
+    pub displacement: f32,    // Altura máxima del relieve en unidades de la malla (0 = sin relieve)
+    pub noise_frequency: f32, // Frecuencia del ruido del shader con el que se levanta el relieve
+    pub vertex_animation: Option<VertexAnimation>, // Mueve los vértices con el tiempo (None = rígido)
+    pub geometry_shader: Option<GeometryShader>,   // Emite triángulos extra (None = solo la malla)
+}
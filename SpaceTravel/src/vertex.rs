@@ -8,9 +8,21 @@ pub struct Vertex {
   pub position: Vector3,
   pub normal: Vector3,
   pub tex_coords: Vector2,
-  pub color: Vector3,
+  pub color: Vector3,     // Color difuso del material (Kd), por el del vértice si el OBJ lo trae
+  pub specular: Vector3,  // Ks
+  pub shininess: f32,     // Ns
+  pub emissive: Vector3,  // Ke
+  pub material_id: Option<usize>,
+  pub tangent: Vector3,   // Dirección +U de las coordenadas de textura
+  pub bitangent: Vector3, // Dirección +V de las coordenadas de textura
   pub transformed_position: Vector3,
+  pub world_position: Vector3,      // Posición tras la model matrix (para iluminación)
   pub transformed_normal: Vector3,
+  pub transformed_tangent: Vector3,
+  pub transformed_bitangent: Vector3,
+  // Luz calculada en el vértice (iluminación Gouraud): parte difusa y brillo especular
+  pub light_diffuse: Vector3,
+  pub light_specular: Vector3,
 }
 
 impl Vertex {
@@ -20,8 +32,19 @@ impl Vertex {
       normal,
       tex_coords,
       color: Vector3::new(0.0, 0.0, 0.0), // Black
+      specular: Vector3::new(0.0, 0.0, 0.0),
+      shininess: 0.0,
+      emissive: Vector3::new(0.0, 0.0, 0.0),
+      material_id: None,
+      tangent: Vector3::new(0.0, 0.0, 0.0),
+      bitangent: Vector3::new(0.0, 0.0, 0.0),
       transformed_position: position,
+      world_position: position,
       transformed_normal: normal,
+      transformed_tangent: Vector3::new(0.0, 0.0, 0.0),
+      transformed_bitangent: Vector3::new(0.0, 0.0, 0.0),
+      light_diffuse: Vector3::new(0.0, 0.0, 0.0),
+      light_specular: Vector3::new(0.0, 0.0, 0.0),
     }
   }
 
@@ -31,8 +54,19 @@ impl Vertex {
       normal: Vector3::new(0.0, 0.0, 0.0),
       tex_coords: Vector2::new(0.0, 0.0),
       color,
+      specular: Vector3::new(0.0, 0.0, 0.0),
+      shininess: 0.0,
+      emissive: Vector3::new(0.0, 0.0, 0.0),
+      material_id: None,
+      tangent: Vector3::new(0.0, 0.0, 0.0),
+      bitangent: Vector3::new(0.0, 0.0, 0.0),
       transformed_position: Vector3::new(0.0, 0.0, 0.0),
+      world_position: position,
       transformed_normal: Vector3::new(0.0, 0.0, 0.0),
+      transformed_tangent: Vector3::new(0.0, 0.0, 0.0),
+      transformed_bitangent: Vector3::new(0.0, 0.0, 0.0),
+      light_diffuse: Vector3::new(0.0, 0.0, 0.0),
+      light_specular: Vector3::new(0.0, 0.0, 0.0),
     }
   }
 
@@ -49,8 +83,19 @@ impl Default for Vertex {
       normal: Vector3::new(0.0, 1.0, 0.0),
       tex_coords: Vector2::new(0.0, 0.0),
       color: Vector3::new(0.0, 0.0, 0.0), // Black
+      specular: Vector3::new(0.0, 0.0, 0.0),
+      shininess: 0.0,
+      emissive: Vector3::new(0.0, 0.0, 0.0),
+      material_id: None,
+      tangent: Vector3::new(1.0, 0.0, 0.0),
+      bitangent: Vector3::new(0.0, 0.0, 1.0),
       transformed_position: Vector3::new(0.0, 0.0, 0.0),
+      world_position: Vector3::new(0.0, 0.0, 0.0),
       transformed_normal: Vector3::new(0.0, 1.0, 0.0),
+      transformed_tangent: Vector3::new(1.0, 0.0, 0.0),
+      transformed_bitangent: Vector3::new(0.0, 0.0, 1.0),
+      light_diffuse: Vector3::new(0.0, 0.0, 0.0),
+      light_specular: Vector3::new(0.0, 0.0, 0.0),
     }
   }
 }
\ No newline at end of file
@@ -0,0 +1,143 @@
+// warp.rs
+// Viaje rápido a un planeta (teclas 1-5, o a cualquier cuerpo con el comando "warp" de la
+// consola) y su efecto visual: estelas radiales desde el centro de la pantalla, un golpe de FOV
+// y un tinte azulado que se desvanece al llegar. Durante el viaje la cámara se endereza con slerp
+use crate::console::CommandRegistry;
+use crate::events::Event;
+use crate::world::World;
+use space_travel::camera::Camera;
+use space_travel::framebuffer::Framebuffer;
+use space_travel::matrix::Quaternion;
+use space_travel::noise::hash;
+use raylib::prelude::*;
+use std::f32::consts::TAU;
+
+const DURATION: f32 = 1.2;       // Segundos que tarda el viaje
+const STREAKS: u32 = 160;        // Estelas radiales del efecto
+const STREAK_SPEED: f32 = 2.5;   // Fracción de la media diagonal recorrida por segundo
+const ARRIVAL_PITCH: f32 = 0.3;  // Radianes sobre el plano de la órbita con los que se llega
+
+/// Index of the `number`-th planet (0-based) orbiting the center of the system, skipping
+/// moons and stars; keys 1-5 travel to the first five
+pub fn warp_target(world: &World, number: usize) -> Option<usize> {
+    (0..world.transforms.len())
+        .filter(|&entity| world.orbits[entity].is_none_or(|orbit| orbit.parent.is_none()) && world.lights[entity].is_none())
+        .nth(number)
+}
+
+/// Registers `warp <body>`: travel to any body by name, moons and stars included
+pub fn register_commands(registry: &mut CommandRegistry) {
+    registry.register("warp", "warp <body>", |context, arguments| {
+        let [name] = arguments else {
+            return Err("Expected a body name".to_string());
+        };
+        let index = context.world.find(name).ok_or_else(|| format!("No body named '{}'", name))?;
+        *context.warp = Some(Warp::new(index, context.camera));
+        // La consola imprime "Warping to ..." al recibir el evento
+        context.events.publish(Event::WarpStarted { body: index });
+        Ok(String::new())
+    });
+}
+
+/// Camera travel towards a body, following it as it orbits
+pub struct Warp {
+    body: usize,
+    start_target: Vector3,
+    start_distance: f32,
+    start_orientation: Quaternion,
+    end_orientation: Quaternion, // Misma guiñada, algo por encima y sin alabeo
+    elapsed: f32,
+}
+
+impl Warp {
+    pub fn new(body: usize, camera: &Camera) -> Self {
+        Warp {
+            body,
+            start_target: camera.target,
+            start_distance: camera.distance,
+            start_orientation: camera.orientation(),
+            end_orientation: Camera::orientation_from_angles(camera.yaw, ARRIVAL_PITCH, 0.0),
+            elapsed: 0.0,
+        }
+    }
+
+    /// Moves the camera along the warp; returns false once it has arrived
+    pub fn update(&mut self, camera: &mut Camera, world: &World, dt: f32) -> bool {
+        let Some(body) = world.transforms.get(self.body) else {
+            return false;
+        };
+        self.elapsed += dt;
+        let t = (self.elapsed / DURATION).min(1.0);
+        let eased = t * t * (3.0 - 2.0 * t);
+        // Se detiene a unos radios del planeta para verlo completo
+        let distance = body.scale * 3.0;
+        let target = self.start_target + (body.position - self.start_target) * eased;
+        // Interpolar la orientación entera evita los saltos de interpolar cada ángulo por separado
+        camera.set_orientation(self.start_orientation.slerp(self.end_orientation, eased));
+        camera.look_at(target, self.start_distance + (distance - self.start_distance) * eased);
+        t < 1.0
+    }
+
+    /// Strength of the visual effect (0..1): rises quickly and fades as the warp completes
+    pub fn intensity(&self) -> f32 {
+        let t = (self.elapsed / DURATION).clamp(0.0, 1.0);
+        (t * 8.0).min(1.0) * (1.0 - t)
+    }
+}
+
+// Estela en coordenadas polares respecto al centro de la pantalla
+struct Streak {
+    angle: f32,
+    distance: f32, // Fracción de la media diagonal
+    speed: f32,
+}
+
+/// Star lines that rush outwards from the center of the screen while warping
+pub struct WarpStreaks {
+    streaks: Vec<Streak>,
+}
+
+impl WarpStreaks {
+    pub fn new() -> Self {
+        let streaks = (0..STREAKS).map(|i| {
+            let random = |k: u32| (hash(i.wrapping_mul(3).wrapping_add(k) ^ 0xA7) >> 8) as f32 / (1 << 24) as f32;
+            Streak { angle: random(0) * TAU, distance: random(1), speed: 0.5 + random(2) }
+        }).collect();
+        WarpStreaks { streaks }
+    }
+
+    /// Advances the streaks outwards, faster with the intensity; the ones that leave the
+    /// screen start again near the center
+    pub fn update(&mut self, dt: f32, intensity: f32) {
+        for streak in &mut self.streaks {
+            streak.distance += dt * STREAK_SPEED * streak.speed * (0.3 + intensity);
+            if streak.distance > 1.0 {
+                streak.distance -= 1.0;
+            }
+        }
+    }
+
+    /// Adds the streaks on top of the frame; longer and brighter with the intensity
+    pub fn draw(&self, framebuffer: &mut Framebuffer, intensity: f32) {
+        if intensity <= 0.0 {
+            return;
+        }
+        let (cx, cy) = (framebuffer.width as f32 * 0.5, framebuffer.height as f32 * 0.5);
+        let half_diagonal = (cx * cx + cy * cy).sqrt();
+        for streak in &self.streaks {
+            let (sin, cos) = streak.angle.sin_cos();
+            // Las estelas se alargan al alejarse del centro, como si pasaran junto a la cámara
+            let outer = streak.distance * half_diagonal;
+            let inner = outer * (1.0 - 0.5 * intensity * streak.distance.sqrt());
+            let steps = (outer - inner).ceil().max(1.0) as i32;
+            for step in 0..=steps {
+                let t = step as f32 / steps as f32;
+                let radius = inner + (outer - inner) * t;
+                // Más brillante en la punta exterior
+                let brightness = intensity * streak.distance.min(1.0) * t;
+                let color = Vector3::new(0.7, 0.8, 1.0) * brightness;
+                framebuffer.add_pixel((cx + cos * radius) as i32, (cy + sin * radius) as i32, color);
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+// world.rs
+// Estado del mundo como entidades con componentes: cada cuerpo de la escena es una entidad (un
+// índice) y cada tipo de componente se guarda en su propio vector, con None donde la entidad no
+// lo tiene. CelestialBody queda como la descripción que se lee de la escena; a partir de ahí
+// los sistemas (systems.rs), los scripts y la consola trabajan sobre los componentes
+use crate::console::CommandRegistry;
+use space_travel::light::Light;
+use space_travel::lighting::Surface;
+use space_travel::raytrace::Rings;
+use space_travel::sdf::Sdf;
+use space_travel::scene::{self, CelestialBody, StarLight};
+use raylib::prelude::*;
+
+// Radio de colisión de los cuerpos respecto al de su malla (margen sobre la superficie)
+const COLLIDER_MARGIN: f32 = 1.6;
+
+/// Index of an entity in the world
+pub type Entity = usize;
+
+/// Position, orientation and size of an entity
+#[derive(Clone, Copy)]
+pub struct Transform {
+    pub position: Vector3,
+    pub rotation: Vector3,
+    pub scale: f32,
+    pub spin: f32, // Giro sobre su eje Y, rad/s
+}
+
+// Las órbitas las coloca la biblioteca (scene::place_orbits); el padre es una entidad
+pub use space_travel::scene::Orbit;
+
+/// How an entity is drawn
+#[derive(Clone)]
+pub struct Renderable {
+    pub shader: String,
+    pub color: Color,
+    pub surface: Surface,
+    pub displacement: f32, // Altura del relieve en unidades de la malla (ver vertex_shader)
+    pub vertex_animation: String, // Vacío = cuerpo rígido
+    pub geometry_shader: String,  // Vacío = solo la malla
+    pub ray_traced: bool,         // Esfera exacta trazada por píxel (ver raytrace.rs)
+    pub rings: Option<Rings>,     // Anillos, dibujados solo con la esfera trazada
+    pub sdf: Option<Sdf>,         // Forma marchada por píxel en lugar de la malla (ver sdf.rs)
+}
+
+/// Sphere the camera can't enter, `margin` times the radius of the mesh (scaled with the entity)
+#[derive(Clone, Copy)]
+pub struct Collider {
+    pub margin: f32,
+}
+
+/// Every entity of the running scene, one vector per component
+#[derive(Clone)]
+pub struct World {
+    pub names: Vec<String>,
+    pub transforms: Vec<Transform>,
+    pub orbits: Vec<Option<Orbit>>,
+    pub renderables: Vec<Option<Renderable>>,
+    pub colliders: Vec<Option<Collider>>,
+    pub lights: Vec<Option<StarLight>>,
+    pub scripts: Vec<Option<String>>, // Código Rhai (ver scripting.rs)
+}
+
+impl World {
+    /// One entity per body of the scene, in the same order
+    pub fn new(bodies: &[CelestialBody]) -> Self {
+        World {
+            names: bodies.iter().map(|body| body.name.clone()).collect(),
+            transforms: bodies.iter().map(|body| Transform {
+                position: body.translation,
+                rotation: body.rotation,
+                scale: body.scale,
+                spin: body.rotation_speed,
+            }).collect(),
+            orbits: scene::orbits(bodies),
+            renderables: bodies.iter().map(|body| Some(Renderable {
+                shader: body.shader.clone(),
+                color: body.color,
+                surface: body.surface,
+                displacement: body.displacement,
+                vertex_animation: body.vertex_animation.clone(),
+                geometry_shader: body.geometry_shader.clone(),
+                ray_traced: body.ray_traced,
+                rings: body.rings,
+                sdf: body.sdf,
+            })).collect(),
+            colliders: bodies.iter().map(|_| Some(Collider { margin: COLLIDER_MARGIN })).collect(),
+            lights: bodies.iter().map(|body| body.light).collect(),
+            scripts: bodies.iter().map(|body| body.script.clone()).collect(),
+        }
+    }
+
+    /// The entity with this name, ignoring case
+    pub fn find(&self, name: &str) -> Option<Entity> {
+        self.names.iter().position(|entity_name| entity_name.eq_ignore_ascii_case(name))
+    }
+
+    /// Orbit of `entity`, added (with radius zero) if it had none
+    pub fn orbit_mut(&mut self, entity: Entity) -> &mut Orbit {
+        self.orbits[entity].get_or_insert_with(Orbit::default)
+    }
+
+    /// Draws every renderable entity as a ray-traced sphere (`on`) or with its mesh
+    pub fn set_ray_traced(&mut self, on: bool) {
+        for renderable in self.renderables.iter_mut().flatten() {
+            renderable.ray_traced = on;
+        }
+    }
+
+    /// Lights emitted by the entities with a light, at their current positions
+    pub fn scene_lights(&self) -> Vec<Light> {
+        self.lights.iter().enumerate()
+            .filter_map(|(entity, light)| light.map(|light| {
+                let transform = &self.transforms[entity];
+                Light::new(transform.position)
+                    .with_color(light.color)
+                    .with_intensity(light.intensity)
+                    .with_shadows(light.shadows)
+                    .with_radius(transform.scale * 0.5) // Las mallas de esfera tienen radio 0.5
+            }))
+            .collect()
+    }
+
+    /// The nearest entity hit by the ray from `origin` along the unit vector `direction`
+    pub fn pick(&self, origin: Vector3, direction: Vector3) -> Option<Entity> {
+        let mut nearest: Option<(Entity, f32)> = None;
+        for (entity, transform) in self.transforms.iter().enumerate() {
+            let radius = transform.scale * 0.5;
+            let to_center = transform.position - origin;
+            // Distancia del rayo al centro, desde el punto del rayo más cercano a él
+            let along = to_center.dot(direction);
+            let miss_squared = to_center.dot(to_center) - along * along;
+            if miss_squared > radius * radius {
+                continue;
+            }
+            let hit = along - (radius * radius - miss_squared).sqrt();
+            if hit > 0.0 && nearest.is_none_or(|(_, distance)| hit < distance) {
+                nearest = Some((entity, hit));
+            }
+        }
+        nearest.map(|(entity, _)| entity)
+    }
+
+    /// Pairs (occluder, star) where an entity covers part of a star as seen from `eye`
+    pub fn eclipses(&self, eye: Vector3) -> Vec<(Entity, Entity)> {
+        let mut pairs = Vec::new();
+        for (light, star) in self.transforms.iter().enumerate().filter(|(entity, _)| self.lights[*entity].is_some()) {
+            let to_star = star.position - eye;
+            let star_distance = to_star.length();
+            let star_radius = star.scale * 0.5;
+            if star_distance <= star_radius {
+                continue;
+            }
+            // Radio aparente de la estrella (ángulo) y dirección en que se ve
+            let star_angle = (star_radius / star_distance).asin();
+            let star_direction = to_star / star_distance;
+            for (occluder, transform) in self.transforms.iter().enumerate() {
+                if self.lights[occluder].is_some() {
+                    continue;
+                }
+                let to_body = transform.position - eye;
+                let distance = to_body.length();
+                let radius = transform.scale * 0.5;
+                if distance >= star_distance || distance <= radius {
+                    continue;
+                }
+                // Los discos se tocan cuando la separación angular es menor que la suma de los radios
+                let separation = (to_body / distance).dot(star_direction).clamp(-1.0, 1.0).acos();
+                if separation < (radius / distance).asin() + star_angle {
+                    pairs.push((occluder, light));
+                }
+            }
+        }
+        pairs
+    }
+}
+
+/// Registers `set body.<name>.<field> <value>` to change the orbit, spin and size of a body
+/// while the scene runs (a reload of the scene file restores them)
+pub fn register_commands(registry: &mut CommandRegistry) {
+    registry.register("set", "set body.<name>.<orbit_speed|rotation_speed|orbit_radius|scale> <value>", |context, arguments| {
+        let [path, value] = arguments else {
+            return Err("Expected a path and a value".to_string());
+        };
+        let value: f32 = value.parse().map_err(|_| format!("'{}' is not a number", value))?;
+        let mut parts = path.split('.');
+        let (Some("body"), Some(name), Some(field), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(format!("Unknown path '{}'", path));
+        };
+        let world = &mut *context.world;
+        let entity = world.find(name).ok_or_else(|| format!("No body named '{}'", name))?;
+        let target = match field {
+            "orbit_speed" => &mut world.orbit_mut(entity).speed,
+            "rotation_speed" => &mut world.transforms[entity].spin,
+            "orbit_radius" => &mut world.orbit_mut(entity).radius,
+            "scale" => &mut world.transforms[entity].scale,
+            _ => return Err(format!("Unknown field '{}'", field)),
+        };
+        *target = value;
+        Ok(format!("{}.{} = {}", world.names[entity], field, value))
+    });
+    registry.register("raytrace", "raytrace <body|all> <on|off>", |context, arguments| {
+        let [name, state] = arguments else {
+            return Err("Expected a body (or all) and on or off".to_string());
+        };
+        let on = match *state {
+            "on" => true,
+            "off" => false,
+            _ => return Err(format!("Expected on or off, not '{}'", state)),
+        };
+        let world = &mut *context.world;
+        if *name == "all" {
+            world.set_ray_traced(on);
+        } else {
+            let entity = world.find(name).ok_or_else(|| format!("No body named '{}'", name))?;
+            if let Some(renderable) = world.renderables[entity].as_mut() {
+                renderable.ray_traced = on;
+            }
+        }
+        Ok(format!("Ray-traced sphere {} for {}", if on { "on" } else { "off" }, name))
+    });
+}